@@ -29,6 +29,7 @@ use aptos_api_types::{
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_lifecycle_tracing::Stage;
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
@@ -1208,6 +1209,8 @@ impl TransactionsApi {
 
     /// Submits a single transaction, and converts mempool codes to errors
     async fn create_internal(&self, txn: SignedTransaction) -> Result<(), AptosError> {
+        let mut lifecycle_span =
+            aptos_lifecycle_tracing::transaction_span(&txn.committed_hash(), Stage::ApiSubmit);
         let (mempool_status, vm_status_opt) = self
             .context
             .submit_transaction(txn)
@@ -1216,6 +1219,7 @@ impl TransactionsApi {
             .map_err(|err| {
                 aptos_api_types::AptosError::new_with_error_code(err, AptosErrorCode::InternalError)
             })?;
+        lifecycle_span.set_attribute("mempool_status", format!("{:?}", mempool_status.code));
         match mempool_status.code {
             MempoolStatusCode::Accepted => Ok(()),
             MempoolStatusCode::MempoolIsFull | MempoolStatusCode::TooManyTransactions => {
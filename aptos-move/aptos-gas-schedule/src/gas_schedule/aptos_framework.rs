@@ -4,7 +4,7 @@
 //! This module defines the gas parameters for Aptos Framework & Stdlib.
 
 use crate::{
-    gas_feature_versions::{RELEASE_V1_14, RELEASE_V1_8, RELEASE_V1_9_SKIPPED},
+    gas_feature_versions::{RELEASE_V1_14, RELEASE_V1_22, RELEASE_V1_8, RELEASE_V1_9_SKIPPED},
     gas_schedule::NativeGasParameters,
     ver::gas_feature_versions::{RELEASE_V1_12, RELEASE_V1_13},
 };
@@ -190,6 +190,19 @@ crate::gas_schedule::macros::define_gas_parameters!(
         [secp256k1_base: InternalGas, "secp256k1.base", 551],
         [secp256k1_ecdsa_recover: InternalGasPerArg, "secp256k1.ecdsa_recover", 5918360],
 
+        // TODO(Gas): Calibrate. Deserialization and verification costs are back-of-the-envelope
+        // estimates based on secp256k1's recovery and ed25519's costs, and hashing uses SHA2-256's cost.
+        [secp256r1_base: InternalGas, { RELEASE_V1_22.. => "secp256r1.base" }, 551],
+        [secp256r1_per_pubkey_deserialize: InternalGasPerArg, { RELEASE_V1_22.. => "secp256r1.per_pubkey_deserialize" }, 139688],
+        [secp256r1_per_sig_deserialize: InternalGasPerArg, { RELEASE_V1_22.. => "secp256r1.per_sig_deserialize" }, 1378],
+        [secp256r1_per_msg_byte_hashing: InternalGasPerByte, { RELEASE_V1_22.. => "secp256r1.per_msg_byte_hashing" }, 183],
+        [secp256r1_ecdsa_verify: InternalGasPerArg, { RELEASE_V1_22.. => "secp256r1.ecdsa_verify" }, 5918360],
+
+        // TODO(Gas): Calibrate. The cost of Poseidon grows faster than linearly in the number of
+        // inputs (the state width is `num_inputs + 1`), so this is tuned for the larger widths.
+        [poseidon_bn254_base: InternalGas, { RELEASE_V1_22.. => "poseidon.bn254_base" }, 183755],
+        [poseidon_bn254_per_input: InternalGasPerArg, { RELEASE_V1_22.. => "poseidon.bn254_per_input" }, 110253],
+
         [ristretto255_basepoint_mul: InternalGasPerArg, "ristretto255.basepoint_mul", 470528],
         [ristretto255_basepoint_double_mul: InternalGasPerArg, "ristretto255.basepoint_double_mul", 1617440],
 
@@ -267,6 +280,10 @@ crate::gas_schedule::macros::define_gas_parameters!(
         [util_from_bytes_base: InternalGas, "util.from_bytes.base", 1102],
         [util_from_bytes_per_byte: InternalGasPerByte, "util.from_bytes.per_byte", 18],
 
+        // Based on the cost of comparing values for equality in the VM
+        [big_ordered_map_compare_base: InternalGas, { RELEASE_V1_22.. => "big_ordered_map.compare.base" }, 367],
+        [big_ordered_map_compare_per_abs_val_unit: InternalGasPerAbstractValueUnit, { RELEASE_V1_22.. => "big_ordered_map.compare.per_abs_val_unit" }, 14],
+
         [transaction_context_get_txn_hash_base: InternalGas, { 10.. => "transaction_context.get_txn_hash.base" }, 735],
         [transaction_context_get_script_hash_base: InternalGas, "transaction_context.get_script_hash.base", 735],
         // Based on SHA3-256's cost
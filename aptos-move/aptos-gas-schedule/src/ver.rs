@@ -8,6 +8,10 @@
 ///   - Changing how gas is calculated in any way
 ///
 /// Change log:
+/// - V26
+///    - Gas parameters for the secp256r1 (P-256) ECDSA natives
+///    - Gas parameters for the Poseidon-BN254 hash native
+///    - Gas parameters for the key comparison native of `big_ordered_map`
 /// - V22
 ///    - Gas parameters for enums
 ///    - Gas parameters for new native function `bcs::serialized_size`
@@ -69,7 +73,7 @@
 ///       global operations.
 /// - V1
 ///   - TBA
-pub const LATEST_GAS_FEATURE_VERSION: u64 = gas_feature_versions::RELEASE_V1_21;
+pub const LATEST_GAS_FEATURE_VERSION: u64 = gas_feature_versions::RELEASE_V1_22;
 
 pub mod gas_feature_versions {
     pub const RELEASE_V1_8: u64 = 11;
@@ -86,4 +90,5 @@ pub mod gas_feature_versions {
     pub const RELEASE_V1_19: u64 = 23;
     pub const RELEASE_V1_20: u64 = 24;
     pub const RELEASE_V1_21: u64 = 25;
+    pub const RELEASE_V1_22: u64 = 26;
 }
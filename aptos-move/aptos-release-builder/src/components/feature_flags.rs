@@ -131,6 +131,9 @@ pub enum FeatureFlag {
     FederatedKeyless,
     TransactionSimulationEnhancement,
     CollectionOwner,
+    Secp256r1Natives,
+    PoseidonBn254Natives,
+    BigOrderedMapNatives,
 }
 
 fn generate_features_blob(writer: &CodeWriter, data: &[u64]) {
@@ -347,6 +350,9 @@ impl From<FeatureFlag> for AptosFeatureFlag {
                 AptosFeatureFlag::TRANSACTION_SIMULATION_ENHANCEMENT
             },
             FeatureFlag::CollectionOwner => AptosFeatureFlag::COLLECTION_OWNER,
+            FeatureFlag::Secp256r1Natives => AptosFeatureFlag::SECP256R1_NATIVES,
+            FeatureFlag::PoseidonBn254Natives => AptosFeatureFlag::POSEIDON_BN254_NATIVES,
+            FeatureFlag::BigOrderedMapNatives => AptosFeatureFlag::BIG_ORDERED_MAP_NATIVES,
         }
     }
 }
@@ -490,6 +496,9 @@ impl From<AptosFeatureFlag> for FeatureFlag {
                 FeatureFlag::TransactionSimulationEnhancement
             },
             AptosFeatureFlag::COLLECTION_OWNER => FeatureFlag::CollectionOwner,
+            AptosFeatureFlag::SECP256R1_NATIVES => FeatureFlag::Secp256r1Natives,
+            AptosFeatureFlag::POSEIDON_BN254_NATIVES => FeatureFlag::PoseidonBn254Natives,
+            AptosFeatureFlag::BIG_ORDERED_MAP_NATIVES => FeatureFlag::BigOrderedMapNatives,
         }
     }
 }
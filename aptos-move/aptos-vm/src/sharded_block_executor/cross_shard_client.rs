@@ -42,6 +42,11 @@ impl CrossShardCommitReceiver {
                     trace!("Cross shard commit receiver stopped for round {}", round);
                     break;
                 },
+                CrossShardMsg::AbortMsg => {
+                    trace!("Cross shard commit receiver aborted for round {}", round);
+                    cross_shard_state_view.abort();
+                    break;
+                },
             }
         }
     }
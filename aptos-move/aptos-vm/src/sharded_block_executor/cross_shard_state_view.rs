@@ -11,7 +11,13 @@ use aptos_types::{
     },
     transaction::analyzed_transaction::AnalyzedTransaction,
 };
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 /// A state view for reading cross shard state values. It is backed by a state view
 /// and a hashmap of cross shard state keys. When a cross shard state value is not
@@ -20,6 +26,8 @@ use std::collections::{HashMap, HashSet};
 pub struct CrossShardStateView<'a, S> {
     cross_shard_data: HashMap<StateKey, RemoteStateValue>,
     base_view: &'a S,
+    // Set when the cross shard values can no longer arrive, after which reading them fails.
+    aborted: Arc<AtomicBool>,
 }
 
 impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
@@ -35,6 +43,7 @@ impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
         Self {
             cross_shard_data,
             base_view,
+            aborted: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -55,6 +64,21 @@ impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
         // trace!("waiting count for shard id {} is {}", self.shard_id, self.waiting_count());
     }
 
+    /// Stops waiting for the cross shard values which haven't arrived yet. Reading any cross shard
+    /// value fails from then on, as the values read before may be incomplete.
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+        for value in self.cross_shard_data.values() {
+            if !value.is_ready() {
+                value.set_value(None);
+            }
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
     pub fn create_cross_shard_state_view(
         base_view: &'a S,
         transactions: &[TransactionWithDependencies<AnalyzedTransaction>],
@@ -76,7 +100,14 @@ impl<'a, S: StateView + Sync + Send> TStateView for CrossShardStateView<'a, S> {
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
         if let Some(value) = self.cross_shard_data.get(state_key) {
-            return Ok(value.get_value());
+            let value = value.get_value();
+            if self.is_aborted() {
+                return Err(StateviewError::Other(format!(
+                    "Waiting for cross shard value of {:?} was aborted",
+                    state_key
+                )));
+            }
+            return Ok(value);
         }
         self.base_view.get_state_value(state_key)
     }
@@ -8,6 +8,9 @@ use serde::{Deserialize, Serialize};
 pub enum CrossShardMsg {
     RemoteTxnWriteMsg(RemoteTxnWrite),
     StopMsg,
+    /// Received instead of the remaining messages of a round when they can no longer arrive, e.g.
+    /// because the shard sending them failed. The transactions waiting for them then fail.
+    AbortMsg,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -66,6 +66,10 @@ impl<S: StateView + Sync + Send + 'static, C: ExecutorClient<S>> ShardedBlockExe
         self.executor_client.num_shards()
     }
 
+    pub fn executor_client(&self) -> &C {
+        &self.executor_client
+    }
+
     /// Execute a block of transactions in parallel by splitting the block into num_remote_executors partitions and
     /// dispatching each partition to a remote executor shard.
     pub fn execute_block(
@@ -31,7 +31,7 @@ use aptos_types::{
 };
 use aptos_vm_logging::disable_speculative_logging;
 use futures::{channel::oneshot, executor::block_on};
-use move_core_types::vm_status::VMStatus;
+use move_core_types::vm_status::{StatusCode, VMStatus};
 use std::sync::Arc;
 
 pub struct ShardedExecutorService<S: StateView + Sync + Send + 'static> {
@@ -114,6 +114,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
 
         let cross_shard_state_view_clone = cross_shard_state_view.clone();
         let cross_shard_client_clone = cross_shard_client.clone();
+        let cross_shard_state_view_for_abort = cross_shard_state_view.clone();
 
         let aggr_overridden_state_view = Arc::new(AggregatorOverriddenStateView::new(
             cross_shard_state_view.as_ref(),
@@ -168,7 +169,18 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
             });
         });
 
-        block_on(callback_receiver).unwrap()
+        let ret = block_on(callback_receiver).unwrap();
+        // The outputs are incomplete if cross shard values couldn't be read.
+        if cross_shard_state_view_for_abort.is_aborted() {
+            return Err(VMStatus::error(
+                StatusCode::STORAGE_ERROR,
+                Some(format!(
+                    "Cross shard messages of round {} stopped arriving",
+                    round
+                )),
+            ));
+        }
+        ret
     }
 
     fn execute_block(
@@ -822,6 +822,13 @@ module std::bn254_algebra {
         multi_scalar_mul(&elements, &scalars);
     }
 
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x0c0000, location = aptos_std::crypto_algebra)]
+    fun test_multi_scalar_mul_should_abort_when_natives_disabled(fx: signer) {
+        std::features::change_feature_flags_for_testing(&fx, vector[], vector[std::features::get_cryptography_algebra_natives_feature()]);
+        multi_scalar_mul<G1, Fr>(&vector[], &vector[]);
+    }
+
     #[test_only]
     /// The maximum number of `G1` elements that can be created in a transaction,
     /// calculated by the current memory limit (1MB) and the in-mem G1 representation size (96 bytes per element).
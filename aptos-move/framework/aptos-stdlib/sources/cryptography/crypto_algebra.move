@@ -39,6 +39,8 @@ module aptos_std::crypto_algebra {
     use std::option::{Option, some, none};
     use std::features;
 
+    friend aptos_std::poseidon;
+
     const E_NOT_IMPLEMENTED: u64 = 1;
     const E_NON_EQUAL_LENGTHS: u64 = 2;
     const E_TOO_MUCH_MEMORY_USED: u64 = 3;
@@ -161,8 +163,12 @@ module aptos_std::crypto_algebra {
     /// `P[]` are `n` elements of group `G` represented by parameter `elements`, and
     /// `k[]` are `n` elements of the scalarfield `S` of group `G` represented by parameter `scalars`.
     ///
+    /// This is computed with Pippenger's bucket method, so its cost grows as `O(n / log(n))` group operations,
+    /// which is much cheaper than `n` calls to `scalar_mul()` (e.g., for the public inputs of a Groth16 proof).
+    ///
     /// Abort with code `std::error::invalid_argument(E_NON_EQUAL_LENGTHS)` if the sizes of `elements` and `scalars` do not match.
     public fun multi_scalar_mul<G, S>(elements: &vector<Element<G>>, scalars: &vector<Element<S>>): Element<G> {
+        abort_unless_cryptography_algebra_natives_enabled();
         let element_handles = handles_from_elements(elements);
         let scalar_handles = handles_from_elements(scalars);
         Element<G> {
@@ -286,7 +292,12 @@ module aptos_std::crypto_algebra {
         std::features::change_feature_flags_for_testing(fx, vector[std::features::get_cryptography_algebra_natives_feature()], vector[]);
     }
 
-    fun handles_from_elements<S>(elements: &vector<Element<S>>): vector<u64> {
+    /// Wraps a handle returned by a native function of a friend module into an element.
+    public(friend) fun element_from_handle<S>(handle: u64): Element<S> {
+        Element<S> { handle }
+    }
+
+    public(friend) fun handles_from_elements<S>(elements: &vector<Element<S>>): vector<u64> {
         let num_elements = std::vector::length(elements);
         let element_handles = std::vector::empty();
         let i = 0;
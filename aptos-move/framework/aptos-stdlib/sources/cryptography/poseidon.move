@@ -0,0 +1,117 @@
+/// This module implements the Poseidon hash function over the BN254 scalar field, as used in circom circuits
+/// (e.g., by `circomlib`'s `Poseidon` template) and in Aptos keyless accounts.
+///
+/// The inputs and the output are `crypto_algebra` elements of `bn254_algebra::Fr`, so they can be used with the rest
+/// of the generic algebra API (e.g., to build Merkle trees whose roots are checked by a Groth16 verifier).
+module aptos_std::poseidon {
+    use std::error;
+    use std::features;
+    use std::vector;
+    use aptos_std::bn254_algebra::Fr;
+    use aptos_std::crypto_algebra::{Self, Element};
+
+    /// The number of inputs must be between 1 and `MAX_NUM_INPUTS`.
+    const E_WRONG_NUM_INPUTS: u64 = 1;   // This code must be the same, if ever returned from the native Rust implementation.
+
+    /// The native functions have not been rolled out yet.
+    const E_NATIVE_FUN_NOT_AVAILABLE: u64 = 2;
+
+    /// The maximum number of field elements that can be hashed at once.
+    const MAX_NUM_INPUTS: u64 = 16;
+
+    /// Returns the Poseidon-BN254 hash of `inputs`, which must contain between 1 and 16 elements.
+    ///
+    /// The parameters (round constants, MDS matrix and number of rounds) are the ones of `circomlib`, with a state width
+    /// of `len(inputs) + 1`. Note that hashing different numbers of inputs uses different parameters, so e.g.
+    /// `hash_bn254(&vector[x])` and `hash_bn254(&vector[x, zero()])` are unrelated.
+    public fun hash_bn254(inputs: &vector<Element<Fr>>): Element<Fr> {
+        assert!(features::poseidon_bn254_natives_enabled(), error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE));
+        let num_inputs = vector::length(inputs);
+        assert!(num_inputs > 0 && num_inputs <= MAX_NUM_INPUTS, error::invalid_argument(E_WRONG_NUM_INPUTS));
+        crypto_algebra::element_from_handle(hash_bn254_internal(crypto_algebra::handles_from_elements(inputs)))
+    }
+
+    //
+    // Native functions
+    //
+
+    /// Hashes the `Fr` elements with the given handles, and returns the handle of the result.
+    native fun hash_bn254_internal(handles: vector<u64>): u64;
+
+    //
+    // Tests
+    //
+
+    #[test_only]
+    use aptos_std::bn254_algebra::FormatFrLsb;
+    #[test_only]
+    use aptos_std::crypto_algebra::{deserialize, eq, from_u64};
+
+    #[test_only]
+    fun enable_natives(fx: &signer) {
+        crypto_algebra::enable_cryptography_algebra_natives(fx);
+        features::change_feature_flags_for_testing(fx, vector[ features::get_poseidon_bn254_natives_feature() ], vector[]);
+    }
+
+    #[test_only]
+    fun fr_from_lsb_bytes(bytes: vector<u8>): Element<Fr> {
+        std::option::extract(&mut deserialize<Fr, FormatFrLsb>(&bytes))
+    }
+
+    #[test(fx = @std)]
+    /// Test vectors from https://github.com/arnaucube/poseidon-ark/blob/6d2487aa1308d9d3860a2b724c485d73095c1c68/src/lib.rs#L170
+    fun test_hash_bn254(fx: signer) {
+        enable_natives(&fx);
+
+        // 18586133768512220936620570745912940619677854269274689475585506675881198879027
+        let expected = fr_from_lsb_bytes(x"33018202c57d898b84338b16d1a4960e133c6a4d656cfec1bd62a9ea00611729");
+        assert!(eq(&hash_bn254(&vector[from_u64<Fr>(1)]), &expected), 1);
+
+        // 7853200120776062878684798364095072458815029376092732009249414926327459813530
+        let expected = fr_from_lsb_bytes(x"9a1817447a60199e51453274f217362acfe962966b4cf63d4190d6e7f5c05c11");
+        assert!(eq(&hash_bn254(&vector[from_u64<Fr>(1), from_u64<Fr>(2)]), &expected), 1);
+        assert!(!eq(&hash_bn254(&vector[from_u64<Fr>(2), from_u64<Fr>(1)]), &expected), 1);
+    }
+
+    #[test(fx = @std)]
+    fun test_hash_bn254_max_inputs(fx: signer) {
+        enable_natives(&fx);
+
+        let inputs = vector[];
+        let i = 0;
+        while (i < MAX_NUM_INPUTS) {
+            vector::push_back(&mut inputs, from_u64<Fr>(i));
+            i = i + 1;
+        };
+        hash_bn254(&inputs);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x010001, location = Self)]
+    fun test_hash_bn254_too_many_inputs(fx: signer) {
+        enable_natives(&fx);
+
+        let inputs = vector[];
+        let i = 0;
+        while (i <= MAX_NUM_INPUTS) {
+            vector::push_back(&mut inputs, from_u64<Fr>(i));
+            i = i + 1;
+        };
+        hash_bn254(&inputs);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x010001, location = Self)]
+    fun test_hash_bn254_no_inputs(fx: signer) {
+        enable_natives(&fx);
+        hash_bn254(&vector[]);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x030002, location = Self)]
+    fun test_hash_bn254_feature_disabled(fx: signer) {
+        crypto_algebra::enable_cryptography_algebra_natives(&fx);
+        features::change_feature_flags_for_testing(&fx, vector[], vector[ features::get_poseidon_bn254_natives_feature() ]);
+        hash_bn254(&vector[from_u64<Fr>(1)]);
+    }
+}
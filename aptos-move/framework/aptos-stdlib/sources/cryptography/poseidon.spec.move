@@ -0,0 +1,5 @@
+spec aptos_std::poseidon {
+    spec hash_bn254_internal(handles: vector<u64>): u64 {
+        pragma opaque;
+    }
+}
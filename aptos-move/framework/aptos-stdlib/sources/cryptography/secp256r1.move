@@ -0,0 +1,158 @@
+/// This module implements ECDSA signature verification over the NIST P-256 elliptic curve (a.k.a. secp256r1 or
+/// prime256v1), which is the curve used by WebAuthn authenticators (e.g., passkeys) and secure enclaves.
+///
+/// Signatures are verified over the SHA2-256 hash of the message, as done by WebAuthn (ES256). To prevent signature
+/// malleability, only signatures in canonical low-S form (i.e., with s <= n/2, where n is the order of the curve)
+/// are accepted. A high-S signature can be converted to the canonical form by replacing s with n - s.
+
+module aptos_std::secp256r1 {
+    use std::error;
+    use std::features;
+
+    /// An error occurred while deserializing, for example due to wrong input size.
+    const E_DESERIALIZE: u64 = 1;
+
+    /// The native functions have not been rolled out yet.
+    const E_NATIVE_FUN_NOT_AVAILABLE: u64 = 2;
+
+    /// The size of a raw (i.e., uncompressed, without the SEC1 0x04 prefix) P-256 public key, in bytes.
+    const RAW_PUBLIC_KEY_NUM_BYTES: u64 = 64;
+
+    /// The size of a P-256 ECDSA signature, in bytes.
+    const SIGNATURE_NUM_BYTES: u64 = 64;
+
+    /// A 64-byte ECDSA public key, as the concatenation of the big-endian x and y coordinates.
+    struct ECDSARawPublicKey has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// A 64-byte ECDSA signature, as the concatenation of the big-endian r and s values.
+    struct ECDSASignature has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// Constructs an ECDSASignature struct from the given 64 bytes.
+    public fun ecdsa_signature_from_bytes(bytes: vector<u8>): ECDSASignature {
+        assert!(std::vector::length(&bytes) == SIGNATURE_NUM_BYTES, error::invalid_argument(E_DESERIALIZE));
+        ECDSASignature { bytes }
+    }
+
+    /// Constructs an ECDSARawPublicKey struct, given a 64-byte raw representation.
+    ///
+    /// Note that this does not check that the public key is a point on the curve: use `ecdsa_raw_public_key_validate`
+    /// for that. Verifying a signature under an invalid public key always fails.
+    public fun ecdsa_raw_public_key_from_64_bytes(bytes: vector<u8>): ECDSARawPublicKey {
+        assert!(std::vector::length(&bytes) == RAW_PUBLIC_KEY_NUM_BYTES, error::invalid_argument(E_DESERIALIZE));
+        ECDSARawPublicKey { bytes }
+    }
+
+    /// Serializes an ECDSARawPublicKey struct to 64-bytes.
+    public fun ecdsa_raw_public_key_to_bytes(pk: &ECDSARawPublicKey): vector<u8> {
+        pk.bytes
+    }
+
+    /// Serializes an ECDSASignature struct to 64-bytes.
+    public fun ecdsa_signature_to_bytes(sig: &ECDSASignature): vector<u8> {
+        sig.bytes
+    }
+
+    /// Returns `true` if the public key is a valid point on the P-256 curve (and not the point at infinity).
+    public fun ecdsa_raw_public_key_validate(pk: &ECDSARawPublicKey): bool {
+        assert!(features::secp256r1_natives_enabled(), error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE));
+        ecdsa_raw_public_key_validate_internal(pk.bytes)
+    }
+
+    /// Returns `true` if `signature` is a valid low-S ECDSA signature on the SHA2-256 hash of `message` under the
+    /// public key `pk`, and `false` otherwise (including when `pk` is not a valid point on the curve).
+    public fun ecdsa_verify(
+        message: vector<u8>,
+        pk: &ECDSARawPublicKey,
+        signature: &ECDSASignature,
+    ): bool {
+        assert!(features::secp256r1_natives_enabled(), error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE));
+        ecdsa_verify_internal(message, pk.bytes, signature.bytes)
+    }
+
+    //
+    // Native functions
+    //
+
+    /// Returns `true` if `public_key` is the 64-byte raw representation of a point on the P-256 curve.
+    native fun ecdsa_raw_public_key_validate_internal(public_key: vector<u8>): bool;
+
+    /// Returns `true` if `signature` verifies on `message` under `public_key`. Returns `false` if either the public
+    /// key or the signature fails to deserialize, or if the signature is not in low-S form.
+    native fun ecdsa_verify_internal(
+        message: vector<u8>,
+        public_key: vector<u8>,
+        signature: vector<u8>
+    ): bool;
+
+    //
+    // Tests
+    //
+
+    #[test_only]
+    /// The public key for sk = x"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+    const TEST_PUBLIC_KEY: vector<u8> = x"d8cd12ea5c67f2f8a00c1124893edcfa6754c4d6cede6be13bdf2295c810a97fa5a89d2d2a360c0ca9a4d6c7c9ed4b28d3e199d6627f2e696d689c310a5b0f48";
+
+    #[test_only]
+    /// A low-S signature on b"test aptos secp256r1" under `TEST_PUBLIC_KEY`.
+    const TEST_SIGNATURE: vector<u8> = x"5fafbb26753c09f7bf2ed2c6029d5ef9eddf7bb09d16d02ab730036469faf33240cd6b4ef9554523dafc363f058e3b9a243be63e09211e5ae568321c66b61892";
+
+    #[test_only]
+    /// The high-S version of `TEST_SIGNATURE`, i.e., with s replaced by n - s.
+    const TEST_HIGH_S_SIGNATURE: vector<u8> = x"5fafbb26753c09f7bf2ed2c6029d5ef9eddf7bb09d16d02ab730036469faf332bf3294b006aabadd2503c9c0fa71c46598ab146f9df6802a0e5198a695ad0cbf";
+
+    #[test(fx = @std)]
+    fun test_ecdsa_verify(fx: signer) {
+        features::change_feature_flags_for_testing(&fx, vector[ features::get_secp256r1_natives_feature() ], vector[]);
+
+        let pk = ecdsa_raw_public_key_from_64_bytes(TEST_PUBLIC_KEY);
+        let sig = ecdsa_signature_from_bytes(TEST_SIGNATURE);
+        assert!(ecdsa_raw_public_key_validate(&pk), 1);
+        assert!(ecdsa_verify(b"test aptos secp256r1", &pk, &sig), 1);
+
+        // Wrong message
+        assert!(!ecdsa_verify(b"test aptos secp256k1", &pk, &sig), 1);
+
+        // High-S signatures are rejected
+        let high_s_sig = ecdsa_signature_from_bytes(TEST_HIGH_S_SIGNATURE);
+        assert!(!ecdsa_verify(b"test aptos secp256r1", &pk, &high_s_sig), 1);
+
+        // Flipped bits; signature becomes invalid
+        let sig = ecdsa_signature_from_bytes(x"ffafbb26753c09f7bf2ed2c6029d5ef9eddf7bb09d16d02ab730036469faf33240cd6b4ef9554523dafc363f058e3b9a243be63e09211e5ae568321c66b61892");
+        assert!(!ecdsa_verify(b"test aptos secp256r1", &pk, &sig), 1);
+    }
+
+    #[test(fx = @std)]
+    fun test_ecdsa_invalid_public_key(fx: signer) {
+        features::change_feature_flags_for_testing(&fx, vector[ features::get_secp256r1_natives_feature() ], vector[]);
+
+        // The y coordinate was modified, so the point is not on the curve anymore.
+        let pk = ecdsa_raw_public_key_from_64_bytes(x"d8cd12ea5c67f2f8a00c1124893edcfa6754c4d6cede6be13bdf2295c810a97fa5a89d2d2a360c0ca9a4d6c7c9ed4b28d3e199d6627f2e696d689c310a5b0f49");
+        assert!(!ecdsa_raw_public_key_validate(&pk), 1);
+        assert!(!ecdsa_verify(b"test aptos secp256r1", &pk, &ecdsa_signature_from_bytes(TEST_SIGNATURE)), 1);
+
+        let pk = ecdsa_raw_public_key_from_64_bytes(x"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000");
+        assert!(!ecdsa_raw_public_key_validate(&pk), 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0x010001, location = Self)]
+    fun test_ecdsa_signature_wrong_size() {
+        ecdsa_signature_from_bytes(x"5fafbb26");
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x030002, location = Self)]
+    fun test_ecdsa_verify_feature_disabled(fx: signer) {
+        features::change_feature_flags_for_testing(&fx, vector[], vector[ features::get_secp256r1_natives_feature() ]);
+
+        ecdsa_verify(
+            b"test aptos secp256r1",
+            &ecdsa_raw_public_key_from_64_bytes(TEST_PUBLIC_KEY),
+            &ecdsa_signature_from_bytes(TEST_SIGNATURE),
+        );
+    }
+}
@@ -0,0 +1,830 @@
+/// A scalable ordered map, implemented as a B+ tree whose nodes are stored in table slots.
+///
+/// Keys are kept sorted by their natural order: integers by value, vectors (and so strings) lexicographically,
+/// and structs lexicographically by their fields, in declaration order. This makes it suitable for order books
+/// (e.g., keyed by `(price, sequence_number)`) or time-indexed structures.
+///
+/// Every node is stored in its own slot, so an operation only touches the slots on the path from the root to a leaf
+/// (plus, when inserting or removing, a sibling of each node on that path that needs to be split or merged).
+/// With the default degrees this is 3 slots for up to ~30K entries, and 4 slots for up to ~1M entries, and the gas
+/// charged for storage is deterministic given the current content of the map.
+///
+/// Iteration in key order (in both directions) is supported through `IteratorPtr`, e.g. to iterate over a range:
+/// ```
+/// let it = big_ordered_map::lower_bound(&map, &start);
+/// while (!big_ordered_map::iter_is_end(&it) && *big_ordered_map::iter_borrow_key(&it, &map) < end) {
+///     let value = big_ordered_map::iter_borrow(&it, &map);
+///     ...
+///     it = big_ordered_map::iter_next(it, &map);
+/// }
+/// ```
+/// Iterators are invalidated by any modification of the map other than `iter_borrow_mut`.
+module aptos_std::big_ordered_map {
+    use std::error;
+    use std::features;
+    use std::option::{Self, Option};
+    use std::vector;
+    use aptos_std::table_with_length::{Self, TableWithLength};
+
+    /// The map already contains the key.
+    const EKEY_ALREADY_EXISTS: u64 = 1;
+    /// The map does not contain the key.
+    const EKEY_NOT_FOUND: u64 = 2;
+    /// The map is not empty.
+    const EMAP_NOT_EMPTY: u64 = 3;
+    /// The iterator points to the end of the map.
+    const EITER_OUT_OF_BOUNDS: u64 = 4;
+    /// The degree of the nodes must be between `MIN_DEGREE` and `MAX_DEGREE`.
+    const EINVALID_DEGREE: u64 = 5;
+    /// The native functions have not been rolled out yet.
+    const E_NATIVE_FUN_NOT_AVAILABLE: u64 = 6;
+
+    /// Results of `compare`, these must be the same as in the native Rust implementation.
+    const EQUAL: u8 = 0;
+    const SMALLER: u8 = 1;
+    const GREATER: u8 = 2;
+
+    /// Index of no node, e.g. the successor of the last leaf.
+    const NULL_INDEX: u64 = 0;
+
+    const MIN_DEGREE: u64 = 4;
+    const MAX_DEGREE: u64 = 4096;
+    const DEFAULT_INNER_MAX_DEGREE: u64 = 32;
+    const DEFAULT_LEAF_MAX_DEGREE: u64 = 32;
+
+    /// A node of the tree.
+    ///
+    /// In a leaf, `keys` and `values` are the entries, sorted by key, and `prev`/`next` link the leaves in key order.
+    /// In an inner node, `children` are the child nodes, and `keys[i]` separates them: all the keys under
+    /// `children[i]` are smaller than it, and all the keys under `children[i + 1]` are greater than or equal to it.
+    struct Node<K: store, V: store> has store {
+        is_leaf: bool,
+        keys: vector<K>,
+        values: vector<V>,
+        children: vector<u64>,
+        prev: u64,
+        next: u64,
+    }
+
+    /// The map. Nodes are stored in `nodes` by index, starting at 1.
+    struct BigOrderedMap<K: store, V: store> has store {
+        nodes: TableWithLength<u64, Node<K, V>>,
+        root_index: u64,
+        /// The first and last leaves, where iteration begins and ends.
+        min_leaf_index: u64,
+        max_leaf_index: u64,
+        next_node_index: u64,
+        inner_max_degree: u64,
+        leaf_max_degree: u64,
+        size: u64,
+    }
+
+    /// A position in the map: the entry at `pos` in the leaf `node_index`, or the end of the map if `node_index` is
+    /// `NULL_INDEX`.
+    struct IteratorPtr has copy, drop {
+        node_index: u64,
+        pos: u64,
+    }
+
+    /// Creates an empty map with the default node degrees.
+    public fun new<K: copy + drop + store, V: store>(): BigOrderedMap<K, V> {
+        new_with_config(DEFAULT_INNER_MAX_DEGREE, DEFAULT_LEAF_MAX_DEGREE)
+    }
+
+    /// Creates an empty map, with at most `inner_max_degree` children per inner node and at most `leaf_max_degree`
+    /// entries per leaf. Larger leaves fit more (small) entries in a slot, but each slot write costs more.
+    /// Aborts if the natives the map needs are not enabled yet.
+    public fun new_with_config<K: copy + drop + store, V: store>(
+        inner_max_degree: u64,
+        leaf_max_degree: u64,
+    ): BigOrderedMap<K, V> {
+        assert!(features::big_ordered_map_natives_enabled(), error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE));
+        assert!(
+            inner_max_degree >= MIN_DEGREE && inner_max_degree <= MAX_DEGREE,
+            error::invalid_argument(EINVALID_DEGREE)
+        );
+        assert!(
+            leaf_max_degree >= MIN_DEGREE && leaf_max_degree <= MAX_DEGREE,
+            error::invalid_argument(EINVALID_DEGREE)
+        );
+        let nodes = table_with_length::new();
+        table_with_length::add(&mut nodes, 1, new_leaf(vector[], vector[], NULL_INDEX, NULL_INDEX));
+        BigOrderedMap {
+            nodes,
+            root_index: 1,
+            min_leaf_index: 1,
+            max_leaf_index: 1,
+            next_node_index: 2,
+            inner_max_degree,
+            leaf_max_degree,
+            size: 0,
+        }
+    }
+
+    /// Destroys an empty map.
+    /// Aborts if `self` is not empty.
+    public fun destroy_empty<K: store, V: store>(self: BigOrderedMap<K, V>) {
+        assert!(self.size == 0, error::invalid_argument(EMAP_NOT_EMPTY));
+        let BigOrderedMap {
+            nodes,
+            root_index,
+            min_leaf_index: _,
+            max_leaf_index: _,
+            next_node_index: _,
+            inner_max_degree: _,
+            leaf_max_degree: _,
+            size: _,
+        } = self;
+        // An empty map only has its (empty) root leaf.
+        destroy_empty_node(table_with_length::remove(&mut nodes, root_index));
+        table_with_length::destroy_empty(nodes);
+    }
+
+    /// Returns the number of entries in the map.
+    public fun length<K: store, V: store>(self: &BigOrderedMap<K, V>): u64 {
+        self.size
+    }
+
+    /// Returns true iff the map is empty.
+    public fun is_empty<K: store, V: store>(self: &BigOrderedMap<K, V>): bool {
+        self.size == 0
+    }
+
+    /// Adds the entry `(key, value)` to the map.
+    /// Aborts if the map already contains `key`.
+    public fun add<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: K, value: V) {
+        let path = find_path(self, &key);
+        let leaf_index = vector::pop_back(&mut path);
+        let leaf = table_with_length::borrow_mut(&mut self.nodes, leaf_index);
+        let pos = lower_pos(&leaf.keys, &key);
+        assert!(
+            pos == vector::length(&leaf.keys) || compare(vector::borrow(&leaf.keys, pos), &key) != EQUAL,
+            error::already_exists(EKEY_ALREADY_EXISTS)
+        );
+        vector::insert(&mut leaf.keys, pos, key);
+        vector::insert(&mut leaf.values, pos, value);
+        let leaf_size = vector::length(&leaf.keys);
+        self.size = self.size + 1;
+        if (leaf_size > self.leaf_max_degree) {
+            split_path(self, leaf_index, path);
+        };
+    }
+
+    /// Adds the entry `(key, value)` to the map, or replaces the value if the map already contains `key`, in which
+    /// case the previous value is returned.
+    public fun upsert<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        key: K,
+        value: V,
+    ): Option<V> {
+        let leaf_index = find_leaf(self, &key);
+        let leaf = table_with_length::borrow_mut(&mut self.nodes, leaf_index);
+        let pos = lower_pos(&leaf.keys, &key);
+        if (pos < vector::length(&leaf.keys) && compare(vector::borrow(&leaf.keys, pos), &key) == EQUAL) {
+            vector::push_back(&mut leaf.values, value);
+            option::some(vector::swap_remove(&mut leaf.values, pos))
+        } else {
+            add(self, key, value);
+            option::none()
+        }
+    }
+
+    /// Removes the entry with `key` from the map, and returns its value.
+    /// Aborts if the map does not contain `key`.
+    public fun remove<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: &K): V {
+        let path = find_path(self, key);
+        let leaf_index = vector::pop_back(&mut path);
+        let leaf = table_with_length::borrow_mut(&mut self.nodes, leaf_index);
+        let pos = lower_pos(&leaf.keys, key);
+        assert!(
+            pos < vector::length(&leaf.keys) && compare(vector::borrow(&leaf.keys, pos), key) == EQUAL,
+            error::not_found(EKEY_NOT_FOUND)
+        );
+        vector::remove(&mut leaf.keys, pos);
+        let value = vector::remove(&mut leaf.values, pos);
+        self.size = self.size - 1;
+        rebalance(self, leaf_index, path);
+        value
+    }
+
+    /// Returns true iff the map contains `key`.
+    public fun contains<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): bool {
+        let leaf = table_with_length::borrow(&self.nodes, find_leaf(self, key));
+        let pos = lower_pos(&leaf.keys, key);
+        pos < vector::length(&leaf.keys) && compare(vector::borrow(&leaf.keys, pos), key) == EQUAL
+    }
+
+    /// Returns a reference to the value of `key`.
+    /// Aborts if the map does not contain `key`.
+    public fun borrow<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): &V {
+        let leaf = table_with_length::borrow(&self.nodes, find_leaf(self, key));
+        let pos = lower_pos(&leaf.keys, key);
+        assert!(
+            pos < vector::length(&leaf.keys) && compare(vector::borrow(&leaf.keys, pos), key) == EQUAL,
+            error::not_found(EKEY_NOT_FOUND)
+        );
+        vector::borrow(&leaf.values, pos)
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    /// Aborts if the map does not contain `key`.
+    public fun borrow_mut<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: &K): &mut V {
+        let leaf_index = find_leaf(self, key);
+        let leaf = table_with_length::borrow_mut(&mut self.nodes, leaf_index);
+        let pos = lower_pos(&leaf.keys, key);
+        assert!(
+            pos < vector::length(&leaf.keys) && compare(vector::borrow(&leaf.keys, pos), key) == EQUAL,
+            error::not_found(EKEY_NOT_FOUND)
+        );
+        vector::borrow_mut(&mut leaf.values, pos)
+    }
+
+    //
+    // Iteration
+    //
+
+    /// Returns an iterator to the smallest key of the map (or the end, if the map is empty).
+    public fun new_begin_iter<K: store, V: store>(self: &BigOrderedMap<K, V>): IteratorPtr {
+        if (self.size == 0) {
+            new_end_iter(self)
+        } else {
+            IteratorPtr { node_index: self.min_leaf_index, pos: 0 }
+        }
+    }
+
+    /// Returns an iterator to the end of the map, i.e. past the largest key.
+    public fun new_end_iter<K: store, V: store>(_self: &BigOrderedMap<K, V>): IteratorPtr {
+        IteratorPtr { node_index: NULL_INDEX, pos: 0 }
+    }
+
+    /// Returns an iterator to the smallest key that is greater than or equal to `key` (or the end, if there is none).
+    public fun lower_bound<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): IteratorPtr {
+        let leaf_index = find_leaf(self, key);
+        let leaf = table_with_length::borrow(&self.nodes, leaf_index);
+        let pos = lower_pos(&leaf.keys, key);
+        iter_in_leaf(leaf_index, leaf, pos)
+    }
+
+    /// Returns an iterator to the smallest key that is greater than `key` (or the end, if there is none).
+    public fun upper_bound<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): IteratorPtr {
+        let leaf_index = find_leaf(self, key);
+        let leaf = table_with_length::borrow(&self.nodes, leaf_index);
+        let pos = upper_pos(&leaf.keys, key);
+        iter_in_leaf(leaf_index, leaf, pos)
+    }
+
+    /// Returns true iff the iterator points to the end of the map.
+    public fun iter_is_end(self: &IteratorPtr): bool {
+        self.node_index == NULL_INDEX
+    }
+
+    /// Returns true iff the iterator points to the smallest key of the map (or to the end of an empty map).
+    public fun iter_is_begin<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): bool {
+        if (map.size == 0) {
+            iter_is_end(self)
+        } else {
+            self.node_index == map.min_leaf_index && self.pos == 0
+        }
+    }
+
+    /// Returns a reference to the key the iterator points to.
+    /// Aborts if the iterator points to the end of the map.
+    public fun iter_borrow_key<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): &K {
+        assert!(!iter_is_end(self), error::invalid_argument(EITER_OUT_OF_BOUNDS));
+        vector::borrow(&table_with_length::borrow(&map.nodes, self.node_index).keys, self.pos)
+    }
+
+    /// Returns a reference to the value the iterator points to.
+    /// Aborts if the iterator points to the end of the map.
+    public fun iter_borrow<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): &V {
+        assert!(!iter_is_end(self), error::invalid_argument(EITER_OUT_OF_BOUNDS));
+        vector::borrow(&table_with_length::borrow(&map.nodes, self.node_index).values, self.pos)
+    }
+
+    /// Returns a mutable reference to the value the iterator points to.
+    /// Aborts if the iterator points to the end of the map.
+    public fun iter_borrow_mut<K: store, V: store>(self: &IteratorPtr, map: &mut BigOrderedMap<K, V>): &mut V {
+        assert!(!iter_is_end(self), error::invalid_argument(EITER_OUT_OF_BOUNDS));
+        vector::borrow_mut(&mut table_with_length::borrow_mut(&mut map.nodes, self.node_index).values, self.pos)
+    }
+
+    /// Returns an iterator to the next key in the map (or the end, if this is the largest key).
+    /// Aborts if the iterator points to the end of the map.
+    public fun iter_next<K: store, V: store>(self: IteratorPtr, map: &BigOrderedMap<K, V>): IteratorPtr {
+        assert!(!iter_is_end(&self), error::invalid_argument(EITER_OUT_OF_BOUNDS));
+        let leaf = table_with_length::borrow(&map.nodes, self.node_index);
+        iter_in_leaf(self.node_index, leaf, self.pos + 1)
+    }
+
+    /// Returns an iterator to the previous key in the map. The previous key of the end is the largest key.
+    /// Aborts if the iterator points to the smallest key of the map.
+    public fun iter_prev<K: store, V: store>(self: IteratorPtr, map: &BigOrderedMap<K, V>): IteratorPtr {
+        assert!(!iter_is_begin(&self, map), error::invalid_argument(EITER_OUT_OF_BOUNDS));
+        if (iter_is_end(&self)) {
+            let leaf = table_with_length::borrow(&map.nodes, map.max_leaf_index);
+            return IteratorPtr { node_index: map.max_leaf_index, pos: vector::length(&leaf.keys) - 1 }
+        };
+        if (self.pos > 0) {
+            return IteratorPtr { node_index: self.node_index, pos: self.pos - 1 }
+        };
+        let prev_index = table_with_length::borrow(&map.nodes, self.node_index).prev;
+        let prev_leaf = table_with_length::borrow(&map.nodes, prev_index);
+        IteratorPtr { node_index: prev_index, pos: vector::length(&prev_leaf.keys) - 1 }
+    }
+
+    //
+    // Private functions
+    //
+
+    /// Compares two values of the same type, returning `EQUAL`, `SMALLER` or `GREATER`.
+    native fun compare<K>(left: &K, right: &K): u8;
+
+    /// Returns the first position in `keys` with a key greater than or equal to `key`.
+    fun lower_pos<K>(keys: &vector<K>, key: &K): u64 {
+        let low = 0;
+        let high = vector::length(keys);
+        while (low < high) {
+            let mid = (low + high) / 2;
+            if (compare(vector::borrow(keys, mid), key) == SMALLER) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            };
+        };
+        low
+    }
+
+    /// Returns the first position in `keys` with a key greater than `key`.
+    fun upper_pos<K>(keys: &vector<K>, key: &K): u64 {
+        let low = 0;
+        let high = vector::length(keys);
+        while (low < high) {
+            let mid = (low + high) / 2;
+            if (compare(vector::borrow(keys, mid), key) != GREATER) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            };
+        };
+        low
+    }
+
+    /// Returns the leaf that contains `key`, if the map contains it.
+    fun find_leaf<K: store, V: store>(self: &BigOrderedMap<K, V>, key: &K): u64 {
+        let node_index = self.root_index;
+        loop {
+            let node = table_with_length::borrow(&self.nodes, node_index);
+            if (node.is_leaf) {
+                return node_index
+            };
+            node_index = *vector::borrow(&node.children, upper_pos(&node.keys, key));
+        }
+    }
+
+    /// Returns the nodes from the root to the leaf that contains `key`, if the map contains it.
+    fun find_path<K: store, V: store>(self: &BigOrderedMap<K, V>, key: &K): vector<u64> {
+        let path = vector[];
+        let node_index = self.root_index;
+        loop {
+            vector::push_back(&mut path, node_index);
+            let node = table_with_length::borrow(&self.nodes, node_index);
+            if (node.is_leaf) {
+                return path
+            };
+            node_index = *vector::borrow(&node.children, upper_pos(&node.keys, key));
+        }
+    }
+
+    /// Returns an iterator to the entry at `pos` in the leaf, or to the first entry of the next leaf if `pos` is past
+    /// the end of the leaf. Only the root leaf can be empty, so the next leaf, if any, has entries.
+    fun iter_in_leaf<K: store, V: store>(leaf_index: u64, leaf: &Node<K, V>, pos: u64): IteratorPtr {
+        if (pos < vector::length(&leaf.keys)) {
+            IteratorPtr { node_index: leaf_index, pos }
+        } else {
+            IteratorPtr { node_index: leaf.next, pos: 0 }
+        }
+    }
+
+    fun new_leaf<K: store, V: store>(keys: vector<K>, values: vector<V>, prev: u64, next: u64): Node<K, V> {
+        Node { is_leaf: true, keys, values, children: vector[], prev, next }
+    }
+
+    fun new_inner<K: store, V: store>(keys: vector<K>, children: vector<u64>): Node<K, V> {
+        Node { is_leaf: false, keys, values: vector[], children, prev: NULL_INDEX, next: NULL_INDEX }
+    }
+
+    fun add_node<K: store, V: store>(self: &mut BigOrderedMap<K, V>, node: Node<K, V>): u64 {
+        let node_index = self.next_node_index;
+        self.next_node_index = node_index + 1;
+        table_with_length::add(&mut self.nodes, node_index, node);
+        node_index
+    }
+
+    fun destroy_empty_node<K: store, V: store>(node: Node<K, V>) {
+        let Node { is_leaf: _, keys, values, children: _, prev: _, next: _ } = node;
+        vector::destroy_empty(keys);
+        vector::destroy_empty(values);
+    }
+
+    /// Moves the upper half of the entries of a leaf to a new leaf, inserted after it. Returns the smallest key of
+    /// the new leaf, and its index.
+    fun split_leaf<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, leaf_index: u64): (K, u64) {
+        let new_index = self.next_node_index;
+        let leaf = table_with_length::borrow_mut(&mut self.nodes, leaf_index);
+        let mid = vector::length(&leaf.keys) / 2;
+        let keys = vector::trim(&mut leaf.keys, mid);
+        let values = vector::trim(&mut leaf.values, mid);
+        let next = leaf.next;
+        leaf.next = new_index;
+        if (next == NULL_INDEX) {
+            self.max_leaf_index = new_index;
+        } else {
+            table_with_length::borrow_mut(&mut self.nodes, next).prev = new_index;
+        };
+        let separator = *vector::borrow(&keys, 0);
+        add_node(self, new_leaf(keys, values, leaf_index, next));
+        (separator, new_index)
+    }
+
+    /// Moves the upper half of the children of an inner node to a new inner node. Returns the key separating them,
+    /// and the index of the new node.
+    fun split_inner<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, node_index: u64): (K, u64) {
+        let node = table_with_length::borrow_mut(&mut self.nodes, node_index);
+        let mid = vector::length(&node.keys) / 2;
+        let keys = vector::trim(&mut node.keys, mid + 1);
+        let separator = vector::pop_back(&mut node.keys);
+        let children = vector::trim(&mut node.children, mid + 1);
+        let new_index = add_node(self, new_inner(keys, children));
+        (separator, new_index)
+    }
+
+    /// Splits a leaf that became too large after an entry was added to it, and then the nodes on `path` that became
+    /// too large, bottom-up. `path` is the path from the root to the parent of `leaf_index`.
+    fun split_path<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        leaf_index: u64,
+        path: vector<u64>,
+    ) {
+        let (separator, new_index) = split_leaf(self, leaf_index);
+        while (!vector::is_empty(&path)) {
+            let parent_index = vector::pop_back(&mut path);
+            let parent = table_with_length::borrow_mut(&mut self.nodes, parent_index);
+            let pos = upper_pos(&parent.keys, &separator);
+            vector::insert(&mut parent.keys, pos, separator);
+            vector::insert(&mut parent.children, pos + 1, new_index);
+            if (vector::length(&parent.children) <= self.inner_max_degree) {
+                return
+            };
+            (separator, new_index) = split_inner(self, parent_index);
+        };
+
+        // The root was split.
+        let new_root = new_inner(vector[separator], vector[self.root_index, new_index]);
+        self.root_index = add_node(self, new_root);
+    }
+
+    /// Restores the minimum size of the nodes after an entry was removed from a leaf, by merging the nodes that are
+    /// too small with a sibling, or moving entries from a sibling, bottom-up. `path` is the path from the root to the
+    /// parent of `node_index`.
+    fun rebalance<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        node_index: u64,
+        path: vector<u64>,
+    ) {
+        loop {
+            let node = table_with_length::borrow(&self.nodes, node_index);
+            if (vector::is_empty(&path)) {
+                // The root can be smaller, but an inner root with a single child is replaced by the child.
+                if (!node.is_leaf && vector::length(&node.children) == 1) {
+                    let Node { is_leaf: _, keys, values, children, prev: _, next: _ } =
+                        table_with_length::remove(&mut self.nodes, node_index);
+                    vector::destroy_empty(keys);
+                    vector::destroy_empty(values);
+                    self.root_index = vector::pop_back(&mut children);
+                };
+                return
+            };
+            let (size, max_degree) = if (node.is_leaf) {
+                (vector::length(&node.keys), self.leaf_max_degree)
+            } else {
+                (vector::length(&node.children), self.inner_max_degree)
+            };
+            if (size >= max_degree / 2) {
+                return
+            };
+
+            let parent_index = vector::pop_back(&mut path);
+            let parent = table_with_length::borrow(&self.nodes, parent_index);
+            let (_, child_pos) = vector::index_of(&parent.children, &node_index);
+            // Use the left sibling if there is one, otherwise the right one.
+            let left_pos = if (child_pos > 0) { child_pos - 1 } else { child_pos };
+            let left_index = *vector::borrow(&parent.children, left_pos);
+            let right_index = *vector::borrow(&parent.children, left_pos + 1);
+            let separator = *vector::borrow(&parent.keys, left_pos);
+            let right_size = {
+                let right = table_with_length::borrow(&self.nodes, right_index);
+                if (right.is_leaf) { vector::length(&right.keys) } else { vector::length(&right.children) }
+            };
+            let left_size = {
+                let left = table_with_length::borrow(&self.nodes, left_index);
+                if (left.is_leaf) { vector::length(&left.keys) } else { vector::length(&left.children) }
+            };
+
+            if (left_size + right_size <= max_degree) {
+                merge(self, left_index, right_index, separator);
+                let parent = table_with_length::borrow_mut(&mut self.nodes, parent_index);
+                vector::remove(&mut parent.keys, left_pos);
+                vector::remove(&mut parent.children, left_pos + 1);
+                // The parent lost a child, so it may be too small now.
+                node_index = parent_index;
+            } else {
+                let new_separator = redistribute(self, left_index, right_index, separator);
+                let parent = table_with_length::borrow_mut(&mut self.nodes, parent_index);
+                *vector::borrow_mut(&mut parent.keys, left_pos) = new_separator;
+                return
+            };
+        }
+    }
+
+    /// Moves all the entries (or children) of the right node to the left node, and removes the right node.
+    fun merge<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        left_index: u64,
+        right_index: u64,
+        separator: K,
+    ) {
+        let Node { is_leaf, keys, values, children, prev: _, next } =
+            table_with_length::remove(&mut self.nodes, right_index);
+        let left = table_with_length::borrow_mut(&mut self.nodes, left_index);
+        if (!is_leaf) {
+            vector::push_back(&mut left.keys, separator);
+        };
+        vector::append(&mut left.keys, keys);
+        vector::append(&mut left.values, values);
+        vector::append(&mut left.children, children);
+        if (is_leaf) {
+            left.next = next;
+            if (next == NULL_INDEX) {
+                self.max_leaf_index = left_index;
+            } else {
+                table_with_length::borrow_mut(&mut self.nodes, next).prev = left_index;
+            };
+        };
+    }
+
+    /// Moves entries (or children) between two sibling nodes so that they have the same size (within one). Returns
+    /// the new key separating them.
+    fun redistribute<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        left_index: u64,
+        right_index: u64,
+        separator: K,
+    ): K {
+        let Node { is_leaf, keys, values, children, prev: _, next } =
+            table_with_length::remove(&mut self.nodes, right_index);
+        let left = table_with_length::borrow_mut(&mut self.nodes, left_index);
+        let (right_node, new_separator) = if (is_leaf) {
+            vector::append(&mut left.keys, keys);
+            vector::append(&mut left.values, values);
+            vector::destroy_empty(children);
+            let mid = vector::length(&left.keys) / 2;
+            let keys = vector::trim(&mut left.keys, mid);
+            let values = vector::trim(&mut left.values, mid);
+            let new_separator = *vector::borrow(&keys, 0);
+            (new_leaf(keys, values, left_index, next), new_separator)
+        } else {
+            vector::push_back(&mut left.keys, separator);
+            vector::append(&mut left.keys, keys);
+            vector::append(&mut left.children, children);
+            vector::destroy_empty(values);
+            let mid = vector::length(&left.children) / 2;
+            let keys = vector::trim(&mut left.keys, mid);
+            let new_separator = vector::pop_back(&mut left.keys);
+            let children = vector::trim(&mut left.children, mid);
+            (new_inner(keys, children), new_separator)
+        };
+        table_with_length::add(&mut self.nodes, right_index, right_node);
+        new_separator
+    }
+
+    //
+    // Tests
+    //
+
+    #[test_only]
+    fun enable_natives_for_testing(fx: &signer) {
+        features::change_feature_flags_for_testing(fx, vector[ features::get_big_ordered_map_natives_feature() ], vector[]);
+    }
+
+    #[test_only]
+    /// Checks the invariants of the tree, and returns its keys in iteration order.
+    fun check_and_collect_keys<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>): vector<K> {
+        let keys = vector[];
+        let it = new_begin_iter(self);
+        while (!iter_is_end(&it)) {
+            let key = *iter_borrow_key(&it, self);
+            let num_keys = vector::length(&keys);
+            if (num_keys > 0) {
+                assert!(compare(vector::borrow(&keys, num_keys - 1), &key) == SMALLER, 1);
+            };
+            assert!(contains(self, &key), 2);
+            vector::push_back(&mut keys, key);
+            it = iter_next(it, self);
+        };
+        assert!(vector::length(&keys) == self.size, 3);
+        keys
+    }
+
+    #[test_only]
+    fun destroy_for_testing<K: copy + drop + store, V: drop + store>(self: BigOrderedMap<K, V>) {
+        let keys = check_and_collect_keys(&self);
+        vector::for_each_ref(&keys, |key| { remove(&mut self, key); });
+        destroy_empty(self);
+    }
+
+    #[test(fx = @std)]
+    fun test_add_remove_borrow(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        add(&mut map, 2, 20);
+        add(&mut map, 1, 10);
+        add(&mut map, 256, 2560);
+        assert!(length(&map) == 3, 1);
+        assert!(*borrow(&map, &1) == 10, 1);
+        assert!(*borrow(&map, &256) == 2560, 1);
+        *borrow_mut(&mut map, &2) = 21;
+        assert!(*borrow(&map, &2) == 21, 1);
+        // Integers are ordered by value, not by their serialization.
+        assert!(check_and_collect_keys(&map) == vector[1, 2, 256], 1);
+
+        assert!(upsert(&mut map, 2, 22) == option::some(21), 1);
+        assert!(upsert(&mut map, 3, 30) == option::none(), 1);
+        assert!(remove(&mut map, &1) == 10, 1);
+        assert!(!contains(&map, &1), 1);
+        assert!(check_and_collect_keys(&map) == vector[2, 3, 256], 1);
+        destroy_for_testing(map);
+    }
+
+    #[test(fx = @std)]
+    fun test_many_entries(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new_with_config<u64, u64>(4, 4);
+        // Insert 0..500 in a scrambled order.
+        let i = 0;
+        while (i < 500) {
+            let key = (i * 7919) % 500;
+            add(&mut map, key, key * 10);
+            i = i + 1;
+        };
+        assert!(check_and_collect_keys(&map) == vector::range(0, 500), 1);
+
+        // Remove the odd keys.
+        let i = 1;
+        while (i < 500) {
+            assert!(remove(&mut map, &i) == i * 10, 1);
+            i = i + 2;
+        };
+        assert!(check_and_collect_keys(&map) == vector::range_with_step(0, 500, 2), 1);
+
+        // Remove the remaining keys from the largest one.
+        let i = 500;
+        while (i > 0) {
+            i = i - 2;
+            assert!(remove(&mut map, &i) == i * 10, 1);
+            if (i % 50 == 0) {
+                assert!(check_and_collect_keys(&map) == vector::range_with_step(0, i, 2), 1);
+            };
+        };
+        assert!(is_empty(&map), 1);
+        assert!(table_with_length::length(&map.nodes) == 1, 1);
+        destroy_empty(map);
+    }
+
+    #[test(fx = @std)]
+    fun test_bounds_and_iteration(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new_with_config<u64, u64>(4, 4);
+        let i = 0;
+        while (i < 100) {
+            add(&mut map, i * 10, i);
+            i = i + 1;
+        };
+
+        let it = lower_bound(&map, &250);
+        assert!(*iter_borrow_key(&it, &map) == 250, 1);
+        let it = upper_bound(&map, &250);
+        assert!(*iter_borrow_key(&it, &map) == 260, 1);
+        let it = lower_bound(&map, &251);
+        assert!(*iter_borrow_key(&it, &map) == 260, 1);
+        assert!(iter_is_end(&lower_bound(&map, &991)), 1);
+        assert!(iter_is_end(&upper_bound(&map, &990)), 1);
+        assert!(iter_is_begin(&lower_bound(&map, &0), &map), 1);
+
+        // Iterate over the range [95, 305).
+        let sum = 0;
+        let it = lower_bound(&map, &95);
+        while (!iter_is_end(&it) && *iter_borrow_key(&it, &map) < 305) {
+            sum = sum + *iter_borrow(&it, &map);
+            *iter_borrow_mut(&it, &mut map) = 0;
+            it = iter_next(it, &map);
+        };
+        // The keys 100, 110, ..., 300 have the values 10, 11, ..., 30.
+        assert!(sum == 420, 1);
+        assert!(*borrow(&map, &100) == 0 && *borrow(&map, &310) == 31, 1);
+
+        // Iterate backwards from the end.
+        let it = new_end_iter(&map);
+        let expected = 100;
+        while (!iter_is_begin(&it, &map)) {
+            it = iter_prev(it, &map);
+            expected = expected - 1;
+            assert!(*iter_borrow_key(&it, &map) == expected * 10, 1);
+        };
+        assert!(expected == 0, 1);
+
+        destroy_for_testing(map);
+    }
+
+    #[test_only]
+    struct Order has copy, drop, store {
+        price: u64,
+        sequence_number: u64,
+    }
+
+    #[test(fx = @std)]
+    fun test_struct_keys(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<Order, address>();
+        add(&mut map, Order { price: 100, sequence_number: 2 }, @0x2);
+        add(&mut map, Order { price: 99, sequence_number: 3 }, @0x3);
+        add(&mut map, Order { price: 100, sequence_number: 1 }, @0x1);
+        assert!(
+            check_and_collect_keys(&map) == vector[
+                Order { price: 99, sequence_number: 3 },
+                Order { price: 100, sequence_number: 1 },
+                Order { price: 100, sequence_number: 2 },
+            ],
+            1
+        );
+        // The best (lowest) ask at a price of at least 100.
+        let it = lower_bound(&map, &Order { price: 100, sequence_number: 0 });
+        assert!(*iter_borrow(&it, &map) == @0x1, 1);
+        destroy_for_testing(map);
+    }
+
+    #[test(fx = @std)]
+    fun test_empty_map_iteration(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        assert!(iter_is_end(&new_begin_iter(&map)), 1);
+        assert!(iter_is_begin(&new_end_iter(&map), &map), 1);
+        assert!(iter_is_end(&lower_bound(&map, &1)), 1);
+        destroy_empty(map);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0x30006, location = Self)]
+    fun test_natives_not_enabled() {
+        destroy_empty(new<u64, u64>());
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x80001, location = Self)]
+    fun test_add_existing_key(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        add(&mut map, 1, 1);
+        add(&mut map, 1, 2);
+        destroy_for_testing(map);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x60002, location = Self)]
+    fun test_remove_missing_key(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        add(&mut map, 1, 1);
+        remove(&mut map, &2);
+        destroy_for_testing(map);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x10003, location = Self)]
+    fun test_destroy_non_empty(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        add(&mut map, 1, 1);
+        destroy_empty(map);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 0x10004, location = Self)]
+    fun test_iter_next_past_end(fx: signer) {
+        enable_natives_for_testing(&fx);
+        let map = new<u64, u64>();
+        add(&mut map, 1, 1);
+        let it = iter_next(new_begin_iter(&map), &map);
+        iter_next(it, &map);
+        destroy_for_testing(map);
+    }
+}
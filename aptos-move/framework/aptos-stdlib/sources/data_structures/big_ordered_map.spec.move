@@ -0,0 +1,269 @@
+spec aptos_std::big_ordered_map {
+    spec BigOrderedMap {
+        invariant root_index != NULL_INDEX;
+        invariant min_leaf_index != NULL_INDEX;
+        invariant max_leaf_index != NULL_INDEX;
+        invariant next_node_index > root_index;
+        invariant inner_max_degree >= MIN_DEGREE && inner_max_degree <= MAX_DEGREE;
+        invariant leaf_max_degree >= MIN_DEGREE && leaf_max_degree <= MAX_DEGREE;
+    }
+
+    // The lookup of a key is modeled by the leaf the path from the root leads to, and the position of the key in
+    // it. The tree walk and the binary searches are opaque, so that the operations on a single key can be verified
+    // without the invariants of the whole tree.
+
+    /// The leaf that contains `key`, if the map contains it.
+    spec fun spec_find_leaf<K, V>(self: BigOrderedMap<K, V>, key: K): u64;
+
+    /// The first position in `keys` with a key greater than or equal to `key`.
+    spec fun spec_lower_pos<K>(keys: vector<K>, key: K): u64;
+
+    /// The first position in `keys` with a key greater than `key`.
+    spec fun spec_upper_pos<K>(keys: vector<K>, key: K): u64;
+
+    spec fun spec_leaf<K, V>(self: BigOrderedMap<K, V>, key: K): Node<K, V> {
+        table_with_length::spec_get(self.nodes, spec_find_leaf(self, key))
+    }
+
+    spec fun spec_contains<K, V>(self: BigOrderedMap<K, V>, key: K): bool {
+        let keys = spec_leaf(self, key).keys;
+        let pos = spec_lower_pos(keys, key);
+        pos < len(keys) && keys[pos] == key
+    }
+
+    spec fun spec_get<K, V>(self: BigOrderedMap<K, V>, key: K): V {
+        let leaf = spec_leaf(self, key);
+        leaf.values[spec_lower_pos(leaf.keys, key)]
+    }
+
+    /// A leaf that is stored in the map, has a value for every key, and is empty if the map is.
+    spec fun spec_is_valid_leaf<K, V>(self: BigOrderedMap<K, V>, leaf_index: u64): bool {
+        let leaf = table_with_length::spec_get(self.nodes, leaf_index);
+        table_with_length::spec_contains(self.nodes, leaf_index)
+            && leaf.is_leaf
+            && len(leaf.keys) == len(leaf.values)
+            && (self.size == 0 ==> len(leaf.keys) == 0)
+    }
+
+    spec new_with_config<K: copy + drop + store, V: store>(
+        inner_max_degree: u64,
+        leaf_max_degree: u64,
+    ): BigOrderedMap<K, V> {
+        aborts_if !features::spec_big_ordered_map_natives_enabled();
+        aborts_if inner_max_degree < MIN_DEGREE || inner_max_degree > MAX_DEGREE;
+        aborts_if leaf_max_degree < MIN_DEGREE || leaf_max_degree > MAX_DEGREE;
+        ensures result.size == 0;
+        ensures result.root_index == 1;
+        ensures result.min_leaf_index == 1 && result.max_leaf_index == 1;
+        ensures result.inner_max_degree == inner_max_degree;
+        ensures result.leaf_max_degree == leaf_max_degree;
+    }
+
+    spec destroy_empty<K: store, V: store>(self: BigOrderedMap<K, V>) {
+        pragma verify = false;
+        aborts_if self.size != 0;
+    }
+
+    spec length<K: store, V: store>(self: &BigOrderedMap<K, V>): u64 {
+        aborts_if false;
+        ensures result == self.size;
+    }
+
+    spec is_empty<K: store, V: store>(self: &BigOrderedMap<K, V>): bool {
+        aborts_if false;
+        ensures result == (self.size == 0);
+    }
+
+    spec add<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: K, value: V) {
+        aborts_if spec_contains(self, key);
+        aborts_if self.size + 1 > MAX_U64;
+        ensures self.size == old(self.size) + 1;
+    }
+
+    spec upsert<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: K, value: V): Option<V> {
+        aborts_if !spec_contains(self, key) && self.size + 1 > MAX_U64;
+        ensures old(spec_contains(self, key)) ==> result == option::spec_some(old(spec_get(self, key)));
+        ensures old(spec_contains(self, key)) ==> self.size == old(self.size);
+        ensures !old(spec_contains(self, key)) ==> option::is_none(result);
+        ensures !old(spec_contains(self, key)) ==> self.size == old(self.size) + 1;
+    }
+
+    spec remove<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: &K): V {
+        aborts_if !spec_contains(self, key);
+        ensures result == old(spec_get(self, key));
+        ensures self.size == old(self.size) - 1;
+    }
+
+    spec contains<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): bool {
+        ensures result == spec_contains(self, key);
+    }
+
+    spec borrow<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): &V {
+        aborts_if !spec_contains(self, key);
+        ensures result == spec_get(self, key);
+    }
+
+    spec borrow_mut<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, key: &K): &mut V {
+        aborts_if !spec_contains(self, key);
+    }
+
+    spec new_begin_iter<K: store, V: store>(self: &BigOrderedMap<K, V>): IteratorPtr {
+        aborts_if false;
+        ensures self.size == 0 ==> result.node_index == NULL_INDEX;
+        ensures self.size != 0 ==> result == IteratorPtr { node_index: self.min_leaf_index, pos: 0 };
+    }
+
+    spec new_end_iter<K: store, V: store>(_self: &BigOrderedMap<K, V>): IteratorPtr {
+        aborts_if false;
+        ensures result.node_index == NULL_INDEX;
+    }
+
+    spec lower_bound<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): IteratorPtr {
+        let leaf_index = spec_find_leaf(self, key);
+        let leaf = spec_leaf(self, key);
+        let pos = spec_lower_pos(leaf.keys, key);
+        ensures pos < len(leaf.keys) ==> result == IteratorPtr { node_index: leaf_index, pos };
+        ensures pos >= len(leaf.keys) ==> result == IteratorPtr { node_index: leaf.next, pos: 0 };
+    }
+
+    spec upper_bound<K: copy + drop + store, V: store>(self: &BigOrderedMap<K, V>, key: &K): IteratorPtr {
+        let leaf_index = spec_find_leaf(self, key);
+        let leaf = spec_leaf(self, key);
+        let pos = spec_upper_pos(leaf.keys, key);
+        ensures pos < len(leaf.keys) ==> result == IteratorPtr { node_index: leaf_index, pos };
+        ensures pos >= len(leaf.keys) ==> result == IteratorPtr { node_index: leaf.next, pos: 0 };
+    }
+
+    spec iter_is_end(self: &IteratorPtr): bool {
+        aborts_if false;
+        ensures result == (self.node_index == NULL_INDEX);
+    }
+
+    spec iter_is_begin<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): bool {
+        aborts_if false;
+        ensures map.size == 0 ==> result == (self.node_index == NULL_INDEX);
+        ensures map.size != 0 ==> result == (self.node_index == map.min_leaf_index && self.pos == 0);
+    }
+
+    spec iter_borrow_key<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): &K {
+        pragma verify = false;
+        aborts_if self.node_index == NULL_INDEX;
+    }
+
+    spec iter_borrow<K: store, V: store>(self: &IteratorPtr, map: &BigOrderedMap<K, V>): &V {
+        pragma verify = false;
+        aborts_if self.node_index == NULL_INDEX;
+    }
+
+    spec iter_borrow_mut<K: store, V: store>(self: &IteratorPtr, map: &mut BigOrderedMap<K, V>): &mut V {
+        pragma verify = false;
+        aborts_if self.node_index == NULL_INDEX;
+    }
+
+    spec iter_next<K: store, V: store>(self: IteratorPtr, map: &BigOrderedMap<K, V>): IteratorPtr {
+        pragma verify = false;
+        aborts_if self.node_index == NULL_INDEX;
+    }
+
+    spec iter_prev<K: store, V: store>(self: IteratorPtr, map: &BigOrderedMap<K, V>): IteratorPtr {
+        pragma verify = false;
+    }
+
+    spec compare<K>(left: &K, right: &K): u8 {
+        pragma opaque;
+        aborts_if false;
+        ensures result == EQUAL || result == SMALLER || result == GREATER;
+        ensures (result == EQUAL) == (left == right);
+    }
+
+    // The binary searches and the tree walks are assumed to be correct, which the unit tests check.
+
+    spec lower_pos<K>(keys: &vector<K>, key: &K): u64 {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures result == spec_lower_pos(keys, key);
+        ensures result <= len(keys);
+    }
+
+    spec upper_pos<K>(keys: &vector<K>, key: &K): u64 {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures result == spec_upper_pos(keys, key);
+        ensures result <= len(keys);
+    }
+
+    spec find_leaf<K: store, V: store>(self: &BigOrderedMap<K, V>, key: &K): u64 {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures result == spec_find_leaf(self, key);
+        ensures spec_is_valid_leaf(self, result);
+    }
+
+    spec find_path<K: store, V: store>(self: &BigOrderedMap<K, V>, key: &K): vector<u64> {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures len(result) > 0;
+        ensures result[len(result) - 1] == spec_find_leaf(self, key);
+        ensures spec_is_valid_leaf(self, spec_find_leaf(self, key));
+    }
+
+    spec iter_in_leaf<K: store, V: store>(leaf_index: u64, leaf: &Node<K, V>, pos: u64): IteratorPtr {
+        aborts_if false;
+        ensures pos < len(leaf.keys) ==> result == IteratorPtr { node_index: leaf_index, pos };
+        ensures pos >= len(leaf.keys) ==> result == IteratorPtr { node_index: leaf.next, pos: 0 };
+    }
+
+    // Restructuring the tree keeps its entries, and so its size.
+
+    spec split_path<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        leaf_index: u64,
+        path: vector<u64>,
+    ) {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures self.size == old(self.size);
+    }
+
+    spec rebalance<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        node_index: u64,
+        path: vector<u64>,
+    ) {
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures self.size == old(self.size);
+    }
+
+    spec merge<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        left_index: u64,
+        right_index: u64,
+        separator: K,
+    ) {
+        pragma verify = false;
+    }
+
+    spec redistribute<K: copy + drop + store, V: store>(
+        self: &mut BigOrderedMap<K, V>,
+        left_index: u64,
+        right_index: u64,
+        separator: K,
+    ): K {
+        pragma verify = false;
+    }
+
+    spec split_leaf<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, leaf_index: u64): (K, u64) {
+        pragma verify = false;
+    }
+
+    spec split_inner<K: copy + drop + store, V: store>(self: &mut BigOrderedMap<K, V>, node_index: u64): (K, u64) {
+        pragma verify = false;
+    }
+}
@@ -603,6 +603,39 @@ module std::features {
         is_enabled(COLLECTION_OWNER)
     }
 
+    /// Whether the secp256r1 (P-256) ECDSA module is enabled, and the related native functions are available.
+    /// This is needed because of the introduction of new native functions.
+    /// Lifetime: transient
+    const SECP256R1_NATIVES: u64 = 80;
+
+    public fun get_secp256r1_natives_feature(): u64 { SECP256R1_NATIVES }
+
+    public fun secp256r1_natives_enabled(): bool acquires Features {
+        is_enabled(SECP256R1_NATIVES)
+    }
+
+    /// Whether the Poseidon-BN254 hash module is enabled, and the related native function is available.
+    /// This is needed because of the introduction of a new native function.
+    /// Lifetime: transient
+    const POSEIDON_BN254_NATIVES: u64 = 81;
+
+    public fun get_poseidon_bn254_natives_feature(): u64 { POSEIDON_BN254_NATIVES }
+
+    public fun poseidon_bn254_natives_enabled(): bool acquires Features {
+        is_enabled(POSEIDON_BN254_NATIVES)
+    }
+
+    /// Whether the key comparison native used by `aptos_std::big_ordered_map` is available.
+    /// This is needed because of the introduction of a new native function.
+    /// Lifetime: transient
+    const BIG_ORDERED_MAP_NATIVES: u64 = 82;
+
+    public fun get_big_ordered_map_natives_feature(): u64 { BIG_ORDERED_MAP_NATIVES }
+
+    public fun big_ordered_map_natives_enabled(): bool acquires Features {
+        is_enabled(BIG_ORDERED_MAP_NATIVES)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
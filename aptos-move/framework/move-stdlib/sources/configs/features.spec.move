@@ -121,4 +121,8 @@ spec std::features {
     spec fun spec_sha_512_and_ripemd_160_enabled(): bool {
         spec_is_enabled(SHA_512_AND_RIPEMD_160_NATIVES)
     }
+
+    spec fun spec_big_ordered_map_natives_enabled(): bool {
+        spec_is_enabled(BIG_ORDERED_MAP_NATIVES)
+    }
 }
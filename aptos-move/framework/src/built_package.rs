@@ -165,6 +165,24 @@ pub struct BuiltPackage {
     pub package: CompiledPackage,
 }
 
+/// Identifies this build of the compiler in the keys of the build cache of compiled dependencies.
+/// Builds from a checkout with local changes cannot be identified by their commit, so they do not
+/// use the cache.
+pub fn compiler_build() -> Option<String> {
+    let build_information = aptos_build_info::get_build_information();
+    if build_information
+        .get(aptos_build_info::BUILD_CLEAN_CHECKOUT)
+        .map(String::as_str)
+        != Some("true")
+    {
+        return None;
+    }
+    build_information
+        .get(aptos_build_info::BUILD_COMMIT_HASH)
+        .filter(|commit_hash| !commit_hash.is_empty())
+        .cloned()
+}
+
 pub fn build_model(
     dev_mode: bool,
     package_path: &Path,
@@ -196,6 +214,9 @@ pub fn build_model(
         force_recompilation: false,
         fetch_deps_only: false,
         skip_fetch_latest_git_deps: true,
+        update_lock_file: false,
+        build_cache: false,
+        compiler_build: compiler_build(),
         compiler_config: CompilerConfig {
             bytecode_version,
             compiler_version,
@@ -241,6 +262,9 @@ impl BuiltPackage {
             force_recompilation: false,
             fetch_deps_only: false,
             skip_fetch_latest_git_deps: options.skip_fetch_latest_git_deps,
+            update_lock_file: false,
+            build_cache: false,
+            compiler_build: compiler_build(),
             compiler_config: CompilerConfig {
                 bytecode_version,
                 compiler_version,
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
+use aptos_native_interface::{
+    safely_pop_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeResult,
+};
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{Reference, Value},
+};
+use smallvec::{smallvec, SmallVec};
+use std::{cmp::Ordering, collections::VecDeque};
+
+// !!!! NOTE !!!!
+// This file is intended for natives from the big_ordered_map module in the framework.
+// DO NOT PUT HELPER FUNCTIONS HERE!
+
+/// The results of `compare`, which must match the constants in the Move implementation.
+const EQUAL: u8 = 0;
+const SMALLER: u8 = 1;
+const GREATER: u8 = 2;
+
+/***************************************************************************************************
+ * native fun compare
+ *
+ *   gas cost: base_cost + unit_cost * (abstract_size(left) + abstract_size(right))
+ *
+ **************************************************************************************************/
+fn native_compare(
+    context: &mut SafeNativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert_eq!(ty_args.len(), 1);
+    debug_assert_eq!(args.len(), 2);
+
+    let right = safely_pop_arg!(args, Reference).read_ref()?;
+    let left = safely_pop_arg!(args, Reference).read_ref()?;
+
+    context.charge(
+        BIG_ORDERED_MAP_COMPARE_BASE
+            + BIG_ORDERED_MAP_COMPARE_PER_ABS_VAL_UNIT
+                * (context.abs_val_size(&left) + context.abs_val_size(&right)),
+    )?;
+
+    let result = match left.compare(&right)? {
+        Ordering::Equal => EQUAL,
+        Ordering::Less => SMALLER,
+        Ordering::Greater => GREATER,
+    };
+    Ok(smallvec![Value::u8(result)])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    builder: &SafeNativeBuilder,
+) -> impl Iterator<Item = (String, NativeFunction)> + '_ {
+    let natives = [("compare", native_compare as RawSafeNative)];
+
+    builder.make_named_natives(natives)
+}
@@ -203,7 +203,7 @@ macro_rules! ark_msm_internal {
                 abort_code: MOVE_ABORT_CODE_INPUT_VECTOR_SIZES_NOT_MATCHING,
             });
         }
-        let mut bases = Vec::with_capacity(num_elements);
+        let mut elements = Vec::with_capacity(num_elements);
         $context.charge($proj_to_affine_cost * NumArgs::from(num_elements as u64))?;
         for handle in element_handles {
             safe_borrow_element!(
@@ -213,12 +213,15 @@ macro_rules! ark_msm_internal {
                 element_ptr,
                 element
             );
-            bases.push(element.into_affine());
+            elements.push(*element);
         }
+        // NOTE: Batch normalization shares a single field inversion across all the elements, so it
+        // is cheaper than the per-element conversion the gas above is charged for.
+        let bases = <$element_typ>::normalize_batch(&elements);
         let mut scalars = Vec::with_capacity(num_scalars);
         for handle in scalar_handles {
             safe_borrow_element!($context, handle as usize, $scalar_typ, scalar_ptr, scalar);
-            scalars.push(scalar.clone());
+            scalars.push(*scalar);
         }
         $context.charge(ark_msm_bigint_wnaf_cost!(
             $proj_add_cost,
@@ -39,6 +39,7 @@ pub mod eq;
 pub mod hash_to_structure;
 pub mod new;
 pub mod pairing;
+pub mod poseidon;
 #[cfg(feature = "testing")]
 pub mod rand;
 pub mod serialization;
@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    natives::cryptography::algebra::{
+        abort_invariant_violated, AlgebraContext, E_TOO_MUCH_MEMORY_USED, MEMORY_LIMIT_IN_BYTES,
+    },
+    safe_borrow_element, store_element,
+};
+use aptos_crypto::poseidon_bn254;
+use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
+use aptos_native_interface::{
+    safely_pop_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeError,
+    SafeNativeResult,
+};
+use move_core_types::gas_algebra::NumArgs;
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use smallvec::{smallvec, SmallVec};
+use std::{collections::VecDeque, rc::Rc};
+
+/// Equivalent to `std::error::invalid_argument(1)` in Move.
+/// NOTE: This must match the code in the Move implementation
+const E_WRONG_NUM_INPUTS: u64 = 0x01_0001;
+
+/***************************************************************************************************
+ * native fun hash_bn254_internal
+ *
+ *   gas cost: base_cost + per_input_cost * num_inputs
+ *
+ **************************************************************************************************/
+fn hash_bn254_internal(
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(args.len() == 1);
+
+    let handles = safely_pop_arg!(args, Vec<u64>);
+    let num_inputs = handles.len();
+    if num_inputs == 0 || num_inputs > poseidon_bn254::MAX_NUM_INPUT_SCALARS {
+        return Err(SafeNativeError::Abort {
+            abort_code: E_WRONG_NUM_INPUTS,
+        });
+    }
+
+    context
+        .charge(POSEIDON_BN254_BASE + POSEIDON_BN254_PER_INPUT * NumArgs::new(num_inputs as u64))?;
+
+    let mut inputs = Vec::with_capacity(num_inputs);
+    for handle in handles {
+        safe_borrow_element!(context, handle as usize, ark_bn254::Fr, input_ptr, input);
+        inputs.push(*input);
+    }
+    // NOTE: The number of inputs was checked above, so this cannot fail.
+    let output = poseidon_bn254::hash_scalars(inputs).map_err(|_| abort_invariant_violated())?;
+    let output_handle = store_element!(context, output)?;
+    Ok(smallvec![Value::u64(output_handle as u64)])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    builder: &SafeNativeBuilder,
+) -> impl Iterator<Item = (String, NativeFunction)> + '_ {
+    let natives = [("hash_bn254_internal", hash_bn254_internal as RawSafeNative)];
+
+    builder.make_named_natives(natives)
+}
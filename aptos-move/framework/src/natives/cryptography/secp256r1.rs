@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::{secp256r1_ecdsa, traits::*};
+use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
+use aptos_native_interface::{
+    safely_pop_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeResult,
+};
+use move_core_types::gas_algebra::{NumArgs, NumBytes};
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use smallvec::{smallvec, SmallVec};
+use std::{collections::VecDeque, convert::TryFrom};
+
+/// The SEC1 tag of an uncompressed point, which is prepended to the 64-byte raw public keys
+/// (i.e., x || y) used in Move.
+const SEC1_UNCOMPRESSED_TAG: u8 = 0x04;
+
+/// Deserializes a 64-byte raw public key, checking that it is a point on the P-256 curve.
+fn deserialize_raw_public_key(bytes: &[u8]) -> Option<secp256r1_ecdsa::PublicKey> {
+    if bytes.len() != secp256r1_ecdsa::PUBLIC_KEY_LENGTH - 1 {
+        return None;
+    }
+    let mut sec1_bytes = Vec::with_capacity(secp256r1_ecdsa::PUBLIC_KEY_LENGTH);
+    sec1_bytes.push(SEC1_UNCOMPRESSED_TAG);
+    sec1_bytes.extend_from_slice(bytes);
+    secp256r1_ecdsa::PublicKey::try_from(sec1_bytes.as_slice()).ok()
+}
+
+/***************************************************************************************************
+ * native fun ecdsa_raw_public_key_validate_internal
+ *
+ *   gas cost: base_cost + per_pubkey_deserialize_cost
+ *
+ **************************************************************************************************/
+fn native_ecdsa_raw_public_key_validate(
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 1);
+
+    let pubkey = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(SECP256R1_BASE + SECP256R1_PER_PUBKEY_DESERIALIZE * NumArgs::one())?;
+
+    let valid = deserialize_raw_public_key(&pubkey).is_some();
+    Ok(smallvec![Value::bool(valid)])
+}
+
+/***************************************************************************************************
+ * native fun ecdsa_verify_internal
+ *
+ *   gas cost: base_cost + per_pubkey_deserialize_cost +? per_sig_deserialize_cost +?
+ *             (per_msg_byte_hashing_cost * |msg| + ecdsa_verify_cost)
+ *
+ * where +? indicates that the expression stops evaluating there if the previous gas-charging step
+ * failed
+ **************************************************************************************************/
+fn native_ecdsa_verify(
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 3);
+
+    let signature = safely_pop_arg!(arguments, Vec<u8>);
+    let pubkey = safely_pop_arg!(arguments, Vec<u8>);
+    let msg = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(SECP256R1_BASE + SECP256R1_PER_PUBKEY_DESERIALIZE * NumArgs::one())?;
+
+    let pk = match deserialize_raw_public_key(&pubkey) {
+        Some(pk) => pk,
+        None => return Ok(smallvec![Value::bool(false)]),
+    };
+
+    context.charge(SECP256R1_PER_SIG_DESERIALIZE * NumArgs::one())?;
+
+    // NOTE: This rejects high-S (i.e., malleable) signatures.
+    let sig = match secp256r1_ecdsa::Signature::try_from(signature.as_slice()) {
+        Ok(sig) => sig,
+        Err(_) => return Ok(smallvec![Value::bool(false)]),
+    };
+
+    // NOTE(Gas): SHA2-256 hashing of the message and a size-2 multi-scalar multiplication
+    context.charge(
+        SECP256R1_PER_MSG_BYTE_HASHING * NumBytes::new(msg.len() as u64)
+            + SECP256R1_ECDSA_VERIFY * NumArgs::one(),
+    )?;
+
+    let verify_result = sig.verify_arbitrary_msg(msg.as_slice(), &pk).is_ok();
+    Ok(smallvec![Value::bool(verify_result)])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    builder: &SafeNativeBuilder,
+) -> impl Iterator<Item = (String, NativeFunction)> + '_ {
+    let natives = [
+        (
+            "ecdsa_raw_public_key_validate_internal",
+            native_ecdsa_raw_public_key_validate as RawSafeNative,
+        ),
+        ("ecdsa_verify_internal", native_ecdsa_verify),
+    ];
+
+    builder.make_named_natives(natives)
+}
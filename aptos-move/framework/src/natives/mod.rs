@@ -4,6 +4,7 @@
 
 pub mod account;
 pub mod aggregator_natives;
+pub mod big_ordered_map;
 pub mod code;
 pub mod consensus_config;
 pub mod create_signer;
@@ -55,10 +56,15 @@ pub fn all_natives(
     add_natives_from_module!("create_signer", create_signer::make_all(builder));
     add_natives_from_module!("ed25519", ed25519::make_all(builder));
     add_natives_from_module!("crypto_algebra", cryptography::algebra::make_all(builder));
+    add_natives_from_module!(
+        "poseidon",
+        cryptography::algebra::poseidon::make_all(builder)
+    );
     add_natives_from_module!("genesis", create_signer::make_all(builder));
     add_natives_from_module!("multi_ed25519", multi_ed25519::make_all(builder));
     add_natives_from_module!("bls12381", cryptography::bls12381::make_all(builder));
     add_natives_from_module!("secp256k1", cryptography::secp256k1::make_all(builder));
+    add_natives_from_module!("secp256r1", cryptography::secp256r1::make_all(builder));
     add_natives_from_module!("aptos_hash", hash::make_all(builder));
     add_natives_from_module!(
         "ristretto255",
@@ -66,6 +72,7 @@ pub fn all_natives(
     );
     add_natives_from_module!("type_info", type_info::make_all(builder));
     add_natives_from_module!("util", util::make_all(builder));
+    add_natives_from_module!("big_ordered_map", big_ordered_map::make_all(builder));
     add_natives_from_module!("from_bcs", util::make_all(builder));
     add_natives_from_module!("randomness", randomness::make_all(builder));
     add_natives_from_module!(
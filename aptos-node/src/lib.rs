@@ -618,6 +618,14 @@ pub fn setup_environment_and_start_node(
     // Log the node config at node startup
     node_config.log_all_configs();
 
+    // Start exporting transaction and block lifecycle spans (if enabled)
+    let node_name = node_config
+        .get_peer_id()
+        .map_or_else(|| "unknown".to_string(), |peer_id| peer_id.to_string());
+    if let Err(error) = aptos_lifecycle_tracing::init(&node_config.lifecycle_tracing, node_name) {
+        warn!("Failed to initialize lifecycle tracing: {:?}", error);
+    }
+
     // Starts the admin service
     let mut admin_service = services::start_admin_service(&node_config);
 
@@ -45,7 +45,10 @@ pub fn create_logger(
         logger_builder.enable_backtrace();
     }
     if let Some(log_file) = log_file {
-        logger_builder.printer(Box::new(FileWriter::new(log_file)));
+        logger_builder.printer(Box::new(FileWriter::new_with_rotation(
+            log_file,
+            node_config.logger.file_rotation.rotation_policy(),
+        )));
     }
     if node_config.logger.enable_telemetry_remote_log {
         let (tx, rx) = mpsc::channel(TELEMETRY_LOG_INGEST_BUFFER_SIZE);
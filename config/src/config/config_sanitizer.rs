@@ -6,8 +6,8 @@ use crate::config::{
     node_config_loader::NodeType,
     utils::{are_failpoints_enabled, get_config_name},
     AdminServiceConfig, ApiConfig, BaseConfig, ConsensusConfig, DagConsensusConfig, Error,
-    ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig,
-    NetbenchConfig, NodeConfig, StateSyncConfig, StorageConfig,
+    ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig, LifecycleTracingConfig,
+    LoggerConfig, MempoolConfig, NetbenchConfig, NodeConfig, StateSyncConfig, StorageConfig,
 };
 use aptos_types::chain_id::ChainId;
 use std::collections::HashSet;
@@ -58,6 +58,7 @@ impl ConfigSanitizer for NodeConfig {
         sanitize_fullnode_network_configs(node_config, node_type, chain_id)?;
         IndexerGrpcConfig::sanitize(node_config, node_type, chain_id)?;
         InspectionServiceConfig::sanitize(node_config, node_type, chain_id)?;
+        LifecycleTracingConfig::sanitize(node_config, node_type, chain_id)?;
         LoggerConfig::sanitize(node_config, node_type, chain_id)?;
         MempoolConfig::sanitize(node_config, node_type, chain_id)?;
         NetbenchConfig::sanitize(node_config, node_type, chain_id)?;
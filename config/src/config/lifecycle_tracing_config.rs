@@ -0,0 +1,100 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// Config for tracing transactions and blocks through the API, mempool,
+/// quorum store, consensus pipeline and executor, exported as OpenTelemetry
+/// spans over OTLP.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LifecycleTracingConfig {
+    /// Whether to record and export lifecycle spans
+    pub enabled: bool,
+    /// The OTLP gRPC endpoint of the collector to export spans to
+    pub otlp_endpoint: String,
+    /// The fraction of transactions (by hash) to trace. The decision is a
+    /// function of the hash, so all nodes trace the same transactions.
+    pub transaction_sample_rate: f64,
+    /// The fraction of blocks (by block id) to trace
+    pub block_sample_rate: f64,
+    /// The maximum number of spans buffered before new ones are dropped
+    pub max_queue_size: usize,
+    /// The maximum number of spans exported in a single batch
+    pub max_export_batch_size: usize,
+    /// How often buffered spans are exported (in milliseconds)
+    pub export_interval_ms: u64,
+}
+
+impl Default for LifecycleTracingConfig {
+    fn default() -> LifecycleTracingConfig {
+        LifecycleTracingConfig {
+            enabled: false,
+            otlp_endpoint: "http://localhost:4317".to_string(),
+            transaction_sample_rate: 0.001,
+            block_sample_rate: 0.01,
+            max_queue_size: 10_000,
+            max_export_batch_size: 512,
+            export_interval_ms: 5_000,
+        }
+    }
+}
+
+impl ConfigSanitizer for LifecycleTracingConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let tracing_config = &node_config.lifecycle_tracing;
+
+        // Verify that the sample rates are valid fractions
+        for (name, sample_rate) in [
+            (
+                "transaction_sample_rate",
+                tracing_config.transaction_sample_rate,
+            ),
+            ("block_sample_rate", tracing_config.block_sample_rate),
+        ] {
+            if !(0.0..=1.0).contains(&sample_rate) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("The {} must be between 0 and 1, got {}", name, sample_rate),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_sample_rates() {
+        // Create a node config with an invalid sample rate
+        let node_config = NodeConfig {
+            lifecycle_tracing: LifecycleTracingConfig {
+                transaction_sample_rate: 1.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error =
+            LifecycleTracingConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that the default config passes sanitization
+        LifecycleTracingConfig::sanitize(&NodeConfig::default(), NodeType::Validator, None)
+            .unwrap();
+    }
+}
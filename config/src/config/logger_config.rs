@@ -9,10 +9,11 @@ use crate::{
     },
     utils,
 };
-use aptos_logger::{Level, CHANNEL_SIZE};
+use aptos_logger::{Level, LogCompression, RotationPolicy, CHANNEL_SIZE};
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::time::Duration;
 
 // Useful constants for the logger config
 const DEFAULT_TOKIO_CONSOLE_PORT: u16 = 6669;
@@ -36,6 +37,8 @@ pub struct LoggerConfig {
     pub telemetry_level: Level,
     /// Tokio console port for local debugging
     pub tokio_console_port: Option<u16>,
+    /// Rotation and retention of the log file (if logging to a file)
+    pub file_rotation: LogFileRotationConfig,
 }
 
 impl Default for LoggerConfig {
@@ -53,6 +56,38 @@ impl Default for LoggerConfig {
             // Setting this to None will disable tokio-console
             // even if the "tokio-console" feature is enabled.
             tokio_console_port: None,
+            file_rotation: LogFileRotationConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogFileRotationConfig {
+    /// Rotate the log file once it reaches this size (in bytes)
+    pub max_file_size_bytes: Option<u64>,
+    /// Rotate the log file once it has been written to for this long (in seconds)
+    pub rotation_interval_secs: Option<u64>,
+    /// The maximum number of rotated log files to keep
+    pub max_rotated_files: Option<usize>,
+    /// Rotated log files older than this are deleted (in seconds)
+    pub max_rotated_file_age_secs: Option<u64>,
+    /// How to compress rotated log files (none, gzip or zstd)
+    pub compression: LogCompression,
+    /// Reopen the log file on SIGHUP, e.g., for use with an external logrotate
+    pub reopen_on_signal: bool,
+}
+
+impl LogFileRotationConfig {
+    /// Returns the rotation policy for the logger's file writer
+    pub fn rotation_policy(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_file_size_bytes: self.max_file_size_bytes,
+            rotation_interval: self.rotation_interval_secs.map(Duration::from_secs),
+            max_rotated_files: self.max_rotated_files,
+            max_rotated_file_age: self.max_rotated_file_age_secs.map(Duration::from_secs),
+            compression: self.compression,
+            reopen_on_signal: self.reopen_on_signal,
         }
     }
 }
@@ -91,6 +126,17 @@ impl ConfigSanitizer for LoggerConfig {
             ));
         }
 
+        // Verify that the log file rotation triggers are non-zero
+        let file_rotation = &logger_config.file_rotation;
+        if file_rotation.max_file_size_bytes == Some(0)
+            || file_rotation.rotation_interval_secs == Some(0)
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The log file rotation size and interval must be greater than zero!".into(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -202,4 +248,25 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_zero_rotation_size() {
+        // Create a logger config that rotates files of size zero
+        let node_config = NodeConfig {
+            logger: LoggerConfig {
+                file_rotation: LogFileRotationConfig {
+                    max_file_size_bytes: Some(0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error =
+            LoggerConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
@@ -22,6 +22,7 @@ mod indexer_table_info_config;
 mod inspection_service_config;
 pub mod internal_indexer_db_config;
 mod jwk_consensus_config;
+mod lifecycle_tracing_config;
 mod logger_config;
 mod mempool_config;
 mod netbench_config;
@@ -55,6 +56,7 @@ pub use indexer_config::*;
 pub use indexer_grpc_config::*;
 pub use indexer_table_info_config::*;
 pub use inspection_service_config::*;
+pub use lifecycle_tracing_config::*;
 pub use logger_config::*;
 pub use mempool_config::*;
 pub use netbench_config::*;
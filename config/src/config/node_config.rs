@@ -10,8 +10,9 @@ use crate::{
         node_config_loader::NodeConfigLoader, node_startup_config::NodeStartupConfig,
         persistable_config::PersistableConfig, utils::RootPath, AdminServiceConfig, ApiConfig,
         BaseConfig, ConsensusConfig, Error, ExecutionConfig, IndexerConfig, IndexerGrpcConfig,
-        InspectionServiceConfig, LoggerConfig, MempoolConfig, NetworkConfig,
-        PeerMonitoringServiceConfig, SafetyRulesTestConfig, StateSyncConfig, StorageConfig,
+        InspectionServiceConfig, LifecycleTracingConfig, LoggerConfig, MempoolConfig,
+        NetworkConfig, PeerMonitoringServiceConfig, SafetyRulesTestConfig, StateSyncConfig,
+        StorageConfig,
     },
     network_id::NetworkId,
 };
@@ -65,6 +66,8 @@ pub struct NodeConfig {
     #[serde(default)]
     pub jwk_consensus: JWKConsensusConfig,
     #[serde(default)]
+    pub lifecycle_tracing: LifecycleTracingConfig,
+    #[serde(default)]
     pub logger: LoggerConfig,
     #[serde(default)]
     pub mempool: MempoolConfig,
@@ -6,6 +6,7 @@ use crate::{
     network::{NetworkReceivers, NetworkSender},
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     test_utils::{self, consensus_runtime, placeholder_ledger_info, timed_block_on},
+    twins::byzantine::{ByzantineNetwork, Interception},
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
@@ -34,12 +35,12 @@ use aptos_network::{
 use aptos_types::{block_info::BlockInfo, PeerId};
 use futures::{channel::mpsc, SinkExt, StreamExt};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::FromIterator,
     sync::Arc,
     time::Duration,
 };
-use tokio::runtime::Handle;
+use tokio::{runtime::Handle, time::Instant};
 
 /// `TwinId` is used by the NetworkPlayground to uniquely identify
 /// nodes, even if they have the same `AccountAddress` (e.g. for Twins)
@@ -84,6 +85,12 @@ pub struct NetworkPlayground {
     author_to_twin_ids: Arc<RwLock<AuthorToTwinIds>>,
     /// Information about connections
     peers_and_metadata: Arc<PeersAndMetadata>,
+    /// Byzantine behaviors applied to the outgoing messages of these authors
+    adversaries: Arc<Mutex<HashMap<Author, ByzantineNetwork>>>,
+    /// Direct-send messages held back by adversaries, by the time they are due
+    /// and the order they were held back in
+    delayed_msgs: BTreeMap<(Instant, u64), (TwinId, TwinId, ReceivedMessage)>,
+    num_delayed_msgs: u64,
 }
 
 impl NetworkPlayground {
@@ -100,6 +107,9 @@ impl NetworkPlayground {
             executor,
             author_to_twin_ids: Arc::new(RwLock::new(AuthorToTwinIds::default())),
             peers_and_metadata: PeersAndMetadata::new(&[NetworkId::Validator]),
+            adversaries: Arc::new(Mutex::new(HashMap::new())),
+            delayed_msgs: BTreeMap::new(),
+            num_delayed_msgs: 0,
         }
     }
 
@@ -130,6 +140,7 @@ impl NetworkPlayground {
             Mutex<HashMap<TwinId, aptos_channel::Sender<(PeerId, ProtocolId), ReceivedMessage>>>,
         >,
         author_to_twin_ids: Arc<RwLock<AuthorToTwinIds>>,
+        adversaries: Arc<Mutex<HashMap<Author, ByzantineNetwork>>>,
     ) {
         while let Some(net_req) = network_reqs_rx.next().await {
             match net_req {
@@ -155,6 +166,16 @@ impl NetworkPlayground {
                         None => continue, // drop rpc
                     };
 
+                    if let Some(adversary) = adversaries.lock().get(&src_twin_id.author) {
+                        let msg: ConsensusMsg = outbound_req
+                            .protocol_id
+                            .from_bytes(&outbound_req.data)
+                            .unwrap();
+                        if adversary.withholds(&msg) {
+                            continue; // drop rpc
+                        }
+                    }
+
                     if timeout_config
                         .read()
                         .is_message_timedout(&src_twin_id, dst_twin_id)
@@ -217,6 +238,7 @@ impl NetworkPlayground {
             self.outbound_msgs_tx.clone(),
             self.node_consensus_txs.clone(),
             self.author_to_twin_ids.clone(),
+            self.adversaries.clone(),
         );
         let fut2 = conn_mgr_reqs_rx.map(Ok).forward(::futures::sink::drain());
         self.executor.spawn(futures::future::join(fut1, fut2));
@@ -399,9 +421,46 @@ impl NetworkPlayground {
         self.timeout_config.clone()
     }
 
+    /// Tamper with all messages sent by `author`, as configured by `adversary`.
+    /// Rpc messages can only be withheld. Direct-send messages are only tampered with by
+    /// `start`, messages delivered through `wait_for_messages` are untouched.
+    pub fn add_adversary(&mut self, author: Author, adversary: ByzantineNetwork) {
+        self.adversaries.lock().insert(author, adversary);
+    }
+
+    /// Deliver all messages held back by adversaries that are due by now.
+    async fn deliver_delayed_messages(&mut self) {
+        let now = Instant::now();
+        while let Some(entry) = self.delayed_msgs.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let (src_twin_id, dst_twin_id, rmsg) = entry.remove();
+            self.deliver_message(src_twin_id, dst_twin_id, rmsg).await;
+        }
+    }
+
+    async fn sleep_until_next_delayed_message(next_due: Option<Instant>) {
+        match next_due {
+            Some(due) => tokio::time::sleep_until(due).await,
+            None => futures::future::pending().await,
+        }
+    }
+
     pub async fn start(mut self) {
-        // Take the next queued message
-        while let Some((src_twin_id, net_req)) = self.outbound_msgs_rx.next().await {
+        loop {
+            // Take the next queued message, unless a held back message is due first
+            let next_due = self.delayed_msgs.keys().next().map(|(due, _)| *due);
+            let (src_twin_id, net_req) = tokio::select! {
+                outbound = self.outbound_msgs_rx.next() => match outbound {
+                    Some(outbound) => outbound,
+                    None => break,
+                },
+                _ = Self::sleep_until_next_delayed_message(next_due) => {
+                    self.deliver_delayed_messages().await;
+                    continue;
+                },
+            };
             // Convert PeerManagerRequest to corresponding ReceivedMessage,
             // and extract destination peer
             let (dst, msg) = match &net_req {
@@ -417,21 +476,48 @@ impl NetworkPlayground {
             let dst_twin_ids = self.get_twin_ids(dst);
 
             for dst_twin_id in dst_twin_ids.iter() {
+                let consensus_msg: ConsensusMsg = msg.to_message().unwrap();
+                if self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg.clone()) {
+                    continue;
+                }
+
+                let interception = self
+                    .adversaries
+                    .lock()
+                    .get_mut(&src_twin_id.author)
+                    .map(|adversary| adversary.intercept(dst_twin_id.author, consensus_msg));
+                let (raw_msg, delay) = match interception {
+                    None => (msg.mdata.clone().into(), None),
+                    Some(Interception::Deliver(tampered)) => {
+                        (msg.protocol_id.to_bytes(&tampered).unwrap(), None)
+                    },
+                    Some(Interception::Delay(tampered, delay)) => {
+                        (msg.protocol_id.to_bytes(&tampered).unwrap(), Some(delay))
+                    },
+                    Some(Interception::Withhold) => continue,
+                };
                 let rmsg = ReceivedMessage {
                     message: NetworkMessage::DirectSendMsg(DirectSendMsg {
                         protocol_id: msg.protocol_id,
                         priority: 0,
-                        raw_msg: msg.mdata.clone().into(),
+                        raw_msg,
                     }),
                     sender: PeerNetworkId::new(NetworkId::Validator, src_twin_id.author),
                     receive_timestamp_micros: 0,
                     rpc_replier: None,
                 };
-                let consensus_msg = msg.to_message().unwrap();
 
-                // Deliver and copy message it if it's not dropped
-                if !self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg) {
-                    self.deliver_message(src_twin_id, *dst_twin_id, rmsg).await;
+                match delay {
+                    Some(delay) => {
+                        self.num_delayed_msgs += 1;
+                        self.delayed_msgs.insert(
+                            (Instant::now() + delay, self.num_delayed_msgs),
+                            (src_twin_id, *dst_twin_id, rmsg),
+                        );
+                    },
+                    None => {
+                        self.deliver_message(src_twin_id, *dst_twin_id, rmsg).await;
+                    },
                 }
             }
         }
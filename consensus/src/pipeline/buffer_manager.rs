@@ -33,6 +33,7 @@ use aptos_consensus_types::{
 };
 use aptos_crypto::{bls12381, HashValue};
 use aptos_executor_types::ExecutorResult;
+use aptos_lifecycle_tracing::Stage;
 use aptos_logger::prelude::*;
 use aptos_network::protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId};
 use aptos_reliable_broadcast::{DropGuard, ReliableBroadcast};
@@ -58,6 +59,7 @@ use std::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
+    time::UNIX_EPOCH,
 };
 use tokio::time::{Duration, Instant};
 use tokio_retry::strategy::ExponentialBackoff;
@@ -398,6 +400,20 @@ impl BufferManager {
             self.buffer.len() + 1,
         );
 
+        if aptos_lifecycle_tracing::is_enabled() {
+            for block in &ordered_blocks {
+                aptos_lifecycle_tracing::record_block_stage(
+                    &block.id(),
+                    Stage::ConsensusOrder,
+                    UNIX_EPOCH + Duration::from_micros(block.timestamp_usecs()),
+                    vec![
+                        ("epoch", block.epoch().to_string()),
+                        ("round", block.round().to_string()),
+                    ],
+                );
+            }
+        }
+
         let request = self.create_new_request(ExecutionRequest {
             ordered_blocks: ordered_blocks.clone(),
             lifetime_guard: self.create_new_request(()),
@@ -11,6 +11,7 @@ use crate::{
 use aptos_consensus_types::pipelined_block::PipelinedBlock;
 use aptos_crypto::HashValue;
 use aptos_executor_types::ExecutorError;
+use aptos_lifecycle_tracing::Stage;
 use aptos_logger::debug;
 use async_trait::async_trait;
 use futures::TryFutureExt;
@@ -75,6 +76,12 @@ impl StatelessPipeline for ExecutionSchedulePhase {
             },
         };
 
+        // The execution spans end when the compute result of each block is received
+        let lifecycle_spans: Vec<_> = ordered_blocks
+            .iter()
+            .map(|b| aptos_lifecycle_tracing::block_span(&b.id(), Stage::PipelineExecute))
+            .collect();
+
         // Call schedule_compute() for each block here (not in the fut being returned) to
         // make sure they are scheduled in order.
         let mut futs = vec![];
@@ -94,9 +101,19 @@ impl StatelessPipeline for ExecutionSchedulePhase {
         // In the future being returned, wait for the compute results in order.
         let fut = tokio::task::spawn(async move {
             let mut results = vec![];
-            for (block, fut) in itertools::zip_eq(ordered_blocks, futs) {
+            for ((block, fut), mut lifecycle_span) in
+                itertools::zip_eq(ordered_blocks, futs).zip(lifecycle_spans)
+            {
                 debug!("try to receive compute result for block {}", block.id());
-                results.push(block.set_execution_result(fut.await?));
+                let compute_result = fut.await?;
+                lifecycle_span.set_attribute(
+                    "num_transactions",
+                    compute_result
+                        .compute_status_for_input_txns()
+                        .len()
+                        .to_string(),
+                );
+                results.push(block.set_execution_result(compute_result));
             }
             Ok(results)
         })
@@ -8,6 +8,7 @@ use crate::{
 };
 use aptos_consensus_types::{common::Round, pipelined_block::PipelinedBlock};
 use aptos_executor_types::ExecutorResult;
+use aptos_lifecycle_tracing::Stage;
 use aptos_types::ledger_info::LedgerInfoWithSignatures;
 use async_trait::async_trait;
 use std::{
@@ -69,6 +70,11 @@ impl StatelessPipeline for PersistingPhase {
         } = req;
         let round = commit_ledger_info.ledger_info().round();
 
+        // One span per block, all ending once the blocks are committed
+        let _lifecycle_spans: Vec<_> = blocks
+            .iter()
+            .map(|block| aptos_lifecycle_tracing::block_span(&block.id(), Stage::PipelineCommit))
+            .collect();
         self.persisting_handle
             .commit(&blocks, commit_ledger_info, callback)
             .await
@@ -17,6 +17,7 @@ use aptos_consensus_types::{
     proof_of_store::{BatchId, BatchInfo},
 };
 use aptos_experimental_runtimes::thread_manager::optimal_min_len;
+use aptos_lifecycle_tracing::Stage;
 use aptos_logger::prelude::*;
 use aptos_mempool::QuorumStoreRequest;
 use aptos_types::{transaction::SignedTransaction, PeerId};
@@ -25,7 +26,7 @@ use rayon::prelude::*;
 use std::{
     collections::{btree_map::Entry, BTreeMap, HashMap},
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::time::Interval;
 
@@ -332,6 +333,7 @@ impl BatchGenerator {
             self.txns_in_progress_sorted.len()
         );
 
+        let pull_start_time = SystemTime::now();
         let mut pulled_txns = self
             .mempool_proxy
             .pull_internal(
@@ -369,6 +371,26 @@ impl BatchGenerator {
         self.last_end_batch_time = Instant::now();
         counters::BATCH_CREATION_COMPUTE_LATENCY.observe_duration(bucket_compute_start.elapsed());
 
+        if aptos_lifecycle_tracing::is_enabled() {
+            for batch in &batches {
+                for txn in batch.txns() {
+                    let txn_hash = txn.committed_hash();
+                    if !aptos_lifecycle_tracing::is_transaction_sampled(&txn_hash) {
+                        continue;
+                    }
+                    aptos_lifecycle_tracing::record_transaction_stage(
+                        &txn_hash,
+                        Stage::QuorumStoreBatch,
+                        pull_start_time,
+                        vec![
+                            ("batch_digest", batch.digest().to_hex()),
+                            ("batch_id", batch.batch_id().to_string()),
+                        ],
+                    );
+                }
+            }
+        }
+
         batches
     }
 
@@ -189,6 +189,10 @@ impl Batch {
         Ok(())
     }
 
+    pub fn txns(&self) -> &[SignedTransaction] {
+        self.payload.txns()
+    }
+
     pub fn into_transactions(self) -> Vec<SignedTransaction> {
         self.payload.into_transactions()
     }
@@ -0,0 +1,215 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::NetworkPlayground,
+    test_utils::{consensus_runtime, timed_block_on},
+    twins::{byzantine::ByzantineBehavior, twins_node::SMRNode},
+};
+use aptos_consensus_types::common::Round;
+use aptos_crypto::HashValue;
+use aptos_types::on_chain_config::{
+    ConsensusAlgorithmConfig, ConsensusConfigV1, OnChainConsensusConfig,
+    ProposerElectionType::RotatingProposer, ValidatorTxnConfig,
+};
+use futures::StreamExt;
+use maplit::hashmap;
+use std::{collections::HashMap, time::Duration};
+
+/// A named setup of honest and Byzantine nodes, together with the progress the
+/// honest nodes are expected to make despite the Byzantine ones.
+pub struct AttackScenario {
+    pub name: &'static str,
+    pub num_nodes: usize,
+    /// Behaviors of the Byzantine nodes, by index in the validator set.
+    pub byzantine: HashMap<usize, Vec<ByzantineBehavior>>,
+    pub consensus_config: OnChainConsensusConfig,
+    /// Low enough for rounds stalled by Byzantine nodes to time out within the test.
+    pub round_initial_timeout_ms: u64,
+    /// Every honest node has to commit a block at or above this round.
+    pub min_commit_round: Round,
+}
+
+impl AttackScenario {
+    fn new(name: &'static str, byzantine: Vec<ByzantineBehavior>) -> Self {
+        Self {
+            name,
+            num_nodes: 4,
+            byzantine: hashmap! { 0 => byzantine },
+            consensus_config: OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                proposer_election_type: RotatingProposer(1),
+                ..ConsensusConfigV1::default()
+            }),
+            round_initial_timeout_ms: 1_000,
+            min_commit_round: 10,
+        }
+    }
+
+    /// The leader of every 4th round sends conflicting proposals to half of the validators.
+    pub fn equivocating_leader() -> Self {
+        Self::new("equivocating_leader", vec![
+            ByzantineBehavior::EquivocateProposals,
+        ])
+    }
+
+    /// One validator never votes, so every quorum needs all honest validators.
+    pub fn vote_withholding() -> Self {
+        Self::new("vote_withholding", vec![ByzantineBehavior::WithholdVotes])
+    }
+
+    /// One validator keeps advertising the quorum certs it saw first.
+    pub fn stale_quorum_certs() -> Self {
+        Self::new("stale_quorum_certs", vec![
+            ByzantineBehavior::SendStaleQuorumCerts,
+        ])
+    }
+
+    /// One validator holds back its quorum store traffic. The mempools of twins nodes are
+    /// empty, so this mostly checks that quorum store payloads keep being ordered.
+    pub fn delayed_batches() -> Self {
+        Self {
+            consensus_config: OnChainConsensusConfig::V3 {
+                alg: ConsensusAlgorithmConfig::Jolteon {
+                    main: ConsensusConfigV1 {
+                        proposer_election_type: RotatingProposer(1),
+                        ..ConsensusConfigV1::default()
+                    },
+                    quorum_store_enabled: true,
+                },
+                vtxn: ValidatorTxnConfig::default_disabled(),
+            },
+            ..Self::new("delayed_batches", vec![ByzantineBehavior::DelayBatches(
+                Duration::from_millis(500),
+            )])
+        }
+    }
+
+    /// A Byzantine leader that both equivocates and withholds its votes.
+    pub fn equivocating_and_withholding_leader() -> Self {
+        Self::new("equivocating_and_withholding_leader", vec![
+            ByzantineBehavior::EquivocateProposals,
+            ByzantineBehavior::WithholdVotes,
+        ])
+    }
+
+    /// Runs the scenario and asserts that
+    /// - safety: honest nodes never commit different blocks for the same round, and
+    /// - liveness: every honest node commits up to `min_commit_round`.
+    pub fn run(&self) {
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let nodes = SMRNode::start_num_nodes_with_byzantine(
+            self.num_nodes,
+            &mut playground,
+            self.consensus_config.clone(),
+            &self.byzantine,
+            self.round_initial_timeout_ms,
+        );
+        runtime.spawn(playground.start());
+
+        timed_block_on(&runtime, async {
+            let mut committed: HashMap<Round, HashValue> = HashMap::new();
+            for mut node in nodes
+                .into_iter()
+                .filter(|node| !self.byzantine.contains_key(&node.id.id))
+            {
+                loop {
+                    let ledger_info = node
+                        .commit_cb_receiver
+                        .next()
+                        .await
+                        .expect("Node stopped committing");
+                    let commit_info = ledger_info.commit_info();
+                    let block_id = *committed
+                        .entry(commit_info.round())
+                        .or_insert_with(|| commit_info.id());
+                    assert_eq!(
+                        block_id,
+                        commit_info.id(),
+                        "[{}] Conflicting commits for round {}",
+                        self.name,
+                        commit_info.round()
+                    );
+                    if commit_info.round() >= self.min_commit_round {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[test]
+/// Setup:
+///
+/// 4 nodes, node 0 equivocates whenever it is the leader
+///
+/// Test:
+///
+/// Honest nodes commit the same blocks and make progress past the equivocating rounds
+///
+/// Run the test:
+/// cargo xtest -p consensus equivocating_leader_test -- --nocapture
+fn equivocating_leader_test() {
+    AttackScenario::equivocating_leader().run();
+}
+
+#[test]
+/// Setup:
+///
+/// 4 nodes, node 0 never votes
+///
+/// Test:
+///
+/// The 3 honest nodes form quorums on their own and make progress
+///
+/// Run the test:
+/// cargo xtest -p consensus vote_withholding_test -- --nocapture
+fn vote_withholding_test() {
+    AttackScenario::vote_withholding().run();
+}
+
+#[test]
+/// Setup:
+///
+/// 4 nodes, node 0 attaches stale quorum certs to its votes and sync info
+///
+/// Test:
+///
+/// Honest nodes ignore the stale certificates and make progress
+///
+/// Run the test:
+/// cargo xtest -p consensus stale_quorum_certs_test -- --nocapture
+fn stale_quorum_certs_test() {
+    AttackScenario::stale_quorum_certs().run();
+}
+
+#[test]
+/// Setup:
+///
+/// 4 nodes with quorum store, node 0 delays its batches and proofs of store
+///
+/// Test:
+///
+/// Honest nodes make progress
+///
+/// Run the test:
+/// cargo xtest -p consensus delayed_batches_test -- --nocapture
+fn delayed_batches_test() {
+    AttackScenario::delayed_batches().run();
+}
+
+#[test]
+/// Setup:
+///
+/// 4 nodes, node 0 equivocates whenever it is the leader and never votes
+///
+/// Test:
+///
+/// Honest nodes commit the same blocks and make progress
+///
+/// Run the test:
+/// cargo xtest -p consensus equivocating_and_withholding_leader_test -- --nocapture
+fn equivocating_and_withholding_leader_test() {
+    AttackScenario::equivocating_and_withholding_leader().run();
+}
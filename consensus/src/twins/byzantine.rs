@@ -0,0 +1,178 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scriptable Byzantine behaviors for nodes started by the twins test harness.
+//!
+//! All behaviors are applied by the `NetworkPlayground` through [`ByzantineNetwork`],
+//! which withholds, tampers with or delays the outgoing messages of a Byzantine node
+//! before they are delivered. The node itself runs unmodified.
+
+use crate::{network_interface::ConsensusMsg, pipeline::commit_reliable_broadcast::CommitMessage};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Author, Round},
+    proposal_msg::ProposalMsg,
+    sync_info::SyncInfo,
+    vote_msg::VoteMsg,
+};
+use aptos_types::validator_signer::ValidatorSigner;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+/// A Byzantine behavior a node started by the twins harness can exhibit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ByzantineBehavior {
+    /// When elected, send a conflicting proposal for the same round to half of the validators.
+    EquivocateProposals,
+    /// Never send votes, order votes or commit votes.
+    WithholdVotes,
+    /// Attach the oldest known SyncInfo (and thus stale quorum certs) to outgoing votes
+    /// and SyncInfo messages.
+    SendStaleQuorumCerts,
+    /// Delay outgoing quorum store batches, signed batch infos and proofs of store.
+    DelayBatches(Duration),
+}
+
+/// What the network does with an outgoing message of a Byzantine node.
+#[derive(Debug)]
+pub enum Interception {
+    /// Deliver this message instead of the original one.
+    Deliver(ConsensusMsg),
+    /// Deliver the message once the delay has passed.
+    Delay(ConsensusMsg, Duration),
+    /// Drop the message.
+    Withhold,
+}
+
+/// Withholds, tampers with or delays the outgoing messages of a Byzantine node.
+pub struct ByzantineNetwork {
+    signer: ValidatorSigner,
+    behaviors: Vec<ByzantineBehavior>,
+    /// Validators that receive the conflicting proposal when equivocating.
+    equivocation_targets: HashSet<Author>,
+    /// Conflicting proposals already created, so every target sees the same one.
+    conflicting_proposals: HashMap<Round, ProposalMsg>,
+    stale_sync_info: Option<SyncInfo>,
+}
+
+impl ByzantineNetwork {
+    /// `validators` is the full validator set; every other validator (by position)
+    /// is targeted with conflicting proposals.
+    pub fn new(
+        signer: ValidatorSigner,
+        behaviors: Vec<ByzantineBehavior>,
+        validators: &[Author],
+    ) -> Self {
+        Self {
+            signer,
+            behaviors,
+            equivocation_targets: validators.iter().skip(1).step_by(2).cloned().collect(),
+            conflicting_proposals: HashMap::new(),
+            stale_sync_info: None,
+        }
+    }
+
+    fn has(&self, behavior: &ByzantineBehavior) -> bool {
+        self.behaviors.contains(behavior)
+    }
+
+    fn batch_delay(&self) -> Option<Duration> {
+        self.behaviors.iter().find_map(|behavior| match behavior {
+            ByzantineBehavior::DelayBatches(delay) => Some(*delay),
+            _ => None,
+        })
+    }
+
+    /// Whether `msg` is never sent, regardless of its destination. This is all that
+    /// applies to rpc messages, as they are delivered immediately.
+    pub fn withholds(&self, msg: &ConsensusMsg) -> bool {
+        self.has(&ByzantineBehavior::WithholdVotes)
+            && match msg {
+                ConsensusMsg::VoteMsg(_)
+                | ConsensusMsg::OrderVoteMsg(_)
+                | ConsensusMsg::CommitVoteMsg(_) => true,
+                ConsensusMsg::CommitMessage(commit) => matches!(**commit, CommitMessage::Vote(_)),
+                _ => false,
+            }
+    }
+
+    /// Decides what to deliver to `dst` instead of the direct-send message `msg`.
+    pub fn intercept(&mut self, dst: Author, msg: ConsensusMsg) -> Interception {
+        if self.withholds(&msg) {
+            return Interception::Withhold;
+        }
+        match msg {
+            ConsensusMsg::ProposalMsg(proposal)
+                if self.has(&ByzantineBehavior::EquivocateProposals)
+                    && self.equivocation_targets.contains(&dst) =>
+            {
+                let conflicting = self.conflicting_proposal(&proposal);
+                Interception::Deliver(ConsensusMsg::ProposalMsg(Box::new(conflicting)))
+            },
+            ConsensusMsg::VoteMsg(vote_msg)
+                if self.has(&ByzantineBehavior::SendStaleQuorumCerts) =>
+            {
+                let sync_info = self.stale_sync_info(vote_msg.sync_info());
+                Interception::Deliver(ConsensusMsg::VoteMsg(Box::new(VoteMsg::new(
+                    vote_msg.vote().clone(),
+                    sync_info,
+                ))))
+            },
+            ConsensusMsg::SyncInfo(sync_info)
+                if self.has(&ByzantineBehavior::SendStaleQuorumCerts) =>
+            {
+                let stale = self.stale_sync_info(&sync_info);
+                Interception::Deliver(ConsensusMsg::SyncInfo(Box::new(stale)))
+            },
+            msg @ (ConsensusMsg::BatchMsg(_)
+            | ConsensusMsg::SignedBatchInfo(_)
+            | ConsensusMsg::ProofOfStoreMsg(_)) => match self.batch_delay() {
+                Some(delay) => Interception::Delay(msg, delay),
+                None => Interception::Deliver(msg),
+            },
+            msg => Interception::Deliver(msg),
+        }
+    }
+
+    /// Same round, parent and payload, but a different timestamp, and thus a different block id.
+    fn conflicting_proposal(&mut self, proposal: &ProposalMsg) -> ProposalMsg {
+        let block = proposal.proposal();
+        self.conflicting_proposals
+            .entry(block.round())
+            .or_insert_with(|| {
+                let conflicting = Block::new_proposal_ext(
+                    block.validator_txns().cloned().unwrap_or_default(),
+                    block
+                        .payload()
+                        .cloned()
+                        .expect("Proposals always have a payload"),
+                    block.round(),
+                    block.timestamp_usecs() + 1,
+                    block.quorum_cert().clone(),
+                    &self.signer,
+                    block
+                        .block_data()
+                        .failed_authors()
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+                .expect("Failed to sign conflicting proposal");
+                ProposalMsg::new(conflicting, proposal.sync_info().clone())
+            })
+            .clone()
+    }
+
+    /// The first SyncInfo seen in the epoch of `current`, as SyncInfo of other epochs fails
+    /// verification outright instead of just being stale.
+    fn stale_sync_info(&mut self, current: &SyncInfo) -> SyncInfo {
+        match &self.stale_sync_info {
+            Some(stale) if stale.epoch() == current.epoch() => stale.clone(),
+            _ => {
+                self.stale_sync_info = Some(current.clone());
+                current.clone()
+            },
+        }
+    }
+}
@@ -2,5 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod attack_scenarios;
 mod basic_twins_test;
+pub(crate) mod byzantine;
 mod twins_node;
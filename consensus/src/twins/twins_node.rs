@@ -13,6 +13,7 @@ use crate::{
     quorum_store::quorum_store_db::MockQuorumStoreDB,
     rand::rand_gen::storage::in_memory::InMemRandDb,
     test_utils::{mock_execution_client::MockExecutionClient, MockStorage},
+    twins::byzantine::{ByzantineBehavior, ByzantineNetwork},
     util::time_service::ClockTimeService,
 };
 use aptos_bounded_executor::BoundedExecutor;
@@ -46,6 +47,7 @@ use aptos_types::{
     },
     transaction::SignedTransaction,
     validator_info::ValidatorInfo,
+    validator_signer::ValidatorSigner,
     waypoint::Waypoint,
 };
 use aptos_validator_transaction_pool::VTxnPoolState;
@@ -195,15 +197,12 @@ impl SMRNode {
         }
     }
 
-    /// Starts a given number of nodes and their twins
-    pub fn start_num_nodes_with_twins(
+    /// Generates the configs of `num_nodes` validators sorted by the peer id, registers
+    /// them with the playground and returns them together with their ValidatorSet.
+    fn generate_validators(
         num_nodes: usize,
-        num_twins: usize,
-        playground: &mut NetworkPlayground,
-        proposer_type: ProposerElectionType,
-        round_proposers_idx: Option<HashMap<Round, usize>>,
-    ) -> Vec<Self> {
-        assert!(num_nodes >= num_twins);
+        playground: &NetworkPlayground,
+    ) -> (Vec<NodeConfig>, ValidatorSet) {
         let ValidatorSwarm {
             nodes: mut node_configs,
         } = generator::validator_swarm_for_testing(num_nodes);
@@ -238,7 +237,39 @@ impl SMRNode {
                 })
                 .collect(),
         );
-        // sort by the peer id
+        (node_configs, validator_set)
+    }
+
+    /// Prepares the config and storage of a node to start from genesis of `validator_set`.
+    fn prepare_for_genesis(
+        config: &mut NodeConfig,
+        validator_set: &ValidatorSet,
+    ) -> Arc<MockStorage> {
+        let (_, storage) = MockStorage::start_for_testing(validator_set.clone());
+
+        let waypoint = Waypoint::new_epoch_boundary(&storage.get_ledger_info())
+            .expect("Unable to produce waypoint with the provided LedgerInfo");
+        config
+            .consensus
+            .safety_rules
+            .test
+            .as_mut()
+            .unwrap()
+            .waypoint = Some(waypoint);
+        config.base.waypoint = WaypointConfig::FromConfig(waypoint);
+        storage
+    }
+
+    /// Starts a given number of nodes and their twins
+    pub fn start_num_nodes_with_twins(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ProposerElectionType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        assert!(num_nodes >= num_twins);
+        let (mut node_configs, validator_set) = Self::generate_validators(num_nodes, playground);
 
         let proposer_type = match proposer_type {
             RoundProposer(_) => {
@@ -265,18 +296,7 @@ impl SMRNode {
         let mut smr_nodes = vec![];
 
         for (smr_id, mut config) in node_configs.into_iter().enumerate() {
-            let (_, storage) = MockStorage::start_for_testing(validator_set.clone());
-
-            let waypoint = Waypoint::new_epoch_boundary(&storage.get_ledger_info())
-                .expect("Unable to produce waypoint with the provided LedgerInfo");
-            config
-                .consensus
-                .safety_rules
-                .test
-                .as_mut()
-                .unwrap()
-                .waypoint = Some(waypoint);
-            config.base.waypoint = WaypointConfig::FromConfig(waypoint);
+            let storage = Self::prepare_for_genesis(&mut config, &validator_set);
             // Disable timeout in twins test to avoid flakiness
             config.consensus.round_initial_timeout_ms = 2_000_000;
 
@@ -301,4 +321,49 @@ impl SMRNode {
         }
         smr_nodes
     }
+
+    /// Starts a given number of nodes, where the nodes at the indices of `byzantine`
+    /// exhibit the given Byzantine behaviors.
+    pub fn start_num_nodes_with_byzantine(
+        num_nodes: usize,
+        playground: &mut NetworkPlayground,
+        consensus_config: OnChainConsensusConfig,
+        byzantine: &HashMap<usize, Vec<ByzantineBehavior>>,
+        round_initial_timeout_ms: u64,
+    ) -> Vec<Self> {
+        assert!(byzantine.keys().all(|index| *index < num_nodes));
+        let (node_configs, validator_set) = Self::generate_validators(num_nodes, playground);
+        let authors: Vec<_> = node_configs.iter().map(author_from_config).collect();
+
+        let mut smr_nodes = vec![];
+        for (smr_id, mut config) in node_configs.into_iter().enumerate() {
+            let storage = Self::prepare_for_genesis(&mut config, &validator_set);
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
+
+            let author = author_from_config(&config);
+            let twin_id = TwinId { id: smr_id, author };
+
+            if let Some(behaviors) = byzantine.get(&smr_id) {
+                let sr_test_config = config.consensus.safety_rules.test.as_ref().unwrap();
+                let signer = ValidatorSigner::new(
+                    author,
+                    Arc::new(sr_test_config.consensus_key.as_ref().unwrap().private_key()),
+                );
+                playground.add_adversary(
+                    author,
+                    ByzantineNetwork::new(signer, behaviors.clone(), &authors),
+                );
+            }
+
+            smr_nodes.push(Self::start(
+                playground,
+                config,
+                consensus_config.clone(),
+                storage,
+                twin_id,
+                VTxnPoolState::default(),
+            ));
+        }
+        smr_nodes
+    }
 }
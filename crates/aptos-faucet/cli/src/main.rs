@@ -103,7 +103,7 @@ impl FaucetCliArgs {
         // Mint coins to each of the accounts.
         for account in accounts {
             let response = mint_funder
-                .fund(Some(self.amount), account, false, false)
+                .fund(Some(self.amount), account, None, false, false)
                 .await;
             match response {
                 Ok(response) => println!(
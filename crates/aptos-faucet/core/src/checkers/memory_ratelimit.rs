@@ -9,13 +9,18 @@ use crate::{
 use async_trait::async_trait;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
-use std::{net::IpAddr, num::NonZeroUsize, sync::atomic::AtomicU64};
+use std::{collections::HashMap, net::IpAddr, num::NonZeroUsize, sync::atomic::AtomicU64};
 use tokio::sync::Mutex;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MemoryRatelimitCheckerConfig {
     pub max_requests_per_day: u32,
 
+    /// Overrides `max_requests_per_day` for the given assets, keyed by asset
+    /// name. Requests for each asset are counted separately.
+    #[serde(default)]
+    pub max_requests_per_day_per_asset: HashMap<String, u32>,
+
     #[serde(default = "MemoryRatelimitCheckerConfig::default_max_entries_in_map")]
     pub max_entries_in_map: NonZeroUsize,
 }
@@ -31,10 +36,12 @@ impl MemoryRatelimitCheckerConfig {
 pub struct MemoryRatelimitChecker {
     pub max_requests_per_day: u32,
 
-    /// Map of IP to how many requests they've submitted today (where the
-    /// response wasn't a 500). To avoid OOMing the server, we set a limit
+    pub max_requests_per_day_per_asset: HashMap<String, u32>,
+
+    /// Map of IP and asset to how many requests they've submitted today (where
+    /// the response wasn't a 500). To avoid OOMing the server, we set a limit
     /// on how many entries we have in the table.
-    pub ip_to_requests_today: Mutex<LruCache<IpAddr, u32>>,
+    pub ip_to_requests_today: Mutex<LruCache<(IpAddr, String), u32>>,
 
     /// Used for tracking daily ratelimit. See the comment in RedisRatelimitChecker
     /// for more information on how we track daily limits.
@@ -45,6 +52,7 @@ impl MemoryRatelimitChecker {
     pub fn new(args: MemoryRatelimitCheckerConfig) -> Self {
         Self {
             max_requests_per_day: args.max_requests_per_day,
+            max_requests_per_day_per_asset: args.max_requests_per_day_per_asset,
             ip_to_requests_today: Mutex::new(LruCache::new(args.max_entries_in_map)),
             current_day: AtomicU64::new(days_since_tap_epoch(get_current_time_secs())),
         }
@@ -61,6 +69,13 @@ impl MemoryRatelimitChecker {
             self.ip_to_requests_today.lock().await.clear();
         }
     }
+
+    fn max_requests_per_day(&self, asset: &str) -> u32 {
+        self.max_requests_per_day_per_asset
+            .get(asset)
+            .copied()
+            .unwrap_or(self.max_requests_per_day)
+    }
 }
 
 #[async_trait]
@@ -74,12 +89,14 @@ impl CheckerTrait for MemoryRatelimitChecker {
 
         let mut ip_to_requests_today = self.ip_to_requests_today.lock().await;
 
-        let requests_today = ip_to_requests_today.get_or_insert_mut(data.source_ip, || 1);
-        if *requests_today >= self.max_requests_per_day {
+        let max_requests_per_day = self.max_requests_per_day(&data.asset);
+        let requests_today =
+            ip_to_requests_today.get_or_insert_mut((data.source_ip, data.asset.clone()), || 1);
+        if *requests_today >= max_requests_per_day {
             return Ok(vec![RejectionReason::new(
                 format!(
-                    "IP {} has exceeded the daily limit of {} requests",
-                    data.source_ip, self.max_requests_per_day
+                    "IP {} has exceeded the daily limit of {} requests for {}",
+                    data.source_ip, max_requests_per_day, data.asset
                 ),
                 RejectionReasonCode::IpUsageLimitExhausted,
             )]);
@@ -96,7 +113,8 @@ impl CheckerTrait for MemoryRatelimitChecker {
                 .ip_to_requests_today
                 .lock()
                 .await
-                .get_or_insert_mut(data.checker_data.source_ip, || 1) -= 1;
+                .get_or_insert_mut((data.checker_data.source_ip, data.checker_data.asset), || 1) -=
+                1;
         }
         Ok(())
     }
@@ -6,21 +6,26 @@ mod google_captcha;
 mod ip_blocklist;
 mod magic_header;
 mod memory_ratelimit;
+mod proof_of_work;
 mod redis_ratelimit;
 mod referer_blocklist;
 mod tap_captcha;
 
-pub use self::tap_captcha::CaptchaManager;
 use self::{
     auth_token::AuthTokenChecker,
     google_captcha::{CaptchaChecker as GoogleCaptchaChecker, GoogleCaptchaCheckerConfig},
     ip_blocklist::IpBlocklistChecker,
     magic_header::{MagicHeaderChecker, MagicHeaderCheckerConfig},
     memory_ratelimit::{MemoryRatelimitChecker, MemoryRatelimitCheckerConfig},
+    proof_of_work::ProofOfWorkChecker,
     redis_ratelimit::{RedisRatelimitChecker, RedisRatelimitCheckerConfig},
     referer_blocklist::RefererBlocklistChecker,
     tap_captcha::{TapCaptchaChecker, TapCaptchaCheckerConfig},
 };
+pub use self::{
+    proof_of_work::{ProofOfWorkCheckerConfig, ProofOfWorkManager},
+    tap_captcha::CaptchaManager,
+};
 use crate::{
     common::{IpRangeManagerConfig, ListManagerConfig},
     endpoints::{AptosTapError, RejectionReason},
@@ -96,6 +101,10 @@ pub enum CheckerConfig {
     /// Basic in memory ratelimiter that allows a single successful request per IP.
     MemoryRatelimit(MemoryRatelimitCheckerConfig),
 
+    /// Requires the solution to a proof of work challenge, with a difficulty
+    /// that goes up with demand. Unlike captchas, scripts can solve these.
+    ProofOfWork(ProofOfWorkCheckerConfig),
+
     /// Ratelimiter that uses Redis.
     RedisRatelimit(RedisRatelimitCheckerConfig),
 
@@ -107,7 +116,11 @@ pub enum CheckerConfig {
 }
 
 impl CheckerConfig {
-    pub async fn build(self, captcha_manager: Arc<Mutex<CaptchaManager>>) -> Result<Checker> {
+    pub async fn build(
+        self,
+        captcha_manager: Arc<Mutex<CaptchaManager>>,
+        proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>,
+    ) -> Result<Checker> {
         Ok(match self {
             CheckerConfig::AuthToken(config) => Checker::from(AuthTokenChecker::new(config)?),
             CheckerConfig::GoogleCaptcha(config) => {
@@ -118,6 +131,10 @@ impl CheckerConfig {
             CheckerConfig::MemoryRatelimit(config) => {
                 Checker::from(MemoryRatelimitChecker::new(config))
             },
+            // The config is used by the ProofOfWorkManager instead.
+            CheckerConfig::ProofOfWork(_) => {
+                Checker::from(ProofOfWorkChecker::new(proof_of_work_manager)?)
+            },
             CheckerConfig::RedisRatelimit(config) => {
                 Checker::from(RedisRatelimitChecker::new(config).await?)
             },
@@ -139,6 +156,7 @@ pub enum Checker {
     IpBlocklistChecker,
     MagicHeaderChecker,
     MemoryRatelimitChecker,
+    ProofOfWorkChecker,
     RedisRatelimitChecker,
     RefererBlocklistChecker,
     TapCaptchaChecker,
@@ -148,6 +166,8 @@ pub enum Checker {
 pub struct CheckerData {
     pub time_request_received_secs: u64,
     pub receiver: AccountAddress,
+    /// The name of the requested asset, `apt` if the request didn't specify one.
+    pub asset: String,
     pub source_ip: IpAddr,
     pub headers: Arc<HeaderMap>,
 }
@@ -0,0 +1,531 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A proof of work challenge for clients that can't solve captchas, e.g. the
+//! CLI. The client requests a challenge from `/request_pow_challenge`, finds a
+//! nonce such that sha3-256(challenge || receiver || nonce) starts with as many
+//! zero bits as the challenge difficulty, and includes the challenge and nonce
+//! in the headers of the funding request.
+//!
+//! Challenges are stateless: they carry their own expiration time and
+//! difficulty, authenticated with an HMAC. So we only need to keep track of the
+//! challenges that were used, until they expire.
+
+use super::{google_captcha::KeyString, CheckerData, CheckerTrait};
+use crate::{
+    endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode},
+    helpers::get_current_time_secs,
+};
+use anyhow::{bail, ensure, Context, Result};
+use aptos_sdk::{
+    rest_client::faucet::{
+        is_proof_of_work_solution, ProofOfWorkChallenge, POW_CHALLENGE_HEADER, POW_NONCE_HEADER,
+    },
+    types::account_address::AccountAddress,
+};
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2_0_10_6::Sha256;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    net::IpAddr,
+    sync::Arc,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProofOfWorkCheckerConfig {
+    /// Number of leading zero bits a solution must have when demand is low.
+    /// Every extra bit doubles the expected work.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_base_difficulty")]
+    pub base_difficulty: u8,
+
+    /// The difficulty never goes above this.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_max_difficulty")]
+    pub max_difficulty: u8,
+
+    /// The difficulty goes up by one bit for every this many challenges issued
+    /// within the last `demand_window_secs`.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_challenges_per_difficulty_step")]
+    pub challenges_per_difficulty_step: usize,
+
+    /// How far back we look when measuring demand and challenges per IP.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_demand_window_secs")]
+    pub demand_window_secs: u64,
+
+    /// How long a client has to solve a challenge and use it.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_challenge_ttl_secs")]
+    pub challenge_ttl_secs: u64,
+
+    /// How many challenges a single IP can request within the last
+    /// `demand_window_secs`. This stops a single client from driving up the
+    /// difficulty for everyone else.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_max_challenges_per_ip")]
+    pub max_challenges_per_ip: usize,
+
+    /// Key used to sign challenges. Set the same key on every instance of the
+    /// faucet so challenges issued by one instance can be used with any other.
+    /// If not set, a random key is generated at startup.
+    #[serde(default)]
+    pub secret_key: Option<KeyString>,
+}
+
+impl ProofOfWorkCheckerConfig {
+    fn default_base_difficulty() -> u8 {
+        20
+    }
+
+    fn default_max_difficulty() -> u8 {
+        26
+    }
+
+    fn default_challenges_per_difficulty_step() -> usize {
+        100
+    }
+
+    fn default_demand_window_secs() -> u64 {
+        60
+    }
+
+    fn default_challenge_ttl_secs() -> u64 {
+        300
+    }
+
+    fn default_max_challenges_per_ip() -> usize {
+        10
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.base_difficulty <= self.max_difficulty,
+            "base_difficulty ({}) must not be greater than max_difficulty ({})",
+            self.base_difficulty,
+            self.max_difficulty
+        );
+        ensure!(
+            self.challenges_per_difficulty_step > 0,
+            "challenges_per_difficulty_step must be greater than 0"
+        );
+        ensure!(
+            self.max_challenges_per_ip > 0,
+            "max_challenges_per_ip must be greater than 0"
+        );
+        Ok(())
+    }
+}
+
+impl Default for ProofOfWorkCheckerConfig {
+    fn default() -> Self {
+        Self {
+            base_difficulty: Self::default_base_difficulty(),
+            max_difficulty: Self::default_max_difficulty(),
+            challenges_per_difficulty_step: Self::default_challenges_per_difficulty_step(),
+            demand_window_secs: Self::default_demand_window_secs(),
+            challenge_ttl_secs: Self::default_challenge_ttl_secs(),
+            max_challenges_per_ip: Self::default_max_challenges_per_ip(),
+            secret_key: None,
+        }
+    }
+}
+
+pub struct ProofOfWorkChecker {
+    /// Reference to the one proof of work manager. This must be passed in
+    /// because we need to be able to use it from the challenge endpoint too.
+    proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>,
+}
+
+impl ProofOfWorkChecker {
+    pub fn new(proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>) -> Result<Self> {
+        Ok(Self {
+            proof_of_work_manager,
+        })
+    }
+}
+
+#[async_trait]
+impl CheckerTrait for ProofOfWorkChecker {
+    async fn check(
+        &self,
+        data: CheckerData,
+        dry_run: bool,
+    ) -> Result<Vec<RejectionReason>, AptosTapError> {
+        let challenge = match data.headers.get(POW_CHALLENGE_HEADER) {
+            Some(header_value) => header_value.to_str().map_err(|e| {
+                AptosTapError::new_with_error_code(e, AptosTapErrorCode::InvalidRequest)
+            })?,
+            None => {
+                return Ok(vec![RejectionReason::new(
+                    format!("Proof of work header {} not found", POW_CHALLENGE_HEADER),
+                    RejectionReasonCode::ProofOfWorkInvalid,
+                )])
+            },
+        };
+
+        let nonce = match data.headers.get(POW_NONCE_HEADER) {
+            Some(header_value) => match header_value
+                .to_str()
+                .map_err(|e| {
+                    AptosTapError::new_with_error_code(e, AptosTapErrorCode::InvalidRequest)
+                })?
+                .parse::<u64>()
+            {
+                Ok(value) => value,
+                Err(e) => {
+                    return Ok(vec![RejectionReason::new(
+                        format!("Proof of work nonce not a number: {:#}", e),
+                        RejectionReasonCode::ProofOfWorkInvalid,
+                    )])
+                },
+            },
+            None => {
+                return Ok(vec![RejectionReason::new(
+                    format!("Proof of work header {} not found", POW_NONCE_HEADER),
+                    RejectionReasonCode::ProofOfWorkInvalid,
+                )])
+            },
+        };
+
+        let solved = match self.proof_of_work_manager.lock().await.check_challenge(
+            challenge,
+            data.receiver,
+            nonce,
+            dry_run,
+        ) {
+            Ok(solved) => solved,
+            Err(e) => {
+                return Ok(vec![RejectionReason::new(
+                    format!("{:#}", e),
+                    RejectionReasonCode::ProofOfWorkInvalid,
+                )])
+            },
+        };
+
+        if !solved {
+            return Ok(vec![RejectionReason::new(
+                format!(
+                    "Nonce {} does not solve proof of work challenge {} for account {}",
+                    nonce, challenge, data.receiver
+                ),
+                RejectionReasonCode::ProofOfWorkInvalid,
+            )]);
+        }
+
+        Ok(vec![])
+    }
+
+    fn cost(&self) -> u8 {
+        3
+    }
+}
+
+/// ProofOfWorkManager is responsible for creating proof of work challenges and
+/// later checking solutions to them. A challenge has the form
+/// `<expiration time>.<difficulty>.<salt>.<hmac>`, so checking it requires no
+/// record of issuing it. We only remember which challenges have been used, so
+/// each can fund a single request.
+pub struct ProofOfWorkManager {
+    config: ProofOfWorkCheckerConfig,
+
+    secret_key: Vec<u8>,
+
+    /// When and to which IP we issued the challenges within the demand window,
+    /// oldest first.
+    recently_issued: VecDeque<(u64, IpAddr)>,
+
+    /// Number of challenges in `recently_issued` per IP.
+    recently_issued_per_ip: HashMap<IpAddr, usize>,
+
+    /// Challenges that were used and have not expired yet.
+    used_challenges: HashSet<String>,
+
+    /// The same challenges, ordered by expiration time, so we can forget them
+    /// once they expire without looking at the others.
+    used_challenges_by_expiration: BinaryHeap<Reverse<(u64, String)>>,
+}
+
+impl ProofOfWorkManager {
+    pub fn new(config: ProofOfWorkCheckerConfig) -> Result<Self> {
+        config
+            .validate()
+            .context("Invalid proof of work checker config")?;
+        let secret_key = match &config.secret_key {
+            Some(secret_key) => secret_key.0.as_bytes().to_vec(),
+            None => rand::thread_rng().gen::<[u8; 32]>().to_vec(),
+        };
+        Ok(Self {
+            config,
+            secret_key,
+            recently_issued: VecDeque::new(),
+            recently_issued_per_ip: HashMap::new(),
+            used_challenges: HashSet::new(),
+            used_challenges_by_expiration: BinaryHeap::new(),
+        })
+    }
+
+    /// Forget the challenges issued before the demand window.
+    fn prune_recently_issued(&mut self, now: u64) {
+        while let Some((issued_secs, source_ip)) = self.recently_issued.front() {
+            if *issued_secs + self.config.demand_window_secs > now {
+                break;
+            }
+            if let Some(count) = self.recently_issued_per_ip.get_mut(source_ip) {
+                *count -= 1;
+                if *count == 0 {
+                    self.recently_issued_per_ip.remove(source_ip);
+                }
+            }
+            self.recently_issued.pop_front();
+        }
+    }
+
+    /// Forget the used challenges that have expired, they are rejected anyway.
+    fn prune_used_challenges(&mut self, now: u64) {
+        while let Some(Reverse((expiration_time_secs, _))) =
+            self.used_challenges_by_expiration.peek()
+        {
+            if *expiration_time_secs > now {
+                break;
+            }
+            let Reverse((_, challenge)) = self.used_challenges_by_expiration.pop().unwrap();
+            self.used_challenges.remove(&challenge);
+        }
+    }
+
+    /// The difficulty of challenges issued now, which goes up with the number
+    /// of challenges issued recently.
+    pub fn current_difficulty(&mut self) -> u8 {
+        self.prune_recently_issued(get_current_time_secs());
+        let extra_bits = self.recently_issued.len() / self.config.challenges_per_difficulty_step;
+        let difficulty = (self.config.base_difficulty as usize).saturating_add(extra_bits);
+        std::cmp::min(difficulty, self.config.max_difficulty as usize) as u8
+    }
+
+    fn sign(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take keys of any size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// Create a new proof of work challenge for a client at `source_ip`.
+    pub fn create_challenge(
+        &mut self,
+        source_ip: IpAddr,
+    ) -> Result<ProofOfWorkChallenge, AptosTapError> {
+        let now = get_current_time_secs();
+        self.prune_recently_issued(now);
+        let issued_to_ip = self
+            .recently_issued_per_ip
+            .get(&source_ip)
+            .copied()
+            .unwrap_or(0);
+        if issued_to_ip >= self.config.max_challenges_per_ip {
+            return Err(AptosTapError::new(
+                "Too many proof of work challenges requested".to_string(),
+                AptosTapErrorCode::Rejected,
+            )
+            .rejection_reasons(vec![RejectionReason::new(
+                format!(
+                    "IP {} has exceeded the limit of {} proof of work challenges per {} seconds",
+                    source_ip, self.config.max_challenges_per_ip, self.config.demand_window_secs
+                ),
+                RejectionReasonCode::IpUsageLimitExhausted,
+            )
+            .retry_after(self.config.demand_window_secs)]));
+        }
+
+        let difficulty = self.current_difficulty();
+        let payload = format!(
+            "{}.{}.{}",
+            now + self.config.challenge_ttl_secs,
+            difficulty,
+            hex::encode(rand::thread_rng().gen::<[u8; 16]>())
+        );
+        let signature = hex::encode(self.sign(&payload).finalize().into_bytes());
+        self.recently_issued.push_back((now, source_ip));
+        *self.recently_issued_per_ip.entry(source_ip).or_insert(0) += 1;
+
+        Ok(ProofOfWorkChallenge {
+            challenge: format!("{}.{}", payload, signature),
+            difficulty,
+        })
+    }
+
+    /// Verifies that we issued `challenge` and returns its expiration time and
+    /// difficulty.
+    fn parse_challenge(&self, challenge: &str) -> Result<(u64, u8)> {
+        let (payload, signature) = match challenge.rsplit_once('.') {
+            Some(parts) => parts,
+            None => bail!("Proof of work challenge malformed: {}", challenge),
+        };
+        let signature = match hex::decode(signature) {
+            Ok(signature) => signature,
+            Err(_) => bail!("Proof of work challenge malformed: {}", challenge),
+        };
+        if self.sign(payload).verify_slice(&signature).is_err() {
+            bail!("Proof of work challenge unknown: {}", challenge);
+        }
+        let mut parts = payload.split('.');
+        match (
+            parts.next().map(str::parse::<u64>),
+            parts.next().map(str::parse::<u8>),
+        ) {
+            (Some(Ok(expiration_time_secs)), Some(Ok(difficulty))) => {
+                Ok((expiration_time_secs, difficulty))
+            },
+            _ => bail!("Proof of work challenge malformed: {}", challenge),
+        }
+    }
+
+    /// Check a solution to a proof of work challenge. Returns true if the
+    /// nonce solves the challenge for the receiver. Challenges can only be
+    /// used once, unless dry_run is set.
+    pub fn check_challenge(
+        &mut self,
+        challenge: &str,
+        receiver: AccountAddress,
+        nonce: u64,
+        dry_run: bool,
+    ) -> Result<bool> {
+        let (expiration_time_secs, difficulty) = self.parse_challenge(challenge)?;
+        let now = get_current_time_secs();
+        if expiration_time_secs <= now {
+            bail!("Proof of work challenge expired: {}", challenge);
+        }
+        self.prune_used_challenges(now);
+        if self.used_challenges.contains(challenge) {
+            bail!("Proof of work challenge already used: {}", challenge);
+        }
+        if !is_proof_of_work_solution(challenge, receiver, nonce, difficulty) {
+            return Ok(false);
+        }
+        if !dry_run {
+            self.used_challenges.insert(challenge.to_string());
+            self.used_challenges_by_expiration
+                .push(Reverse((expiration_time_secs, challenge.to_string())));
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::rest_client::faucet::solve_proof_of_work;
+
+    fn build_manager() -> ProofOfWorkManager {
+        ProofOfWorkManager::new(ProofOfWorkCheckerConfig {
+            base_difficulty: 4,
+            max_difficulty: 6,
+            challenges_per_difficulty_step: 2,
+            max_challenges_per_ip: 8,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([127, 0, 0, last])
+    }
+
+    #[test]
+    fn test_check_challenge() {
+        let mut manager = build_manager();
+        let receiver = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        let challenge = manager.create_challenge(ip(1)).unwrap();
+        let nonce = solve_proof_of_work(&challenge, receiver);
+
+        // Find a nonce that doesn't solve the challenge.
+        let wrong_nonce = (0..)
+            .find(|n| !is_proof_of_work_solution(&challenge.challenge, receiver, *n, 4))
+            .unwrap();
+        assert!(!manager
+            .check_challenge(&challenge.challenge, receiver, wrong_nonce, false)
+            .unwrap());
+
+        // Dry runs don't use up the challenge.
+        assert!(manager
+            .check_challenge(&challenge.challenge, receiver, nonce, true)
+            .unwrap());
+        assert!(manager
+            .check_challenge(&challenge.challenge, receiver, nonce, false)
+            .unwrap());
+
+        // Challenges can only be used once.
+        assert!(manager
+            .check_challenge(&challenge.challenge, receiver, nonce, false)
+            .is_err());
+        assert!(manager
+            .check_challenge("unknown", receiver, nonce, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_challenges_are_signed() {
+        let mut manager = build_manager();
+        let receiver = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        let challenge = manager.create_challenge(ip(1)).unwrap().challenge;
+
+        // Lowering the difficulty invalidates the signature.
+        let mut parts: Vec<&str> = challenge.split('.').collect();
+        parts[1] = "0";
+        let forged = parts.join(".");
+        assert!(manager.check_challenge(&forged, receiver, 0, true).is_err());
+
+        // Challenges of another manager are only valid with the same key.
+        let config = ProofOfWorkCheckerConfig {
+            base_difficulty: 4,
+            max_difficulty: 4,
+            secret_key: Some(KeyString("secret".to_string())),
+            ..Default::default()
+        };
+        let mut issuer = ProofOfWorkManager::new(config.clone()).unwrap();
+        let mut other = ProofOfWorkManager::new(config).unwrap();
+        let challenge = issuer.create_challenge(ip(1)).unwrap();
+        let nonce = solve_proof_of_work(&challenge, receiver);
+        assert!(manager
+            .check_challenge(&challenge.challenge, receiver, nonce, true)
+            .is_err());
+        assert!(other
+            .check_challenge(&challenge.challenge, receiver, nonce, true)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_difficulty_scales_with_demand() {
+        let _ = crate::endpoints::USE_HELPFUL_ERRORS.set(true);
+        let mut manager = build_manager();
+        let difficulties: Vec<u8> = (0..8)
+            .map(|_| manager.create_challenge(ip(1)).unwrap().difficulty)
+            .collect();
+        assert_eq!(difficulties, vec![4, 4, 5, 5, 6, 6, 6, 6]);
+
+        // Each IP can only request so many challenges.
+        let error = manager.create_challenge(ip(1)).unwrap_err();
+        assert_eq!(
+            error.status_and_retry_after().0,
+            poem::http::StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(manager.create_challenge(ip(2)).unwrap().difficulty, 6);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(ProofOfWorkManager::new(ProofOfWorkCheckerConfig {
+            base_difficulty: 10,
+            max_difficulty: 8,
+            ..Default::default()
+        })
+        .is_err());
+        assert!(ProofOfWorkManager::new(ProofOfWorkCheckerConfig {
+            challenges_per_difficulty_step: 0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -4,6 +4,7 @@
 use super::{CheckerData, CheckerTrait, CompleteData};
 use crate::{
     endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode},
+    funder::DEFAULT_ASSET_NAME,
     helpers::{days_since_tap_epoch, get_current_time_secs, seconds_until_next_day},
 };
 use anyhow::{Context, Result};
@@ -13,7 +14,7 @@ use deadpool_redis::{
     Config, Connection, Pool, Runtime,
 };
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
+use std::{collections::HashMap, net::IpAddr};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RedisRatelimitCheckerConfig {
@@ -38,6 +39,11 @@ pub struct RedisRatelimitCheckerConfig {
     /// Max number of requests per IP per day. 500s are not counted, because
     /// they are not the user's fault, but everything else is.
     pub max_requests_per_ip_per_day: u32,
+
+    /// Overrides `max_requests_per_ip_per_day` for the given assets, keyed by
+    /// asset name. Requests for each asset are counted separately.
+    #[serde(default)]
+    pub max_requests_per_ip_per_day_per_asset: HashMap<String, u32>,
 }
 
 impl RedisRatelimitCheckerConfig {
@@ -128,26 +134,45 @@ impl RedisRatelimitChecker {
         })
     }
 
-    // Returns the key and the seconds until the next day.
-    fn get_key_and_secs_until_next_day(&self, source_ip: &IpAddr) -> (String, u64) {
+    // Returns the key and the seconds until the next day. APT requests use the
+    // same keys as before assets other than APT were supported.
+    fn get_key_and_secs_until_next_day(&self, source_ip: &IpAddr, asset: &str) -> (String, u64) {
         let now_secs = get_current_time_secs();
         let seconds_until_next_day = seconds_until_next_day(now_secs);
-        let key = format!("ip:{}:{}", source_ip, days_since_tap_epoch(now_secs));
+        let key = if asset == DEFAULT_ASSET_NAME {
+            format!("ip:{}:{}", source_ip, days_since_tap_epoch(now_secs))
+        } else {
+            format!(
+                "ip:{}:{}:{}",
+                source_ip,
+                asset,
+                days_since_tap_epoch(now_secs)
+            )
+        };
         (key, seconds_until_next_day)
     }
 
+    fn max_requests_per_ip_per_day(&self, asset: &str) -> u32 {
+        self.args
+            .max_requests_per_ip_per_day_per_asset
+            .get(asset)
+            .copied()
+            .unwrap_or(self.args.max_requests_per_ip_per_day)
+    }
+
     fn check_limit_value(
         &self,
         data: &CheckerData,
         limit_value: Option<i64>,
         seconds_until_next_day: u64,
     ) -> Option<RejectionReason> {
-        if limit_value.unwrap_or(0) > self.args.max_requests_per_ip_per_day as i64 {
+        let max_requests_per_ip_per_day = self.max_requests_per_ip_per_day(&data.asset);
+        if limit_value.unwrap_or(0) > max_requests_per_ip_per_day as i64 {
             Some(
                 RejectionReason::new(
                     format!(
-                        "IP {} has reached the maximum allowed number of requests per day: {}",
-                        data.source_ip, self.args.max_requests_per_ip_per_day
+                        "IP {} has reached the maximum allowed number of requests per day for {}: {}",
+                        data.source_ip, data.asset, max_requests_per_ip_per_day
                     ),
                     RejectionReasonCode::IpUsageLimitExhausted,
                 )
@@ -172,7 +197,8 @@ impl CheckerTrait for RedisRatelimitChecker {
             .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::StorageError))?;
 
         // Generate a key corresponding to this IP address and the current day.
-        let (key, seconds_until_next_day) = self.get_key_and_secs_until_next_day(&data.source_ip);
+        let (key, seconds_until_next_day) =
+            self.get_key_and_secs_until_next_day(&data.source_ip, &data.asset);
 
         // Get the value for the key, indicating how many non-500 requests we
         // have serviced for this it today.
@@ -250,7 +276,10 @@ impl CheckerTrait for RedisRatelimitChecker {
             .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::StorageError))?;
 
         // Generate a key corresponding to this IP address and the current day.
-        let (key, _) = self.get_key_and_secs_until_next_day(&data.checker_data.source_ip);
+        let (key, _) = self.get_key_and_secs_until_next_day(
+            &data.checker_data.source_ip,
+            &data.checker_data.asset,
+        );
 
         conn.decr(&key, 1).await.map_err(|e| {
             AptosTapError::new_with_error_code(
@@ -9,7 +9,7 @@ use crate::{
     bypasser::{Bypasser, BypasserTrait},
     checkers::{Checker, CheckerData, CheckerTrait, CompleteData},
     endpoints::AptosTapErrorCode,
-    funder::{Funder, FunderTrait},
+    funder::{Funder, FunderTrait, DEFAULT_ASSET_NAME},
     helpers::{get_current_time_secs, transaction_hashes},
 };
 use aptos_logger::info;
@@ -43,6 +43,10 @@ pub struct FundRequest {
 
     /// Either this or `auth_key` / `address` must be provided.
     pub pub_key: Option<String>,
+
+    /// The name of the asset to fund, as configured in the faucet. If not
+    /// set, or set to `apt`, the account is funded with APT.
+    pub asset: Option<String>,
 }
 
 #[derive(Clone, Debug, Object)]
@@ -82,7 +86,13 @@ impl FundRequest {
 
 impl std::fmt::Display for FundRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "<Fund {:?} to {:?}>", self.amount, self.receiver())
+        write!(
+            f,
+            "<Fund {:?} of {} to {:?}>",
+            self.amount,
+            self.asset.as_deref().unwrap_or(DEFAULT_ASSET_NAME),
+            self.receiver()
+        )
     }
 }
 
@@ -157,7 +167,13 @@ impl FundApi {
         // to fund the account.
         self.components
             .funder
-            .fund(fund_request.amount, checker_data.receiver, true, bypass)
+            .fund(
+                fund_request.amount,
+                checker_data.receiver,
+                fund_request.asset.clone(),
+                true,
+                bypass,
+            )
             .await?;
 
         Ok(())
@@ -231,8 +247,20 @@ impl FundApiComponents {
             },
         };
 
+        let asset = fund_request
+            .asset
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ASSET_NAME.to_string());
+        if !self.funder.supports_asset(&asset) {
+            return Err(AptosTapError::new(
+                format!("Asset {} is not supported by this faucet", asset),
+                AptosTapErrorCode::InvalidRequest,
+            ));
+        }
+
         let checker_data = CheckerData {
             receiver,
+            asset,
             source_ip,
             headers: Arc::new(header_map.clone()),
             time_request_received_secs: get_current_time_secs(),
@@ -294,7 +322,13 @@ impl FundApiComponents {
         // Fund the account.
         let fund_result = self
             .funder
-            .fund(fund_request.amount, checker_data.receiver, false, bypass)
+            .fund(
+                fund_request.amount,
+                checker_data.receiver,
+                fund_request.asset.clone(),
+                false,
+                bypass,
+            )
             .await;
 
         // This might be empty if there is an error and we never got to the
@@ -308,6 +342,7 @@ impl FundApiComponents {
         info!(
             source_ip = checker_data.source_ip,
             address = checker_data.receiver,
+            asset = checker_data.asset,
             requested_amount = fund_request.amount,
             txn_hashes = txn_hashes,
             success = fund_result.is_ok(),
@@ -388,6 +423,7 @@ pub async fn mint(
         auth_key,
         address,
         pub_key,
+        asset: None,
     };
     let txns = fund_api_components
         .0
@@ -0,0 +1,78 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This API is for the ProofOfWorkChecker.
+
+use super::{errors::AptosTapErrorResponse, ApiTags, AptosTapError, AptosTapErrorCode};
+use crate::checkers::ProofOfWorkManager;
+use futures::lock::Mutex;
+use poem::{web::RealIp, Result};
+use poem_openapi::{payload::Json, Object, OpenApi};
+use std::sync::Arc;
+
+pub struct ProofOfWorkApi {
+    pub enabled: bool,
+    pub proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>,
+}
+
+#[derive(Clone, Debug, Object)]
+pub struct ProofOfWorkChallengeResponse {
+    /// The challenge to solve. Include it in the `POW_CHALLENGE` header of
+    /// the call to `/fund`.
+    pub challenge: String,
+    /// Number of leading zero bits sha3-256(challenge || receiver || nonce)
+    /// must have, where receiver is the 32 byte address of the account to
+    /// fund and nonce is a little endian u64. Include the nonce in the
+    /// `POW_NONCE` header of the call to `/fund`.
+    pub difficulty: u8,
+}
+
+#[OpenApi]
+impl ProofOfWorkApi {
+    /// Initiate proof of work flow
+    ///
+    /// With this endpoint you can initiate a proof of work flow, an
+    /// alternative to captchas for scripts. The response contains a challenge
+    /// and its difficulty. The difficulty goes up when the faucet is in high
+    /// demand. Each IP can only request so many challenges at a time. This
+    /// endpoint is only relevant if the ProofOfWorkChecker is enabled.
+    #[oai(
+        path = "/request_pow_challenge",
+        method = "get",
+        operation_id = "request_pow_challenge",
+        tag = "ApiTags::ProofOfWork"
+    )]
+    async fn request_pow_challenge(
+        &self,
+        // This automagically uses FromRequest to get this data from the request.
+        // It takes into things like X-Forwarded-IP and X-Real-IP.
+        source_ip: RealIp,
+    ) -> Result<Json<ProofOfWorkChallengeResponse>, AptosTapErrorResponse> {
+        if !self.enabled {
+            return Err(AptosTapError::new(
+                "The ProofOfWorkChecker is not enabled".to_string(),
+                AptosTapErrorCode::EndpointNotEnabled,
+            )
+            .into());
+        }
+        let source_ip = match source_ip.0 {
+            Some(ip) => ip,
+            None => {
+                return Err(AptosTapError::new(
+                    "No source IP found in the request".to_string(),
+                    AptosTapErrorCode::SourceIpMissing,
+                )
+                .into())
+            },
+        };
+        let challenge = self
+            .proof_of_work_manager
+            .lock()
+            .await
+            .create_challenge(source_ip)?;
+        Ok(Json(ProofOfWorkChallengeResponse {
+            challenge: challenge.challenge,
+            difficulty: challenge.difficulty,
+        }))
+    }
+}
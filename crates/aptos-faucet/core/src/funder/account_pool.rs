@@ -0,0 +1,498 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::middleware::{
+    FUNDING_ACCOUNT_BALANCE, FUNDING_ACCOUNT_IN_FLIGHT_REQUESTS, FUNDING_ACCOUNT_REQUESTS,
+    FUNDING_ACCOUNT_TOP_UPS,
+};
+use anyhow::{bail, Context, Result};
+use aptos_logger::{info, warn};
+use aptos_sdk::{
+    crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey},
+    rest_client::Client,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+        LocalAccount,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::RwLock;
+
+/// Used to derive the keys of the pool accounts from the key of the master account.
+const POOL_ACCOUNT_KEY_SALT: &[u8] = b"APTOS_FAUCET_FUNDING_ACCOUNT_POOL";
+
+/// Configuration for a pool of funding accounts. Requests are spread across the
+/// accounts in the pool, so the faucet isn't limited to the sequence numbers of a
+/// single account, and a stuck transaction only stalls the account that sent it.
+/// The account configured for the funder becomes the master account, which only
+/// tops up the accounts in the pool.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccountPoolConfig {
+    /// Number of accounts in the pool. Their keys are derived from the key of
+    /// the master account, so the same accounts are used across restarts.
+    pub num_accounts: usize,
+
+    /// How to pick the account that handles a request.
+    #[serde(default)]
+    pub assignment_strategy: AssignmentStrategy,
+
+    /// Once the balance of an account in the pool drops below this, it is
+    /// topped up from the master account. This should be above `minimum_funds`,
+    /// which applies to each account in the pool.
+    pub top_up_threshold: u64,
+
+    /// Amount the master account transfers to an account per top up.
+    pub top_up_amount: u64,
+
+    /// How often to check whether accounts in the pool need a top up.
+    #[serde(default = "AccountPoolConfig::default_top_up_interval_secs")]
+    pub top_up_interval_secs: u64,
+}
+
+impl AccountPoolConfig {
+    fn default_top_up_interval_secs() -> u64 {
+        30
+    }
+
+    pub fn build(&self, master_account: LocalAccount) -> Result<FundingAccountPool> {
+        if self.num_accounts == 0 {
+            bail!("The funding account pool must have at least one account");
+        }
+        if self.top_up_amount == 0 {
+            bail!("top_up_amount of the funding account pool must be greater than zero");
+        }
+
+        let accounts = derive_pool_keys(master_account.private_key(), self.num_accounts)
+            .into_iter()
+            .map(|key| LocalAccount::new(account_address_from_private_key(&key), key, 0))
+            .collect();
+
+        Ok(
+            FundingAccountPool::new(accounts, self.assignment_strategy).with_top_up(TopUp {
+                master_account: RwLock::new(master_account),
+                threshold: self.top_up_threshold,
+                amount: self.top_up_amount,
+                interval: Duration::from_secs(self.top_up_interval_secs),
+            }),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AssignmentStrategy {
+    /// Hand out the accounts one after another.
+    #[default]
+    RoundRobin,
+
+    /// Pick the account handling the fewest requests right now. This steers
+    /// requests away from accounts that are waiting for their outstanding
+    /// transactions to catch up.
+    LeastLoaded,
+}
+
+/// An account the funder sends funding transactions from.
+pub struct FundingAccount {
+    pub account: RwLock<LocalAccount>,
+
+    /// When recovering from being overloaded, this ensures we handle the
+    /// requests assigned to this account in the order they came in.
+    pub outstanding_requests: RwLock<Vec<(AccountAddress, u64)>>,
+
+    address: AccountAddress,
+
+    /// Address of the account, used as the label of its metrics.
+    label: String,
+
+    /// Number of requests currently assigned to this account.
+    in_flight_requests: AtomicUsize,
+
+    /// Accounts without the minimum funds are unhealthy, and don't get new
+    /// requests assigned while there are healthy ones.
+    healthy: AtomicBool,
+}
+
+impl FundingAccount {
+    fn new(account: LocalAccount) -> Self {
+        let address = account.address();
+        Self {
+            account: RwLock::new(account),
+            outstanding_requests: RwLock::new(vec![]),
+            address,
+            label: address.to_hex_literal(),
+            in_flight_requests: AtomicUsize::new(0),
+            healthy: AtomicBool::new(true),
+        }
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    pub fn set_healthy(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::Relaxed);
+    }
+
+    pub fn record_balance(&self, balance: u64) {
+        FUNDING_ACCOUNT_BALANCE
+            .with_label_values(&[&self.label])
+            .set(balance as i64);
+    }
+
+    fn in_flight_requests(&self) -> usize {
+        self.in_flight_requests.load(Ordering::Relaxed)
+    }
+}
+
+/// A funding account assigned to a request. The account counts as loaded by the
+/// request until this is dropped.
+pub struct FundingAccountGuard<'a> {
+    funding_account: &'a FundingAccount,
+}
+
+impl<'a> FundingAccountGuard<'a> {
+    fn new(funding_account: &'a FundingAccount) -> Self {
+        funding_account
+            .in_flight_requests
+            .fetch_add(1, Ordering::Relaxed);
+        FUNDING_ACCOUNT_REQUESTS
+            .with_label_values(&[&funding_account.label])
+            .inc();
+        FUNDING_ACCOUNT_IN_FLIGHT_REQUESTS
+            .with_label_values(&[&funding_account.label])
+            .inc();
+        Self { funding_account }
+    }
+}
+
+impl Deref for FundingAccountGuard<'_> {
+    type Target = FundingAccount;
+
+    fn deref(&self) -> &Self::Target {
+        self.funding_account
+    }
+}
+
+impl Drop for FundingAccountGuard<'_> {
+    fn drop(&mut self) {
+        self.funding_account
+            .in_flight_requests
+            .fetch_sub(1, Ordering::Relaxed);
+        FUNDING_ACCOUNT_IN_FLIGHT_REQUESTS
+            .with_label_values(&[&self.funding_account.label])
+            .dec();
+    }
+}
+
+/// How the master account keeps the accounts in the pool funded.
+struct TopUp {
+    master_account: RwLock<LocalAccount>,
+    threshold: u64,
+    amount: u64,
+    interval: Duration,
+}
+
+/// The accounts a funder sends funding transactions from. Without a pool
+/// configured, this holds just the funder account.
+pub struct FundingAccountPool {
+    accounts: Vec<FundingAccount>,
+    assignment_strategy: AssignmentStrategy,
+    next_account: AtomicUsize,
+    top_up: Option<TopUp>,
+}
+
+impl FundingAccountPool {
+    pub fn new(accounts: Vec<LocalAccount>, assignment_strategy: AssignmentStrategy) -> Self {
+        assert!(
+            !accounts.is_empty(),
+            "A funding account pool needs at least one account"
+        );
+        Self {
+            accounts: accounts.into_iter().map(FundingAccount::new).collect(),
+            assignment_strategy,
+            next_account: AtomicUsize::new(0),
+            top_up: None,
+        }
+    }
+
+    fn with_top_up(mut self, top_up: TopUp) -> Self {
+        self.top_up = Some(top_up);
+        self
+    }
+
+    pub fn accounts(&self) -> &[FundingAccount] {
+        &self.accounts
+    }
+
+    /// How often the accounts should be topped up, None if there is no master
+    /// account to top them up from.
+    pub fn top_up_interval(&self) -> Option<Duration> {
+        self.top_up.as_ref().map(|top_up| top_up.interval)
+    }
+
+    /// Assigns a funding account to a request. Only healthy accounts are
+    /// considered, unless all of them are unhealthy, in which case the request
+    /// is assigned to one anyway and is expected to fail its balance check.
+    pub fn acquire(&self) -> FundingAccountGuard<'_> {
+        let mut candidates: Vec<&FundingAccount> = self
+            .accounts
+            .iter()
+            .filter(|account| account.is_healthy())
+            .collect();
+        if candidates.is_empty() {
+            candidates = self.accounts.iter().collect();
+        }
+
+        let start = self.next_account.fetch_add(1, Ordering::Relaxed);
+        let funding_account = match self.assignment_strategy {
+            AssignmentStrategy::RoundRobin => candidates[start % candidates.len()],
+            // Start the search at a different account every time, so idle
+            // accounts share the load rather than the first one taking it all.
+            AssignmentStrategy::LeastLoaded => (0..candidates.len())
+                .map(|i| candidates[(start + i) % candidates.len()])
+                .min_by_key(|account| account.in_flight_requests())
+                .unwrap(),
+        };
+        FundingAccountGuard::new(funding_account)
+    }
+
+    /// Transfers funds from the master account to every account in the pool
+    /// whose balance is below the top up threshold. Does nothing if the pool
+    /// has no master account. An account we fail to read the balance of or to
+    /// top up is skipped, the error lists all such accounts.
+    pub async fn top_up_accounts(
+        &self,
+        client: &Client,
+        transaction_factory: &TransactionFactory,
+    ) -> Result<()> {
+        let top_up = match &self.top_up {
+            Some(top_up) => top_up,
+            None => return Ok(()),
+        };
+
+        let mut failed_accounts = vec![];
+        for funding_account in &self.accounts {
+            // Accounts in a new pool don't exist yet, their balance is just 0.
+            // Any error here means we don't know the balance, in which case we
+            // must not send a top up that may not be needed.
+            let balance = match client
+                .view_apt_account_balance(funding_account.address)
+                .await
+            {
+                Ok(response) => response.into_inner(),
+                Err(e) => {
+                    warn!(
+                        "Failed to get the balance of funding account {}: {:#}",
+                        funding_account.label, e
+                    );
+                    failed_accounts.push(funding_account.label.as_str());
+                    continue;
+                },
+            };
+            funding_account.record_balance(balance);
+            if balance >= top_up.threshold {
+                continue;
+            }
+
+            let result = top_up
+                .transfer(client, transaction_factory, funding_account.address)
+                .await;
+            FUNDING_ACCOUNT_TOP_UPS
+                .with_label_values(&[
+                    &funding_account.label,
+                    if result.is_ok() { "success" } else { "failure" },
+                ])
+                .inc();
+            if let Err(e) = result {
+                warn!(
+                    "Failed to top up funding account {}: {:#}",
+                    funding_account.label, e
+                );
+                failed_accounts.push(funding_account.label.as_str());
+                continue;
+            }
+            info!(
+                account = funding_account.address,
+                balance = balance,
+                amount = top_up.amount,
+                event = "funding_account_topped_up"
+            );
+        }
+        if !failed_accounts.is_empty() {
+            bail!(
+                "Failed to top up funding accounts: {}",
+                failed_accounts.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+impl TopUp {
+    async fn transfer(
+        &self,
+        client: &Client,
+        transaction_factory: &TransactionFactory,
+        receiver_address: AccountAddress,
+    ) -> Result<()> {
+        // We hold the lock for the whole transfer so top ups are never sent
+        // with the same sequence number.
+        let master_account = self.master_account.write().await;
+        let sequence_number = client
+            .get_account(master_account.address())
+            .await
+            .context("Failed to get the master account")?
+            .into_inner()
+            .sequence_number;
+        master_account.set_sequence_number(sequence_number);
+
+        let gas_unit_price = client.estimate_gas_price().await?.into_inner().gas_estimate;
+        let transaction = master_account.sign_with_transaction_builder(
+            transaction_factory
+                .clone()
+                .with_gas_unit_price(gas_unit_price)
+                .payload(aptos_stdlib::aptos_account_transfer(
+                    receiver_address,
+                    self.amount,
+                )),
+        );
+        client.submit_and_wait(&transaction).await?;
+        Ok(())
+    }
+}
+
+/// Derives the keys of the accounts in the pool from the key of the master account.
+fn derive_pool_keys(master_key: &Ed25519PrivateKey, num_accounts: usize) -> Vec<Ed25519PrivateKey> {
+    (0..num_accounts as u64)
+        .map(|index| {
+            let seed = HashValue::sha3_256_of(
+                &[
+                    POOL_ACCOUNT_KEY_SALT,
+                    &master_key.to_bytes(),
+                    &index.to_le_bytes(),
+                ]
+                .concat(),
+            );
+            Ed25519PrivateKey::try_from(seed.as_ref())
+                .expect("Any 32 bytes are a valid Ed25519 private key")
+        })
+        .collect()
+}
+
+pub fn account_address_from_private_key(private_key: &Ed25519PrivateKey) -> AccountAddress {
+    let public_key = private_key.public_key();
+    let auth_key = AuthenticationKey::ed25519(&public_key);
+    AccountAddress::new(*auth_key.account_address())
+}
+
+/// Starts a task that periodically tops up the accounts in the pool. Does
+/// nothing if the pool has no master account.
+pub fn spawn_top_up_task(
+    pool: Arc<FundingAccountPool>,
+    client: Client,
+    transaction_factory: TransactionFactory,
+    join_set: &mut tokio::task::JoinSet<anyhow::Result<()>>,
+) {
+    let interval = match pool.top_up_interval() {
+        Some(interval) => interval,
+        None => return,
+    };
+    join_set.spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            // A failed top up is retried on the next tick. In the meantime
+            // accounts that run out of funds are marked unhealthy and don't
+            // get new requests.
+            if let Err(e) = pool.top_up_accounts(&client, &transaction_factory).await {
+                warn!("Failed to top up the funding account pool: {:#}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master_key() -> Ed25519PrivateKey {
+        Ed25519PrivateKey::try_from([7u8; 32].as_slice()).unwrap()
+    }
+
+    fn build_pool(
+        num_accounts: usize,
+        assignment_strategy: AssignmentStrategy,
+    ) -> FundingAccountPool {
+        let accounts = derive_pool_keys(&master_key(), num_accounts)
+            .into_iter()
+            .map(|key| LocalAccount::new(account_address_from_private_key(&key), key, 0))
+            .collect();
+        FundingAccountPool::new(accounts, assignment_strategy)
+    }
+
+    #[test]
+    fn test_derive_pool_keys() {
+        let keys = derive_pool_keys(&master_key(), 3);
+        let again = derive_pool_keys(&master_key(), 3);
+        for (key, same_key) in keys.iter().zip(again.iter()) {
+            assert_eq!(key.to_bytes(), same_key.to_bytes());
+            assert_ne!(key.to_bytes(), master_key().to_bytes());
+        }
+        assert_ne!(keys[0].to_bytes(), keys[1].to_bytes());
+        assert_ne!(keys[1].to_bytes(), keys[2].to_bytes());
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let pool = build_pool(3, AssignmentStrategy::RoundRobin);
+        let addresses: Vec<AccountAddress> = (0..6).map(|_| pool.acquire().address()).collect();
+        assert_eq!(addresses[0..3], addresses[3..6]);
+        assert_ne!(addresses[0], addresses[1]);
+        assert_ne!(addresses[1], addresses[2]);
+
+        // Unhealthy accounts are skipped.
+        pool.accounts()[1].set_healthy(false);
+        for _ in 0..6 {
+            assert_ne!(pool.acquire().address(), pool.accounts()[1].address());
+        }
+
+        // Unless there is nothing else.
+        pool.accounts()[0].set_healthy(false);
+        pool.accounts()[2].set_healthy(false);
+        let _guard = pool.acquire();
+    }
+
+    #[test]
+    fn test_least_loaded() {
+        let pool = build_pool(3, AssignmentStrategy::LeastLoaded);
+        let first = pool.acquire();
+        let second = pool.acquire();
+        let third = pool.acquire();
+        assert_ne!(first.address(), second.address());
+        assert_ne!(second.address(), third.address());
+        assert_ne!(first.address(), third.address());
+
+        // Once a request finishes, its account is the least loaded one.
+        let second_address = second.address();
+        drop(second);
+        assert_eq!(pool.acquire().address(), second_address);
+        assert_eq!(
+            pool.accounts()[0].in_flight_requests()
+                + pool.accounts()[1].in_flight_requests()
+                + pool.accounts()[2].in_flight_requests(),
+            2
+        );
+    }
+}
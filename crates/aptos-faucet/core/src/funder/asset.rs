@@ -0,0 +1,310 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::endpoints::{AptosTapError, AptosTapErrorCode};
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{
+    bcs,
+    move_types::{
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    },
+    transaction_builder::aptos_stdlib,
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+
+/// The name of APT in the `asset` field of requests. Requests without an
+/// `asset` are for APT too.
+pub const DEFAULT_ASSET_NAME: &str = "apt";
+
+/// Configuration for an asset other than APT that the faucet hands out, e.g. a
+/// test stablecoin on devnet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AssetConfig {
+    #[serde(flatten)]
+    pub asset_type: AssetType,
+
+    /// If given, the funder mints new units of the asset by calling this entry
+    /// function, e.g. `0x1::managed_coin::mint`, with the receiver address and
+    /// the amount as arguments. For coins, the coin type is passed as the only
+    /// type argument. The funder account must hold the capability the function
+    /// requires to mint. If not given, the funder transfers the asset from the
+    /// balance of the funder account instead.
+    pub mint_function: Option<String>,
+
+    /// Maximum amount of the asset to give an account, in the smallest unit of
+    /// the asset. This is also the amount given if the request doesn't specify one.
+    pub maximum_amount: u64,
+
+    /// Like `maximum_amount`, but for requests that were allowed to skip the
+    /// Checkers by a Bypasser. If not given, `maximum_amount` is used.
+    pub maximum_amount_with_bypass: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum AssetType {
+    /// A coin, e.g. `0x1::coin::Coin<0xcafe::usdc::USDC>`. The coin type is
+    /// given as a string, e.g. `0xcafe::usdc::USDC`.
+    Coin { coin_type: String },
+
+    /// A fungible asset, identified by the address of its metadata object.
+    FungibleAsset { metadata_address: AccountAddress },
+}
+
+/// An asset built from an [`AssetConfig`], ready to build funding transactions.
+#[derive(Clone, Debug)]
+pub struct Asset {
+    kind: AssetKind,
+    mint_function: Option<(ModuleId, Identifier)>,
+    maximum_amount: u64,
+    maximum_amount_with_bypass: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+enum AssetKind {
+    Coin(TypeTag),
+    FungibleAsset(AccountAddress),
+}
+
+impl AssetConfig {
+    pub fn build(&self) -> Result<Asset> {
+        let kind = match &self.asset_type {
+            AssetType::Coin { coin_type } => AssetKind::Coin(
+                TypeTag::from_str(coin_type)
+                    .with_context(|| format!("Invalid coin type {}", coin_type))?,
+            ),
+            AssetType::FungibleAsset { metadata_address } => {
+                AssetKind::FungibleAsset(*metadata_address)
+            },
+        };
+        let mint_function = self
+            .mint_function
+            .as_deref()
+            .map(parse_entry_function_id)
+            .transpose()?;
+        Ok(Asset {
+            kind,
+            mint_function,
+            maximum_amount: self.maximum_amount,
+            maximum_amount_with_bypass: self.maximum_amount_with_bypass,
+        })
+    }
+}
+
+/// Builds the assets of a funder config, keyed by the name requests use for them.
+pub fn build_assets(configs: &HashMap<String, AssetConfig>) -> Result<HashMap<String, Asset>> {
+    configs
+        .iter()
+        .map(|(name, config)| {
+            if name == DEFAULT_ASSET_NAME {
+                bail!(
+                    "The asset name {} is reserved for APT, use a different name",
+                    DEFAULT_ASSET_NAME
+                );
+            }
+            let asset = config
+                .build()
+                .with_context(|| format!("Failed to build asset {}", name))?;
+            Ok((name.clone(), asset))
+        })
+        .collect()
+}
+
+/// Looks up the asset of a request. Returns None if the request is for APT.
+pub fn get_asset<'a>(
+    assets: &'a HashMap<String, Asset>,
+    asset: Option<&str>,
+) -> Result<Option<&'a Asset>, AptosTapError> {
+    match asset {
+        None | Some(DEFAULT_ASSET_NAME) => Ok(None),
+        Some(name) => match assets.get(name) {
+            Some(asset) => Ok(Some(asset)),
+            None => Err(AptosTapError::new(
+                format!("Asset {} is not supported by this faucet", name),
+                AptosTapErrorCode::InvalidRequest,
+            )),
+        },
+    }
+}
+
+impl Asset {
+    /// Given a requested amount, determine the amount of the asset to fund.
+    pub fn get_amount(&self, amount: Option<u64>, did_bypass_checkers: bool) -> u64 {
+        let maximum_amount = match (self.maximum_amount_with_bypass, did_bypass_checkers) {
+            (Some(maximum_amount), true) => maximum_amount,
+            _ => self.maximum_amount,
+        };
+        match amount {
+            Some(amount) => std::cmp::min(amount, maximum_amount),
+            None => maximum_amount,
+        }
+    }
+
+    /// Builds the payload that mints or transfers `amount` of the asset to `receiver_address`.
+    pub fn build_payload(
+        &self,
+        receiver_address: AccountAddress,
+        amount: u64,
+    ) -> TransactionPayload {
+        let args = vec![
+            bcs::to_bytes(&receiver_address).unwrap(),
+            bcs::to_bytes(&amount).unwrap(),
+        ];
+        match (&self.mint_function, &self.kind) {
+            (Some((module, function)), AssetKind::Coin(coin_type)) => {
+                TransactionPayload::EntryFunction(EntryFunction::new(
+                    module.clone(),
+                    function.clone(),
+                    vec![coin_type.clone()],
+                    args,
+                ))
+            },
+            (Some((module, function)), AssetKind::FungibleAsset(_)) => {
+                TransactionPayload::EntryFunction(EntryFunction::new(
+                    module.clone(),
+                    function.clone(),
+                    vec![],
+                    args,
+                ))
+            },
+            (None, AssetKind::Coin(coin_type)) => aptos_stdlib::aptos_account_transfer_coins(
+                coin_type.clone(),
+                receiver_address,
+                amount,
+            ),
+            // 0x1::primary_fungible_store::transfer<0x1::fungible_asset::Metadata>.
+            (None, AssetKind::FungibleAsset(metadata_address)) => {
+                TransactionPayload::EntryFunction(EntryFunction::new(
+                    ModuleId::new(
+                        AccountAddress::ONE,
+                        Identifier::new("primary_fungible_store").unwrap(),
+                    ),
+                    Identifier::new("transfer").unwrap(),
+                    vec![TypeTag::from_str("0x1::fungible_asset::Metadata").unwrap()],
+                    [vec![bcs::to_bytes(metadata_address).unwrap()], args].concat(),
+                ))
+            },
+        }
+    }
+}
+
+/// Parses an entry function id of the form `0xcafe::module::function`.
+fn parse_entry_function_id(function_id: &str) -> Result<(ModuleId, Identifier)> {
+    let parts: Vec<&str> = function_id.split("::").collect();
+    let [address, module, function] = parts.as_slice() else {
+        bail!(
+            "Invalid entry function {}, expected <address>::<module>::<function>",
+            function_id
+        );
+    };
+    let address = AccountAddress::from_hex_literal(address)
+        .with_context(|| format!("Invalid address in entry function {}", function_id))?;
+    Ok((
+        ModuleId::new(address, Identifier::new(*module)?),
+        Identifier::new(*function)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin_config(mint_function: Option<&str>) -> AssetConfig {
+        AssetConfig {
+            asset_type: AssetType::Coin {
+                coin_type: "0xcafe::usdc::USDC".to_string(),
+            },
+            mint_function: mint_function.map(|f| f.to_string()),
+            maximum_amount: 100,
+            maximum_amount_with_bypass: Some(1000),
+        }
+    }
+
+    fn entry_function(payload: TransactionPayload) -> EntryFunction {
+        match payload {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => panic!("Expected an entry function payload"),
+        }
+    }
+
+    #[test]
+    fn test_get_amount() {
+        let asset = coin_config(None).build().unwrap();
+        assert_eq!(asset.get_amount(None, false), 100);
+        assert_eq!(asset.get_amount(Some(10), false), 10);
+        assert_eq!(asset.get_amount(Some(500), false), 100);
+        assert_eq!(asset.get_amount(Some(500), true), 500);
+        assert_eq!(asset.get_amount(None, true), 1000);
+    }
+
+    #[test]
+    fn test_build_payload() {
+        let receiver = AccountAddress::from_hex_literal("0xbeef").unwrap();
+
+        let transfer = entry_function(
+            coin_config(None)
+                .build()
+                .unwrap()
+                .build_payload(receiver, 5),
+        );
+        assert_eq!(transfer.module().address(), &AccountAddress::ONE);
+        assert_eq!(transfer.module().name().as_str(), "aptos_account");
+        assert_eq!(transfer.function().as_str(), "transfer_coins");
+        assert_eq!(transfer.ty_args()[0].to_string(), "0xcafe::usdc::USDC");
+
+        let mint = entry_function(
+            coin_config(Some("0x1::managed_coin::mint"))
+                .build()
+                .unwrap()
+                .build_payload(receiver, 5),
+        );
+        assert_eq!(mint.module().address(), &AccountAddress::ONE);
+        assert_eq!(mint.module().name().as_str(), "managed_coin");
+        assert_eq!(mint.function().as_str(), "mint");
+        assert_eq!(mint.ty_args().len(), 1);
+        assert_eq!(mint.args()[0], bcs::to_bytes(&receiver).unwrap());
+        assert_eq!(mint.args()[1], bcs::to_bytes(&5u64).unwrap());
+
+        let metadata_address = AccountAddress::from_hex_literal("0xa").unwrap();
+        let fungible_asset = AssetConfig {
+            asset_type: AssetType::FungibleAsset { metadata_address },
+            mint_function: None,
+            maximum_amount: 100,
+            maximum_amount_with_bypass: None,
+        };
+        let transfer = entry_function(fungible_asset.build().unwrap().build_payload(receiver, 5));
+        assert_eq!(transfer.module().address(), &AccountAddress::ONE);
+        assert_eq!(transfer.module().name().as_str(), "primary_fungible_store");
+        assert_eq!(transfer.args().len(), 3);
+        assert_eq!(
+            transfer.args()[0],
+            bcs::to_bytes(&metadata_address).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_assets() {
+        let mut configs = HashMap::new();
+        configs.insert("usdc".to_string(), coin_config(None));
+        let assets = build_assets(&configs).unwrap();
+        assert!(get_asset(&assets, None).unwrap().is_none());
+        assert!(get_asset(&assets, Some(DEFAULT_ASSET_NAME))
+            .unwrap()
+            .is_none());
+        assert!(get_asset(&assets, Some("usdc")).unwrap().is_some());
+        assert!(get_asset(&assets, Some("usdt")).is_err());
+
+        configs.insert("bad".to_string(), coin_config(Some("0x1::managed_coin")));
+        assert!(build_assets(&configs).is_err());
+
+        let mut configs = HashMap::new();
+        configs.insert(DEFAULT_ASSET_NAME.to_string(), coin_config(None));
+        assert!(build_assets(&configs).is_err());
+    }
+}
@@ -2,9 +2,15 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{FunderHealthMessage, FunderTrait};
-use crate::endpoints::{AptosTapError, AptosTapErrorCode};
-use anyhow::{Context, Result};
+use super::{
+    asset::{build_assets, get_asset, Asset, AssetConfig},
+    FunderHealthMessage, FunderTrait,
+};
+use crate::{
+    endpoints::{AptosTapError, AptosTapErrorCode},
+    funder::DEFAULT_ASSET_NAME,
+};
+use anyhow::{bail, Context, Result};
 use aptos_logger::info;
 use aptos_sdk::{
     crypto::ed25519::Ed25519PublicKey,
@@ -22,6 +28,7 @@ use aptos_sdk::{
 use async_trait::async_trait;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tokio::sync::RwLock;
 
 static MINTER_SCRIPT: &[u8] = include_bytes!(
@@ -50,12 +57,23 @@ pub struct MintFunderConfig {
     /// Just use the account given in funder args, don't make a new one and
     /// delegate the mint capability to it.
     pub do_not_delegate: bool,
+
+    /// Assets other than APT this funder can fund, keyed by the name requests
+    /// use for them. The delegated account doesn't hold any capabilities or
+    /// balances for these, so `do_not_delegate` must be set to use them.
+    #[serde(default)]
+    pub assets: HashMap<String, AssetConfig>,
 }
 
 impl MintFunderConfig {
     pub async fn build_funder(self) -> Result<MintFunder> {
         let key = self.api_connection_config.get_key()?;
 
+        if !self.assets.is_empty() && !self.do_not_delegate {
+            bail!("do_not_delegate must be set to fund assets other than APT");
+        }
+        let assets = build_assets(&self.assets)?;
+
         let faucet_account = LocalAccount::new(
             self.mint_account_address.unwrap_or_else(|| {
                 AuthenticationKey::ed25519(&Ed25519PublicKey::from(&key)).account_address()
@@ -69,7 +87,8 @@ impl MintFunderConfig {
             self.api_connection_config.chain_id,
             self.transaction_submission_config,
             faucet_account,
-        );
+        )
+        .with_assets(assets);
 
         if !self.do_not_delegate {
             minter
@@ -97,6 +116,9 @@ pub struct MintFunder {
     /// When recovering from being overloaded, this struct ensures we handle
     /// requests in the order they came in.
     outstanding_requests: RwLock<Vec<(AccountAddress, u64)>>,
+
+    /// Assets other than APT this funder can fund, keyed by name.
+    assets: HashMap<String, Asset>,
 }
 
 impl MintFunder {
@@ -118,9 +140,15 @@ impl MintFunder {
             transaction_factory,
             gas_unit_price_manager,
             outstanding_requests: RwLock::new(vec![]),
+            assets: HashMap::new(),
         }
     }
 
+    pub fn with_assets(mut self, assets: HashMap<String, Asset>) -> Self {
+        self.assets = assets;
+        self
+    }
+
     async fn get_gas_unit_price(&self) -> Result<u64, AptosTapError> {
         match self.txn_config.gas_unit_price_override {
             Some(gas_unit_price) => Ok(gas_unit_price),
@@ -261,6 +289,50 @@ impl MintFunder {
             .await?,
         ])
     }
+
+    /// Mints or transfers an asset other than APT to the receiver. Unlike for
+    /// APT, the receiver account may already exist.
+    async fn process_asset(
+        &self,
+        client: &Client,
+        asset: &Asset,
+        amount: u64,
+        receiver_address: AccountAddress,
+        check_only: bool,
+    ) -> Result<Vec<SignedTransaction>, AptosTapError> {
+        update_sequence_numbers(
+            client,
+            &self.faucet_account,
+            &self.outstanding_requests,
+            receiver_address,
+            amount,
+            self.txn_config.wait_for_outstanding_txns_secs,
+        )
+        .await?;
+
+        if check_only {
+            return Ok(vec![]);
+        }
+
+        let txn = {
+            let faucet_account = self.faucet_account.write().await;
+            let transaction_factory = self.get_transaction_factory().await?;
+            faucet_account.sign_with_transaction_builder(
+                transaction_factory.payload(asset.build_payload(receiver_address, amount)),
+            )
+        };
+
+        Ok(vec![
+            submit_transaction(
+                client,
+                &self.faucet_account,
+                txn,
+                &receiver_address,
+                self.txn_config.wait_for_transactions,
+            )
+            .await?,
+        ])
+    }
 }
 
 #[async_trait]
@@ -269,10 +341,17 @@ impl FunderTrait for MintFunder {
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
         let client = self.get_api_client();
+        if let Some(asset) = get_asset(&self.assets, asset.as_deref())? {
+            let amount = asset.get_amount(amount, did_bypass_checkers);
+            return self
+                .process_asset(&client, asset, amount, receiver_address, check_only)
+                .await;
+        }
         let amount = self.get_amount(amount, did_bypass_checkers);
         self.process(
             &client,
@@ -296,6 +375,10 @@ impl FunderTrait for MintFunder {
         }
     }
 
+    fn supports_asset(&self, asset: &str) -> bool {
+        asset == DEFAULT_ASSET_NAME || self.assets.contains_key(asset)
+    }
+
     /// Assert the funder account actually exists.
     async fn is_healthy(&self) -> FunderHealthMessage {
         let account_address = self.faucet_account.read().await.address();
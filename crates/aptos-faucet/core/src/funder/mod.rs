@@ -1,12 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod account_pool;
+mod asset;
 mod common;
 mod fake;
 mod mint;
 mod transfer;
 
 pub use self::{
+    account_pool::{AccountPoolConfig, AssignmentStrategy},
+    asset::{AssetConfig, AssetType, DEFAULT_ASSET_NAME},
     common::{ApiConnectionConfig, TransactionSubmissionConfig},
     mint::MintFunderConfig,
 };
@@ -20,6 +24,7 @@ pub use fake::FakeFunder;
 pub use mint::MintFunder;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::task::JoinSet;
 pub use transfer::TransferFunder;
 
 /// explain
@@ -33,10 +38,13 @@ pub trait FunderTrait: Sync + Send + 'static {
     ///
     /// If `check_only` is set, this function will only do the initial checks
     /// without actually submitting any transactions.
+    ///
+    /// `asset` is the name of the asset to fund, APT if not given.
     async fn fund(
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         // True if a Bypasser let this request bypass the Checkers.
         did_bypass_checkers: bool,
@@ -51,6 +59,12 @@ pub trait FunderTrait: Sync + Send + 'static {
         did_bypass_checkers: bool,
     ) -> u64;
 
+    /// Whether this Funder can fund the asset with the given name. All Funders
+    /// can fund APT.
+    fn supports_asset(&self, asset: &str) -> bool {
+        asset == DEFAULT_ASSET_NAME
+    }
+
     /// This should return whether the Funder is healthy and able to accept
     /// requests. With this a Funder can indicate some issue that will get
     /// exposed at the `/` (the healthcheck endpoint), e.g. that that it
@@ -61,6 +75,11 @@ pub trait FunderTrait: Sync + Send + 'static {
             message: None,
         }
     }
+
+    /// This function will be called once at startup. In it, the Funder can
+    /// spawn any periodic tasks it wants, e.g. to keep its accounts funded.
+    /// As with Checkers, a task returning signals that there is an issue.
+    fn spawn_periodic_tasks(&self, _join_set: &mut JoinSet<anyhow::Result<()>>) {}
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -2,6 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
+    account_pool::{
+        account_address_from_private_key, spawn_top_up_task, AccountPoolConfig, AssignmentStrategy,
+        FundingAccount, FundingAccountPool,
+    },
+    asset::{build_assets, get_asset, Asset, AssetConfig},
     common::{
         submit_transaction, ApiConnectionConfig, GasUnitPriceManager, TransactionSubmissionConfig,
     },
@@ -9,27 +14,26 @@ use super::{
 };
 use crate::{
     endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode},
-    funder::common::update_sequence_numbers,
+    funder::{common::update_sequence_numbers, DEFAULT_ASSET_NAME},
     middleware::TRANSFER_FUNDER_ACCOUNT_BALANCE,
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use aptos_logger::info;
 use aptos_sdk::{
-    crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
     rest_client::Client,
     transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{
         account_address::AccountAddress,
         chain_id::ChainId,
-        transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionPayload},
+        transaction::{SignedTransaction, TransactionPayload},
         LocalAccount,
     },
 };
 use async_trait::async_trait;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, time::Duration};
-use tokio::sync::RwLock;
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+use tokio::task::JoinSet;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransferFunderConfig {
@@ -46,6 +50,18 @@ pub struct TransferFunderConfig {
 
     /// The amount of coins to fund the receiver account.
     pub amount_to_fund: AmountToFund,
+
+    /// Assets other than APT this funder can fund, keyed by the name requests
+    /// use for them. Unless they're minted, they're transferred from the
+    /// balance of the funder account.
+    #[serde(default)]
+    pub assets: HashMap<String, AssetConfig>,
+
+    /// If given, payouts are sent from a pool of funding accounts, which the
+    /// account configured here tops up, rather than from that account directly.
+    /// In that case `minimum_funds` applies to each account in the pool.
+    #[serde(default)]
+    pub account_pool: Option<AccountPoolConfig>,
 }
 
 impl TransferFunderConfig {
@@ -59,8 +75,23 @@ impl TransferFunderConfig {
         // Build local representation of account.
         let faucet_account = LocalAccount::new(account_address, key, 0);
 
+        // Without a pool, the faucet account funds requests itself.
+        let funding_accounts = match &self.account_pool {
+            Some(account_pool_config) => {
+                if account_pool_config.top_up_threshold < self.minimum_funds.0 {
+                    bail!(
+                        "top_up_threshold ({}) of the account pool must not be below minimum_funds ({})",
+                        account_pool_config.top_up_threshold,
+                        self.minimum_funds
+                    );
+                }
+                account_pool_config.build(faucet_account)?
+            },
+            None => FundingAccountPool::new(vec![faucet_account], AssignmentStrategy::default()),
+        };
+
         let funder = TransferFunder::new(
-            faucet_account,
+            funding_accounts,
             self.api_connection_config.chain_id,
             self.api_connection_config.node_url.clone(),
             self.minimum_funds,
@@ -74,14 +105,24 @@ impl TransferFunderConfig {
             self.transaction_submission_config
                 .wait_for_outstanding_txns_secs,
             self.transaction_submission_config.wait_for_transactions,
-        );
+        )
+        .with_assets(build_assets(&self.assets)?);
+
+        // Make sure the accounts in the pool exist and are funded before we
+        // start handling requests.
+        funder
+            .funding_accounts
+            .top_up_accounts(&funder.get_api_client(), &funder.transaction_factory)
+            .await
+            .context("Failed to top up the funding account pool")?;
 
         Ok(funder)
     }
 }
 
 pub struct TransferFunder {
-    faucet_account: RwLock<LocalAccount>,
+    /// The accounts we send funding transactions from.
+    funding_accounts: Arc<FundingAccountPool>,
 
     transaction_factory: TransactionFactory,
 
@@ -101,20 +142,19 @@ pub struct TransferFunder {
     /// from the gas_unit_price_manager.
     gas_unit_price_override: Option<u64>,
 
-    /// When recovering from being overloaded, this struct ensures we handle
-    /// requests in the order they came in.
-    outstanding_requests: RwLock<Vec<(AccountAddress, u64)>>,
-
     /// Amount of time we'll wait for the seqnum to catch up before resetting it.
     wait_for_outstanding_txns_secs: u64,
 
     /// If set, we won't return responses until the transaction is processed.
     wait_for_transactions: bool,
+
+    /// Assets other than APT this funder can fund, keyed by name.
+    assets: HashMap<String, Asset>,
 }
 
 impl TransferFunder {
     pub fn new(
-        faucet_account: LocalAccount,
+        funding_accounts: FundingAccountPool,
         chain_id: ChainId,
         node_url: Url,
         minimum_funds: MinimumFunds,
@@ -130,7 +170,7 @@ impl TransferFunder {
             GasUnitPriceManager::new(node_url.clone(), gas_unit_price_ttl_secs);
 
         Self {
-            faucet_account: RwLock::new(faucet_account),
+            funding_accounts: Arc::new(funding_accounts),
             transaction_factory: TransactionFactory::new(chain_id)
                 .with_max_gas_amount(max_gas_amount)
                 .with_transaction_expiration_time(transaction_expiration_secs),
@@ -139,12 +179,17 @@ impl TransferFunder {
             amount_to_fund,
             gas_unit_price_manager,
             gas_unit_price_override,
-            outstanding_requests: RwLock::new(vec![]),
             wait_for_outstanding_txns_secs,
             wait_for_transactions,
+            assets: HashMap::new(),
         }
     }
 
+    pub fn with_assets(mut self, assets: HashMap<String, Asset>) -> Self {
+        self.assets = assets;
+        self
+    }
+
     /// Within a single request we should just call this once and use this client
     /// the entire time because it uses cookies, ensuring we're talking to the same
     /// node behind the LB every time.
@@ -170,6 +215,7 @@ impl TransferFunder {
     async fn execute_transaction(
         &self,
         client: &Client,
+        funding_account: &FundingAccount,
         payload: TransactionPayload,
         // Only used for logging.
         receiver_address: &AccountAddress,
@@ -184,15 +230,15 @@ impl TransferFunder {
 
         let transaction_builder = transaction_factory.payload(payload);
 
-        let signed_transaction = self
-            .faucet_account
+        let signed_transaction = funding_account
+            .account
             .write()
             .await
             .sign_with_transaction_builder(transaction_builder);
 
         submit_transaction(
             client,
-            &self.faucet_account,
+            &funding_account.account,
             signed_transaction,
             receiver_address,
             self.wait_for_transactions,
@@ -200,20 +246,35 @@ impl TransferFunder {
         .await
     }
 
-    async fn is_healthy_as_result(&self) -> Result<(), AptosTapError> {
-        let funder_health = self.is_healthy().await;
-        if !funder_health.can_process_requests {
-            return Err(AptosTapError::new(
-                format!(
-                    "Tap TransferFunder is not able to handle requests right now: {}",
-                    funder_health
-                        .message
-                        .unwrap_or_else(|| "no message".to_string()),
-                ),
-                AptosTapErrorCode::FunderAccountProblem,
-            ));
+    /// Checks that the funding account exists and has the minimum funds,
+    /// returning its balance. Accounts without the minimum funds are marked
+    /// unhealthy so they don't get new requests.
+    async fn check_funding_account(
+        &self,
+        client: &Client,
+        funding_account: &FundingAccount,
+    ) -> Result<u64, String> {
+        let account_address = funding_account.address();
+        let funder_balance = match client.view_apt_account_balance(account_address).await {
+            Ok(response) => response.into_inner(),
+            Err(e) => return Err(format!(
+                "Failed to get account balance to determine whether tap account has sufficient funds: {:#}",
+                e
+            )),
+        };
+
+        funding_account.record_balance(funder_balance);
+
+        if funder_balance < self.minimum_funds.0 {
+            funding_account.set_healthy(false);
+            Err(format!(
+                "Funder account {} has insufficient funds. It has {}, but the minimum is {}",
+                account_address, funder_balance, self.minimum_funds.0
+            ))
+        } else {
+            funding_account.set_healthy(true);
+            Ok(funder_balance)
         }
-        Ok(())
     }
 }
 
@@ -233,25 +294,66 @@ impl FunderTrait for TransferFunder {
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
-        // Confirm the funder has sufficient balance, return a 500 if not. This
-        // will only happen briefly, soon after we get into this state the LB
-        // will deregister this instance based on the health check responses
-        // being returned from `/`.
-        self.is_healthy_as_result().await?;
+        // Pick the account to fund the receiver from. It is assigned to this
+        // request until the guard is dropped.
+        let funding_account = self.funding_accounts.acquire();
 
         let client = self.get_api_client();
 
+        // Confirm the funding account has sufficient balance, return a 500 if
+        // not. This will only happen briefly, soon after we get into this state
+        // the account stops getting requests, or, if no account in the pool has
+        // sufficient funds, the LB will deregister this instance based on the
+        // health check responses being returned from `/`.
+        if let Err(message) = self.check_funding_account(&client, &funding_account).await {
+            return Err(AptosTapError::new(
+                format!(
+                    "Tap TransferFunder is not able to handle requests right now: {}",
+                    message
+                ),
+                AptosTapErrorCode::FunderAccountProblem,
+            ));
+        }
+
+        // Assets other than APT can be sent to accounts that already exist, so
+        // we skip the account existence check for them.
+        if let Some(asset) = get_asset(&self.assets, asset.as_deref())? {
+            let amount = asset.get_amount(amount, did_bypass_checkers);
+            update_sequence_numbers(
+                &client,
+                &funding_account.account,
+                &funding_account.outstanding_requests,
+                receiver_address,
+                amount,
+                self.wait_for_outstanding_txns_secs,
+            )
+            .await?;
+            if check_only {
+                return Ok(vec![]);
+            }
+            let txn = self
+                .execute_transaction(
+                    &client,
+                    &funding_account,
+                    asset.build_payload(receiver_address, amount),
+                    &receiver_address,
+                )
+                .await?;
+            return Ok(vec![txn]);
+        }
+
         // Determine amount to fund.
         let amount = self.get_amount(amount, did_bypass_checkers);
 
         // Update the sequence numbers of the accounts.
         let (_funder_seq_num, receiver_seq_num) = update_sequence_numbers(
             &client,
-            &self.faucet_account,
-            &self.outstanding_requests,
+            &funding_account.account,
+            &funding_account.outstanding_requests,
             receiver_address,
             amount,
             self.wait_for_outstanding_txns_secs,
@@ -280,6 +382,7 @@ impl FunderTrait for TransferFunder {
             let txn = self
                 .execute_transaction(
                     &client,
+                    &funding_account,
                     aptos_stdlib::aptos_account_transfer(receiver_address, amount),
                     &receiver_address,
                 )
@@ -309,32 +412,46 @@ impl FunderTrait for TransferFunder {
         }
     }
 
-    /// Assert funder account actually exists and has the minimum funds.
-    async fn is_healthy(&self) -> FunderHealthMessage {
-        let account_address = self.faucet_account.read().await.address();
-        let funder_balance = match self
-            .get_api_client()
-            .view_apt_account_balance(account_address)
-            .await
-        {
-            Ok(response) => response.into_inner(),
-            Err(e) => return FunderHealthMessage {
-                can_process_requests: false,
-                message: Some(format!(
-                    "Failed to get account balance to determine whether tap account has sufficient funds: {:#}",
-                    e
-                )),
-            },
-        };
-
-        TRANSFER_FUNDER_ACCOUNT_BALANCE.set(funder_balance as i64);
+    fn supports_asset(&self, asset: &str) -> bool {
+        asset == DEFAULT_ASSET_NAME || self.assets.contains_key(asset)
+    }
 
-        if funder_balance < self.minimum_funds.0 {
+    /// Assert the funding accounts actually exist and have the minimum funds.
+    /// We can process requests as long as one of them does.
+    async fn is_healthy(&self) -> FunderHealthMessage {
+        let client = self.get_api_client();
+        let results = futures::future::join_all(
+            self.funding_accounts
+                .accounts()
+                .iter()
+                .map(|funding_account| self.check_funding_account(&client, funding_account)),
+        )
+        .await;
+
+        let total_balance: u64 = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .sum();
+        TRANSFER_FUNDER_ACCOUNT_BALANCE.set(total_balance as i64);
+
+        let problems: Vec<String> = results
+            .into_iter()
+            .filter_map(|result| result.err())
+            .collect();
+        let num_accounts = self.funding_accounts.accounts().len();
+        if problems.len() == num_accounts {
             FunderHealthMessage {
                 can_process_requests: false,
+                message: Some(problems.join("; ")),
+            }
+        } else if !problems.is_empty() {
+            FunderHealthMessage {
+                can_process_requests: true,
                 message: Some(format!(
-                    "Funder account {} has insufficient funds. It has {}, but the minimum is {}",
-                    account_address, funder_balance, self.minimum_funds.0
+                    "{} of {} funding accounts can't be used: {}",
+                    problems.len(),
+                    num_accounts,
+                    problems.join("; ")
                 )),
             }
         } else {
@@ -344,12 +461,15 @@ impl FunderTrait for TransferFunder {
             }
         }
     }
-}
 
-fn account_address_from_private_key(private_key: &Ed25519PrivateKey) -> AccountAddress {
-    let public_key = private_key.public_key();
-    let auth_key = AuthenticationKey::ed25519(&public_key);
-    AccountAddress::new(*auth_key.account_address())
+    fn spawn_periodic_tasks(&self, join_set: &mut JoinSet<anyhow::Result<()>>) {
+        spawn_top_up_task(
+            self.funding_accounts.clone(),
+            self.get_api_client(),
+            self.transaction_factory.clone(),
+            join_set,
+        );
+    }
 }
 
 // Use newtypes so we don't accidentally mix these up.
@@ -3,8 +3,8 @@
 
 use crate::endpoints::RejectionReason;
 use aptos_metrics_core::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge, HistogramVec,
-    IntCounterVec, IntGauge,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -43,11 +43,46 @@ pub static NUM_OUTSTANDING_TRANSACTIONS: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
-// TODO: Consider using IntGaugeVec to attach the account address as a label.
 pub static TRANSFER_FUNDER_ACCOUNT_BALANCE: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "aptos_tap_transfer_funder_account_balance",
-        "Balance of the account used by the tap instance. Only populated for the TransferFunder.",
+        "Total balance of the accounts used by the tap instance to fund requests. Only populated for the TransferFunder.",
+    )
+    .unwrap()
+});
+
+pub static FUNDING_ACCOUNT_BALANCE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_tap_funding_account_balance",
+        "Balance of each account in the funding account pool of the TransferFunder.",
+        &["account"]
+    )
+    .unwrap()
+});
+
+pub static FUNDING_ACCOUNT_IN_FLIGHT_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_tap_funding_account_in_flight_requests",
+        "Number of requests currently being handled by each account in the funding account pool.",
+        &["account"]
+    )
+    .unwrap()
+});
+
+pub static FUNDING_ACCOUNT_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_tap_funding_account_requests",
+        "Number of requests assigned to each account in the funding account pool.",
+        &["account"]
+    )
+    .unwrap()
+});
+
+pub static FUNDING_ACCOUNT_TOP_UPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_tap_funding_account_top_ups",
+        "Number of top ups of each account in the funding account pool, by result.",
+        &["account", "result"]
     )
     .unwrap()
 });
@@ -7,7 +7,8 @@ mod metrics;
 pub use self::{
     log::middleware_log,
     metrics::{
-        bump_rejection_reason_counters, NUM_OUTSTANDING_TRANSACTIONS,
-        TRANSFER_FUNDER_ACCOUNT_BALANCE,
+        bump_rejection_reason_counters, FUNDING_ACCOUNT_BALANCE,
+        FUNDING_ACCOUNT_IN_FLIGHT_REQUESTS, FUNDING_ACCOUNT_REQUESTS, FUNDING_ACCOUNT_TOP_UPS,
+        NUM_OUTSTANDING_TRANSACTIONS, TRANSFER_FUNDER_ACCOUNT_BALANCE,
     },
 };
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    checkers::CaptchaManager,
-    endpoints::{build_openapi_service, BasicApi, CaptchaApi, FundApi, FundApiComponents},
+    checkers::{CaptchaManager, ProofOfWorkManager},
+    endpoints::{
+        build_openapi_service, BasicApi, CaptchaApi, FundApi, FundApiComponents, ProofOfWorkApi,
+    },
     funder::{FakeFunder, Funder},
 };
 use anyhow::Result;
@@ -39,6 +41,12 @@ impl GenerateOpenapi {
                 enabled: false,
                 captcha_manager: Arc::new(Mutex::new(CaptchaManager::new())),
             },
+            ProofOfWorkApi {
+                enabled: false,
+                proof_of_work_manager: Arc::new(Mutex::new(ProofOfWorkManager::new(
+                    Default::default(),
+                )?)),
+            },
             fund_api,
         );
 
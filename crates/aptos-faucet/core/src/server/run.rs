@@ -4,12 +4,15 @@
 use super::server_args::ServerConfig;
 use crate::{
     bypasser::{Bypasser, BypasserConfig},
-    checkers::{CaptchaManager, Checker, CheckerConfig, CheckerTrait},
+    checkers::{CaptchaManager, Checker, CheckerConfig, CheckerTrait, ProofOfWorkManager},
     endpoints::{
         build_openapi_service, convert_error, mint, BasicApi, CaptchaApi, FundApi,
-        FundApiComponents,
+        FundApiComponents, ProofOfWorkApi,
+    },
+    funder::{
+        ApiConnectionConfig, FunderConfig, FunderTrait, MintFunderConfig,
+        TransactionSubmissionConfig,
     },
-    funder::{ApiConnectionConfig, FunderConfig, MintFunderConfig, TransactionSubmissionConfig},
     middleware::middleware_log,
 };
 use anyhow::{anyhow, Context, Result};
@@ -25,7 +28,9 @@ use futures::{channel::oneshot::Sender as OneShotSender, lock::Mutex};
 use poem::{http::Method, listener::TcpAcceptor, middleware::Cors, EndpointExt, Route, Server};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::BufReader, path::PathBuf, pin::Pin, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap, fs::File, io::BufReader, path::PathBuf, pin::Pin, str::FromStr, sync::Arc,
+};
 use tokio::{net::TcpListener, sync::Semaphore, task::JoinSet};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -88,12 +93,17 @@ impl RunConfig {
             .max_concurrent_requests
             .map(|v| Arc::new(Semaphore::new(v)));
 
-        // Build Funder.
+        // Create a periodic task manager.
+        let mut join_set = JoinSet::new();
+
+        // Build Funder and let it spawn tasks on the periodic task manager if
+        // it wants.
         let funder = self
             .funder_config
             .build()
             .await
             .context("Failed to build Funder")?;
+        funder.spawn_periodic_tasks(&mut join_set);
 
         // Build basic API.
         let basic_api = BasicApi {
@@ -104,6 +114,21 @@ impl RunConfig {
         // Create a CaptchaManager.
         let captcha_manager = Arc::new(Mutex::new(CaptchaManager::new()));
 
+        // Create a ProofOfWorkManager. It is configured by the config of the
+        // ProofOfWorkChecker, if there is one.
+        let proof_of_work_config =
+            self.checker_configs
+                .iter()
+                .find_map(|checker_config| match checker_config {
+                    CheckerConfig::ProofOfWork(config) => Some(config.clone()),
+                    _ => None,
+                });
+        let proof_of_work_api_enabled = proof_of_work_config.is_some();
+        let proof_of_work_manager = Arc::new(Mutex::new(
+            ProofOfWorkManager::new(proof_of_work_config.unwrap_or_default())
+                .context("Failed to build ProofOfWorkManager")?,
+        ));
+
         // Build Bypassers.
         let mut bypassers: Vec<Bypasser> = Vec::new();
         for bypasser_config in &self.bypasser_configs {
@@ -113,16 +138,13 @@ impl RunConfig {
             bypassers.push(bypasser);
         }
 
-        // Create a periodic task manager.
-        let mut join_set = JoinSet::new();
-
         // Build Checkers and let them spawn tasks on the periodic task
         // manager if they want.
         let mut checkers: Vec<Checker> = Vec::new();
         for checker_config in &self.checker_configs {
             let checker = checker_config
                 .clone()
-                .build(captcha_manager.clone())
+                .build(captcha_manager.clone(), proof_of_work_manager.clone())
                 .await
                 .with_context(|| {
                     format!("Failed to build Checker with args: {:?}", checker_config)
@@ -161,7 +183,14 @@ impl RunConfig {
             captcha_manager,
         };
 
-        let api_service = build_openapi_service(basic_api, captcha_api, fund_api);
+        // Build the ProofOfWorkApi.
+        let proof_of_work_api = ProofOfWorkApi {
+            enabled: proof_of_work_api_enabled,
+            proof_of_work_manager,
+        };
+
+        let api_service =
+            build_openapi_service(basic_api, captcha_api, proof_of_work_api, fund_api);
         let spec_json = api_service.spec_endpoint();
         let spec_yaml = api_service.spec_endpoint_yaml();
 
@@ -287,6 +316,7 @@ impl RunConfig {
                 ),
                 mint_account_address: Some(aptos_test_root_address()),
                 do_not_delegate,
+                assets: HashMap::new(),
             }),
             handler_config: HandlerConfig {
                 use_helpful_errors: true,
@@ -580,6 +610,25 @@ mod test {
         )
         .await?;
 
+        // Assert that requests for assets the funder doesn't support are rejected.
+        let response = reqwest::Client::new()
+            .post(get_fund_endpoint(port))
+            .body(
+                FundRequest {
+                    asset: Some("usdc".to_string()),
+                    ..get_fund_request(Some(10))
+                }
+                .to_json_string(),
+            )
+            .header(CONTENT_TYPE, "application/json")
+            .header(AUTHORIZATION, "Bearer test_token")
+            .header("what_wallet_my_guy", "the_wallet_that_rocks")
+            .send()
+            .await?;
+        let aptos_error = AptosTapError::parse_from_json_string(&response.text().await?)
+            .expect("Failed to read response as AptosError");
+        assert_eq!(aptos_error.error_code, AptosTapErrorCode::InvalidRequest);
+
         // Assert that the magic header and auth token checkers work.
         let response = reqwest::Client::new()
             .post(get_fund_endpoint(port))
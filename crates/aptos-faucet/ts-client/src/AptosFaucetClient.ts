@@ -8,6 +8,7 @@ import { AxiosHttpRequest } from './core/AxiosHttpRequest';
 import { CaptchaService } from './services/CaptchaService';
 import { FundService } from './services/FundService';
 import { GeneralService } from './services/GeneralService';
+import { ProofOfWorkService } from './services/ProofOfWorkService';
 
 type HttpRequestConstructor = new (config: OpenAPIConfig) => BaseHttpRequest;
 
@@ -16,6 +17,7 @@ export class AptosFaucetClient {
     public readonly captcha: CaptchaService;
     public readonly fund: FundService;
     public readonly general: GeneralService;
+    public readonly proofOfWork: ProofOfWorkService;
 
     public readonly request: BaseHttpRequest;
 
@@ -35,6 +37,7 @@ export class AptosFaucetClient {
         this.captcha = new CaptchaService(this.request);
         this.fund = new FundService(this.request);
         this.general = new GeneralService(this.request);
+        this.proofOfWork = new ProofOfWorkService(this.request);
     }
 }
 
@@ -13,6 +13,7 @@ export type { AptosTapError } from './models/AptosTapError';
 export { AptosTapErrorCode } from './models/AptosTapErrorCode';
 export type { FundRequest } from './models/FundRequest';
 export type { FundResponse } from './models/FundResponse';
+export type { ProofOfWorkChallengeResponse } from './models/ProofOfWorkChallengeResponse';
 export type { RejectionReason } from './models/RejectionReason';
 export { RejectionReasonCode } from './models/RejectionReasonCode';
 
@@ -20,9 +21,11 @@ export { $AptosTapError } from './schemas/$AptosTapError';
 export { $AptosTapErrorCode } from './schemas/$AptosTapErrorCode';
 export { $FundRequest } from './schemas/$FundRequest';
 export { $FundResponse } from './schemas/$FundResponse';
+export { $ProofOfWorkChallengeResponse } from './schemas/$ProofOfWorkChallengeResponse';
 export { $RejectionReason } from './schemas/$RejectionReason';
 export { $RejectionReasonCode } from './schemas/$RejectionReasonCode';
 
 export { CaptchaService } from './services/CaptchaService';
 export { FundService } from './services/FundService';
 export { GeneralService } from './services/GeneralService';
+export { ProofOfWorkService } from './services/ProofOfWorkService';
@@ -21,5 +21,10 @@ export type FundRequest = {
      * Either this or `auth_key` / `address` must be provided.
      */
     pub_key?: string;
+    /**
+     * The name of the asset to fund, as configured in the faucet. If not
+     * set, or set to `apt`, the account is funded with APT.
+     */
+    asset?: string;
 };
 
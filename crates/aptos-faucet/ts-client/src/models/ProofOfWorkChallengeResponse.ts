@@ -0,0 +1,19 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+export type ProofOfWorkChallengeResponse = {
+    /**
+     * The challenge to solve. Include it in the `POW_CHALLENGE` header of
+     * the call to `/fund`.
+     */
+    challenge: string;
+    /**
+     * Number of leading zero bits sha3-256(challenge || receiver || nonce)
+     * must have, where receiver is the 32 byte address of the account to
+     * fund and nonce is a little endian u64. Include the nonce in the
+     * `POW_NONCE` header of the call to `/fund`.
+     */
+    difficulty: number;
+};
+
@@ -22,5 +22,10 @@ export const $FundRequest = {
             type: 'string',
             description: `Either this or \`auth_key\` / \`address\` must be provided.`,
         },
+        asset: {
+            type: 'string',
+            description: `The name of the asset to fund, as configured in the faucet. If not
+            set, or set to \`apt\`, the account is funded with APT.`,
+        },
     },
 } as const;
@@ -0,0 +1,32 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+import type { AptosTapError } from '../models/AptosTapError';
+import type { ProofOfWorkChallengeResponse } from '../models/ProofOfWorkChallengeResponse';
+
+import type { CancelablePromise } from '../core/CancelablePromise';
+import type { BaseHttpRequest } from '../core/BaseHttpRequest';
+
+export class ProofOfWorkService {
+
+    constructor(public readonly httpRequest: BaseHttpRequest) {}
+
+    /**
+     * Initiate proof of work flow
+     * With this endpoint you can initiate a proof of work flow, an
+     * alternative to captchas for scripts. The response contains a challenge
+     * and its difficulty. The difficulty goes up when the faucet is in high
+     * demand. Each IP can only request so many challenges at a time. This
+     * endpoint is only relevant if the ProofOfWorkChecker is enabled.
+     * @returns ProofOfWorkChallengeResponse
+     * @returns AptosTapError
+     * @throws ApiError
+     */
+    public requestPowChallenge(): CancelablePromise<ProofOfWorkChallengeResponse | AptosTapError> {
+        return this.httpRequest.request({
+            method: 'GET',
+            url: '/request_pow_challenge',
+        });
+    }
+
+}
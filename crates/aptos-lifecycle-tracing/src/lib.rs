@@ -0,0 +1,306 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Tracing of transactions and blocks as they move through the node (API,
+//! mempool, quorum store, consensus pipeline and executor), exported as
+//! OpenTelemetry spans over OTLP.
+//!
+//! Every span of a transaction (or block) shares a trace id derived from the
+//! transaction hash (or block id), and whether a transaction is sampled is a
+//! function of its hash. This means all components, and all nodes with the
+//! same sample rate, trace the same transactions without having to propagate
+//! any context, and a collector sees one trace per transaction across nodes.
+
+use aptos_config::config::LifecycleTracingConfig;
+use aptos_crypto::HashValue;
+use aptos_logger::{info, warn};
+use once_cell::sync::OnceCell;
+use opentelemetry::{
+    trace::{Span, SpanBuilder, TraceId, Tracer},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    runtime,
+    trace::{self as sdktrace, BatchConfig},
+    Resource,
+};
+use std::time::{Duration, SystemTime};
+use tokio::runtime::Runtime;
+
+/// The global lifecycle tracer. This is only set if tracing is enabled.
+static LIFECYCLE_TRACER: OnceCell<LifecycleTracer> = OnceCell::new();
+
+/// The stages of the lifecycle of a transaction or block that we record spans for
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stage {
+    /// The API receiving a transaction and submitting it to mempool
+    ApiSubmit,
+    /// Mempool validating a transaction and adding it to the pool
+    MempoolInsert,
+    /// Quorum store pulling a transaction from mempool into a batch
+    QuorumStoreBatch,
+    /// Consensus ordering a block, from proposal to ordered
+    ConsensusOrder,
+    /// The consensus pipeline executing an ordered block
+    PipelineExecute,
+    /// The consensus pipeline committing an executed block
+    PipelineCommit,
+    /// The executor executing a block (or a transaction in a block)
+    ExecutorExecute,
+    /// The executor committing a block to storage
+    ExecutorCommit,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::ApiSubmit => "api.submit_transaction",
+            Stage::MempoolInsert => "mempool.add_transaction",
+            Stage::QuorumStoreBatch => "quorum_store.batch",
+            Stage::ConsensusOrder => "consensus.order",
+            Stage::PipelineExecute => "consensus.pipeline.execute",
+            Stage::PipelineCommit => "consensus.pipeline.commit",
+            Stage::ExecutorExecute => "executor.execute",
+            Stage::ExecutorCommit => "executor.commit",
+        }
+    }
+}
+
+struct LifecycleTracer {
+    tracer: sdktrace::Tracer,
+    transaction_sample_rate: f64,
+    block_sample_rate: f64,
+    // The runtime the span exporter runs on. It has to live as long as the tracer.
+    _runtime: Runtime,
+}
+
+/// Initializes the global lifecycle tracer, if tracing is enabled in the config.
+/// Spans are exported to the configured OTLP collector in the background.
+pub fn init(config: &LifecycleTracingConfig, node_name: String) -> anyhow::Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+
+    // The batch exporter spawns its export task on the current tokio runtime,
+    // so we build it inside a dedicated one.
+    let runtime = aptos_runtimes::spawn_named_runtime("lifecycle-tr".into(), Some(1));
+    let tracer = {
+        let _guard = runtime.enter();
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(config.otlp_endpoint.clone()),
+            )
+            .with_trace_config(sdktrace::config().with_resource(Resource::new(vec![
+                KeyValue::new("service.name", "aptos-node"),
+                KeyValue::new("service.instance.id", node_name),
+            ])))
+            .with_batch_config(
+                BatchConfig::default()
+                    .with_max_queue_size(config.max_queue_size)
+                    .with_max_export_batch_size(config.max_export_batch_size)
+                    .with_scheduled_delay(Duration::from_millis(config.export_interval_ms)),
+            )
+            .install_batch(runtime::Tokio)?
+    };
+
+    let lifecycle_tracer = LifecycleTracer {
+        tracer,
+        transaction_sample_rate: config.transaction_sample_rate,
+        block_sample_rate: config.block_sample_rate,
+        _runtime: runtime,
+    };
+    if LIFECYCLE_TRACER.set(lifecycle_tracer).is_err() {
+        warn!("The lifecycle tracer was already initialized!");
+        return Ok(());
+    }
+
+    info!(
+        "Lifecycle tracing enabled, exporting to {} (transaction sample rate: {}, block sample rate: {})",
+        config.otlp_endpoint, config.transaction_sample_rate, config.block_sample_rate
+    );
+    Ok(())
+}
+
+/// Returns true iff lifecycle tracing has been initialized and is enabled
+pub fn is_enabled() -> bool {
+    LIFECYCLE_TRACER.get().is_some()
+}
+
+/// Returns true iff the transaction with the given hash is traced
+pub fn is_transaction_sampled(txn_hash: &HashValue) -> bool {
+    LIFECYCLE_TRACER.get().map_or(false, |tracer| {
+        is_sampled(txn_hash, tracer.transaction_sample_rate)
+    })
+}
+
+/// Returns true iff the block with the given id is traced
+pub fn is_block_sampled(block_id: &HashValue) -> bool {
+    LIFECYCLE_TRACER.get().map_or(false, |tracer| {
+        is_sampled(block_id, tracer.block_sample_rate)
+    })
+}
+
+/// Starts a span for the given stage of a transaction. The span ends when
+/// the returned guard is dropped. If the transaction isn't sampled, this is a no-op.
+pub fn transaction_span(txn_hash: &HashValue, stage: Stage) -> LifecycleSpan {
+    if !is_transaction_sampled(txn_hash) {
+        return LifecycleSpan::noop();
+    }
+    LifecycleSpan::start(txn_hash, "txn_hash", stage, SystemTime::now(), vec![])
+}
+
+/// Starts a span for the given stage of a block. The span ends when the
+/// returned guard is dropped. If the block isn't sampled, this is a no-op.
+pub fn block_span(block_id: &HashValue, stage: Stage) -> LifecycleSpan {
+    if !is_block_sampled(block_id) {
+        return LifecycleSpan::noop();
+    }
+    LifecycleSpan::start(block_id, "block_id", stage, SystemTime::now(), vec![])
+}
+
+/// Records a span for the given stage of a transaction, from the start time
+/// until now. This is useful when the start of the stage isn't known until the
+/// transaction is seen, e.g. when a batch of transactions is processed at once.
+/// If the transaction isn't sampled, this is a no-op.
+pub fn record_transaction_stage(
+    txn_hash: &HashValue,
+    stage: Stage,
+    start_time: SystemTime,
+    attributes: Vec<(&'static str, String)>,
+) {
+    if !is_transaction_sampled(txn_hash) {
+        return;
+    }
+    LifecycleSpan::start(txn_hash, "txn_hash", stage, start_time, attributes);
+}
+
+/// Records a span for the given stage of a block, from the start time until
+/// now. If the block isn't sampled, this is a no-op.
+pub fn record_block_stage(
+    block_id: &HashValue,
+    stage: Stage,
+    start_time: SystemTime,
+    attributes: Vec<(&'static str, String)>,
+) {
+    if !is_block_sampled(block_id) {
+        return;
+    }
+    LifecycleSpan::start(block_id, "block_id", stage, start_time, attributes);
+}
+
+/// A guard for a lifecycle span. The span ends (and is exported) when this is dropped.
+pub struct LifecycleSpan {
+    span: Option<sdktrace::Span>,
+}
+
+impl LifecycleSpan {
+    fn noop() -> Self {
+        Self { span: None }
+    }
+
+    fn start(
+        id: &HashValue,
+        id_name: &'static str,
+        stage: Stage,
+        start_time: SystemTime,
+        attributes: Vec<(&'static str, String)>,
+    ) -> Self {
+        let span = LIFECYCLE_TRACER.get().map(|lifecycle_tracer| {
+            let mut attributes: Vec<KeyValue> = attributes
+                .into_iter()
+                .map(|(key, value)| KeyValue::new(key, value))
+                .collect();
+            attributes.push(KeyValue::new(id_name, id.to_hex()));
+            let span_builder = SpanBuilder::from_name(stage.as_str())
+                .with_trace_id(trace_id(id))
+                .with_start_time(start_time)
+                .with_attributes(attributes);
+            lifecycle_tracer.tracer.build(span_builder)
+        });
+        Self { span }
+    }
+
+    /// Returns true iff this span is recorded (i.e., the transaction or block is sampled)
+    pub fn is_recording(&self) -> bool {
+        self.span.is_some()
+    }
+
+    /// Adds an attribute to the span
+    pub fn set_attribute(&mut self, key: &'static str, value: String) {
+        if let Some(span) = self.span.as_mut() {
+            span.set_attribute(KeyValue::new(key, value));
+        }
+    }
+}
+
+impl Drop for LifecycleSpan {
+    fn drop(&mut self) {
+        if let Some(mut span) = self.span.take() {
+            span.end();
+        }
+    }
+}
+
+/// Derives the trace id of a transaction or block from its hash
+fn trace_id(id: &HashValue) -> TraceId {
+    let mut trace_id = [0u8; 16];
+    trace_id.copy_from_slice(&id.as_ref()[..16]);
+    TraceId::from_bytes(trace_id)
+}
+
+/// Returns true iff the hash falls within the sampled fraction of the hash space
+fn is_sampled(id: &HashValue, sample_rate: f64) -> bool {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&id.as_ref()[16..24]);
+    let position = u64::from_be_bytes(bytes) as f64 / (u64::MAX as f64 + 1.0);
+    position < sample_rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_is_deterministic() {
+        let hashes: Vec<HashValue> = (0..1000u64)
+            .map(|i| HashValue::sha3_256_of(&i.to_le_bytes()))
+            .collect();
+
+        // Nothing is sampled at a rate of 0, and everything at a rate of 1
+        assert!(hashes.iter().all(|hash| !is_sampled(hash, 0.0)));
+        assert!(hashes.iter().all(|hash| is_sampled(hash, 1.0)));
+
+        // Sampling at a lower rate is a subset of sampling at a higher rate
+        for hash in &hashes {
+            if is_sampled(hash, 0.1) {
+                assert!(is_sampled(hash, 0.5));
+            }
+        }
+
+        // Roughly the expected fraction is sampled
+        let num_sampled = hashes.iter().filter(|hash| is_sampled(hash, 0.5)).count();
+        assert!((350..650).contains(&num_sampled));
+    }
+
+    #[test]
+    fn test_trace_id() {
+        let hash = HashValue::sha3_256_of(b"transaction");
+        assert_eq!(trace_id(&hash).to_bytes(), hash.as_ref()[..16]);
+        assert_eq!(trace_id(&hash), trace_id(&hash));
+        assert_ne!(trace_id(&hash), trace_id(&HashValue::sha3_256_of(b"block")));
+    }
+
+    #[test]
+    fn test_disabled_is_noop() {
+        let hash = HashValue::sha3_256_of(b"transaction");
+        assert!(!is_enabled());
+        assert!(!is_transaction_sampled(&hash));
+        assert!(!transaction_span(&hash, Stage::ApiSubmit).is_recording());
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
     counters::{
         PROCESSED_STRUCT_LOG_COUNT, STRUCT_LOG_PARSE_ERROR_COUNT, STRUCT_LOG_QUEUE_ERROR_COUNT,
     },
+    file_rotation::{RotatingFile, RotationPolicy},
     logger::Logger,
     sample,
     sample::SampleRate,
@@ -715,16 +716,17 @@ impl Writer for StdoutWriter {
 
 /// A struct for writing logs to a file
 pub struct FileWriter {
-    log_file: RwLock<std::fs::File>,
+    log_file: RwLock<RotatingFile>,
 }
 
 impl FileWriter {
     pub fn new(log_file: std::path::PathBuf) -> Self {
-        let file = std::fs::OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(log_file)
-            .expect("Unable to open log file");
+        Self::new_with_rotation(log_file, RotationPolicy::default())
+    }
+
+    /// Creates a writer that rotates the log file according to the given policy
+    pub fn new_with_rotation(log_file: std::path::PathBuf, policy: RotationPolicy) -> Self {
+        let file = RotatingFile::open(log_file, policy).expect("Unable to open log file");
         Self {
             log_file: RwLock::new(file),
         }
@@ -734,7 +736,7 @@ impl FileWriter {
 impl Writer for FileWriter {
     /// Write to file
     fn write(&self, log: String) {
-        if let Err(err) = writeln!(self.log_file.write(), "{}", log) {
+        if let Err(err) = self.log_file.write().write_line(&log) {
             eprintln!("Unable to write to log file: {}", err);
         }
     }
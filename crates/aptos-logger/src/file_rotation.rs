@@ -0,0 +1,559 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rotation, retention and compression of log files written by the [`FileWriter`].
+//!
+//! When the current log file gets too large (or too old) it is renamed to
+//! `<file name>.<timestamp>`, optionally compressed, and a new file is opened in
+//! its place. Rotated files beyond the configured count or age are deleted. If
+//! rotation fails, we keep writing to the current file and retry later.
+//!
+//! [`FileWriter`]: crate::aptos_logger::FileWriter
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The format of the timestamp appended to rotated log files. This sorts
+/// lexicographically in the order the files were rotated.
+const ROTATED_FILE_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.6f";
+
+/// How long to wait before trying again after a rotation failed
+const ROTATION_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How rotated log files are compressed
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl LogCompression {
+    fn extension(&self) -> Option<&'static str> {
+        match self {
+            LogCompression::None => None,
+            LogCompression::Gzip => Some("gz"),
+            LogCompression::Zstd => Some("zst"),
+        }
+    }
+}
+
+/// When to rotate the log file, and which rotated files to keep
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Rotate the log file once it reaches this size
+    pub max_file_size_bytes: Option<u64>,
+    /// Rotate the log file once it has been written to for this long
+    pub rotation_interval: Option<Duration>,
+    /// The maximum number of rotated files to keep
+    pub max_rotated_files: Option<usize>,
+    /// Rotated files older than this are deleted
+    pub max_rotated_file_age: Option<Duration>,
+    /// How to compress rotated files
+    pub compression: LogCompression,
+    /// Reopen the log file when the process receives SIGHUP. This allows
+    /// external tools (e.g., logrotate) to move the log file away.
+    pub reopen_on_signal: bool,
+}
+
+/// A log file that is rotated according to a [`RotationPolicy`]
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    policy: RotationPolicy,
+    file: File,
+    file_size_bytes: u64,
+    file_opened_at: Instant,
+    reopen_requested: Arc<AtomicBool>,
+    // Set after a failed rotation, so we don't retry on every write
+    rotation_retry_at: Option<Instant>,
+    // The compression and retention work of the last rotation, done off the write path
+    background_task: Option<JoinHandle<()>>,
+}
+
+impl RotatingFile {
+    pub(crate) fn open(path: PathBuf, policy: RotationPolicy) -> io::Result<Self> {
+        let file = open_log_file(&path)?;
+        let file_size_bytes = file.metadata()?.len();
+
+        let reopen_requested = Arc::new(AtomicBool::new(false));
+        if policy.reopen_on_signal {
+            register_reopen_signal(reopen_requested.clone())?;
+        }
+
+        Ok(Self {
+            path,
+            policy,
+            file,
+            file_size_bytes,
+            file_opened_at: Instant::now(),
+            reopen_requested,
+            rotation_retry_at: None,
+            background_task: None,
+        })
+    }
+
+    /// Writes the log line to the file, rotating or reopening it first if required
+    pub(crate) fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.reopen_requested.swap(false, Ordering::Relaxed) {
+            self.reopen()?;
+        }
+        if self.should_rotate() {
+            if let Err(error) = self.rotate() {
+                eprintln!(
+                    "Unable to rotate log file {}: {}",
+                    self.path.display(),
+                    error
+                );
+                self.rotation_retry_at = Some(Instant::now() + ROTATION_RETRY_INTERVAL);
+            }
+        }
+
+        writeln!(self.file, "{}", line)?;
+        self.file_size_bytes += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn should_rotate(&self) -> bool {
+        if self.file_size_bytes == 0 {
+            return false;
+        }
+        if let Some(retry_at) = self.rotation_retry_at {
+            if Instant::now() < retry_at {
+                return false;
+            }
+        }
+        let too_large = self
+            .policy
+            .max_file_size_bytes
+            .map_or(false, |max_size| self.file_size_bytes >= max_size);
+        let too_old = self
+            .policy
+            .rotation_interval
+            .map_or(false, |interval| self.file_opened_at.elapsed() >= interval);
+        too_large || too_old
+    }
+
+    /// Reopens the log file at its path, e.g., after it was moved by an external tool
+    fn reopen(&mut self) -> io::Result<()> {
+        self.file = open_log_file(&self.path)?;
+        self.file_size_bytes = self.file.metadata()?.len();
+        self.file_opened_at = Instant::now();
+        Ok(())
+    }
+
+    /// Moves the current log file aside and starts a new one. The rotated file
+    /// is compressed, and old rotated files cleaned up, in the background. If
+    /// no new file can be opened, the current one is moved back.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated_path = self.unused_rotated_path();
+        fs::rename(&self.path, &rotated_path)?;
+        if let Err(error) = self.reopen() {
+            if let Err(rename_error) = fs::rename(&rotated_path, &self.path) {
+                eprintln!(
+                    "Unable to move rotated log file {} back: {}",
+                    rotated_path.display(),
+                    rename_error
+                );
+            }
+            return Err(error);
+        }
+        self.rotation_retry_at = None;
+
+        // Only run one background task at a time, so they don't race on the same files
+        self.wait_for_background_task();
+        let path = self.path.clone();
+        let policy = self.policy;
+        self.background_task = Some(std::thread::spawn(move || {
+            if let Err(error) = compress_file(&rotated_path, policy.compression) {
+                eprintln!(
+                    "Unable to compress rotated log file {}: {}",
+                    rotated_path.display(),
+                    error
+                );
+            }
+            if let Err(error) = remove_old_rotated_files(&path, &policy) {
+                eprintln!("Unable to remove old rotated log files: {}", error);
+            }
+        }));
+        Ok(())
+    }
+
+    /// The path to rotate the log file to. Rotating twice within the timestamp
+    /// precision (e.g., when the clock goes backwards) appends a counter, so an
+    /// earlier rotated file is never overwritten.
+    fn unused_rotated_path(&self) -> PathBuf {
+        let timestamp = Utc::now().format(ROTATED_FILE_TIMESTAMP_FORMAT);
+        (0..)
+            .map(|collision| match collision {
+                0 => PathBuf::from(format!("{}.{}", self.path.display(), timestamp)),
+                _ => PathBuf::from(format!(
+                    "{}.{}-{}",
+                    self.path.display(),
+                    timestamp,
+                    collision
+                )),
+            })
+            .find(|rotated_path| {
+                // The rotated file may already have been compressed
+                ![
+                    None,
+                    LogCompression::Gzip.extension(),
+                    LogCompression::Zstd.extension(),
+                ]
+                .into_iter()
+                .any(|extension| match extension {
+                    Some(extension) => {
+                        Path::new(&format!("{}.{}", rotated_path.display(), extension)).exists()
+                    },
+                    None => rotated_path.exists(),
+                })
+            })
+            .expect("There are always unused rotated paths")
+    }
+
+    pub(crate) fn wait_for_background_task(&mut self) {
+        if let Some(background_task) = self.background_task.take() {
+            let _ = background_task.join();
+        }
+    }
+}
+
+fn open_log_file(path: &Path) -> io::Result<File> {
+    OpenOptions::new().append(true).create(true).open(path)
+}
+
+#[cfg(unix)]
+fn register_reopen_signal(reopen_requested: Arc<AtomicBool>) -> io::Result<()> {
+    signal_hook::flag::register(signal_hook::consts::SIGHUP, reopen_requested).map(|_| ())
+}
+
+#[cfg(not(unix))]
+fn register_reopen_signal(_reopen_requested: Arc<AtomicBool>) -> io::Result<()> {
+    Ok(())
+}
+
+/// Compresses the file (if compression is enabled) and removes the original
+fn compress_file(path: &Path, compression: LogCompression) -> io::Result<()> {
+    let Some(extension) = compression.extension() else {
+        return Ok(());
+    };
+    let compressed_path = PathBuf::from(format!("{}.{}", path.display(), extension));
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let writer = BufWriter::new(File::create(&compressed_path)?);
+    match compression {
+        LogCompression::None => unreachable!("Uncompressed files have no extension"),
+        LogCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()?.flush()?;
+        },
+        LogCompression::Zstd => {
+            let mut encoder = zstd::Encoder::new(writer, 0)?;
+            io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()?.flush()?;
+        },
+    }
+    fs::remove_file(path)
+}
+
+/// Parses `<timestamp>[-<counter>][.<compression extension>]`, the suffix the
+/// log file name gets when it is rotated, into its timestamp and counter
+fn parse_rotated_file_suffix(suffix: &str) -> Option<(&str, u64)> {
+    let suffix = match suffix.rsplit_once('.') {
+        Some((rest, extension))
+            if Some(extension) == LogCompression::Gzip.extension()
+                || Some(extension) == LogCompression::Zstd.extension() =>
+        {
+            rest
+        },
+        _ => suffix,
+    };
+    // Timestamps have a fixed length, but we check they format back to the
+    // same string to rule out anything else chrono would parse
+    let timestamp_len = "YYYYMMDD-HHMMSS.ffffff".len();
+    if suffix.len() < timestamp_len || !suffix.is_char_boundary(timestamp_len) {
+        return None;
+    }
+    let (timestamp, counter) = suffix.split_at(timestamp_len);
+    let parsed = NaiveDateTime::parse_from_str(timestamp, ROTATED_FILE_TIMESTAMP_FORMAT).ok()?;
+    if parsed.format(ROTATED_FILE_TIMESTAMP_FORMAT).to_string() != timestamp {
+        return None;
+    }
+    let counter = match counter.strip_prefix('-') {
+        Some(counter) if counter.bytes().all(|byte| byte.is_ascii_digit()) => {
+            counter.parse().ok()?
+        },
+        Some(_) => return None,
+        None if counter.is_empty() => 0,
+        None => return None,
+    };
+    Some((timestamp, counter))
+}
+
+/// Returns the rotated files of the log file, oldest first
+fn rotated_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let rotated_prefix = match path.file_name() {
+        Some(file_name) => format!("{}.", file_name.to_string_lossy()),
+        None => return Ok(vec![]),
+    };
+
+    let mut rotated_files = vec![];
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let order = file_name
+            .strip_prefix(&rotated_prefix)
+            .and_then(parse_rotated_file_suffix)
+            .map(|(timestamp, counter)| (timestamp.to_string(), counter));
+        if let Some(order) = order {
+            rotated_files.push((order, entry.path()));
+        }
+    }
+    rotated_files.sort();
+    Ok(rotated_files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Removes the rotated files beyond the maximum count or age of the policy
+fn remove_old_rotated_files(path: &Path, policy: &RotationPolicy) -> io::Result<()> {
+    let mut rotated_files = rotated_files(path)?;
+
+    if let Some(max_age) = policy.max_rotated_file_age {
+        let now = SystemTime::now();
+        let mut kept_files = vec![];
+        for rotated_file in rotated_files {
+            let modified = fs::metadata(&rotated_file)?.modified()?;
+            if now.duration_since(modified).unwrap_or_default() > max_age {
+                fs::remove_file(&rotated_file)?;
+            } else {
+                kept_files.push(rotated_file);
+            }
+        }
+        rotated_files = kept_files;
+    }
+
+    if let Some(max_files) = policy.max_rotated_files {
+        let num_to_remove = rotated_files.len().saturating_sub(max_files);
+        for rotated_file in &rotated_files[..num_to_remove] {
+            fs::remove_file(rotated_file)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn open_rotating_file(directory: &Path, policy: RotationPolicy) -> RotatingFile {
+        RotatingFile::open(directory.join("node.log"), policy).unwrap()
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut rotating_file = open_rotating_file(directory.path(), RotationPolicy {
+            max_file_size_bytes: Some(10),
+            ..Default::default()
+        });
+
+        // Each line fills the file, so every write after the first rotates it
+        for i in 0..3 {
+            rotating_file
+                .write_line(&format!("log line {}", i))
+                .unwrap();
+            rotating_file.wait_for_background_task();
+        }
+
+        let rotated_files = rotated_files(&directory.path().join("node.log")).unwrap();
+        assert_eq!(rotated_files.len(), 2);
+        assert_eq!(
+            fs::read_to_string(&rotated_files[0]).unwrap(),
+            "log line 0\n"
+        );
+        assert_eq!(
+            fs::read_to_string(directory.path().join("node.log")).unwrap(),
+            "log line 2\n"
+        );
+    }
+
+    #[test]
+    fn test_retention_by_count() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut rotating_file = open_rotating_file(directory.path(), RotationPolicy {
+            max_file_size_bytes: Some(1),
+            max_rotated_files: Some(2),
+            ..Default::default()
+        });
+
+        for i in 0..6 {
+            rotating_file
+                .write_line(&format!("log line {}", i))
+                .unwrap();
+            rotating_file.wait_for_background_task();
+        }
+
+        // Only the newest rotated files are kept
+        let rotated_files = rotated_files(&directory.path().join("node.log")).unwrap();
+        assert_eq!(rotated_files.len(), 2);
+        assert_eq!(
+            fs::read_to_string(&rotated_files[1]).unwrap(),
+            "log line 4\n"
+        );
+    }
+
+    #[test]
+    fn test_compression() {
+        for compression in [LogCompression::Gzip, LogCompression::Zstd] {
+            let directory = tempfile::tempdir().unwrap();
+            let mut rotating_file = open_rotating_file(directory.path(), RotationPolicy {
+                max_file_size_bytes: Some(1),
+                compression,
+                ..Default::default()
+            });
+            rotating_file.write_line("first").unwrap();
+            rotating_file.write_line("second").unwrap();
+            rotating_file.wait_for_background_task();
+
+            let rotated_files = rotated_files(&directory.path().join("node.log")).unwrap();
+            assert_eq!(rotated_files.len(), 1);
+            let rotated_file = &rotated_files[0];
+            assert_eq!(
+                rotated_file.extension().unwrap().to_str(),
+                compression.extension()
+            );
+
+            let mut contents = String::new();
+            let file = File::open(rotated_file).unwrap();
+            match compression {
+                LogCompression::Gzip => flate2::read::GzDecoder::new(file)
+                    .read_to_string(&mut contents)
+                    .unwrap(),
+                LogCompression::Zstd => zstd::Decoder::new(file)
+                    .unwrap()
+                    .read_to_string(&mut contents)
+                    .unwrap(),
+                LogCompression::None => unreachable!(),
+            };
+            assert_eq!(contents, "first\n");
+        }
+    }
+
+    #[test]
+    fn test_rotated_files_only_matches_rotated_names() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("node.log");
+        for file_name in [
+            "node.log",
+            "node.log.20240101-000000.000001-2",
+            "node.log.20240101-000000.000001.gz",
+            "node.log.20240101-000000.000001-10.zst",
+            "node.log.20240101-000000.000000",
+            // Not written by the rotation
+            "node.log.bak",
+            "node.log.20240101",
+            "node.log.20241301-000000.000000",
+            "node.log.20240101-000000.000001.txt",
+            "node.log.20240101-000000.000001-x",
+            "node.log.old.20240101-000000.000000",
+        ] {
+            fs::write(directory.path().join(file_name), "").unwrap();
+        }
+
+        let file_names: Vec<_> = rotated_files(&path)
+            .unwrap()
+            .iter()
+            .map(|rotated_file| rotated_file.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(file_names, vec![
+            "node.log.20240101-000000.000000",
+            "node.log.20240101-000000.000001.gz",
+            "node.log.20240101-000000.000001-2",
+            "node.log.20240101-000000.000001-10.zst",
+        ]);
+    }
+
+    #[test]
+    fn test_rotation_never_overwrites_rotated_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let rotating_file = open_rotating_file(directory.path(), RotationPolicy::default());
+
+        // Pretend the previous rotation already used the next paths
+        let first = rotating_file.unused_rotated_path();
+        fs::write(&first, "").unwrap();
+        let second = rotating_file.unused_rotated_path();
+        assert_ne!(first, second);
+        fs::write(format!("{}.gz", second.display()), "").unwrap();
+        let third = rotating_file.unused_rotated_path();
+        assert_ne!(second, third);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_rotation_keeps_writing() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("node.log");
+        let mut rotating_file = open_rotating_file(directory.path(), RotationPolicy {
+            max_file_size_bytes: Some(1),
+            ..Default::default()
+        });
+        rotating_file.write_line("first").unwrap();
+
+        // Renaming and creating files fail in a read only directory
+        fs::set_permissions(directory.path(), fs::Permissions::from_mode(0o555)).unwrap();
+        let read_only = fs::write(directory.path().join("probe"), "").is_err();
+        rotating_file.write_line("second").unwrap();
+        fs::set_permissions(directory.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        if !read_only {
+            // Permissions aren't enforced, e.g., when running as root
+            return;
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        assert!(rotated_files(&path).unwrap().is_empty());
+        assert!(rotating_file.rotation_retry_at.is_some());
+    }
+
+    #[test]
+    fn test_reopen() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("node.log");
+        let mut rotating_file = open_rotating_file(directory.path(), RotationPolicy::default());
+        rotating_file.write_line("before move").unwrap();
+
+        // Move the file away (as logrotate would) and request a reopen
+        fs::rename(&path, directory.path().join("moved.log")).unwrap();
+        rotating_file
+            .reopen_requested
+            .store(true, Ordering::Relaxed);
+        rotating_file.write_line("after move").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after move\n");
+        assert_eq!(
+            fs::read_to_string(directory.path().join("moved.log")).unwrap(),
+            "before move\n"
+        );
+    }
+}
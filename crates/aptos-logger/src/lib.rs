@@ -146,6 +146,7 @@ pub mod prelude {
 
 pub mod aptos_logger;
 mod event;
+mod file_rotation;
 mod filter;
 mod kv;
 mod logger;
@@ -162,6 +163,7 @@ pub use crate::aptos_logger::{
 };
 pub use aptos_log_derive::Schema;
 pub use event::Event;
+pub use file_rotation::{LogCompression, RotationPolicy};
 pub use filter::{Filter, LevelFilter};
 pub use kv::{Key, KeyValue, Schema, Value, Visitor};
 pub use logger::flush;
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    get_version_path_with_base, Client, DEFAULT_VERSION_PATH_BASE, X_APTOS_SDK_HEADER_VALUE,
+    get_version_path_with_base, retry::EndpointPool, Client, RetryPolicy,
+    DEFAULT_VERSION_PATH_BASE, X_APTOS_SDK_HEADER_VALUE,
 };
 use anyhow::Result;
 use aptos_api_types::X_APTOS_CLIENT;
@@ -10,7 +11,7 @@ use reqwest::{
     header::{self, HeaderMap, HeaderName, HeaderValue},
     Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder,
 };
-use std::{env, str::FromStr, time::Duration};
+use std::{env, str::FromStr, sync::Arc, time::Duration};
 use url::Url;
 
 pub enum AptosBaseUrl {
@@ -41,6 +42,8 @@ pub struct ClientBuilder {
     base_url: Url,
     timeout: Duration,
     headers: HeaderMap,
+    failover_urls: Vec<Url>,
+    retry_policy: RetryPolicy,
 }
 
 impl ClientBuilder {
@@ -57,6 +60,8 @@ impl ClientBuilder {
             version_path_base: DEFAULT_VERSION_PATH_BASE.to_string(),
             timeout: Duration::from_secs(10), // Default to 10 seconds
             headers,
+            failover_urls: vec![],
+            retry_policy: RetryPolicy::none(),
         };
 
         if let Ok(key) = env::var("X_API_KEY") {
@@ -96,6 +101,21 @@ impl ClientBuilder {
         self
     }
 
+    /// Additional fullnodes to fail over to when the base url is unhealthy, in
+    /// order of preference. They must serve the API under the same path as the
+    /// base url. With failover enabled, responses from nodes that are behind the
+    /// highest ledger version seen so far are rejected.
+    pub fn failover_urls(mut self, failover_urls: Vec<Url>) -> Self {
+        self.failover_urls = failover_urls;
+        self
+    }
+
+    /// How failed requests are retried. Defaults to [`RetryPolicy::none`].
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     pub fn build(self) -> Client {
         let version_path_base = get_version_path_with_base(self.base_url.clone());
 
@@ -107,6 +127,11 @@ impl ClientBuilder {
                 .cookie_store(true)
                 .build()
                 .unwrap(),
+            endpoints: Arc::new(EndpointPool::new(
+                self.base_url.clone(),
+                self.failover_urls,
+                self.retry_policy,
+            )),
             base_url: self.base_url,
             version_path_base,
         }
@@ -3,16 +3,64 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{error::FaucetClientError, Client, Result};
+use aptos_crypto::HashValue;
 use aptos_types::transaction::SignedTransaction;
 use move_core_types::account_address::AccountAddress;
 use reqwest::{Client as ReqwestClient, Response, Url};
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// Header the faucet reads the proof of work challenge being solved from.
+pub const POW_CHALLENGE_HEADER: &str = "POW_CHALLENGE";
+/// Header the faucet reads the solution to the proof of work challenge from.
+pub const POW_NONCE_HEADER: &str = "POW_NONCE";
+
+/// A proof of work challenge, as returned by the `/request_pow_challenge`
+/// endpoint of the faucet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProofOfWorkChallenge {
+    pub challenge: String,
+    /// Number of leading zero bits the hash of a solution must have.
+    pub difficulty: u8,
+}
+
+/// Whether `nonce` solves `challenge` for a request funding `receiver`, i.e.
+/// whether sha3-256(challenge || receiver || nonce) starts with `difficulty`
+/// zero bits. Binding the receiver into the hash means a solution can't be
+/// reused to fund a different account.
+pub fn is_proof_of_work_solution(
+    challenge: &str,
+    receiver: AccountAddress,
+    nonce: u64,
+    difficulty: u8,
+) -> bool {
+    let hash = HashValue::sha3_256_of(
+        &[
+            challenge.as_bytes(),
+            receiver.as_ref(),
+            nonce.to_le_bytes().as_slice(),
+        ]
+        .concat(),
+    );
+    hash.common_prefix_bits_len(HashValue::zero()) >= difficulty as usize
+}
+
+/// Finds a nonce that solves the challenge for `receiver`. This takes about
+/// 2^difficulty hashes.
+pub fn solve_proof_of_work(challenge: &ProofOfWorkChallenge, receiver: AccountAddress) -> u64 {
+    (0..u64::MAX)
+        .find(|nonce| {
+            is_proof_of_work_solution(&challenge.challenge, receiver, *nonce, challenge.difficulty)
+        })
+        .expect("Failed to solve proof of work challenge")
+}
+
 pub struct FaucetClient {
     faucet_url: Url,
     inner: ReqwestClient,
     rest_client: Client,
     token: Option<String>,
+    solve_proof_of_work: bool,
 }
 
 impl FaucetClient {
@@ -34,6 +82,7 @@ impl FaucetClient {
                 .version_path_base("/".to_string())
                 .unwrap(),
             token: None,
+            solve_proof_of_work: false,
         }
     }
 
@@ -46,6 +95,7 @@ impl FaucetClient {
                 .unwrap(),
             rest_client,
             token: None,
+            solve_proof_of_work: false,
         }
     }
 
@@ -55,6 +105,13 @@ impl FaucetClient {
         self
     }
 
+    // Solve a proof of work challenge from the faucet with every request,
+    // for faucets that require one.
+    pub fn with_proof_of_work(mut self) -> Self {
+        self.solve_proof_of_work = true;
+        self
+    }
+
     /// Create an account with zero balance.
     pub async fn create_account(&self, address: AccountAddress) -> Result<()> {
         let mut url = self.faucet_url.clone();
@@ -62,7 +119,7 @@ impl FaucetClient {
         let query = format!("auth_key={}&amount=0&return_txns=true", address);
         url.set_query(Some(&query));
 
-        let response = self.build_and_submit_request(url).await?;
+        let response = self.build_and_submit_request(url, address).await?;
         let status_code = response.status();
         let body = response.text().await.map_err(FaucetClientError::decode)?;
         if !status_code.is_success() {
@@ -90,7 +147,7 @@ impl FaucetClient {
 
         // Faucet returns the transaction that creates the account and needs to be waited on before
         // returning.
-        let response = self.build_and_submit_request(url).await?;
+        let response = self.build_and_submit_request(url, address).await?;
         let status_code = response.status();
         let body = response.text().await.map_err(FaucetClientError::decode)?;
         if !status_code.is_success() {
@@ -117,16 +174,83 @@ impl FaucetClient {
         Ok(())
     }
 
+    /// Requests a proof of work challenge from the faucet and solves it for
+    /// `receiver`. Returns the challenge and the nonce that solves it.
+    async fn solve_challenge(&self, receiver: AccountAddress) -> Result<(String, u64)> {
+        let mut url = self.faucet_url.clone();
+        url.set_path("request_pow_challenge");
+        let response = self
+            .inner
+            .get(url)
+            .send()
+            .await
+            .map_err(FaucetClientError::request)?;
+        let status_code = response.status();
+        if !status_code.is_success() {
+            return Err(FaucetClientError::status(status_code.as_u16()).into());
+        }
+        let challenge: ProofOfWorkChallenge =
+            response.json().await.map_err(FaucetClientError::decode)?;
+
+        // Solving takes a while, so don't block the runtime.
+        let nonce = tokio::task::spawn_blocking({
+            let challenge = challenge.clone();
+            move || solve_proof_of_work(&challenge, receiver)
+        })
+        .await
+        .map_err(FaucetClientError::unknown)?;
+        Ok((challenge.challenge, nonce))
+    }
+
     // Helper to carry out requests.
-    async fn build_and_submit_request(&self, url: Url) -> Result<Response> {
+    async fn build_and_submit_request(
+        &self,
+        url: Url,
+        receiver: AccountAddress,
+    ) -> Result<Response> {
         // build request
         let mut request = self.inner.post(url).header("content-length", 0);
         if let Some(token) = &self.token {
             request = request.header("Authorization", format!("Bearer {}", token));
         }
+        if self.solve_proof_of_work {
+            let (challenge, nonce) = self.solve_challenge(receiver).await?;
+            request = request
+                .header(POW_CHALLENGE_HEADER, challenge)
+                .header(POW_NONCE_HEADER, nonce);
+        }
 
         // carry out and return response
         let response = request.send().await.map_err(FaucetClientError::request)?;
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_of_work() {
+        let challenge = ProofOfWorkChallenge {
+            challenge: "abcd".to_string(),
+            difficulty: 8,
+        };
+        let receiver = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        let nonce = solve_proof_of_work(&challenge, receiver);
+        assert!(is_proof_of_work_solution("abcd", receiver, nonce, 8));
+        assert!(is_proof_of_work_solution("abcd", receiver, nonce, 0));
+
+        // Every nonce solves a challenge with no difficulty.
+        assert_eq!(
+            solve_proof_of_work(
+                &ProofOfWorkChallenge {
+                    challenge: "abcd".to_string(),
+                    difficulty: 0,
+                },
+                receiver
+            ),
+            0
+        );
+    }
+}
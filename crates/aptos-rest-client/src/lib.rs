@@ -11,11 +11,15 @@ pub use faucet::FaucetClient;
 pub mod response;
 pub use response::Response;
 pub mod client_builder;
+mod retry;
 pub mod state;
 pub mod types;
 
-pub use crate::client_builder::{AptosBaseUrl, ClientBuilder};
-use crate::{aptos::AptosVersion, error::RestError};
+use crate::{aptos::AptosVersion, error::RestError, retry::EndpointPool};
+pub use crate::{
+    client_builder::{AptosBaseUrl, ClientBuilder},
+    retry::RetryPolicy,
+};
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
     self, IndexResponseBcs, MoveModuleBytecode, PendingTransaction, Transaction,
@@ -42,12 +46,12 @@ use move_core_types::{
 };
 use reqwest::{
     header::{ACCEPT, CONTENT_TYPE},
-    Client as ReqwestClient, StatusCode,
+    Client as ReqwestClient, RequestBuilder, StatusCode,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 pub use state::State;
-use std::{collections::BTreeMap, future::Future, str::FromStr, time::Duration};
+use std::{collections::BTreeMap, future::Future, str::FromStr, sync::Arc, time::Duration};
 use tokio::time::Instant;
 pub use types::{deserialize_from_prefixed_hex_string, Account, Resource};
 use url::Url;
@@ -69,6 +73,7 @@ pub struct Client {
     inner: ReqwestClient,
     base_url: Url,
     version_path_base: String,
+    endpoints: Arc<EndpointPool>,
 }
 
 impl Client {
@@ -101,6 +106,12 @@ impl Client {
         Ok(self.base_url.join(&self.version_path_base)?.join(path)?)
     }
 
+    /// The base url requests are currently sent to. This differs from the
+    /// configured base url after failing over to one of the failover urls.
+    pub fn active_base_url(&self) -> &Url {
+        self.endpoints.current_url()
+    }
+
     pub async fn get_aptos_version(&self) -> AptosResult<Response<AptosVersion>> {
         self.get_resource::<AptosVersion>(CORE_CODE_ADDRESS, "0x1::version::Version")
             .await
@@ -292,11 +303,12 @@ impl Client {
         }
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, JSON)
-            .body(request)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, JSON)
+                    .body(request),
+            )
             .await?;
 
         self.json(response).await
@@ -314,12 +326,13 @@ impl Client {
         }
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -338,12 +351,13 @@ impl Client {
         }
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
-            .header(ACCEPT, JSON)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
+                    .header(ACCEPT, JSON)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
@@ -357,11 +371,12 @@ impl Client {
         let url = self.build_path("transactions/simulate")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
@@ -381,11 +396,12 @@ impl Client {
         ))?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
@@ -399,12 +415,13 @@ impl Client {
         let url = self.build_path("transactions/simulate")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -424,12 +441,13 @@ impl Client {
         ))?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -444,11 +462,12 @@ impl Client {
         let url = self.build_path("transactions")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json::<PendingTransaction>(response).await
@@ -459,11 +478,12 @@ impl Client {
         let url = self.build_path("transactions")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.check_response(response).await?;
@@ -475,12 +495,13 @@ impl Client {
         let url = self.build_path("transactions")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -495,11 +516,12 @@ impl Client {
         let url = self.build_path("transactions/batch")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .body(txn_payload),
+            )
             .await?;
         self.json(response).await
     }
@@ -512,12 +534,13 @@ impl Client {
         let url = self.build_path("transactions/batch")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -857,7 +880,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -894,7 +917,7 @@ impl Client {
         hash: HashValue,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
+        let response = self.send(self.inner.get(url).header(ACCEPT, BCS)).await?;
         Ok(response)
     }
 
@@ -903,7 +926,7 @@ impl Client {
         hash: HashValue,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        Ok(self.inner.get(url).send().await?)
+        self.send(self.inner.get(url)).await
     }
 
     pub async fn get_transaction_by_version(
@@ -928,7 +951,7 @@ impl Client {
         version: u64,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_version/{}", version))?;
-        Ok(self.inner.get(url).send().await?)
+        self.send(self.inner.get(url)).await
     }
 
     pub async fn get_account_transactions(
@@ -948,7 +971,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -1046,12 +1069,7 @@ impl Client {
             resource_type
         ))?;
 
-        let response = self
-            .inner
-            .get(url)
-            .send()
-            .await
-            .map_err(anyhow::Error::from)?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1131,7 +1149,7 @@ impl Client {
             version
         ))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1223,7 +1241,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         self.json(response).await
     }
 
@@ -1311,7 +1329,7 @@ impl Client {
             "key": json!(key),
         });
 
-        let response = self.inner.post(url).json(&data).send().await?;
+        let response = self.send(self.inner.post(url).json(&data)).await?;
         self.json(response).await
     }
 
@@ -1333,7 +1351,7 @@ impl Client {
             "key": json!(key),
         });
 
-        let response = self.inner.post(url).json(&data).send().await?;
+        let response = self.send(self.inner.post(url).json(&data)).await?;
         self.json(response).await
     }
 
@@ -1414,7 +1432,7 @@ impl Client {
 
     pub async fn get_account(&self, address: AccountAddress) -> AptosResult<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address.to_hex()))?;
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1429,7 +1447,7 @@ impl Client {
 
     pub async fn estimate_gas_price(&self) -> AptosResult<Response<GasEstimation>> {
         let url = self.build_path("estimate_gas_price")?;
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1440,7 +1458,7 @@ impl Client {
             .append_pair("name", &name)
             .append_pair("actions", &actions)
             .finish();
-        let response = self.inner.get(url.clone()).send().await?;
+        let response = self.send(self.inner.get(url.clone())).await?;
 
         if !response.status().is_success() {
             Err(parse_error(response).await)
@@ -1477,10 +1495,7 @@ impl Client {
     pub async fn health_check(&self, seconds: u64) -> AptosResult<()> {
         let url = self.build_path("-/healthy")?;
         let response = self
-            .inner
-            .get(url)
-            .query(&[("duration_secs", seconds)])
-            .send()
+            .send(self.inner.get(url).query(&[("duration_secs", seconds)]))
             .await?;
 
         if !response.status().is_success() {
@@ -1490,12 +1505,18 @@ impl Client {
         }
     }
 
+    /// Sends a request through the endpoint pool, which takes care of retries,
+    /// rate limiting and failover.
+    async fn send(&self, request: RequestBuilder) -> AptosResult<reqwest::Response> {
+        self.endpoints.execute(&self.inner, request.build()?).await
+    }
+
     async fn get<T: DeserializeOwned>(&self, url: Url) -> AptosResult<Response<T>> {
-        self.json(self.inner.get(url).send().await?).await
+        self.json(self.send(self.inner.get(url)).await?).await
     }
 
     async fn get_bcs(&self, url: Url) -> AptosResult<Response<bytes::Bytes>> {
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
+        let response = self.send(self.inner.get(url).header(ACCEPT, BCS)).await?;
         self.check_and_parse_bcs_response(response).await
     }
 
@@ -1505,11 +1526,7 @@ impl Client {
         data: serde_json::Value,
     ) -> AptosResult<Response<bytes::Bytes>> {
         let response = self
-            .inner
-            .post(url)
-            .header(ACCEPT, BCS)
-            .json(&data)
-            .send()
+            .send(self.inner.post(url).header(ACCEPT, BCS).json(&data))
             .await?;
         self.check_and_parse_bcs_response(response).await
     }
@@ -1529,7 +1546,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         self.check_and_parse_bcs_response(response).await
     }
 
@@ -1634,7 +1651,7 @@ impl Client {
                 ledger_version,
                 &cursor,
             )?;
-            let raw_response = self.inner.get(url).send().await?;
+            let raw_response = self.send(self.inner.get(url)).await?;
             let response: Response<Vec<T>> = self.json(raw_response).await?;
             cursor.clone_from(&response.state().cursor);
             if cursor.is_none() {
@@ -1720,6 +1737,11 @@ impl From<(ReqwestClient, Url)> for Client {
     fn from((inner, base_url): (ReqwestClient, Url)) -> Self {
         Client {
             inner,
+            endpoints: Arc::new(EndpointPool::new(
+                base_url.clone(),
+                vec![],
+                RetryPolicy::none(),
+            )),
             base_url,
             version_path_base: DEFAULT_VERSION_PATH_BASE.to_string(),
         }
@@ -0,0 +1,522 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Request retries and endpoint failover for the REST [`Client`](crate::Client).
+//!
+//! Every request issued by the client goes through an [`EndpointPool`]. The pool
+//! keeps the list of fullnodes the client may talk to, tracks which of them are
+//! currently healthy, and retries idempotent requests according to the
+//! configured [`RetryPolicy`]. Rate limited responses (429) honor the
+//! `Retry-After` header sent by the server or the API gateway.
+//!
+//! When more than one endpoint is configured, the pool also remembers the
+//! highest ledger version it has observed from each node, and treats responses
+//! from a node that is behind the version another node has already served as
+//! failures. This guarantees that reads never go backwards in ledger version
+//! when the client switches between nodes. Responses of concurrent requests to
+//! the same node may arrive out of order, so a node is never considered behind
+//! itself.
+
+use crate::error::RestError;
+use aptos_api_types::X_APTOS_LEDGER_VERSION;
+use aptos_infallible::Mutex;
+use aptos_logger::{sample, sample::SampleRate, warn};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Client as ReqwestClient, Method, Request, Response, StatusCode,
+};
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Controls how the client retries failed requests.
+///
+/// Only idempotent requests are retried: all `GET` requests and the `POST`
+/// endpoints that read state (view functions, table items, simulation, ...).
+/// Transaction submissions are only retried if `retry_submissions` is set.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the initial attempt.
+    pub max_retries: usize,
+    /// Delay before the first retry. Doubles on every subsequent retry.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between two attempts, including delays
+    /// requested by the server through `Retry-After`.
+    pub max_backoff: Duration,
+    /// How long a failing endpoint is skipped when other endpoints are available.
+    pub unhealthy_cooldown: Duration,
+    /// Whether transaction submissions are retried as well. Resubmitting the same
+    /// signed transaction is harmless, but the retry may report an error if the
+    /// first attempt was in fact accepted.
+    pub retry_submissions: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries; each request is attempted exactly once.
+    pub fn none() -> Self {
+        Self::exponential(0)
+    }
+
+    /// Exponential backoff starting at 100ms and capped at 10s.
+    pub fn exponential(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            unhealthy_cooldown: Duration::from_secs(30),
+            retry_submissions: false,
+        }
+    }
+
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_unhealthy_cooldown(mut self, unhealthy_cooldown: Duration) -> Self {
+        self.unhealthy_cooldown = unhealthy_cooldown;
+        self
+    }
+
+    pub fn with_retry_submissions(mut self, retry_submissions: bool) -> Self {
+        self.retry_submissions = retry_submissions;
+        self
+    }
+
+    fn is_retriable(&self, request: &Request) -> bool {
+        if request.method() == Method::GET {
+            return true;
+        }
+        self.retry_submissions || !is_submission(request)
+    }
+
+    fn backoff(&self, retry: usize) -> Duration {
+        let factor = 1u32.checked_shl(retry as u32).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// Transaction submission endpoints: `POST /transactions` and `POST /transactions/batch`.
+fn is_submission(request: &Request) -> bool {
+    let path = request.url().path().trim_end_matches('/');
+    request.method() == Method::POST
+        && (path.ends_with("/transactions") || path.ends_with("/transactions/batch"))
+}
+
+/// Parses `Retry-After` given in seconds. The HTTP-date form is not used by the
+/// Aptos API or its gateways and is ignored.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn parse_ledger_version(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(X_APTOS_LEDGER_VERSION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Url,
+    unhealthy_until: Mutex<Option<Instant>>,
+    /// Highest ledger version served by this endpoint.
+    highest_ledger_version: AtomicU64,
+}
+
+impl Endpoint {
+    fn new(url: Url) -> Self {
+        Self {
+            url,
+            unhealthy_until: Mutex::new(None),
+            highest_ledger_version: AtomicU64::new(0),
+        }
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        let unhealthy_until = *self.unhealthy_until.lock();
+        unhealthy_until.map_or(true, |unhealthy_until| unhealthy_until <= now)
+    }
+
+    fn mark_unhealthy(&self, until: Instant) {
+        let mut unhealthy_until = self.unhealthy_until.lock();
+        let current = *unhealthy_until;
+        *unhealthy_until = Some(current.map_or(until, |current| current.max(until)));
+    }
+
+    fn mark_healthy(&self) {
+        *self.unhealthy_until.lock() = None;
+    }
+
+    /// Points a request URL built against the primary endpoint at this endpoint.
+    /// Only the origin is swapped; all endpoints share the same path layout.
+    fn rewrite(&self, url: &mut Url) {
+        // These can only fail for URLs that cannot be a base, which is
+        // already rejected when building request paths.
+        let _ = url.set_scheme(self.url.scheme());
+        let _ = url.set_host(self.url.host_str());
+        let _ = url.set_port(self.url.port_or_known_default());
+    }
+}
+
+/// Outcome of a single attempt that warrants trying again.
+enum Failure {
+    Transport(reqwest::Error),
+    Status(Response),
+    RateLimited(Response, Option<Duration>),
+    Stale(Response, u64, u64),
+}
+
+/// The set of fullnodes a [`Client`](crate::Client) sends requests to.
+#[derive(Debug)]
+pub(crate) struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+    current: AtomicUsize,
+    retry_policy: RetryPolicy,
+}
+
+impl EndpointPool {
+    /// `primary` is the URL request paths are built against; `failover` urls are
+    /// tried in order once the primary becomes unhealthy.
+    pub(crate) fn new(primary: Url, failover: Vec<Url>, retry_policy: RetryPolicy) -> Self {
+        let endpoints = std::iter::once(primary)
+            .chain(failover)
+            .map(Endpoint::new)
+            .collect();
+        Self {
+            endpoints,
+            current: AtomicUsize::new(0),
+            retry_policy,
+        }
+    }
+
+    /// Returns the endpoint requests are currently sent to.
+    pub(crate) fn current_url(&self) -> &Url {
+        &self.endpoints[self.select(Instant::now())].url
+    }
+
+    fn has_failover(&self) -> bool {
+        self.endpoints.len() > 1
+    }
+
+    /// Picks the current endpoint if it is healthy, otherwise the next healthy
+    /// one. If every endpoint is unhealthy the current one is kept.
+    fn select(&self, now: Instant) -> usize {
+        let current = self.current.load(Ordering::Relaxed);
+        (0..self.endpoints.len())
+            .map(|offset| (current + offset) % self.endpoints.len())
+            .find(|index| self.endpoints[*index].is_healthy(now))
+            .unwrap_or(current)
+    }
+
+    /// Marks `index` unhealthy and moves on to the next healthy endpoint.
+    /// Returns whether a different endpoint is now selected.
+    fn fail_over(&self, index: usize, cooldown: Duration) -> bool {
+        let now = Instant::now();
+        self.endpoints[index].mark_unhealthy(now + cooldown);
+        let next = self.select(now);
+        let _ = self
+            .current
+            .compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed);
+        if next != index {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(10)),
+                warn!(
+                    "Failing over from {} to {}",
+                    self.endpoints[index].url, self.endpoints[next].url
+                )
+            );
+        }
+        next != index
+    }
+
+    /// Sends `request`, retrying and failing over according to the retry policy.
+    ///
+    /// Responses with an error status are returned as-is once retries are
+    /// exhausted so the caller can parse the error body.
+    pub(crate) async fn execute(
+        &self,
+        client: &ReqwestClient,
+        request: Request,
+    ) -> Result<Response, RestError> {
+        let policy = &self.retry_policy;
+        let retriable = policy.is_retriable(&request);
+        let mut retry = 0;
+        loop {
+            let index = self.select(Instant::now());
+            let endpoint = &self.endpoints[index];
+            let mut attempt = match request.try_clone() {
+                Some(attempt) => attempt,
+                // Streaming bodies cannot be replayed, send them once.
+                None => return Ok(client.execute(request).await?),
+            };
+            if index != 0 {
+                endpoint.rewrite(attempt.url_mut());
+            }
+
+            let failure = match client.execute(attempt).await {
+                Ok(response) => match self.check(index, response) {
+                    Ok(response) => {
+                        endpoint.mark_healthy();
+                        return Ok(response);
+                    },
+                    Err(failure) => failure,
+                },
+                Err(error) if error.is_connect() || error.is_timeout() || error.is_request() => {
+                    Failure::Transport(error)
+                },
+                Err(error) => return Err(error.into()),
+            };
+
+            let mut delay = policy.backoff(retry);
+            let cooldown = match &failure {
+                Failure::RateLimited(_, Some(retry_after)) => {
+                    delay = (*retry_after).min(policy.max_backoff);
+                    policy.unhealthy_cooldown.max(*retry_after)
+                },
+                _ => policy.unhealthy_cooldown,
+            };
+            let switched = self.has_failover() && self.fail_over(index, cooldown);
+
+            if !retriable || retry >= policy.max_retries {
+                return match failure {
+                    Failure::Transport(error) => Err(error.into()),
+                    Failure::Status(response) | Failure::RateLimited(response, _) => Ok(response),
+                    Failure::Stale(_, version, highest) => Err(anyhow::anyhow!(
+                        "Endpoint {} returned ledger version {} which is behind the version {} served by another endpoint",
+                        endpoint.url,
+                        version,
+                        highest,
+                    )
+                    .into()),
+                };
+            }
+            retry += 1;
+
+            // Another endpoint can serve the request right away; otherwise wait
+            // for the current one to recover.
+            if !switched {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    /// Classifies a response of the endpoint at `index`, recording the ledger
+    /// version of successful ones.
+    fn check(&self, index: usize, response: Response) -> Result<Response, Failure> {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = parse_retry_after(response.headers());
+            return Err(Failure::RateLimited(response, retry_after));
+        }
+        if crate::retriable(status, None) {
+            return Err(Failure::Status(response));
+        }
+        if status.is_success() && self.has_failover() {
+            if let Some(version) = parse_ledger_version(response.headers()) {
+                self.endpoints[index]
+                    .highest_ledger_version
+                    .fetch_max(version, Ordering::Relaxed);
+                let highest_elsewhere = self
+                    .endpoints
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, _)| *other != index)
+                    .map(|(_, endpoint)| endpoint.highest_ledger_version.load(Ordering::Relaxed))
+                    .max()
+                    .unwrap_or(0);
+                if version < highest_elsewhere {
+                    return Err(Failure::Stale(response, version, highest_elsewhere));
+                }
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, Mock, MockServer};
+    use reqwest::header::HeaderValue;
+
+    fn pool(failover: usize) -> EndpointPool {
+        EndpointPool::new(
+            Url::parse("http://primary:8080").unwrap(),
+            (0..failover)
+                .map(|i| Url::parse(&format!("https://failover-{}", i)).unwrap())
+                .collect(),
+            RetryPolicy::exponential(3),
+        )
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy::exponential(10)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1));
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_submissions_are_not_retried_by_default() {
+        let client = ReqwestClient::new();
+        let submit = client
+            .post("http://localhost:8080/v1/transactions")
+            .build()
+            .unwrap();
+        let batch = client
+            .post("http://localhost:8080/v1/transactions/batch")
+            .build()
+            .unwrap();
+        let simulate = client
+            .post("http://localhost:8080/v1/transactions/simulate")
+            .build()
+            .unwrap();
+        let get = client
+            .get("http://localhost:8080/v1/transactions")
+            .build()
+            .unwrap();
+
+        let policy = RetryPolicy::exponential(3);
+        assert!(!policy.is_retriable(&submit));
+        assert!(!policy.is_retriable(&batch));
+        assert!(policy.is_retriable(&simulate));
+        assert!(policy.is_retriable(&get));
+        assert!(policy.with_retry_submissions(true).is_retriable(&submit));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_fail_over_skips_unhealthy_endpoints() {
+        let pool = pool(2);
+        let cooldown = Duration::from_secs(60);
+        assert_eq!(pool.current_url().host_str(), Some("primary"));
+
+        assert!(pool.fail_over(0, cooldown));
+        assert_eq!(pool.current_url().host_str(), Some("failover-0"));
+
+        assert!(pool.fail_over(1, cooldown));
+        assert_eq!(pool.current_url().host_str(), Some("failover-1"));
+
+        // Nothing healthy is left, so the client sticks with the current endpoint.
+        assert!(!pool.fail_over(2, cooldown));
+        assert_eq!(pool.current_url().host_str(), Some("failover-1"));
+
+        // Once the primary recovers it becomes eligible again.
+        pool.endpoints[0].mark_healthy();
+        assert!(pool.fail_over(2, cooldown));
+        assert_eq!(pool.current_url().host_str(), Some("primary"));
+    }
+
+    #[test]
+    fn test_rewrite_keeps_path_and_query() {
+        let pool = pool(1);
+        let mut url = Url::parse("http://primary:8080/v1/accounts/0x1?limit=10").unwrap();
+        pool.endpoints[1].rewrite(&mut url);
+        assert_eq!(url.as_str(), "https://failover-0/v1/accounts/0x1?limit=10");
+    }
+
+    fn mock_ledger_version<'a>(server: &'a MockServer, path: &str, version: u64) -> Mock<'a> {
+        server.mock(|when, then| {
+            when.method(GET).path(path);
+            then.status(200)
+                .header(X_APTOS_LEDGER_VERSION, version.to_string());
+        })
+    }
+
+    fn mock_pool(primary: &MockServer, failover: &MockServer) -> EndpointPool {
+        EndpointPool::new(
+            Url::parse(&primary.base_url()).unwrap(),
+            vec![Url::parse(&failover.base_url()).unwrap()],
+            RetryPolicy::exponential(2).with_initial_backoff(Duration::from_millis(1)),
+        )
+    }
+
+    async fn get(
+        pool: &EndpointPool,
+        primary: &MockServer,
+        path: &str,
+    ) -> Result<Response, RestError> {
+        let client = ReqwestClient::new();
+        let request = client.get(primary.url(path)).build().unwrap();
+        pool.execute(&client, request).await
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_responses_of_one_endpoint_are_not_stale() {
+        let primary = MockServer::start();
+        let failover = MockServer::start();
+        mock_ledger_version(&primary, "/v1/newer", 10);
+        mock_ledger_version(&primary, "/v1/older", 5);
+        let failover_mock = mock_ledger_version(&failover, "/v1/older", 5);
+        let pool = mock_pool(&primary, &failover);
+
+        get(&pool, &primary, "/v1/newer").await.unwrap();
+        // E.g., the response to a request issued concurrently with the previous one, which was
+        // served from an older ledger version but arrived later.
+        get(&pool, &primary, "/v1/older").await.unwrap();
+        assert_eq!(pool.current_url().as_str(), primary.url("/"));
+        assert_eq!(failover_mock.hits(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_behind_another_endpoint_is_stale() {
+        let primary = MockServer::start();
+        let failover = MockServer::start();
+        mock_ledger_version(&primary, "/v1/newer", 10);
+        primary.mock(|when, then| {
+            when.method(GET).path("/v1/data");
+            then.status(500);
+        });
+        let mut failover_mock = mock_ledger_version(&failover, "/v1/data", 5);
+        let pool = mock_pool(&primary, &failover);
+
+        get(&pool, &primary, "/v1/newer").await.unwrap();
+        // The primary fails and the failover is behind the version the primary already served.
+        let error = get(&pool, &primary, "/v1/data").await.unwrap_err();
+        assert!(error.to_string().contains("behind"), "{}", error);
+        assert_eq!(failover_mock.hits(), 2);
+
+        // Once the failover catches up, it serves requests.
+        failover_mock.delete();
+        mock_ledger_version(&failover, "/v1/data", 11);
+        get(&pool, &primary, "/v1/data").await.unwrap();
+        assert_eq!(pool.current_url().as_str(), failover.url("/"));
+    }
+}
@@ -320,6 +320,7 @@ impl CliCommand<()> for InitTool {
                     Url::parse(faucet_url)
                         .map_err(|err| CliError::UnableToParse("rest_url", err.to_string()))?,
                     self.faucet_auth_token.as_deref(),
+                    false,
                     address,
                     NUM_DEFAULT_OCTAS,
                 )
@@ -1507,6 +1507,13 @@ pub struct FaucetOptions {
     /// variable with FAUCET_AUTH_TOKEN.
     #[clap(long, env)]
     faucet_auth_token: Option<String>,
+
+    /// Solve a proof of work challenge from the faucet before funding
+    ///
+    /// Use this with faucets that accept proof of work instead of a captcha or an
+    /// auth token, e.g. for scripts. Solving may take a few seconds.
+    #[clap(long)]
+    faucet_proof_of_work: bool,
 }
 
 impl FaucetOptions {
@@ -1514,6 +1521,7 @@ impl FaucetOptions {
         FaucetOptions {
             faucet_url,
             faucet_auth_token,
+            faucet_proof_of_work: false,
         }
     }
 
@@ -1545,6 +1553,7 @@ impl FaucetOptions {
             rest_client,
             self.faucet_url(profile)?,
             self.faucet_auth_token.as_deref(),
+            self.faucet_proof_of_work,
             address,
             num_octas,
         )
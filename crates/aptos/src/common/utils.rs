@@ -453,6 +453,7 @@ pub async fn fund_account(
     rest_client: Client,
     faucet_url: Url,
     faucet_auth_token: Option<&str>,
+    faucet_proof_of_work: bool,
     address: AccountAddress,
     num_octas: u64,
 ) -> CliTypedResult<()> {
@@ -460,6 +461,9 @@ pub async fn fund_account(
     if let Some(token) = faucet_auth_token {
         client = client.with_auth_token(token.to_string());
     }
+    if faucet_proof_of_work {
+        client = client.with_proof_of_work();
+    }
     client
         .fund(address, num_octas)
         .await
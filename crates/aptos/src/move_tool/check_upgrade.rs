@@ -0,0 +1,390 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            load_account_arg, CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions,
+            RestOptions,
+        },
+        utils::get_feature_flag,
+    },
+    move_tool::{fix_bytecode_version, CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{
+    natives::code::{PackageMetadata, UpgradePolicy},
+    BuildOptions, BuiltPackage, METADATA_FILE_NAME,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::{FeatureFlag, Features},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use colored::Colorize;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::Compatibility,
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::{extension_equals, find_filenames, MOVE_COMPILED_EXTENSION};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_core_types::identifier::IdentStr;
+use move_ir_types::location::Loc;
+use move_package::{
+    compilation::package_layout::CompiledPackageLayout, source_package::layout::SourcePackageLayout,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+/// Checks whether the package can be published as an upgrade of its previous version
+///
+/// The package is compared with the version published on-chain, or with a previous build of it,
+/// and every change which would make publishing the upgrade fail is reported with its location:
+/// removed modules, structs and functions, changed struct layouts, abilities and type
+/// parameters, changed signatures of public and entry functions, removed friend declarations,
+/// and upgrade policies which forbid the upgrade.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account or object the previous version of the package is published at
+    ///
+    /// Defaults to the account of the profile.
+    #[clap(long, value_parser = load_account_arg, conflicts_with = "previous_build")]
+    pub(crate) account: Option<AccountAddress>,
+
+    /// Directory of a previous build of the package to compare with, instead of the version
+    /// published on-chain, e.g. a copy of `build/<package name>` made before the changes
+    #[clap(long, value_parser)]
+    pub(crate) previous_build: Option<PathBuf>,
+
+    /// Whether to allow changes of friend functions and friend declarations, as networks which
+    /// treat friend functions as private do
+    ///
+    /// Defaults to the `TREAT_FRIEND_AS_PRIVATE` feature flag of the network the previous version
+    /// is published on, or to the default value of the flag when comparing with a previous build.
+    #[clap(long)]
+    pub(crate) treat_friend_as_private: Option<bool>,
+
+    /// Artifacts to be generated when building this package.
+    #[clap(long, default_value_t = IncludedArtifacts::Sparse)]
+    pub(crate) included_artifacts: IncludedArtifacts,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// The previous version of a package.
+struct PreviousVersion {
+    /// The upgrade policy, if known.
+    upgrade_policy: Option<UpgradePolicy>,
+    modules: BTreeMap<String, CompiledModule>,
+    /// Whether friend functions are treated as private where the package is published.
+    treat_friend_as_private: bool,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            bytecode_version: fix_bytecode_version(
+                self.move_options.bytecode_version,
+                self.move_options.language_version,
+            ),
+            ..self.included_artifacts.build_options(&self.move_options)?
+        };
+        let pack = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let metadata = pack.extract_metadata()?;
+
+        let previous = match &self.previous_build {
+            Some(path) => read_previous_build(path)?,
+            None => self.fetch_published_version(pack.name()).await?,
+        };
+
+        let treat_friend_as_private = self
+            .treat_friend_as_private
+            .unwrap_or(previous.treat_friend_as_private);
+        let compatibility = Compatibility::new(true, !treat_friend_as_private);
+        let issues = find_incompatibilities(&pack, &metadata, &previous, compatibility);
+        if issues.is_empty() {
+            return Ok("The package is compatible with its previous version");
+        }
+        for (message, location) in &issues {
+            eprintln!(
+                "{} {}\n  {} {}",
+                "error:".red().bold(),
+                message,
+                "-->".blue().bold(),
+                location
+            );
+        }
+        Err(CliError::UnexpectedError(format!(
+            "Found {} incompatible change(s) to the previous version of package `{}`",
+            issues.len(),
+            pack.name()
+        )))
+    }
+}
+
+impl CheckUpgrade {
+    async fn fetch_published_version(&self, package_name: &str) -> CliTypedResult<PreviousVersion> {
+        let account = match self.account {
+            Some(account) => account,
+            None => self.profile_options.account_address()?,
+        };
+        let url = self.rest_options.url(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(url, account, true).await?;
+        let package = registry
+            .get_package(package_name)
+            .await
+            .map_err(|s| CliError::CommandArgumentError(s.to_string()))?;
+        let mut modules = BTreeMap::new();
+        for name in package.module_names() {
+            if let Some(bytecode) = registry.get_bytecode(name).await? {
+                modules.insert(name.to_string(), deserialize_module(name, bytecode)?);
+            }
+        }
+        let client = self.rest_options.client(&self.profile_options)?;
+        Ok(PreviousVersion {
+            upgrade_policy: Some(package.upgrade_policy()),
+            modules,
+            treat_friend_as_private: get_feature_flag(
+                &client,
+                FeatureFlag::TREAT_FRIEND_AS_PRIVATE,
+            )
+            .await?,
+        })
+    }
+}
+
+/// Reads the modules of the package from a directory with the artifacts of a build, and its
+/// upgrade policy if the package metadata has been saved there.
+fn read_previous_build(path: &Path) -> CliTypedResult<PreviousVersion> {
+    let modules_dir = path.join(CompiledPackageLayout::CompiledModules.path());
+    if !modules_dir.is_dir() {
+        return Err(CliError::CommandArgumentError(format!(
+            "No compiled modules found in `{}`",
+            path.display()
+        )));
+    }
+    let mut modules = BTreeMap::new();
+    let files = find_filenames(&[&modules_dir], |file| {
+        extension_equals(file, MOVE_COMPILED_EXTENSION)
+            && file.parent() == Some(modules_dir.as_path())
+    })
+    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+    for file in files {
+        let bytecode = std::fs::read(&file).map_err(|err| CliError::IO(file.clone(), err))?;
+        let module = deserialize_module(&file, &bytecode)?;
+        modules.insert(module.self_id().name().to_string(), module);
+    }
+    let metadata_path = path.join(METADATA_FILE_NAME);
+    let upgrade_policy = if metadata_path.is_file() {
+        let bytes = std::fs::read(&metadata_path)
+            .map_err(|err| CliError::IO(metadata_path.display().to_string(), err))?;
+        let metadata: PackageMetadata =
+            bcs::from_bytes(&bytes).map_err(|err| CliError::BCS("package metadata", err))?;
+        Some(metadata.upgrade_policy)
+    } else {
+        None
+    };
+    Ok(PreviousVersion {
+        upgrade_policy,
+        modules,
+        treat_friend_as_private: Features::default()
+            .is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE),
+    })
+}
+
+fn deserialize_module(name: impl Display, bytecode: &[u8]) -> CliTypedResult<CompiledModule> {
+    CompiledModule::deserialize(bytecode).map_err(|err| {
+        CliError::UnexpectedError(format!("Unable to deserialize module `{}`: {}", name, err))
+    })
+}
+
+/// Returns the messages and source locations of all changes of the built package which are
+/// incompatible with its previous version, following the checks done on publishing.
+fn find_incompatibilities(
+    pack: &BuiltPackage,
+    metadata: &PackageMetadata,
+    previous: &PreviousVersion,
+    compatibility: Compatibility,
+) -> Vec<(String, String)> {
+    let manifest_location = pack
+        .package_path()
+        .join(SourcePackageLayout::Manifest.path())
+        .display()
+        .to_string();
+    let mut issues = vec![];
+    if let Some(old_policy) = previous.upgrade_policy {
+        if old_policy.policy >= UpgradePolicy::immutable().policy {
+            issues.push((
+                "the previous version has upgrade policy `immutable` and cannot be upgraded"
+                    .to_string(),
+                manifest_location.clone(),
+            ));
+        } else if metadata.upgrade_policy.policy < old_policy.policy {
+            issues.push((
+                format!(
+                    "upgrade policy changed from `{}` to the weaker `{}`",
+                    old_policy, metadata.upgrade_policy
+                ),
+                manifest_location.clone(),
+            ));
+        }
+    }
+
+    let new_modules = pack
+        .package
+        .root_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) => Some((
+                module.self_id().name().to_string(),
+                (module, source_map, unit.source_path.as_path()),
+            )),
+            CompiledUnit::Script(_) => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+    for (name, old_module) in &previous.modules {
+        let Some((new_module, source_map, source_path)) = new_modules.get(name) else {
+            issues.push((
+                format!("removed module `{}`", name),
+                manifest_location.clone(),
+            ));
+            continue;
+        };
+        for incompatibility in compatibility.incompatibilities(old_module, new_module) {
+            let loc = incompatibility
+                .item
+                .as_deref()
+                .and_then(|item| item_location(new_module, source_map, item))
+                .unwrap_or(source_map.definition_location);
+            issues.push((
+                format!("module `{}`: {}", name, incompatibility),
+                source_location(source_path, loc),
+            ));
+        }
+    }
+    issues
+}
+
+/// Returns the location of the struct or function with the given name in the module, if it
+/// still exists.
+fn item_location(module: &CompiledModule, source_map: &SourceMap, item: &IdentStr) -> Option<Loc> {
+    if let Some(idx) = module.struct_defs().iter().position(|def| {
+        module.identifier_at(module.struct_handle_at(def.struct_handle).name) == item
+    }) {
+        return source_map
+            .get_struct_source_map(StructDefinitionIndex(idx as u16))
+            .ok()
+            .map(|struct_map| struct_map.definition_location);
+    }
+    let idx = module.function_defs().iter().position(|def| {
+        module.identifier_at(module.function_handle_at(def.function).name) == item
+    })?;
+    source_map
+        .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+        .ok()
+        .map(|function_map| function_map.definition_location)
+}
+
+/// Formats the location as `path:line:column`.
+fn source_location(source_path: &Path, loc: Loc) -> String {
+    let offset = loc.start() as usize;
+    match std::fs::read_to_string(source_path) {
+        Ok(text) if offset <= text.len() => {
+            let before = &text[..offset];
+            let line = before.matches('\n').count() + 1;
+            let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            format!("{}:{}:{}", source_path.display(), line, column)
+        },
+        _ => source_path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CliResult, Tool};
+    use aptos_framework::{BuildOptions, BuiltPackage};
+    use clap::Parser;
+    use std::{fs, path::Path};
+    use tempfile::tempdir;
+
+    const MANIFEST: &str =
+        "[package]\nname = \"Upgrade\"\nversion = \"0.0.0\"\n\n[addresses]\nupgrade = \"0x42\"\n";
+
+    fn write_source(package_dir: &Path, source: &str) {
+        fs::create_dir_all(package_dir.join("sources")).unwrap();
+        fs::write(package_dir.join("sources").join("m.move"), source).unwrap();
+    }
+
+    async fn check_upgrade(package_dir: &Path, previous_build: &Path) -> CliResult {
+        let tool = Tool::try_parse_from([
+            "aptos",
+            "move",
+            "check-upgrade",
+            "--package-dir",
+            package_dir.to_str().unwrap(),
+            "--previous-build",
+            previous_build.to_str().unwrap(),
+        ])
+        .map_err(|msg| msg.to_string())?;
+        tool.execute().await
+    }
+
+    #[tokio::test]
+    async fn test_check_upgrade_with_previous_build() {
+        let package_dir = tempdir().unwrap();
+        let previous_dir = tempdir().unwrap();
+        fs::write(package_dir.path().join("Move.toml"), MANIFEST).unwrap();
+        write_source(
+            package_dir.path(),
+            "module upgrade::m { public fun f(x: u64): u64 { x } }",
+        );
+        BuiltPackage::build(package_dir.path().to_path_buf(), BuildOptions {
+            install_dir: Some(previous_dir.path().to_path_buf()),
+            ..BuildOptions::default()
+        })
+        .unwrap();
+        let previous_build = previous_dir.path().join("build").join("Upgrade");
+
+        // Adding a function is compatible.
+        write_source(
+            package_dir.path(),
+            "module upgrade::m { public fun f(x: u64): u64 { x } public fun g(): u64 { 1 } }",
+        );
+        check_upgrade(package_dir.path(), &previous_build)
+            .await
+            .unwrap();
+
+        // Changing the signature of a public function is not.
+        write_source(
+            package_dir.path(),
+            "module upgrade::m { public fun f(x: u64, y: u64): u64 { x + y } }",
+        );
+        let error = check_upgrade(package_dir.path(), &previous_build)
+            .await
+            .unwrap_err();
+        assert!(
+            error.contains("Found 1 incompatible change(s)"),
+            "unexpected error: {}",
+            error
+        );
+    }
+}
@@ -15,6 +15,14 @@ use std::{collections::BTreeMap, path::PathBuf};
 
 /// Run a Lint tool to show additional warnings about the current package, in addition to ordinary
 /// warnings and/or errors generated by the Move 2 compiler.
+///
+/// The levels of lint checks can be set in the `[lints]` section of `Move.toml`, e.g.
+/// `while_true = "deny"`. Packages can also define their own rules in `[lint-rules]`, which
+/// report the code matching a pattern outside of the functions allowed to contain it, e.g.
+/// `[lint-rules.signer_in_constructors]` with `message = "..."`,
+/// `pattern = "call(aptos_framework::object::generate_signer)"` and
+/// `allowed_in = ["*::*::create_*"]`. Patterns can also match storage operations, loops and
+/// aborts, and be combined, as in `pattern = "inside(borrow_global_mut(0x1::m::*), loop)"`.
 #[derive(Debug, Clone, Parser)]
 pub struct LintPackage {
     /// Path to a move package (the folder with a Move.toml file).  Defaults to current directory.
@@ -20,10 +20,12 @@ use crate::{
     governance::CompileScriptFunction,
     move_tool::{
         bytecode::{Decompile, Disassemble},
+        check_upgrade::CheckUpgrade,
         coverage::SummaryCoverage,
         fmt::Fmt,
         lint::LintPackage,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
+        mutate::MutatePackage,
     },
     CliCommand, CliResult,
 };
@@ -34,6 +36,7 @@ use aptos_framework::{
         chunk_package_and_create_payloads, large_packages_cleanup_staging_area, PublishType,
         LARGE_PACKAGES_MODULE_ADDRESS,
     },
+    compiler_build,
     docgen::DocgenOptions,
     extended_checks,
     natives::code::UpgradePolicy,
@@ -80,10 +83,12 @@ use url::Url;
 
 pub mod aptos_debug_natives;
 mod bytecode;
+mod check_upgrade;
 pub mod coverage;
 mod fmt;
 mod lint;
 mod manifest;
+mod mutate;
 pub mod package_hooks;
 mod show;
 pub mod stored_package;
@@ -100,6 +105,7 @@ const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(CheckUpgrade),
     Clean(CleanPackage),
     ClearStagingArea(ClearStagingArea),
     #[clap(alias = "build")]
@@ -121,6 +127,7 @@ pub enum MoveTool {
     Init(InitPackage),
     Lint(LintPackage),
     List(ListPackage),
+    Mutate(MutatePackage),
     Prove(ProvePackage),
     #[clap(alias = "deploy")]
     Publish(PublishPackage),
@@ -129,6 +136,7 @@ pub enum MoveTool {
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
     Test(TestPackage),
+    UpdateDeps(UpdateDependencies),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
@@ -139,6 +147,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::ClearStagingArea(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
@@ -159,12 +168,14 @@ impl MoveTool {
             MoveTool::Download(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
+            MoveTool::Mutate(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::RunScript(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::UpdateDeps(tool) => tool.execute_serialized().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
@@ -548,6 +559,7 @@ impl CliCommand<&'static str> for TestPackage {
             full_model_generation: self.move_options.check_test_code,
             install_dir: self.move_options.output_dir.clone(),
             skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            compiler_build: compiler_build(),
             compiler_config: CompilerConfig {
                 known_attributes: known_attributes.clone(),
                 skip_attribute_checks: self.move_options.skip_attribute_checks,
@@ -2006,6 +2018,42 @@ impl CliCommand<&'static str> for CleanPackage {
     }
 }
 
+/// Updates the commits that git dependencies are locked to
+///
+/// Builds use the commits recorded in the package's `Move.lock` for its git dependencies,
+/// even if their revisions are branches. This resolves every git dependency of the package,
+/// including dev dependencies, to the latest state of its revision and records the new commits
+/// in `Move.lock`.
+#[derive(Parser)]
+pub struct UpdateDependencies {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<BTreeMap<String, String>> for UpdateDependencies {
+    fn command_name(&self) -> &'static str {
+        "UpdateDependencies"
+    }
+
+    async fn execute(self) -> CliTypedResult<BTreeMap<String, String>> {
+        let config = BuildConfig {
+            dev_mode: true,
+            additional_named_addresses: self.move_options.named_addresses(),
+            ..Default::default()
+        };
+        let path = self.move_options.get_package_path()?;
+        let lock_file = config
+            .update_lock_file_for_package(&path, &mut std::io::stderr())
+            .map_err(|e| CliError::UnexpectedError(format!("{:#}", e)))?;
+        Ok(lock_file
+            .dependencies
+            .into_iter()
+            .map(|(name, locked_dep)| (name.to_string(), locked_dep.commit))
+            .collect())
+    }
+}
+
 /// Run a Move function
 #[derive(Parser)]
 pub struct RunFunction {
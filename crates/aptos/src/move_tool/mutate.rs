@@ -0,0 +1,642 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::extended_checks;
+use async_trait::async_trait;
+use clap::Parser;
+use move_command_line_common::env::MOVE_BUILD_CACHE_ENV_VAR;
+use move_compiler_v2::run_checker;
+use move_model::{
+    ast::{ExpData, Operation, Value},
+    metadata::CompilerVersion,
+    model::{GlobalEnv, Loc},
+};
+use move_package::{
+    compilation::{model_builder::ModelBuilder, package_layout::CompiledPackageLayout},
+    source_package::layout::SourcePackageLayout,
+    BuildConfig, CompilerConfig, ModelConfig,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use tempfile::TempDir;
+
+/// Runs mutation testing for a package
+///
+/// Mutants of the package are generated by changing one operator, constant or condition at a
+/// time in its functions, e.g. `a + b` to `a - b`, `1` to `0`, or `if (c)` to `if (!(c))`. The
+/// unit tests of the package are run against each mutant, in parallel. A mutant for which all
+/// tests still pass survives, and points to behavior which is not checked by the tests.
+/// Surviving mutants are reported per function. Test-only code is not mutated.
+#[derive(Parser)]
+pub struct MutatePackage {
+    /// Only mutate functions whose name, e.g. `coin::transfer`, contains this string
+    #[clap(long)]
+    pub(crate) function: Option<String>,
+
+    /// A filter string to determine which unit tests to run against the mutants
+    #[clap(long, short)]
+    pub(crate) filter: Option<String>,
+
+    /// The number of mutants to test in parallel
+    ///
+    /// Defaults to the number of available CPUs.
+    #[clap(long, short)]
+    pub(crate) jobs: Option<usize>,
+
+    /// The maximum number of instructions that can be executed by a test
+    ///
+    /// This also stops tests which loop forever because of a mutation.
+    #[clap(
+        name = "instructions",
+        default_value_t = 100000,
+        short = 'i',
+        long = "instructions"
+    )]
+    pub(crate) instruction_execution_bound: u64,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+/// A change of the source of a package.
+struct Mutant {
+    /// The name of the function the change is in.
+    function: String,
+    /// The source file, relative to the package directory.
+    file: PathBuf,
+    /// The byte offsets of the changed source text.
+    start: usize,
+    end: usize,
+    original: String,
+    replacement: String,
+    line: usize,
+    column: usize,
+}
+
+impl Mutant {
+    fn describe(&self) -> String {
+        format!(
+            "{}:{}:{}: `{}` -> `{}`",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.original,
+            self.replacement
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum MutantOutcome {
+    /// At least one test fails for the mutant.
+    Killed,
+    /// All tests pass for the mutant.
+    Survived,
+    /// The mutant does not compile.
+    Invalid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MutationTestSummary {
+    pub mutants: usize,
+    pub killed: usize,
+    pub survived: usize,
+    /// Mutants which do not compile, and are therefore neither killed nor surviving.
+    pub invalid: usize,
+    /// The surviving mutants, by function.
+    pub surviving_mutants: BTreeMap<String, Vec<String>>,
+}
+
+#[async_trait]
+impl CliCommand<MutationTestSummary> for MutatePackage {
+    fn command_name(&self) -> &'static str {
+        "MutatePackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<MutationTestSummary> {
+        let package_path = SourcePackageLayout::try_find_root(
+            &self
+                .move_options
+                .get_package_path()?
+                .canonicalize()
+                .map_err(|err| CliError::IO("package directory".to_string(), err))?,
+        )
+        .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+        let env = self.build_model(&package_path)?;
+        let mutants = generate_mutants(&env, &package_path, self.function.as_deref());
+        if mutants.is_empty() {
+            return Err(CliError::UnexpectedError(
+                "No mutants could be generated for the package".to_string(),
+            ));
+        }
+
+        let baseline = PackageCopy::new(&package_path)?;
+        let output = self.run_tests(baseline.path())?;
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+            eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+            return Err(CliError::UnexpectedError(
+                "The unit tests of the package must pass before mutating it".to_string(),
+            ));
+        }
+        drop(baseline);
+
+        eprintln!("Testing {} mutants", mutants.len());
+        let outcomes = self.test_mutants(&package_path, &mutants)?;
+
+        let mut summary = MutationTestSummary {
+            mutants: mutants.len(),
+            killed: 0,
+            survived: 0,
+            invalid: 0,
+            surviving_mutants: BTreeMap::new(),
+        };
+        for (mutant, outcome) in mutants.iter().zip(outcomes) {
+            match outcome {
+                MutantOutcome::Killed => summary.killed += 1,
+                MutantOutcome::Invalid => summary.invalid += 1,
+                MutantOutcome::Survived => {
+                    summary.survived += 1;
+                    summary
+                        .surviving_mutants
+                        .entry(mutant.function.clone())
+                        .or_default()
+                        .push(mutant.describe());
+                },
+            }
+        }
+        Ok(summary)
+    }
+}
+
+impl MutatePackage {
+    /// Builds the model of the package before any rewriting, so that expressions still
+    /// correspond to their source text.
+    fn build_model(&self, package_path: &Path) -> CliTypedResult<GlobalEnv> {
+        let language_version = self.move_options.language_version.unwrap_or_default();
+        let config = BuildConfig {
+            dev_mode: self.move_options.dev,
+            additional_named_addresses: self.move_options.named_addresses(),
+            test_mode: true,
+            skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            compiler_config: CompilerConfig {
+                known_attributes: extended_checks::get_all_attribute_names().clone(),
+                skip_attribute_checks: self.move_options.skip_attribute_checks,
+                compiler_version: Some(CompilerVersion::V2_0),
+                language_version: Some(language_version),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let resolution_graph = config
+            .resolution_graph_for_package(package_path, &mut Vec::new())
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let mut options = ModelBuilder::create(resolution_graph, ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+            compiler_version: CompilerVersion::V2_0,
+            language_version,
+        })
+        .v2_compiler_options()
+        .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        options.compile_test_code = true;
+        let env =
+            run_checker(options).map_err(|err| CliError::MoveCompilationError(err.to_string()))?;
+        if env.has_errors() {
+            return Err(CliError::MoveCompilationError(
+                "The package does not compile, see `aptos move compile`".to_string(),
+            ));
+        }
+        Ok(env)
+    }
+
+    /// Tests all mutants, each worker in its own copy of the package.
+    fn test_mutants(
+        &self,
+        package_path: &Path,
+        mutants: &[Mutant],
+    ) -> CliTypedResult<Vec<MutantOutcome>> {
+        let jobs = self
+            .jobs
+            .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+            .clamp(1, mutants.len());
+        let next = AtomicUsize::new(0);
+        let outcomes = Mutex::new(vec![MutantOutcome::Invalid; mutants.len()]);
+        std::thread::scope(|scope| {
+            let (next, outcomes) = (&next, &outcomes);
+            let workers = (0..jobs)
+                .map(|_| {
+                    scope.spawn(move || -> CliTypedResult<()> {
+                        let copy = PackageCopy::new(package_path)?;
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let Some(mutant) = mutants.get(index) else {
+                                return Ok(());
+                            };
+                            let outcome = self.test_mutant(&copy, mutant)?;
+                            eprintln!(
+                                "[{}/{}] {}: {:?}",
+                                index + 1,
+                                mutants.len(),
+                                mutant.describe(),
+                                outcome
+                            );
+                            outcomes.lock().unwrap()[index] = outcome;
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().expect("mutation worker panicked"))
+        })?;
+        Ok(outcomes.into_inner().unwrap())
+    }
+
+    fn test_mutant(&self, copy: &PackageCopy, mutant: &Mutant) -> CliTypedResult<MutantOutcome> {
+        let path = copy.path().join(&mutant.file);
+        let source = std::fs::read_to_string(&path)
+            .map_err(|err| CliError::IO(path.display().to_string(), err))?;
+        let mut mutated = source.clone();
+        mutated.replace_range(mutant.start..mutant.end, &mutant.replacement);
+        std::fs::write(&path, mutated)
+            .map_err(|err| CliError::IO(path.display().to_string(), err))?;
+        let output = self.run_tests(copy.path());
+        std::fs::write(&path, source)
+            .map_err(|err| CliError::IO(path.display().to_string(), err))?;
+        let output = output?;
+        Ok(if output.status.success() {
+            MutantOutcome::Survived
+        } else if String::from_utf8_lossy(&output.stdout)
+            .contains(&CliError::MoveTestError.to_string())
+        {
+            MutantOutcome::Killed
+        } else {
+            MutantOutcome::Invalid
+        })
+    }
+
+    /// Runs the unit tests of the package in `package_dir` in a separate process, as compilation
+    /// errors of mutants may end the process.
+    fn run_tests(&self, package_dir: &Path) -> CliTypedResult<Output> {
+        let exe = std::env::current_exe()
+            .map_err(|err| CliError::UnexpectedError(format!("Unable to find the CLI: {}", err)))?;
+        let mut command = Command::new(exe);
+        command
+            .args(["move", "test", "--skip-fetch-latest-git-deps"])
+            .arg("--package-dir")
+            .arg(package_dir)
+            .arg("--instructions")
+            .arg(self.instruction_execution_bound.to_string())
+            // Mutants only change the package itself, so its dependencies are compiled once.
+            .env(MOVE_BUILD_CACHE_ENV_VAR, "1");
+        let options = &self.move_options;
+        if let Some(filter) = &self.filter {
+            command.arg("--filter").arg(filter);
+        }
+        if options.dev {
+            command.arg("--dev");
+        }
+        if options.skip_attribute_checks {
+            command.arg("--skip-attribute-checks");
+        }
+        if options.check_test_code {
+            command.arg("--check-test-code");
+        }
+        if !options.named_addresses.is_empty() {
+            let named_addresses = options
+                .named_addresses()
+                .iter()
+                .map(|(name, address)| format!("{}={}", name, address.to_hex_literal()))
+                .collect::<Vec<_>>()
+                .join(",");
+            command.arg("--named-addresses").arg(named_addresses);
+        }
+        if let Some(override_std) = &options.override_std {
+            command.arg("--override-std").arg(override_std.to_string());
+        }
+        if let Some(bytecode_version) = options.bytecode_version {
+            command
+                .arg("--bytecode-version")
+                .arg(bytecode_version.to_string());
+        }
+        if let Some(compiler_version) = options.compiler_version {
+            command
+                .arg("--compiler-version")
+                .arg(compiler_version.to_string());
+        }
+        if let Some(language_version) = options.language_version {
+            command
+                .arg("--language-version")
+                .arg(language_version.to_string());
+        }
+        command
+            .output()
+            .map_err(|err| CliError::UnexpectedError(format!("Unable to run tests: {}", err)))
+    }
+}
+
+/// A copy of a package in a temporary directory, in which mutants can be tested without
+/// changing the package itself.
+struct PackageCopy {
+    dir: TempDir,
+}
+
+impl PackageCopy {
+    fn new(package_path: &Path) -> CliTypedResult<Self> {
+        let dir = TempDir::new().map_err(|err| CliError::IO("temporary directory".into(), err))?;
+        copy_dir(package_path, dir.path(), true)
+            .map_err(|err| CliError::IO(package_path.display().to_string(), err))?;
+        // Local dependencies are relative to the original location of the package.
+        let manifest_path = dir.path().join(SourcePackageLayout::Manifest.path());
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .map_err(|err| CliError::IO(manifest_path.display().to_string(), err))?;
+        let mut manifest: toml::Value = toml::from_str(&manifest).map_err(|err| {
+            CliError::UnableToParse("Move.toml", format!("Invalid manifest: {}", err))
+        })?;
+        for section in ["dependencies", "dev-dependencies"] {
+            let Some(deps) = manifest
+                .get_mut(section)
+                .and_then(toml::Value::as_table_mut)
+            else {
+                continue;
+            };
+            for dep in deps.values_mut() {
+                if let Some(local) = dep.get_mut("local") {
+                    if let Some(local_path) = local.as_str().map(|path| package_path.join(path)) {
+                        *local = toml::Value::String(local_path.display().to_string());
+                    }
+                }
+            }
+        }
+        std::fs::write(&manifest_path, manifest.to_string())
+            .map_err(|err| CliError::IO(manifest_path.display().to_string(), err))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+/// Copies the directory recursively, except for the build output of the package.
+fn copy_dir(from: &Path, to: &Path, is_package_root: bool) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            if !(is_package_root
+                && name.as_os_str() == CompiledPackageLayout::Root.path().as_os_str())
+            {
+                copy_dir(&entry.path(), &to.join(&name), false)?;
+            }
+        } else {
+            std::fs::copy(entry.path(), to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Generates the mutants of all functions of the package which are not test-only.
+fn generate_mutants(
+    env: &GlobalEnv,
+    package_path: &Path,
+    function_filter: Option<&str>,
+) -> Vec<Mutant> {
+    let mut mutants = vec![];
+    for module in env.get_modules() {
+        if !module.is_primary_target() || module.is_test_only() {
+            continue;
+        }
+        let file_id = module.get_loc().file_id();
+        let Some(file) = Path::new(env.get_file(file_id))
+            .canonicalize()
+            .ok()
+            .and_then(|path| Some(path.strip_prefix(package_path).ok()?.to_path_buf()))
+        else {
+            continue;
+        };
+        let source = env.get_file_source(file_id);
+        for fun in module.get_functions() {
+            let name = fun.get_full_name_str();
+            if fun.is_test_only() || function_filter.map_or(false, |filter| !name.contains(filter))
+            {
+                continue;
+            }
+            let Some(def) = fun.get_def() else {
+                continue;
+            };
+            // The same source text may be reached through several expressions.
+            let mut changes = BTreeSet::new();
+            def.visit_pre_order(&mut |exp| {
+                changes.extend(mutations(env, source, exp));
+                true
+            });
+            for (start, end, replacement) in changes {
+                let before = &source[..start];
+                mutants.push(Mutant {
+                    function: name.clone(),
+                    file: file.clone(),
+                    start,
+                    end,
+                    original: source[start..end].to_string(),
+                    replacement,
+                    line: before.matches('\n').count() + 1,
+                    column: start - before.rfind('\n').map_or(0, |i| i + 1) + 1,
+                });
+            }
+        }
+    }
+    mutants
+}
+
+/// Returns the byte range and replacement text of the mutations of the expression, but not of
+/// its subexpressions.
+fn mutations(env: &GlobalEnv, source: &str, exp: &ExpData) -> Vec<(usize, usize, String)> {
+    let source_range = |loc: &Loc| {
+        let (start, end) = (loc.span().start().0 as usize, loc.span().end().0 as usize);
+        (start <= end && end <= source.len()).then_some((start, end))
+    };
+    match exp {
+        ExpData::Call(_, op, args) if args.len() == 2 => {
+            let Some((symbol, replacements)) = operator_mutations(op) else {
+                return vec![];
+            };
+            let (Some((_, left_end)), Some((right_start, _))) = (
+                source_range(&env.get_node_loc(args[0].node_id())),
+                source_range(&env.get_node_loc(args[1].node_id())),
+            ) else {
+                return vec![];
+            };
+            if left_end > right_start {
+                return vec![];
+            }
+            // Only the operator and parentheses around the operands may be between them,
+            // otherwise the expression is not a binary operation in the source, as for `+=`.
+            let between = &source[left_end..right_start];
+            let after_left = between.trim_start_matches(|c: char| c.is_whitespace() || c == ')');
+            if after_left.trim_end_matches(|c: char| c.is_whitespace() || c == '(') != symbol {
+                return vec![];
+            }
+            let start = right_start - after_left.len();
+            replacements
+                .iter()
+                .map(|replacement| (start, start + symbol.len(), replacement.to_string()))
+                .collect()
+        },
+        ExpData::Value(id, Value::Number(_)) => {
+            let Some((start, end)) = source_range(&env.get_node_loc(*id)) else {
+                return vec![];
+            };
+            let text = &source[start..end];
+            // Named constants are inlined, and only literals are mutated.
+            if !text.starts_with(|c: char| c.is_ascii_digit()) {
+                return vec![];
+            }
+            let (digits, suffix) = text.split_at(text.find('u').unwrap_or(text.len()));
+            if !digits
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                || !["", "u8", "u16", "u32", "u64", "u128", "u256"].contains(&suffix)
+            {
+                return vec![];
+            }
+            let is_zero = digits
+                .trim_start_matches("0x")
+                .chars()
+                .all(|c| c == '0' || c == '_');
+            let replacement = if is_zero { "1" } else { "0" };
+            vec![(start, end, format!("{}{}", replacement, suffix))]
+        },
+        ExpData::Value(id, Value::Bool(_)) => {
+            let Some((start, end)) = source_range(&env.get_node_loc(*id)) else {
+                return vec![];
+            };
+            match &source[start..end] {
+                "true" => vec![(start, end, "false".to_string())],
+                "false" => vec![(start, end, "true".to_string())],
+                _ => vec![],
+            }
+        },
+        ExpData::IfElse(_, cond, _, _) => {
+            let Some((start, end)) = source_range(&env.get_node_loc(cond.node_id())) else {
+                return vec![];
+            };
+            if start == end {
+                return vec![];
+            }
+            vec![(start, end, format!("!({})", &source[start..end]))]
+        },
+        _ => vec![],
+    }
+}
+
+/// Returns the source symbol of the operator and the operators it is replaced with.
+fn operator_mutations(op: &Operation) -> Option<(&'static str, &'static [&'static str])> {
+    use Operation::*;
+    Some(match op {
+        Add => ("+", &["-"]),
+        Sub => ("-", &["+"]),
+        Mul => ("*", &["/"]),
+        Div => ("/", &["*"]),
+        Mod => ("%", &["*"]),
+        BitAnd => ("&", &["|"]),
+        BitOr => ("|", &["&"]),
+        Xor => ("^", &["|"]),
+        Shl => ("<<", &[">>"]),
+        Shr => (">>", &["<<"]),
+        Lt => ("<", &["<=", ">="]),
+        Le => ("<=", &["<", ">"]),
+        Gt => (">", &[">=", "<="]),
+        Ge => (">=", &[">", "<"]),
+        Eq => ("==", &["!="]),
+        Neq => ("!=", &["=="]),
+        And => ("&&", &["||"]),
+        Or => ("||", &["&&"]),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_mutants, operator_mutations};
+    use move_compiler_v2::{run_checker, Options};
+    use move_model::{ast::Operation, metadata::LanguageVersion};
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Returns the descriptions of the mutants of a package with the single source file `m.move`.
+    fn mutants(source: &str) -> Vec<String> {
+        let dir = tempdir().unwrap();
+        let package_path = dir.path().canonicalize().unwrap();
+        let file = package_path.join("m.move");
+        fs::write(&file, source).unwrap();
+        let env = run_checker(Options {
+            sources: vec![file.display().to_string()],
+            language_version: Some(LanguageVersion::V2_1),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!env.has_errors());
+        generate_mutants(&env, &package_path, None)
+            .iter()
+            .map(|mutant| mutant.describe())
+            .collect()
+    }
+
+    #[test]
+    fn test_mutations() {
+        let source = "module 0x42::m {
+    fun f(x: u64, y: u64): u64 {
+        x += 1;
+        (x + 1) * (y - 2)
+    }
+
+    fun g(b: bool): bool {
+        if (b && true) false else b
+    }
+
+    #[test]
+    fun test_f() {
+        assert!(f(1, 2) == 0, 0);
+    }
+}
+";
+        assert_eq!(mutants(source), vec![
+            // `+=` is not an operator in the source, only its operand is mutated.
+            "m.move:3:14: `1` -> `0`",
+            // Operators between parenthesized operands are found.
+            "m.move:4:12: `+` -> `-`",
+            "m.move:4:14: `1` -> `0`",
+            "m.move:4:17: `*` -> `/`",
+            "m.move:4:22: `-` -> `+`",
+            "m.move:4:24: `2` -> `0`",
+            "m.move:8:13: `b && true` -> `!(b && true)`",
+            "m.move:8:15: `&&` -> `||`",
+            "m.move:8:18: `true` -> `false`",
+            "m.move:8:24: `false` -> `true`",
+        ]);
+    }
+
+    #[test]
+    fn test_operator_mutations() {
+        assert_eq!(operator_mutations(&Operation::Add), Some(("+", &["-"][..])));
+        assert_eq!(
+            operator_mutations(&Operation::Lt),
+            Some(("<", &["<=", ">="][..]))
+        );
+        assert_eq!(operator_mutations(&Operation::Not), None);
+        assert_eq!(operator_mutations(&Operation::Cast), None);
+    }
+}
@@ -1,11 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Result};
+use super::analyze_validators::AnalyzeValidators;
+use anyhow::{anyhow, ensure, Result};
 use aptos_rest_client::{
     aptos_api_types::{IdentifierWrapper, MoveResource, WriteSetChange},
     Client as RestClient, Transaction, VersionedNewBlockEvent,
 };
+use aptos_storage_interface::DbReader;
 use aptos_types::account_address::AccountAddress;
 use std::str::FromStr;
 
@@ -334,4 +336,72 @@ impl FetchMetadata {
             }
         }
     }
+
+    /// Fetch epochs and their validator sets from DB, for the same range of epochs as
+    /// `fetch_new_block_events`.
+    pub fn fetch_new_block_events_from_db(
+        aptos_db: &dyn DbReader,
+        start_epoch: Option<i64>,
+        end_epoch: Option<i64>,
+    ) -> Result<Vec<EpochInfo>> {
+        let latest_epoch = aptos_db
+            .get_latest_ledger_info()?
+            .ledger_info()
+            .next_block_epoch();
+        let resolve = |epoch: i64| {
+            if epoch < 0 {
+                latest_epoch as i64 + epoch + 1
+            } else {
+                epoch
+            }
+        };
+        let wanted_start_epoch = std::cmp::max(resolve(start_epoch.unwrap_or(2)), 2) as u64;
+        let wanted_end_epoch = std::cmp::min(
+            latest_epoch + 1,
+            std::cmp::max(2, resolve(end_epoch.unwrap_or(i64::MAX))) as u64,
+        );
+        println!(
+            "Fetching from DB, wanting epochs [{}, {}), last epoch: {}",
+            wanted_start_epoch, wanted_end_epoch, latest_epoch,
+        );
+        let mut result = vec![];
+        if wanted_start_epoch >= wanted_end_epoch {
+            return Ok(result);
+        }
+
+        // The validator set of an epoch is in the ledger info ending the previous epoch.
+        let proof =
+            aptos_db.get_epoch_ending_ledger_infos(wanted_start_epoch - 1, wanted_end_epoch - 1)?;
+        ensure!(
+            !proof.more,
+            "Too many epochs to fetch from DB at once, use a smaller range than [{}, {})",
+            wanted_start_epoch,
+            wanted_end_epoch
+        );
+        for ledger_info in proof.ledger_info_with_sigs {
+            let epoch_state = ledger_info
+                .ledger_info()
+                .next_epoch_state()
+                .ok_or_else(|| anyhow!("Epoch ending ledger info without next epoch state"))?;
+            let validators = epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .enumerate()
+                .map(|(index, address)| ValidatorInfo {
+                    address,
+                    voting_power: epoch_state.verifier.get_voting_power(&address).unwrap_or(0),
+                    validator_index: index as u16,
+                })
+                .collect();
+            let mut blocks = AnalyzeValidators::fetch_epoch(epoch_state.epoch, aptos_db)?;
+            blocks.reverse();
+            result.push(EpochInfo {
+                epoch: epoch_state.epoch,
+                blocks,
+                validators,
+                partial: epoch_state.epoch == latest_epoch,
+            });
+        }
+        Ok(result)
+    }
 }
@@ -0,0 +1,589 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::fetch_metadata::{EpochInfo, ValidatorInfo};
+use anyhow::{bail, Result};
+use aptos_bitvec::BitVec;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    on_chain_config::{LeaderReputationType, ProposerAndVoterConfig, ProposerElectionType},
+};
+use std::collections::HashMap;
+
+/// Leader reputation parameters to evaluate, as found in the on-chain consensus config.
+#[derive(Clone, Debug)]
+pub struct ReputationConfig {
+    pub exclude_round: u64,
+    pub leader_reputation_type: LeaderReputationType,
+}
+
+impl ReputationConfig {
+    pub fn new(proposer_election_type: &ProposerElectionType, exclude_round: u64) -> Result<Self> {
+        match proposer_election_type {
+            ProposerElectionType::LeaderReputation(leader_reputation_type) => Ok(Self {
+                exclude_round,
+                leader_reputation_type: leader_reputation_type.clone(),
+            }),
+            other => bail!(
+                "Only leader reputation based proposer election can be analyzed, got {:?}",
+                other
+            ),
+        }
+    }
+
+    fn params(&self) -> &ProposerAndVoterConfig {
+        match &self.leader_reputation_type {
+            LeaderReputationType::ProposerAndVoter(config)
+            | LeaderReputationType::ProposerAndVoterV2(config) => config,
+        }
+    }
+
+    /// Number of epochs before the analyzed one whose history feeds into the reputation.
+    pub fn history_epochs(&self) -> u64 {
+        self.params().use_history_from_previous_epoch_max_count as u64
+    }
+}
+
+/// Which weight the heuristic assigned to a validator, and therefore why.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ReputationBucket {
+    /// Proposed or voted within the window, with failures at or below the threshold.
+    Active,
+    /// Neither proposed nor voted within the window.
+    Inactive,
+    /// Failed proposal rate within the proposer window is above the threshold.
+    Failed,
+}
+
+/// Votes and proposals of a validator within the reputation windows.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WindowCounts {
+    pub votes: u32,
+    pub proposals: u32,
+    pub failed_proposals: u32,
+}
+
+/// Reputation of a single validator, as used to elect the proposer of a single round.
+#[derive(Clone, Copy, Debug)]
+pub struct ValidatorReputation {
+    pub counts: WindowCounts,
+    pub bucket: ReputationBucket,
+    pub weight: u64,
+    pub election_probability: f64,
+}
+
+/// Reputation of all validators for electing the proposer of a round.
+#[derive(Clone, Debug)]
+pub struct ReputationSnapshot {
+    pub epoch: u64,
+    pub round: u64,
+    /// In the order of the validators of the epoch.
+    pub validators: Vec<ValidatorReputation>,
+}
+
+/// How a validator's reputation evolved over an epoch.
+#[derive(Clone, Debug)]
+pub struct ValidatorReputationSummary {
+    pub address: AccountAddress,
+    pub voting_power: u64,
+    /// Election probability averaged over the sampled rounds.
+    pub average_election_probability: f64,
+    /// Number of sampled rounds spent in each bucket.
+    pub bucket_rounds: HashMap<ReputationBucket, u32>,
+    /// Reputation at the last sampled round of the epoch.
+    pub last: ValidatorReputation,
+}
+
+impl ValidatorReputationSummary {
+    pub fn bucket_fraction(&self, bucket: ReputationBucket) -> f64 {
+        let total: u32 = self.bucket_rounds.values().sum();
+        if total == 0 {
+            0.0
+        } else {
+            *self.bucket_rounds.get(&bucket).unwrap_or(&0) as f64 / total as f64
+        }
+    }
+}
+
+/// Reputation over a whole epoch.
+#[derive(Clone, Debug)]
+pub struct EpochReputation {
+    pub epoch: u64,
+    pub partial: bool,
+    pub sampled_rounds: usize,
+    /// Rounds in the epoch, including failed ones.
+    pub total_rounds: u64,
+    pub validators: Vec<ValidatorReputationSummary>,
+}
+
+impl EpochReputation {
+    /// Number of rounds the validator is expected to be elected for, given its
+    /// average election probability.
+    pub fn expected_elections(&self, validator: &ValidatorReputationSummary) -> f64 {
+        validator.average_election_probability * self.total_rounds as f64
+    }
+}
+
+/// Recomputes the leader reputation of `ProposerAndVoterHeuristic` in consensus
+/// (`consensus/src/liveness/leader_reputation.rs`) over committed history.
+///
+/// The same history can be replayed with different parameters to evaluate a
+/// change to the reputation config before proposing it.
+///
+/// Election probabilities are exact, but which validator was actually elected
+/// additionally depends on the accumulator root hash, which is not recomputed.
+pub struct LeaderReputationSimulator {
+    config: ReputationConfig,
+    sample_interval: usize,
+}
+
+impl LeaderReputationSimulator {
+    /// Reputation is evaluated every `sample_interval` blocks, as well as for the
+    /// last block of each epoch.
+    pub fn new(config: ReputationConfig, sample_interval: usize) -> Self {
+        Self {
+            config,
+            sample_interval: std::cmp::max(sample_interval, 1),
+        }
+    }
+
+    /// Replays the given epochs, which need to be consecutive and sorted.
+    /// Epochs without enough preceding epochs in `epochs` are evaluated with
+    /// truncated history, so callers should pass `history_epochs()` more epochs
+    /// than they intend to look at.
+    pub fn simulate(&self, epochs: &[EpochInfo]) -> Vec<EpochReputation> {
+        let all_blocks: Vec<&NewBlockEvent> = epochs
+            .iter()
+            .flat_map(|epoch| epoch.blocks.iter().map(|block| &block.event))
+            .collect();
+        let epoch_to_validators: HashMap<u64, &[ValidatorInfo]> = epochs
+            .iter()
+            .map(|epoch| (epoch.epoch, epoch.validators.as_slice()))
+            .collect();
+
+        epochs
+            .iter()
+            .filter(|epoch| !epoch.blocks.is_empty() && !epoch.validators.is_empty())
+            .map(|epoch_info| {
+                let snapshots: Vec<ReputationSnapshot> = epoch_info
+                    .blocks
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        index % self.sample_interval == 0 || index + 1 == epoch_info.blocks.len()
+                    })
+                    .map(|(_, block)| {
+                        self.snapshot(
+                            &all_blocks,
+                            &epoch_to_validators,
+                            epoch_info.epoch,
+                            block.event.round(),
+                        )
+                    })
+                    .collect();
+                Self::summarize(epoch_info, &snapshots)
+            })
+            .collect()
+    }
+
+    /// Computes the reputation used to elect the proposer of `round` in `epoch`.
+    /// `all_blocks` is the committed history in ascending order.
+    pub fn snapshot(
+        &self,
+        all_blocks: &[&NewBlockEvent],
+        epoch_to_validators: &HashMap<u64, &[ValidatorInfo]>,
+        epoch: u64,
+        round: u64,
+    ) -> ReputationSnapshot {
+        let params = self.config.params();
+        let validators = epoch_to_validators[&epoch];
+
+        // Mirrors EpochManager::extract_epoch_proposers.
+        let first_epoch_to_consider = std::cmp::max(
+            if epoch == 1 { 1 } else { 2 },
+            epoch.saturating_sub(self.config.history_epochs()),
+        );
+        let candidates: HashMap<u64, &[ValidatorInfo]> = epoch_to_validators
+            .iter()
+            .filter(|(e, _)| (first_epoch_to_consider..=epoch).contains(*e))
+            .map(|(e, v)| (*e, *v))
+            .collect();
+
+        let proposer_window_size =
+            validators.len() * params.proposer_window_num_validators_multiplier;
+        let voter_window_size = validators.len() * params.voter_window_num_validators_multiplier;
+        let target = (epoch, round.saturating_sub(self.config.exclude_round));
+        let end = all_blocks.partition_point(|block| (block.epoch(), block.round()) <= target);
+        let history = &all_blocks
+            [end.saturating_sub(std::cmp::max(proposer_window_size, voter_window_size))..end];
+        let from_stale_end = self
+            .config
+            .leader_reputation_type
+            .use_reputation_window_from_stale_end();
+
+        let votes = count_votes(
+            window(history, voter_window_size, from_stale_end),
+            &candidates,
+        );
+        let (proposals, failed_proposals) = count_proposals(
+            window(history, proposer_window_size, from_stale_end),
+            &candidates,
+        );
+
+        let reputations: Vec<(WindowCounts, ReputationBucket, u64)> = validators
+            .iter()
+            .map(|validator| {
+                let counts = WindowCounts {
+                    votes: *votes.get(&validator.address).unwrap_or(&0),
+                    proposals: *proposals.get(&validator.address).unwrap_or(&0),
+                    failed_proposals: *failed_proposals.get(&validator.address).unwrap_or(&0),
+                };
+                let (bucket, weight) = bucket_and_weight(params, &counts);
+                (counts, bucket, weight)
+            })
+            .collect();
+
+        let stake_weights: Vec<u128> = reputations
+            .iter()
+            .zip(validators)
+            .map(|((_, _, weight), validator)| {
+                let voting_power = if params.weight_by_voting_power {
+                    validator.voting_power
+                } else {
+                    1
+                };
+                *weight as u128 * voting_power as u128
+            })
+            .collect();
+        let total_stake_weight: u128 = stake_weights.iter().sum();
+
+        ReputationSnapshot {
+            epoch,
+            round,
+            validators: reputations
+                .into_iter()
+                .zip(stake_weights)
+                .map(
+                    |((counts, bucket, weight), stake_weight)| ValidatorReputation {
+                        counts,
+                        bucket,
+                        weight,
+                        election_probability: if total_stake_weight == 0 {
+                            0.0
+                        } else {
+                            stake_weight as f64 / total_stake_weight as f64
+                        },
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    fn summarize(epoch_info: &EpochInfo, snapshots: &[ReputationSnapshot]) -> EpochReputation {
+        let total_rounds = match (epoch_info.blocks.first(), epoch_info.blocks.last()) {
+            (Some(first), Some(last)) => {
+                last.event.round() - first.event.round()
+                    + 1
+                    + first.event.failed_proposer_indices().len() as u64
+            },
+            _ => 0,
+        };
+        let validators = epoch_info
+            .validators
+            .iter()
+            .enumerate()
+            .filter_map(|(index, validator)| {
+                let last = snapshots.last()?.validators[index];
+                let mut bucket_rounds = HashMap::new();
+                let mut probability_sum = 0.0;
+                for snapshot in snapshots {
+                    let reputation = &snapshot.validators[index];
+                    *bucket_rounds.entry(reputation.bucket).or_insert(0) += 1;
+                    probability_sum += reputation.election_probability;
+                }
+                Some(ValidatorReputationSummary {
+                    address: validator.address,
+                    voting_power: validator.voting_power,
+                    average_election_probability: probability_sum / snapshots.len() as f64,
+                    bucket_rounds,
+                    last,
+                })
+            })
+            .collect();
+
+        EpochReputation {
+            epoch: epoch_info.epoch,
+            partial: epoch_info.partial,
+            sampled_rounds: snapshots.len(),
+            total_rounds,
+            validators,
+        }
+    }
+
+    /// Print the reputation of validators in an epoch, optionally next to the
+    /// reputation the same epoch would have had under an alternative config.
+    pub fn print_epoch_table(
+        baseline: &EpochReputation,
+        alternative: Option<&EpochReputation>,
+        filter: &[AccountAddress],
+    ) {
+        println!(
+            "Leader reputation for {}epoch {}, {} rounds, sampled {} times:",
+            if baseline.partial { "partial " } else { "" },
+            baseline.epoch,
+            baseline.total_rounds,
+            baseline.sampled_rounds,
+        );
+        println!(
+            "{: <8} | {: <8} | {: <8} | {: <8} | {: <8} | {: <8} | {: <8} | {: <8} | {: <10} | {: <10} | {}",
+            "% prob",
+            "expected",
+            "% active",
+            "% inact",
+            "% failed",
+            "proposed",
+            "failed",
+            "voted",
+            "alt % prob",
+            "alt expect",
+            "validator"
+        );
+        let alternative: HashMap<AccountAddress, (&ValidatorReputationSummary, f64)> = alternative
+            .map(|alternative| {
+                alternative
+                    .validators
+                    .iter()
+                    .map(|v| (v.address, (v, alternative.expected_elections(v))))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut validators: Vec<&ValidatorReputationSummary> = baseline
+            .validators
+            .iter()
+            .filter(|v| filter.is_empty() || filter.contains(&v.address))
+            .collect();
+        validators.sort_by(|a, b| {
+            a.average_election_probability
+                .total_cmp(&b.average_election_probability)
+                .then(a.address.cmp(&b.address))
+        });
+
+        for validator in validators {
+            let (alt_probability, alt_expected) = alternative
+                .get(&validator.address)
+                .map(|(v, expected)| {
+                    (
+                        format!("{:7.3}%", 100.0 * v.average_election_probability),
+                        format!("{:.1}", expected),
+                    )
+                })
+                .unwrap_or_default();
+            println!(
+                "{:7.3}% | {: <8.1} | {:7.2}% | {:7.2}% | {:7.2}% | {: <8} | {: <8} | {: <8} | {: <10} | {: <10} | {}",
+                100.0 * validator.average_election_probability,
+                baseline.expected_elections(validator),
+                100.0 * validator.bucket_fraction(ReputationBucket::Active),
+                100.0 * validator.bucket_fraction(ReputationBucket::Inactive),
+                100.0 * validator.bucket_fraction(ReputationBucket::Failed),
+                validator.last.counts.proposals,
+                validator.last.counts.failed_proposals,
+                validator.last.counts.votes,
+                alt_probability,
+                alt_expected,
+                validator.address,
+            );
+        }
+    }
+}
+
+/// The part of `history` (ascending) a window of `window_size` blocks covers.
+/// Mirrors NewBlockEventAggregation::history_iter, which works on descending history.
+fn window<'a>(
+    history: &'a [&'a NewBlockEvent],
+    window_size: usize,
+    from_stale_end: bool,
+) -> &'a [&'a NewBlockEvent] {
+    let size = std::cmp::min(history.len(), window_size);
+    if from_stale_end {
+        &history[..size]
+    } else {
+        &history[history.len() - size..]
+    }
+}
+
+fn count_votes(
+    window: &[&NewBlockEvent],
+    candidates: &HashMap<u64, &[ValidatorInfo]>,
+) -> HashMap<AccountAddress, u32> {
+    let mut votes = HashMap::new();
+    for block in window {
+        let Some(validators) = candidates.get(&block.epoch()) else {
+            continue;
+        };
+        let bitvec: BitVec = block.previous_block_votes_bitvec().clone().into();
+        if BitVec::required_buckets(validators.len() as u16) != bitvec.num_buckets() {
+            continue;
+        }
+        for (index, validator) in validators.iter().enumerate() {
+            if bitvec.is_set(index as u16) {
+                *votes.entry(validator.address).or_insert(0) += 1;
+            }
+        }
+    }
+    votes
+}
+
+fn count_proposals(
+    window: &[&NewBlockEvent],
+    candidates: &HashMap<u64, &[ValidatorInfo]>,
+) -> (HashMap<AccountAddress, u32>, HashMap<AccountAddress, u32>) {
+    let mut proposals = HashMap::new();
+    let mut failed_proposals = HashMap::new();
+    for block in window {
+        let Some(validators) = candidates.get(&block.epoch()) else {
+            continue;
+        };
+        *proposals.entry(block.proposer()).or_insert(0) += 1;
+        for index in block.failed_proposer_indices() {
+            if let Some(validator) = validators.get(*index as usize) {
+                *failed_proposals.entry(validator.address).or_insert(0) += 1;
+            }
+        }
+    }
+    (proposals, failed_proposals)
+}
+
+/// Mirrors ProposerAndVoterHeuristic::get_weights.
+fn bucket_and_weight(
+    params: &ProposerAndVoterConfig,
+    counts: &WindowCounts,
+) -> (ReputationBucket, u64) {
+    if counts.failed_proposals * 100
+        > (counts.proposals + counts.failed_proposals) * params.failure_threshold_percent
+    {
+        (ReputationBucket::Failed, params.failed_weight)
+    } else if counts.proposals > 0 || counts.votes > 0 {
+        (ReputationBucket::Active, params.active_weight)
+    } else {
+        (ReputationBucket::Inactive, params.inactive_weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_rest_client::VersionedNewBlockEvent;
+
+    fn params() -> ProposerAndVoterConfig {
+        ProposerAndVoterConfig {
+            active_weight: 1000,
+            inactive_weight: 10,
+            failed_weight: 1,
+            failure_threshold_percent: 10,
+            proposer_window_num_validators_multiplier: 10,
+            voter_window_num_validators_multiplier: 1,
+            weight_by_voting_power: true,
+            use_history_from_previous_epoch_max_count: 5,
+        }
+    }
+
+    fn epoch_info(epoch: u64, validators: &[AccountAddress], rounds: u64) -> EpochInfo {
+        let mut all_voted = BitVec::with_num_bits(validators.len() as u16);
+        for index in 0..validators.len() {
+            all_voted.set(index as u16);
+        }
+        EpochInfo {
+            epoch,
+            // The last validator fails every round it is elected in, i.e. every third round.
+            blocks: (1..=rounds)
+                .filter(|round| round % 3 != 0)
+                .map(|round| VersionedNewBlockEvent {
+                    event: NewBlockEvent::new(
+                        AccountAddress::ZERO,
+                        epoch,
+                        round,
+                        round,
+                        all_voted.clone().into(),
+                        validators[(round % 3) as usize - 1],
+                        if round % 3 == 1 && round > 1 {
+                            vec![2]
+                        } else {
+                            vec![]
+                        },
+                        round,
+                    ),
+                    version: round,
+                    sequence_number: round,
+                })
+                .collect(),
+            validators: validators
+                .iter()
+                .enumerate()
+                .map(|(index, address)| ValidatorInfo {
+                    address: *address,
+                    voting_power: 100,
+                    validator_index: index as u16,
+                })
+                .collect(),
+            partial: false,
+        }
+    }
+
+    #[test]
+    fn test_bucket_and_weight() {
+        let params = params();
+        let bucket = |votes, proposals, failed_proposals| {
+            bucket_and_weight(&params, &WindowCounts {
+                votes,
+                proposals,
+                failed_proposals,
+            })
+        };
+        assert_eq!(bucket(0, 0, 0), (ReputationBucket::Inactive, 10));
+        assert_eq!(bucket(1, 0, 0), (ReputationBucket::Active, 1000));
+        assert_eq!(bucket(0, 9, 1), (ReputationBucket::Active, 1000));
+        assert_eq!(bucket(5, 8, 1), (ReputationBucket::Failed, 1));
+        assert_eq!(bucket(0, 0, 1), (ReputationBucket::Failed, 1));
+    }
+
+    #[test]
+    fn test_failing_validator_is_elected_less() {
+        let validators: Vec<_> = (0..3).map(|_| AccountAddress::random()).collect();
+        let epochs = vec![epoch_info(2, &validators, 100)];
+        let config = ReputationConfig {
+            exclude_round: 0,
+            leader_reputation_type: LeaderReputationType::ProposerAndVoterV2(params()),
+        };
+
+        let result = LeaderReputationSimulator::new(config.clone(), 10).simulate(&epochs);
+        assert_eq!(result.len(), 1);
+        let epoch = &result[0];
+        assert_eq!(epoch.total_rounds, 100);
+
+        let last = epoch.validators.last().unwrap();
+        assert_eq!(last.address, validators[2]);
+        assert_eq!(last.last.bucket, ReputationBucket::Failed);
+        assert!(last.last.election_probability < 0.01);
+        assert!(
+            last.average_election_probability < epoch.validators[0].average_election_probability
+        );
+        assert!(epoch.validators[0].average_election_probability > 0.45);
+
+        // A threshold above the observed failure rate keeps the validator active.
+        let mut lenient = params();
+        lenient.failure_threshold_percent = 100;
+        let result = LeaderReputationSimulator::new(
+            ReputationConfig {
+                exclude_round: 0,
+                leader_reputation_type: LeaderReputationType::ProposerAndVoterV2(lenient),
+            },
+            10,
+        )
+        .simulate(&epochs);
+        let last = result[0].validators.last().unwrap();
+        assert_eq!(last.last.bucket, ReputationBucket::Active);
+        assert!((last.last.election_probability - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
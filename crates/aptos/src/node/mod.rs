@@ -17,6 +17,7 @@ use crate::{
     node::analyze::{
         analyze_validators::{AnalyzeValidators, ValidatorStats},
         fetch_metadata::FetchMetadata,
+        leader_reputation::{LeaderReputationSimulator, ReputationConfig},
     },
 };
 use aptos_backup_cli::{
@@ -25,19 +26,28 @@ use aptos_backup_cli::{
     utils::GlobalRestoreOpt,
 };
 use aptos_cached_packages::aptos_stdlib;
+use aptos_config::config::{
+    RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
 use aptos_crypto::{bls12381, bls12381::PublicKey, x25519, ValidCryptoMaterialStringExt};
+use aptos_db::AptosDB;
 use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
 use aptos_logger::Level;
 use aptos_network_checker::args::{
     validate_address, CheckEndpointArgs, HandshakeArgs, NodeAddressArgs,
 };
 use aptos_rest_client::{aptos_api_types::VersionedEvent, Client, State};
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{BlockResource, CORE_CODE_ADDRESS},
     chain_id::ChainId,
     network_address::NetworkAddress,
-    on_chain_config::{ConfigurationResource, ConsensusScheme, ValidatorSet},
+    on_chain_config::{
+        ConfigurationResource, ConsensusScheme, OnChainConfig, OnChainConsensusConfig,
+        ProposerElectionType, ValidatorSet,
+    },
     stake_pool::StakePool,
     staking_contract::StakingContractStore,
     validator_info::ValidatorInfo,
@@ -52,7 +62,8 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -64,6 +75,7 @@ const SECS_TO_MICROSECS: u64 = 1_000_000;
 /// identify issues with nodes, and show related information.
 #[derive(Parser)]
 pub enum NodeTool {
+    AnalyzeLeaderReputation(AnalyzeLeaderReputation),
     AnalyzeValidatorPerformance(AnalyzeValidatorPerformance),
     BootstrapDb(BootstrapDb),
     CheckNetworkConnectivity(CheckNetworkConnectivity),
@@ -86,6 +98,7 @@ impl NodeTool {
     pub async fn execute(self) -> CliResult {
         use NodeTool::*;
         match self {
+            AnalyzeLeaderReputation(tool) => tool.execute_serialized().await,
             AnalyzeValidatorPerformance(tool) => tool.execute_serialized().await,
             BootstrapDb(tool) => {
                 tool.execute_serialized_with_logging_level(Level::Info)
@@ -1320,6 +1333,205 @@ impl CliCommand<()> for AnalyzeValidatorPerformance {
     }
 }
 
+/// Explain and simulate leader reputation over past epochs
+///
+/// Recomputes, for each epoch, the reputation weights and election probabilities
+/// consensus used to elect proposers, together with the failed proposal and
+/// voting counts that led to them. Optionally replays the same history with an
+/// alternative proposer election config, to evaluate a change before proposing it.
+#[derive(Parser)]
+pub struct AnalyzeLeaderReputation {
+    /// First epoch to analyze
+    ///
+    /// Negative values are relative to the latest epoch. Defaults to the last two epochs.
+    #[clap(long, default_value_t = -2)]
+    pub start_epoch: i64,
+
+    /// Last epoch to analyze
+    ///
+    /// Defaults to the latest epoch
+    #[clap(long)]
+    pub end_epoch: Option<i64>,
+
+    /// Filter of stake pool addresses to show
+    ///
+    /// Defaults to all stake pool addresses
+    #[clap(long, num_args = 0.., value_parser = crate::common::types::load_account_arg)]
+    pub pool_addresses: Vec<AccountAddress>,
+
+    /// Evaluate reputation every this many blocks
+    ///
+    /// Lower values are more precise, but slower for large validator sets.
+    #[clap(long, default_value_t = 100)]
+    pub sample_interval: usize,
+
+    /// YAML file with an alternative `ProposerElectionType` to simulate
+    ///
+    /// e.g. the `proposer_election_type` section of a consensus config.
+    #[clap(long, value_parser)]
+    pub alternative_config_file: Option<PathBuf>,
+
+    /// Exclude round to use with the alternative config
+    ///
+    /// Defaults to the on-chain value
+    #[clap(long)]
+    pub alternative_exclude_round: Option<u64>,
+
+    /// Directory of a ledger DB to read the epochs and the consensus config from, instead of
+    /// the REST API
+    ///
+    /// e.g. a DB snapshot, which may have a longer history than the REST API serves.
+    #[clap(long, value_parser)]
+    pub db_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for AnalyzeLeaderReputation {
+    fn command_name(&self) -> &'static str {
+        "AnalyzeLeaderReputation"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let source = match &self.db_dir {
+            Some(db_dir) => EpochSource::Db(open_db_readonly(db_dir)?),
+            None => EpochSource::Rest(self.rest_options.client(&self.profile_options)?),
+        };
+
+        let consensus_config = match &source {
+            EpochSource::Db(aptos_db) => {
+                let state_view = aptos_db
+                    .latest_state_checkpoint_view()
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+                OnChainConsensusConfig::fetch_config(&state_view).ok_or_else(|| {
+                    CliError::UnexpectedError("No consensus config found in the DB".to_string())
+                })?
+            },
+            EpochSource::Rest(client) => {
+                // The resource holds the BCS serialized config as a vector<u8>
+                let consensus_config_bytes: Vec<u8> = client
+                    .get_account_resource_bcs(
+                        CORE_CODE_ADDRESS,
+                        "0x1::consensus_config::ConsensusConfig",
+                    )
+                    .await?
+                    .into_inner();
+                bcs::from_bytes(&consensus_config_bytes)?
+            },
+        };
+        if consensus_config.is_dag_enabled() {
+            return Err(CliError::UnexpectedError(
+                "Leader reputation analysis is not supported for DAG consensus".to_string(),
+            ));
+        }
+        // Note: the current on-chain config is applied to all analyzed epochs
+        let baseline = ReputationConfig::new(
+            consensus_config.proposer_election_type(),
+            consensus_config.leader_reputation_exclude_round(),
+        )?;
+        let alternative = match &self.alternative_config_file {
+            Some(path) => {
+                let proposer_election_type: ProposerElectionType =
+                    from_yaml(&String::from_utf8(read_from_file(path)?)?)?;
+                Some(ReputationConfig::new(
+                    &proposer_election_type,
+                    self.alternative_exclude_round
+                        .unwrap_or(baseline.exclude_round),
+                )?)
+            },
+            None => None,
+        };
+
+        // Reputation depends on history from previous epochs, so fetch those as well.
+        let history_epochs = alternative
+            .as_ref()
+            .map_or(0, |config| config.history_epochs())
+            .max(baseline.history_epochs()) as i64;
+        let fetch_start_epoch = if self.start_epoch >= 0 {
+            std::cmp::max(self.start_epoch - history_epochs, 0)
+        } else {
+            self.start_epoch - history_epochs
+        };
+        let epochs = match &source {
+            EpochSource::Db(aptos_db) => FetchMetadata::fetch_new_block_events_from_db(
+                aptos_db.as_ref(),
+                Some(fetch_start_epoch),
+                self.end_epoch,
+            )?,
+            EpochSource::Rest(client) => {
+                FetchMetadata::fetch_new_block_events(
+                    client,
+                    Some(fetch_start_epoch),
+                    self.end_epoch,
+                )
+                .await?
+            },
+        };
+        let latest_epoch = match epochs.last() {
+            Some(epoch) => epoch.epoch as i64,
+            None => {
+                println!("No data found for given input");
+                return Ok(());
+            },
+        };
+        let first_epoch = if self.start_epoch >= 0 {
+            self.start_epoch
+        } else {
+            latest_epoch + self.start_epoch + 1
+        };
+
+        let baseline_result =
+            LeaderReputationSimulator::new(baseline, self.sample_interval).simulate(&epochs);
+        let alternative_result = alternative.map(|config| {
+            LeaderReputationSimulator::new(config, self.sample_interval).simulate(&epochs)
+        });
+
+        for (index, epoch_reputation) in baseline_result.iter().enumerate() {
+            if (epoch_reputation.epoch as i64) < first_epoch {
+                continue;
+            }
+            LeaderReputationSimulator::print_epoch_table(
+                epoch_reputation,
+                alternative_result.as_ref().map(|result| &result[index]),
+                &self.pool_addresses,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Where past epochs are read from.
+enum EpochSource {
+    Db(Arc<dyn DbReader>),
+    Rest(Client),
+}
+
+/// Opens the DB read-only, so that it can be read next to a node using it.
+fn open_db_readonly(db_dir: &Path) -> CliTypedResult<Arc<dyn DbReader>> {
+    let aptos_db = AptosDB::open(
+        StorageDirPaths::from_path(db_dir),
+        true, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        RocksdbConfigs::default(),
+        false, /* indexer */
+        BUFFERED_STATE_TARGET_ITEMS,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        None,
+    )
+    .map_err(|err| {
+        CliError::UnexpectedError(format!(
+            "Failed to open DB at {}: {}",
+            db_dir.display(),
+            err
+        ))
+    })?;
+    Ok(Arc::new(aptos_db))
+}
+
 /// Bootstrap AptosDB from a backup
 ///
 /// Enables users to load from a backup to catch their node's DB up to a known state.
@@ -0,0 +1,53 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::{json, Value};
+use std::{fs, process::Command};
+use tempfile::tempdir;
+
+const MANIFEST: &str = "[package]\nname = \"Mutate\"\nversion = \"0.0.0\"\n";
+
+const SOURCE: &str = "module 0x42::m {
+    public fun double(x: u64): u64 { x + x }
+    public fun is_big(x: u64): bool { x > 10 }
+
+    #[test]
+    fun test_double() { assert!(double(2) == 4, 0) }
+
+    #[test]
+    fun test_is_big() { assert!(!is_big(1), 0) }
+}
+";
+
+/// Runs `aptos move mutate` on a package whose tests do not check the boundary of `is_big`.
+#[test]
+fn test_move_mutate() {
+    let package_dir = tempdir().unwrap();
+    fs::create_dir_all(package_dir.path().join("sources")).unwrap();
+    fs::write(package_dir.path().join("Move.toml"), MANIFEST).unwrap();
+    fs::write(package_dir.path().join("sources").join("m.move"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aptos"))
+        .args(["move", "mutate", "--package-dir"])
+        .arg(package_dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let summary: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        summary["Result"],
+        json!({
+            "mutants": 4,
+            "killed": 3,
+            "survived": 1,
+            "invalid": 0,
+            "surviving_mutants": {
+                "m::is_big": ["sources/m.move:3:41: `>` -> `>=`"],
+            },
+        })
+    );
+}
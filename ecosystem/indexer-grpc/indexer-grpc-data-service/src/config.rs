@@ -13,7 +13,7 @@ use aptos_protos::{
     transaction::v1::FILE_DESCRIPTOR_SET as TRANSACTION_V1_TESTING_FILE_DESCRIPTOR_SET,
     util::timestamp::FILE_DESCRIPTOR_SET as UTIL_TIMESTAMP_FILE_DESCRIPTOR_SET,
 };
-use aptos_transaction_filter::BooleanTransactionFilter;
+use aptos_transaction_filter::{BooleanTransactionFilter, FilterLimits};
 use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, sync::Arc};
 use tonic::{codec::CompressionEncoding, transport::Server};
@@ -82,6 +82,10 @@ pub struct IndexerGrpcDataServiceConfig {
     /// separate filters that describe each type of txn we want to strip.
     #[serde(default = "IndexerGrpcDataServiceConfig::default_txns_to_strip_filter")]
     pub txns_to_strip_filter: BooleanTransactionFilter,
+    /// Limits on the complexity of the `transaction_filter` clients can set in their
+    /// requests. Requests with filters exceeding them are rejected.
+    #[serde(default)]
+    pub transaction_filter_limits: FilterLimits,
 }
 
 impl IndexerGrpcDataServiceConfig {
@@ -95,6 +99,7 @@ impl IndexerGrpcDataServiceConfig {
         enable_cache_compression: bool,
         in_memory_cache_config: InMemoryCacheConfig,
         txns_to_strip_filter: BooleanTransactionFilter,
+        transaction_filter_limits: FilterLimits,
     ) -> Self {
         Self {
             data_service_grpc_tls_config,
@@ -108,6 +113,7 @@ impl IndexerGrpcDataServiceConfig {
             enable_cache_compression,
             in_memory_cache_config,
             txns_to_strip_filter,
+            transaction_filter_limits,
         }
     }
 
@@ -182,6 +188,7 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
             self.file_store_config.clone(),
             self.data_service_response_channel_size,
             self.txns_to_strip_filter.clone(),
+            self.transaction_filter_limits.clone(),
             cache_storage_format,
             Arc::new(in_memory_cache),
         )?;
@@ -162,3 +162,20 @@ pub static NUM_TRANSACTIONS_STRIPPED: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// The number of transactions that were not sent to the client because they didn't
+/// match the `transaction_filter` of the request.
+pub static NUM_TRANSACTIONS_FILTERED_OUT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_grpc_data_service_num_transactions_filtered_out",
+        "Number of transactions that didn't match the filter supplied by the client",
+        &[
+            "identifier_type",
+            "identifier",
+            "email",
+            "application_name",
+            "processor"
+        ],
+    )
+    .unwrap()
+});
@@ -4,8 +4,9 @@
 use crate::metrics::{
     BYTES_READY_TO_TRANSFER_FROM_SERVER, BYTES_READY_TO_TRANSFER_FROM_SERVER_AFTER_STRIPPING,
     CONNECTION_COUNT, ERROR_COUNT, LATEST_PROCESSED_VERSION_PER_PROCESSOR,
-    NUM_TRANSACTIONS_STRIPPED, PROCESSED_LATENCY_IN_SECS_PER_PROCESSOR,
-    PROCESSED_VERSIONS_COUNT_PER_PROCESSOR, SHORT_CONNECTION_COUNT,
+    NUM_TRANSACTIONS_FILTERED_OUT, NUM_TRANSACTIONS_STRIPPED,
+    PROCESSED_LATENCY_IN_SECS_PER_PROCESSOR, PROCESSED_VERSIONS_COUNT_PER_PROCESSOR,
+    SHORT_CONNECTION_COUNT,
 };
 use anyhow::{Context, Result};
 use aptos_indexer_grpc_utils::{
@@ -29,7 +30,7 @@ use aptos_protos::{
     indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
     transaction::v1::{transaction::TxnData, Transaction},
 };
-use aptos_transaction_filter::{BooleanTransactionFilter, Filterable};
+use aptos_transaction_filter::{BooleanTransactionFilter, FilterLimits, Filterable};
 use futures::Stream;
 use prost::Message;
 use redis::Client;
@@ -80,6 +81,7 @@ pub struct RawDataServerWrapper {
     pub file_store_config: IndexerGrpcFileStoreConfig,
     pub data_service_response_channel_size: usize,
     pub txns_to_strip_filter: BooleanTransactionFilter,
+    pub transaction_filter_limits: FilterLimits,
     pub cache_storage_format: StorageFormat,
     in_memory_cache: Arc<InMemoryCache>,
 }
@@ -95,6 +97,7 @@ impl std::fmt::Debug for RawDataServerWrapper {
                 &self.data_service_response_channel_size,
             )
             .field("txns_to_strip_filter", &self.txns_to_strip_filter)
+            .field("transaction_filter_limits", &self.transaction_filter_limits)
             .field("cache_storage_format", &self.cache_storage_format)
             .finish()
     }
@@ -106,6 +109,7 @@ impl RawDataServerWrapper {
         file_store_config: IndexerGrpcFileStoreConfig,
         data_service_response_channel_size: usize,
         txns_to_strip_filter: BooleanTransactionFilter,
+        transaction_filter_limits: FilterLimits,
         cache_storage_format: StorageFormat,
         in_memory_cache: Arc<InMemoryCache>,
     ) -> anyhow::Result<Self> {
@@ -118,6 +122,7 @@ impl RawDataServerWrapper {
             file_store_config,
             data_service_response_channel_size,
             txns_to_strip_filter,
+            transaction_filter_limits,
             cache_storage_format,
             in_memory_cache,
         })
@@ -160,6 +165,25 @@ impl RawData for RawDataServerWrapper {
         let request = req.into_inner();
 
         let transactions_count = request.transactions_count;
+        let transaction_filter = match request
+            .transaction_filter
+            .as_deref()
+            .map(|filter| {
+                BooleanTransactionFilter::from_json_with_limits(
+                    filter,
+                    &self.transaction_filter_limits,
+                )
+            })
+            .transpose()
+        {
+            Ok(transaction_filter) => transaction_filter,
+            Err(e) => {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid transaction filter: {:#}",
+                    e
+                )))
+            },
+        };
 
         // Response channel to stream the data to the client.
         let (tx, rx) = channel(self.data_service_response_channel_size);
@@ -206,6 +230,7 @@ impl RawData for RawDataServerWrapper {
                     transactions_count,
                     tx,
                     txns_to_strip_filter,
+                    transaction_filter,
                     current_version,
                     in_memory_cache,
                 )
@@ -394,6 +419,7 @@ async fn data_fetcher_task(
     transactions_count: Option<u64>,
     tx: tokio::sync::mpsc::Sender<Result<TransactionsResponse, Status>>,
     txns_to_strip_filter: BooleanTransactionFilter,
+    transaction_filter: Option<BooleanTransactionFilter>,
     mut current_version: u64,
     in_memory_cache: Arc<InMemoryCache>,
 ) {
@@ -528,14 +554,24 @@ async fn data_fetcher_task(
         // 2. Push the data to the response channel, i.e. stream the data to the client.
         let current_batch_size = transaction_data.as_slice().len();
         let end_of_batch_version = transaction_data.as_slice().last().unwrap().version;
-        let (resp_items, num_stripped) = get_transactions_responses_builder(
+        let data_latency_in_secs = transaction_data
+            .last()
+            .unwrap()
+            .timestamp
+            .as_ref()
+            .map(time_diff_since_pb_timestamp_in_secs);
+        let (resp_items, num_stripped, num_filtered_out) = get_transactions_responses_builder(
             transaction_data,
             chain_id as u32,
             &txns_to_strip_filter,
+            transaction_filter.as_ref(),
         );
         NUM_TRANSACTIONS_STRIPPED
             .with_label_values(&request_metadata.get_label_values())
             .inc_by(num_stripped as u64);
+        NUM_TRANSACTIONS_FILTERED_OUT
+            .with_label_values(&request_metadata.get_label_values())
+            .inc_by(num_filtered_out as u64);
         let bytes_ready_to_transfer_after_stripping = resp_items
             .iter()
             .flat_map(|response| &response.transactions)
@@ -544,15 +580,6 @@ async fn data_fetcher_task(
         BYTES_READY_TO_TRANSFER_FROM_SERVER_AFTER_STRIPPING
             .with_label_values(&request_metadata.get_label_values())
             .inc_by(bytes_ready_to_transfer_after_stripping as u64);
-        let data_latency_in_secs = resp_items
-            .last()
-            .unwrap()
-            .transactions
-            .last()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .map(time_diff_since_pb_timestamp_in_secs);
 
         match channel_send_multiple_with_timeout(resp_items, tx.clone(), request_metadata.clone())
             .await
@@ -683,14 +710,22 @@ fn ensure_sequential_transactions(mut batches: Vec<Vec<Transaction>>) -> Vec<Tra
 }
 
 /// Builds the response for the get transactions request. Partial batch is ok, i.e., a
-/// batch with transactions < 1000.
+/// batch with transactions < 1000. If the client supplied a filter, only the matching
+/// transactions are included, so there may be no responses at all.
 ///
-/// It also returns the number of txns that were stripped.
+/// It also returns the number of txns that were stripped and filtered out.
 fn get_transactions_responses_builder(
     transactions: Vec<Transaction>,
     chain_id: u32,
     txns_to_strip_filter: &BooleanTransactionFilter,
-) -> (Vec<TransactionsResponse>, usize) {
+    transaction_filter: Option<&BooleanTransactionFilter>,
+) -> (Vec<TransactionsResponse>, usize, usize) {
+    let num_transactions = transactions.len();
+    let transactions = match transaction_filter {
+        Some(transaction_filter) => transaction_filter.filter_vec(transactions),
+        None => transactions,
+    };
+    let num_filtered_out = num_transactions - transactions.len();
     let (stripped_transactions, num_stripped) =
         strip_transactions(transactions, txns_to_strip_filter);
     let chunks = chunk_transactions(stripped_transactions, MESSAGE_SIZE_LIMIT);
@@ -701,7 +736,7 @@ fn get_transactions_responses_builder(
             transactions: chunk,
         })
         .collect();
-    (responses, num_stripped)
+    (responses, num_stripped, num_filtered_out)
 }
 
 // This is a CPU bound operation, so we spawn_blocking
@@ -901,6 +936,10 @@ async fn channel_send_multiple_with_timeout(
     tx: tokio::sync::mpsc::Sender<Result<TransactionsResponse, Status>>,
     request_metadata: Arc<IndexerGrpcRequestMetadata>,
 ) -> Result<(), SendTimeoutError<Result<TransactionsResponse, Status>>> {
+    // Everything in the batch may have been filtered out by the client's filter.
+    if resp_items.is_empty() {
+        return Ok(());
+    }
     let overall_send_start_time = Instant::now();
     let overall_size_in_bytes = resp_items
         .iter()
@@ -1316,4 +1355,50 @@ mod tests {
         assert_ne!(user_transaction.events.len(), 0);
         assert_ne!(txn.info.as_ref().unwrap().changes.len(), 0);
     }
+
+    #[test]
+    fn test_transactions_are_filtered_by_client_filter() {
+        let matching_txn = create_test_transaction(
+            MODULE_ADDRESS.to_string(),
+            MODULE_NAME.to_string(),
+            FUNCTION_NAME.to_string(),
+        );
+        let other_txn = Transaction {
+            version: 2,
+            ..create_test_transaction(
+                "0x5678".to_string(),
+                MODULE_NAME.to_string(),
+                FUNCTION_NAME.to_string(),
+            )
+        };
+        let transaction_filter = BooleanTransactionFilter::from_json_with_limits(
+            &format!(
+                r#"{{"type": "UserTransactionFilter", "payload": {{"function": {{"address": "{}"}}}}}}"#,
+                MODULE_ADDRESS
+            ),
+            &FilterLimits::default(),
+        )
+        .unwrap();
+
+        let (responses, num_stripped, num_filtered_out) = get_transactions_responses_builder(
+            vec![matching_txn.clone(), other_txn.clone()],
+            1,
+            &BooleanTransactionFilter::new_or(vec![]),
+            Some(&transaction_filter),
+        );
+        assert_eq!(num_stripped, 0);
+        assert_eq!(num_filtered_out, 1);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].transactions, vec![matching_txn]);
+
+        // Batches where nothing matches produce no responses at all.
+        let (responses, _, num_filtered_out) = get_transactions_responses_builder(
+            vec![other_txn],
+            1,
+            &BooleanTransactionFilter::new_or(vec![]),
+            Some(&transaction_filter),
+        );
+        assert_eq!(num_filtered_out, 1);
+        assert!(responses.is_empty());
+    }
 }
@@ -7,6 +7,7 @@ use aptos_protos::{
     indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
     internal::fullnode::v1::transactions_from_node_response,
 };
+use aptos_transaction_filter::{BooleanTransactionFilter, FilterLimits, Filterable};
 use futures::Stream;
 use std::pin::Pin;
 use tokio::sync::mpsc;
@@ -44,6 +45,16 @@ impl RawData for LocalnetDataService {
         let output_batch_size = self.service_context.output_batch_size;
         let ledger_chain_id = context.chain_id().id();
         let transactions_count = r.transactions_count;
+        let transaction_filter = r
+            .transaction_filter
+            .as_deref()
+            .map(|filter| {
+                BooleanTransactionFilter::from_json_with_limits(filter, &FilterLimits::default())
+            })
+            .transpose()
+            .map_err(|e| {
+                Status::invalid_argument(format!("Invalid transaction filter: {:#}", e))
+            })?;
         // Creates a channel to send the stream to the client
         let (tx, mut rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
         let (external_service_tx, external_service_rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
@@ -101,11 +112,18 @@ impl RawData for LocalnetDataService {
                                     transactions.into_iter().take(*count as usize).collect();
                                 *count = count.saturating_sub(current_transactions_count);
                             }
-                            transactions
+                            match transaction_filter.as_ref() {
+                                Some(filter) => filter.filter_vec(transactions),
+                                None => transactions,
+                            }
                         },
                         _ => panic!("Unexpected response type."),
                     },
                 });
+                // Don't send empty batches when the client's filter matched nothing.
+                if matches!(&response, Ok(response) if response.transactions.is_empty()) {
+                    continue;
+                }
                 match external_service_tx.send(response).await {
                     Ok(_) => {},
                     Err(e) => {
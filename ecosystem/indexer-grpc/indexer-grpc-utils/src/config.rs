@@ -20,6 +20,27 @@ pub struct LocalFileStore {
     pub local_file_store_path: PathBuf,
     #[serde(default = "default_enable_compression")]
     pub enable_compression: bool,
+    // Also write the transactions as parquet files, partitioned by version range.
+    #[serde(default)]
+    pub enable_parquet_export: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct S3FileStore {
+    pub s3_file_store_bucket_name: String,
+    pub s3_file_store_bucket_sub_dir: Option<PathBuf>,
+    // Falls back to the AWS_REGION environment variable if unset.
+    pub s3_file_store_region: Option<String>,
+    // Required for S3-compatible stores, e.g. MinIO or Cloudflare R2.
+    pub s3_file_store_endpoint: Option<String>,
+    // Allows plain HTTP endpoints, e.g. a local MinIO.
+    #[serde(default)]
+    pub s3_file_store_allow_http: bool,
+    #[serde(default = "default_enable_compression")]
+    pub enable_compression: bool,
+    // Also write the transactions as parquet files, partitioned by version range.
+    #[serde(default)]
+    pub enable_parquet_export: bool,
 }
 
 const fn default_enable_compression() -> bool {
@@ -31,6 +52,7 @@ const fn default_enable_compression() -> bool {
 pub enum IndexerGrpcFileStoreConfig {
     GcsFileStore(GcsFileStore),
     LocalFileStore(LocalFileStore),
+    S3FileStore(S3FileStore),
 }
 
 impl Default for IndexerGrpcFileStoreConfig {
@@ -38,6 +60,7 @@ impl Default for IndexerGrpcFileStoreConfig {
         IndexerGrpcFileStoreConfig::LocalFileStore(LocalFileStore {
             local_file_store_path: std::env::current_dir().unwrap(),
             enable_compression: false,
+            enable_parquet_export: false,
         })
     }
 }
@@ -59,8 +82,20 @@ impl IndexerGrpcFileStoreConfig {
                 crate::file_store_operator::local::LocalFileStoreOperator::new(
                     local_file_store.local_file_store_path.clone(),
                     local_file_store.enable_compression,
+                    local_file_store.enable_parquet_export,
                 ),
             ),
+            IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                Box::new(crate::file_store_operator::s3::S3FileStoreOperator::new(
+                    s3_file_store.s3_file_store_bucket_name.clone(),
+                    s3_file_store.s3_file_store_bucket_sub_dir.clone(),
+                    s3_file_store.s3_file_store_region.clone(),
+                    s3_file_store.s3_file_store_endpoint.clone(),
+                    s3_file_store.s3_file_store_allow_http,
+                    s3_file_store.enable_compression,
+                    s3_file_store.enable_parquet_export,
+                ))
+            },
         }
     }
 }
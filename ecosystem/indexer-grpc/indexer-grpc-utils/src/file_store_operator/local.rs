@@ -4,6 +4,7 @@
 use crate::{
     compression_util::{FileEntry, FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT},
     file_store_operator::{
+        parquet_export::{build_parquet_key, transactions_to_parquet},
         FileStoreOperator, FILE_STORE_UPDATE_FREQUENCY_SECS, METADATA_FILE_NAME,
     },
 };
@@ -18,10 +19,12 @@ pub struct LocalFileStoreOperator {
     /// The timestamp of the latest metadata update; this is to avoid too frequent metadata update.
     latest_metadata_update_timestamp: Option<std::time::Instant>,
     storage_format: StorageFormat,
+    /// Whether to also write the transactions as parquet files.
+    enable_parquet_export: bool,
 }
 
 impl LocalFileStoreOperator {
-    pub fn new(path: PathBuf, enable_compression: bool) -> Self {
+    pub fn new(path: PathBuf, enable_compression: bool, enable_parquet_export: bool) -> Self {
         let storage_format = if enable_compression {
             StorageFormat::Lz4CompressedProto
         } else {
@@ -31,6 +34,7 @@ impl LocalFileStoreOperator {
             path,
             latest_metadata_update_timestamp: None,
             storage_format,
+            enable_parquet_export,
         }
     }
 }
@@ -167,6 +171,15 @@ impl FileStoreOperator for LocalFileStoreOperator {
         for i in transactions.chunks(FILE_ENTRY_TRANSACTION_COUNT as usize) {
             let current_batch = i.iter().cloned().collect_vec();
             let starting_version = current_batch.first().unwrap().version;
+            if self.enable_parquet_export {
+                let ending_version = current_batch.last().unwrap().version;
+                let parquet_path = self
+                    .path
+                    .join(build_parquet_key(starting_version, ending_version));
+                let parquet_file = transactions_to_parquet(&current_batch)?;
+                tokio::fs::create_dir_all(parquet_path.parent().unwrap()).await?;
+                tokio::fs::write(parquet_path, parquet_file).await?;
+            }
             let file_entry = FileEntry::from_transactions(current_batch, self.storage_format);
             let file_entry_key =
                 FileEntry::build_key(starting_version, self.storage_format).to_string();
@@ -12,6 +12,9 @@ pub use gcs::*;
 pub mod local;
 use crate::counters::TRANSACTION_STORE_FETCH_RETRIES;
 pub use local::*;
+pub mod parquet_export;
+pub mod s3;
+pub use s3::*;
 
 const METADATA_FILE_NAME: &str = "metadata.json";
 const FILE_STORE_UPDATE_FREQUENCY_SECS: u64 = 5;
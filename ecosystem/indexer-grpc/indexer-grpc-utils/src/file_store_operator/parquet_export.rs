@@ -0,0 +1,217 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Columnar export of transactions, written by file store operators alongside the regular
+//! transaction files when parquet export is enabled.
+//!
+//! Files are laid out in Hive-style partitions by version range, e.g.
+//! `parquet/partition_start_version=1000000/1001000_1001999.parquet`, so that data lake tools
+//! can prune partitions when querying by version.
+
+use anyhow::Context;
+use aptos_protos::transaction::v1::{
+    transaction::{TransactionType, TxnData},
+    transaction_payload::Payload,
+    Transaction,
+};
+use parquet::{
+    basic::Compression,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    record::RecordWriter,
+};
+use parquet_derive::ParquetRecordWriter;
+use prost::Message;
+use std::sync::Arc;
+
+/// Number of versions in a single parquet partition.
+pub const PARQUET_PARTITION_TRANSACTION_COUNT: u64 = 1_000_000;
+
+/// A single row of the exported parquet files.
+#[derive(Debug, ParquetRecordWriter)]
+pub struct ParquetTransaction {
+    pub version: i64,
+    pub block_height: i64,
+    pub epoch: i64,
+    pub timestamp_usecs: i64,
+    pub txn_type: String,
+    pub hash: String,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: i64,
+    pub num_events: i64,
+    pub num_write_set_changes: i64,
+    /// Only set for user transactions.
+    pub sender: Option<String>,
+    /// Only set for user transactions.
+    pub sequence_number: Option<i64>,
+    /// `address::module::function`, only set for entry function payloads.
+    pub entry_function: Option<String>,
+    /// The full transaction, encoded as protobuf.
+    pub encoded_transaction: Vec<u8>,
+}
+
+impl From<&Transaction> for ParquetTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        let info = transaction.info.clone().unwrap_or_default();
+        let (num_events, request) = match &transaction.txn_data {
+            Some(TxnData::User(user)) => (user.events.len(), user.request.as_ref()),
+            Some(TxnData::BlockMetadata(block_metadata)) => (block_metadata.events.len(), None),
+            Some(TxnData::Genesis(genesis)) => (genesis.events.len(), None),
+            Some(TxnData::Validator(validator)) => (validator.events.len(), None),
+            Some(TxnData::StateCheckpoint(_)) | Some(TxnData::BlockEpilogue(_)) | None => (0, None),
+        };
+        let entry_function = request
+            .and_then(|request| request.payload.as_ref())
+            .and_then(|payload| match &payload.payload {
+                Some(Payload::EntryFunctionPayload(entry_function)) => {
+                    entry_function.function.as_ref()
+                },
+                _ => None,
+            })
+            .map(|function| {
+                let module = function.module.clone().unwrap_or_default();
+                format!("{}::{}::{}", module.address, module.name, function.name)
+            });
+        Self {
+            version: transaction.version as i64,
+            block_height: transaction.block_height as i64,
+            epoch: transaction.epoch as i64,
+            timestamp_usecs: transaction
+                .timestamp
+                .as_ref()
+                .map(|timestamp| timestamp.seconds * 1_000_000 + timestamp.nanos as i64 / 1_000)
+                .unwrap_or_default(),
+            txn_type: TransactionType::try_from(transaction.r#type)
+                .map(|txn_type| txn_type.as_str_name().to_string())
+                .unwrap_or_else(|_| transaction.r#type.to_string()),
+            hash: format!("0x{}", hex::encode(&info.hash)),
+            success: info.success,
+            vm_status: info.vm_status,
+            gas_used: info.gas_used as i64,
+            num_events: num_events as i64,
+            num_write_set_changes: info.changes.len() as i64,
+            sender: request.map(|request| request.sender.clone()),
+            sequence_number: request.map(|request| request.sequence_number as i64),
+            entry_function,
+            encoded_transaction: transaction.encode_to_vec(),
+        }
+    }
+}
+
+/// Builds the key of the parquet file for the batch of transactions `[start_version, end_version]`.
+/// A batch must not span multiple partitions.
+pub fn build_parquet_key(start_version: u64, end_version: u64) -> String {
+    let partition_start_version =
+        start_version / PARQUET_PARTITION_TRANSACTION_COUNT * PARQUET_PARTITION_TRANSACTION_COUNT;
+    format!(
+        "parquet/partition_start_version={}/{}_{}.parquet",
+        partition_start_version, start_version, end_version
+    )
+}
+
+/// Encodes the transactions as a single parquet file with one row group.
+pub fn transactions_to_parquet(transactions: &[Transaction]) -> anyhow::Result<Vec<u8>> {
+    let rows: Vec<ParquetTransaction> = transactions.iter().map(Into::into).collect();
+    let schema = rows.as_slice().schema()?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = SerializedFileWriter::new(Vec::new(), schema, Arc::new(properties))
+        .context("Failed to create parquet writer")?;
+    let mut row_group_writer = writer.next_row_group()?;
+    rows.as_slice()
+        .write_to_row_group(&mut row_group_writer)
+        .context("Failed to write transactions to parquet row group")?;
+    row_group_writer.close()?;
+    writer.into_inner().context("Failed to finish parquet file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::{
+        transaction::v1::{
+            EntryFunctionId, EntryFunctionPayload, MoveModuleId, TransactionInfo,
+            TransactionPayload, UserTransaction, UserTransactionRequest,
+        },
+        util::timestamp::Timestamp,
+    };
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_build_parquet_key() {
+        assert_eq!(
+            build_parquet_key(0, 999),
+            "parquet/partition_start_version=0/0_999.parquet"
+        );
+        assert_eq!(
+            build_parquet_key(1_001_000, 1_001_999),
+            "parquet/partition_start_version=1000000/1001000_1001999.parquet"
+        );
+    }
+
+    #[test]
+    fn test_transactions_to_parquet() {
+        let user_transaction = Transaction {
+            version: 1,
+            timestamp: Some(Timestamp {
+                seconds: 2,
+                nanos: 3_000,
+            }),
+            r#type: TransactionType::User as i32,
+            info: Some(TransactionInfo {
+                hash: vec![0xAB; 32],
+                success: true,
+                gas_used: 10,
+                ..Default::default()
+            }),
+            txn_data: Some(TxnData::User(UserTransaction {
+                request: Some(UserTransactionRequest {
+                    sender: "0x1234".to_string(),
+                    sequence_number: 7,
+                    payload: Some(TransactionPayload {
+                        payload: Some(Payload::EntryFunctionPayload(EntryFunctionPayload {
+                            function: Some(EntryFunctionId {
+                                module: Some(MoveModuleId {
+                                    address: "0x1".to_string(),
+                                    name: "coin".to_string(),
+                                }),
+                                name: "transfer".to_string(),
+                            }),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                events: vec![],
+            })),
+            ..Default::default()
+        };
+        let row = ParquetTransaction::from(&user_transaction);
+        assert_eq!(row.timestamp_usecs, 2_000_003);
+        assert_eq!(row.txn_type, "TRANSACTION_TYPE_USER");
+        assert_eq!(row.hash, format!("0x{}", "ab".repeat(32)));
+        assert_eq!(row.sender.as_deref(), Some("0x1234"));
+        assert_eq!(row.sequence_number, Some(7));
+        assert_eq!(row.entry_function.as_deref(), Some("0x1::coin::transfer"));
+        assert_eq!(
+            Transaction::decode(row.encoded_transaction.as_slice()).unwrap(),
+            user_transaction
+        );
+
+        let transactions = vec![user_transaction, Transaction {
+            version: 2,
+            ..Default::default()
+        }];
+        let bytes = transactions_to_parquet(&transactions).unwrap();
+        let reader = SerializedFileReader::new(bytes::Bytes::from(bytes)).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let versions: Vec<i64> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_long(0).unwrap())
+            .collect();
+        assert_eq!(versions, vec![1, 2]);
+    }
+}
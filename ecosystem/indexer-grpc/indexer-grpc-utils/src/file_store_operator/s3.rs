@@ -0,0 +1,330 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compression_util::{FileEntry, FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT},
+    counters::{log_grpc_step, IndexerGrpcStep},
+    file_store_operator::{
+        parquet_export::{build_parquet_key, transactions_to_parquet},
+        FileStoreOperator, METADATA_FILE_NAME,
+    },
+};
+use anyhow::bail;
+use aptos_protos::transaction::v1::Transaction;
+use object_store::{aws::AmazonS3Builder, path::Path as ObjectPath, ObjectStore};
+use std::{path::PathBuf, sync::Arc};
+
+const FILE_STORE_METADATA_TIMEOUT_MILLIS: u128 = 200;
+
+/// File store operator for AWS S3 and S3-compatible object stores, e.g. MinIO or Cloudflare R2.
+/// Credentials are read from the standard `AWS_*` environment variables.
+#[derive(Clone)]
+pub struct S3FileStoreOperator {
+    bucket_name: String,
+    bucket_sub_dir: Option<PathBuf>,
+    store: Arc<dyn ObjectStore>,
+    file_store_metadata_last_updated: std::time::Instant,
+    storage_format: StorageFormat,
+    enable_parquet_export: bool,
+}
+
+impl S3FileStoreOperator {
+    pub fn new(
+        bucket_name: String,
+        bucket_sub_dir: Option<PathBuf>,
+        region: Option<String>,
+        endpoint: Option<String>,
+        allow_http: bool,
+        enable_compression: bool,
+        enable_parquet_export: bool,
+    ) -> Self {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&bucket_name)
+            .with_allow_http(allow_http);
+        if let Some(region) = region {
+            builder = builder.with_region(region);
+        }
+        if let Some(endpoint) = endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        let store = builder.build().expect("Failed to create S3 client.");
+        Self::new_with_object_store(
+            bucket_name,
+            bucket_sub_dir,
+            Arc::new(store),
+            enable_compression,
+            enable_parquet_export,
+        )
+    }
+
+    /// Creates the operator on top of an existing object store, e.g. an in-memory one in tests.
+    pub fn new_with_object_store(
+        bucket_name: String,
+        bucket_sub_dir: Option<PathBuf>,
+        store: Arc<dyn ObjectStore>,
+        enable_compression: bool,
+        enable_parquet_export: bool,
+    ) -> Self {
+        let storage_format = if enable_compression {
+            StorageFormat::Lz4CompressedProto
+        } else {
+            StorageFormat::JsonBase64UncompressedProto
+        };
+        Self {
+            bucket_name,
+            bucket_sub_dir,
+            store,
+            file_store_metadata_last_updated: std::time::Instant::now(),
+            storage_format,
+            enable_parquet_export,
+        }
+    }
+
+    /// Builds the object path for the key, prefixed with the sub directory if set, in the case of
+    /// a shared bucket.
+    fn get_object_path(&self, key: &str) -> ObjectPath {
+        match &self.bucket_sub_dir {
+            Some(sub_dir) => ObjectPath::from(sub_dir.join(key).to_string_lossy().as_ref()),
+            None => ObjectPath::from(key),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FileStoreOperator for S3FileStoreOperator {
+    /// Bootstraps the file store operator. This is required before any other operations.
+    async fn verify_storage_bucket_existence(&self) {
+        tracing::info!(
+            bucket_name = self.bucket_name,
+            "Before file store operator starts, verify the bucket exists."
+        );
+        // Verifies the bucket exists and is accessible.
+        self.store
+            .list_with_delimiter(None)
+            .await
+            .expect("Failed to read bucket.");
+    }
+
+    fn storage_format(&self) -> StorageFormat {
+        self.storage_format
+    }
+
+    fn store_name(&self) -> &str {
+        "S3"
+    }
+
+    async fn get_raw_file(&self, version: u64) -> anyhow::Result<Vec<u8>> {
+        let file_entry_key = FileEntry::build_key(version, self.storage_format);
+        match self.store.get(&self.get_object_path(&file_entry_key)).await {
+            Ok(file) => Ok(file.bytes().await?.to_vec()),
+            Err(object_store::Error::NotFound { source, .. }) => {
+                anyhow::bail!("[Indexer File] Transactions file not found. Gap might happen between cache and file store. {}", source)
+            },
+            Err(err) => {
+                anyhow::bail!(
+                    "[Indexer File] Error happens when downloading transaction file. {}",
+                    err
+                );
+            },
+        }
+    }
+
+    /// Gets the metadata from the file store. Operator will panic if error happens when accessing the metadata file(except not found).
+    async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
+        let metadata_path = self.get_object_path(METADATA_FILE_NAME);
+        let metadata = match self.store.get(&metadata_path).await {
+            Ok(metadata) => metadata.bytes().await,
+            Err(err) => Err(err),
+        };
+        match metadata {
+            Ok(metadata) => {
+                let metadata: FileStoreMetadata =
+                    serde_json::from_slice(&metadata).expect("Expected metadata to be valid JSON.");
+                Some(metadata)
+            },
+            // Metadata is not found.
+            Err(object_store::Error::NotFound { .. }) => None,
+            Err(err) => {
+                panic!(
+                    "[Indexer File] Error happens when accessing metadata file. {}",
+                    err
+                );
+            },
+        }
+    }
+
+    /// If the file store is empty, the metadata will be created; otherwise, return the existing metadata.
+    async fn update_file_store_metadata_with_timeout(
+        &mut self,
+        expected_chain_id: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        if let Some(metadata) = self.get_file_store_metadata().await {
+            assert_eq!(metadata.chain_id, expected_chain_id, "Chain ID mismatch.");
+            assert_eq!(
+                metadata.storage_format, self.storage_format,
+                "Storage format mismatch."
+            );
+        }
+        if self.file_store_metadata_last_updated.elapsed().as_millis()
+            < FILE_STORE_METADATA_TIMEOUT_MILLIS
+        {
+            bail!("File store metadata is updated too frequently.")
+        }
+        self.update_file_store_metadata_internal(expected_chain_id, version)
+            .await?;
+        Ok(())
+    }
+
+    /// Updates the file store metadata. This is only performed by the operator when new file transactions are uploaded.
+    async fn update_file_store_metadata_internal(
+        &mut self,
+        chain_id: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let metadata = FileStoreMetadata::new(chain_id, version, self.storage_format);
+        // If the metadata is not updated, the indexer will be restarted.
+        self.store
+            .put(
+                &self.get_object_path(METADATA_FILE_NAME),
+                serde_json::to_vec(&metadata).unwrap().into(),
+            )
+            .await?;
+        self.file_store_metadata_last_updated = std::time::Instant::now();
+        Ok(())
+    }
+
+    /// Uploads the transactions to the file store. The transactions are grouped into batches of BLOB_STORAGE_SIZE.
+    /// If parquet export is enabled, the batch is also uploaded as a parquet file.
+    async fn upload_transaction_batch(
+        &mut self,
+        _chain_id: u64,
+        transactions: Vec<Transaction>,
+    ) -> anyhow::Result<(u64, u64)> {
+        let start_version = transactions.first().unwrap().version;
+        let end_version = transactions.last().unwrap().version;
+        let batch_size = transactions.len();
+        anyhow::ensure!(
+            start_version % FILE_ENTRY_TRANSACTION_COUNT == 0,
+            "Starting version has to be a multiple of BLOB_STORAGE_SIZE."
+        );
+        anyhow::ensure!(
+            batch_size == FILE_ENTRY_TRANSACTION_COUNT as usize,
+            "The number of transactions to upload has to be multiplier of BLOB_STORAGE_SIZE."
+        );
+        let start_time = std::time::Instant::now();
+        let parquet_file = if self.enable_parquet_export {
+            Some(transactions_to_parquet(&transactions)?)
+        } else {
+            None
+        };
+        let file_entry = FileEntry::from_transactions(transactions, self.storage_format);
+        let file_entry_key = FileEntry::build_key(start_version, self.storage_format);
+        log_grpc_step(
+            "file_worker",
+            IndexerGrpcStep::FileStoreEncodedTxns,
+            Some(start_version as i64),
+            Some((start_version + FILE_ENTRY_TRANSACTION_COUNT - 1) as i64),
+            None,
+            None,
+            Some(start_time.elapsed().as_secs_f64()),
+            None,
+            Some(FILE_ENTRY_TRANSACTION_COUNT as i64),
+            None,
+        );
+        self.store
+            .put(
+                &self.get_object_path(&file_entry_key),
+                file_entry.into_inner().into(),
+            )
+            .await?;
+        if let Some(parquet_file) = parquet_file {
+            self.store
+                .put(
+                    &self.get_object_path(&build_parquet_key(start_version, end_version)),
+                    parquet_file.into(),
+                )
+                .await?;
+        }
+        Ok((start_version, end_version))
+    }
+
+    fn clone_box(&self) -> Box<dyn FileStoreOperator> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    fn create_operator(store: Arc<InMemory>, enable_parquet_export: bool) -> S3FileStoreOperator {
+        S3FileStoreOperator::new_with_object_store(
+            "test-bucket".to_string(),
+            Some(PathBuf::from("sub_dir")),
+            store,
+            true,
+            enable_parquet_export,
+        )
+    }
+
+    fn create_transactions(start_version: u64) -> Vec<Transaction> {
+        (start_version..start_version + FILE_ENTRY_TRANSACTION_COUNT)
+            .map(|version| Transaction {
+                version,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_metadata_round_trip() {
+        let mut operator = create_operator(Arc::new(InMemory::new()), false);
+        operator.verify_storage_bucket_existence().await;
+        assert!(operator.get_file_store_metadata().await.is_none());
+
+        operator
+            .update_file_store_metadata_internal(1, 2000)
+            .await
+            .unwrap();
+        let metadata = operator.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.chain_id, 1);
+        assert_eq!(metadata.version, 2000);
+        assert_eq!(metadata.storage_format, StorageFormat::Lz4CompressedProto);
+        assert_eq!(operator.get_latest_version().await, Some(2000));
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_get_transactions() {
+        let store = Arc::new(InMemory::new());
+        let mut operator = create_operator(store.clone(), true);
+
+        assert_eq!(
+            operator
+                .upload_transaction_batch(1, create_transactions(1000))
+                .await
+                .unwrap(),
+            (1000, 1999)
+        );
+        // Batches have to be aligned with file entries.
+        assert!(operator
+            .upload_transaction_batch(1, create_transactions(1500))
+            .await
+            .is_err());
+
+        let transactions = operator.get_transactions(1500, 0).await.unwrap();
+        assert_eq!(transactions.len(), 500);
+        assert_eq!(transactions.first().unwrap().version, 1500);
+        assert_eq!(transactions.last().unwrap().version, 1999);
+        assert!(operator.get_transactions(2000, 0).await.is_err());
+
+        // The parquet file is written alongside the transactions file.
+        store
+            .head(&ObjectPath::from(
+                "sub_dir/parquet/partition_start_version=0/1000_1999.parquet",
+            ))
+            .await
+            .unwrap();
+    }
+}
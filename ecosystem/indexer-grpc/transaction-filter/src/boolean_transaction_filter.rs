@@ -6,10 +6,35 @@ use crate::{
     filters::{EventFilter, TransactionRootFilter, UserTransactionFilter},
     traits::Filterable,
 };
+use anyhow::{anyhow, Context};
 use aptos_protos::transaction::v1::{transaction::TxnData, Transaction};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+/// Limits on the complexity of filters supplied by clients, as every filter is evaluated
+/// against every transaction streamed to the client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct FilterLimits {
+    /// Max size of the JSON-encoded filter.
+    pub max_filter_size_bytes: usize,
+    /// Max number of filters, counting logical operators and the API filters they combine.
+    pub max_num_filters: usize,
+    /// Max nesting depth of logical operators.
+    pub max_depth: usize,
+}
+
+impl Default for FilterLimits {
+    fn default() -> Self {
+        Self {
+            max_filter_size_bytes: 10_000,
+            max_num_filters: 100,
+            max_depth: 10,
+        }
+    }
+}
+
 /// BooleanTransactionFilter is the top level filter
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -150,6 +175,61 @@ impl BooleanTransactionFilter {
     pub fn new_filter(filter: APIFilter) -> Self {
         BooleanTransactionFilter::Filter(filter)
     }
+
+    /// Parses a JSON-encoded filter supplied by a client, rejecting it if it is invalid or
+    /// exceeds the given limits.
+    pub fn from_json_with_limits(json: &str, limits: &FilterLimits) -> anyhow::Result<Self> {
+        if json.len() > limits.max_filter_size_bytes {
+            return Err(anyhow!(
+                "Filter is {} bytes, larger than the max of {} bytes",
+                json.len(),
+                limits.max_filter_size_bytes
+            ));
+        }
+        let filter: Self = serde_json::from_str(json).context("Failed to parse filter")?;
+        let num_filters = filter.num_filters();
+        if num_filters > limits.max_num_filters {
+            return Err(anyhow!(
+                "Filter has {} filters, more than the max of {}",
+                num_filters,
+                limits.max_num_filters
+            ));
+        }
+        let depth = filter.depth();
+        if depth > limits.max_depth {
+            return Err(anyhow!(
+                "Filter is nested {} levels deep, more than the max of {}",
+                depth,
+                limits.max_depth
+            ));
+        }
+        filter.is_valid()?;
+        Ok(filter)
+    }
+
+    /// Number of filters in this filter, including itself.
+    pub fn num_filters(&self) -> usize {
+        1 + match self {
+            BooleanTransactionFilter::And(and) => and.and.iter().map(Self::num_filters).sum(),
+            BooleanTransactionFilter::Or(or) => or.or.iter().map(Self::num_filters).sum(),
+            BooleanTransactionFilter::Not(not) => not.not.num_filters(),
+            BooleanTransactionFilter::Filter(_) => 0,
+        }
+    }
+
+    /// Nesting depth of logical operators, 0 for a single API filter.
+    pub fn depth(&self) -> usize {
+        match self {
+            BooleanTransactionFilter::And(and) => {
+                1 + and.and.iter().map(Self::depth).max().unwrap_or(0)
+            },
+            BooleanTransactionFilter::Or(or) => {
+                1 + or.or.iter().map(Self::depth).max().unwrap_or(0)
+            },
+            BooleanTransactionFilter::Not(not) => 1 + not.not.depth(),
+            BooleanTransactionFilter::Filter(_) => 0,
+        }
+    }
 }
 
 impl Filterable<Transaction> for BooleanTransactionFilter {
@@ -419,4 +499,43 @@ mod test {
         let yaml = serde_yaml::to_string(&query).unwrap();
         println!("YAML: \n{}", yaml);
     }
+
+    #[test]
+    fn test_from_json_with_limits() {
+        let utf = UserTransactionFilterBuilder::default()
+            .sender("0x0011")
+            .build()
+            .unwrap();
+        let trf = TransactionRootFilterBuilder::default()
+            .success(true)
+            .build()
+            .unwrap();
+        // (NOT utf) OR trf
+        let query = BooleanTransactionFilter::from(utf).not().or(trf);
+        assert_eq!(query.num_filters(), 4);
+        assert_eq!(query.depth(), 2);
+
+        let json = serde_json::to_string(&query).unwrap();
+        let limits = FilterLimits::default();
+        let parsed = BooleanTransactionFilter::from_json_with_limits(&json, &limits).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+        for limits in [
+            FilterLimits {
+                max_filter_size_bytes: json.len() - 1,
+                ..FilterLimits::default()
+            },
+            FilterLimits {
+                max_num_filters: 3,
+                ..FilterLimits::default()
+            },
+            FilterLimits {
+                max_depth: 1,
+                ..FilterLimits::default()
+            },
+        ] {
+            assert!(BooleanTransactionFilter::from_json_with_limits(&json, &limits).is_err());
+        }
+        assert!(BooleanTransactionFilter::from_json_with_limits("{\"foo\": 1}", &limits).is_err());
+    }
 }
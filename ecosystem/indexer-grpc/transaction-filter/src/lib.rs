@@ -7,7 +7,7 @@ pub mod filters;
 pub mod traits;
 
 // Re-exports for convenience.
-pub use boolean_transaction_filter::BooleanTransactionFilter;
+pub use boolean_transaction_filter::{BooleanTransactionFilter, FilterLimits};
 pub use filters::*;
 pub use traits::Filterable;
 
@@ -0,0 +1,192 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Utilities to compare partitioners on the same blocks, without running the executor.
+
+use crate::BlockPartitioner;
+use aptos_logger::info;
+use aptos_types::{
+    block_executor::partitioner::PartitionedTransactions,
+    transaction::{analyzed_transaction::AnalyzedTransaction, Transaction},
+};
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Quality metrics of the partitioning of a single block.
+#[derive(Clone, Debug, Default)]
+pub struct PartitionQuality {
+    pub num_txns: usize,
+    /// Number of rounds (sub-blocks per shard), excluding the global round.
+    pub num_rounds: usize,
+    /// Number of txns left to the global executor.
+    pub num_global_txns: usize,
+    /// Number of dependencies on a txn of another shard.
+    pub num_cross_shard_deps: usize,
+    /// Number of dependencies on a txn of the same shard, in an earlier round.
+    pub num_cross_round_deps: usize,
+    /// Number of txns with at least one cross-shard dependency.
+    pub num_txns_with_cross_shard_deps: usize,
+    /// Number of txns in each shard (excluding the global txns).
+    pub txns_by_shard: Vec<usize>,
+    pub partition_time: Duration,
+}
+
+impl PartitionQuality {
+    pub fn measure(partitioned: &PartitionedTransactions, partition_time: Duration) -> Self {
+        let mut quality = Self {
+            num_txns: partitioned.num_txns(),
+            num_global_txns: partitioned.global_txns.len(),
+            partition_time,
+            ..Self::default()
+        };
+        for sub_blocks_for_shard in partitioned.sharded_txns() {
+            let shard_id = sub_blocks_for_shard.shard_id;
+            quality.num_rounds = quality
+                .num_rounds
+                .max(sub_blocks_for_shard.num_sub_blocks());
+            quality.txns_by_shard.push(sub_blocks_for_shard.num_txns());
+            for txn in sub_blocks_for_shard.iter() {
+                let mut has_cross_shard_deps = false;
+                for (required_txn_idx, _) in txn.cross_shard_dependencies().required_edges_iter() {
+                    if required_txn_idx.shard_id == shard_id {
+                        quality.num_cross_round_deps += 1;
+                    } else {
+                        quality.num_cross_shard_deps += 1;
+                        has_cross_shard_deps = true;
+                    }
+                }
+                if has_cross_shard_deps {
+                    quality.num_txns_with_cross_shard_deps += 1;
+                }
+            }
+        }
+        for txn in partitioned.global_txns.iter() {
+            let num_deps = txn.cross_shard_dependencies().num_required_edges();
+            quality.num_cross_shard_deps += num_deps;
+            if num_deps > 0 {
+                quality.num_txns_with_cross_shard_deps += 1;
+            }
+        }
+        quality
+    }
+
+    /// The load of the busiest shard relative to the average load, i.e. 1.0 is perfectly balanced.
+    pub fn shard_imbalance(&self) -> f64 {
+        let num_sharded_txns: usize = self.txns_by_shard.iter().sum();
+        if num_sharded_txns == 0 {
+            return 1.0;
+        }
+        let max_load = self.txns_by_shard.iter().copied().max().unwrap_or(0);
+        max_load as f64 * self.txns_by_shard.len() as f64 / num_sharded_txns as f64
+    }
+}
+
+/// Partitions the block and measures the quality of the result.
+pub fn evaluate(
+    partitioner: &dyn BlockPartitioner,
+    txns: Vec<AnalyzedTransaction>,
+    num_shards: usize,
+) -> PartitionQuality {
+    let start = Instant::now();
+    let partitioned = partitioner.partition(txns, num_shards);
+    PartitionQuality::measure(&partitioned, start.elapsed())
+}
+
+/// Logs the quality metrics aggregated over all the blocks.
+pub fn report(name: &str, qualities: &[PartitionQuality]) {
+    let num_blocks = qualities.len().max(1) as f64;
+    let num_txns: usize = qualities.iter().map(|q| q.num_txns).sum();
+    let num_txns = (num_txns as f64).max(1.0);
+    let partition_time: Duration = qualities.iter().map(|q| q.partition_time).sum();
+    let sum = |f: fn(&PartitionQuality) -> usize| qualities.iter().map(f).sum::<usize>() as f64;
+
+    info!(
+        "{}: {} blocks, {} txns, partition time: {:.3} ms/block ({:.0} txn/s)",
+        name,
+        qualities.len(),
+        num_txns,
+        partition_time.as_secs_f64() * 1000.0 / num_blocks,
+        num_txns / partition_time.as_secs_f64()
+    );
+    info!(
+        "{}: cross-shard deps: {:.4}/txn, txns with cross-shard deps: {:.4}, cross-round deps: {:.4}/txn",
+        name,
+        sum(|q| q.num_cross_shard_deps) / num_txns,
+        sum(|q| q.num_txns_with_cross_shard_deps) / num_txns,
+        sum(|q| q.num_cross_round_deps) / num_txns,
+    );
+    info!(
+        "{}: rounds: {:.2}/block (max {}), global txns: {:.4}, shard imbalance: {:.3} (max {:.3})",
+        name,
+        sum(|q| q.num_rounds) / num_blocks,
+        qualities.iter().map(|q| q.num_rounds).max().unwrap_or(0),
+        sum(|q| q.num_global_txns) / num_txns,
+        qualities.iter().map(|q| q.shard_imbalance()).sum::<f64>() / num_blocks,
+        qualities
+            .iter()
+            .map(|q| q.shard_imbalance())
+            .fold(1.0, f64::max),
+    );
+}
+
+/// Saves blocks (e.g. from chain history) to a file, so that partitioners can be evaluated on them.
+pub fn save_blocks(path: &Path, blocks: &[Vec<Transaction>]) -> io::Result<()> {
+    let bytes =
+        bcs::to_bytes(blocks).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(path, bytes)
+}
+
+/// Loads blocks saved with `save_blocks`.
+pub fn load_blocks(path: &Path) -> io::Result<Vec<Vec<Transaction>>> {
+    let bytes = fs::read(path)?;
+    bcs::from_bytes(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{create_non_conflicting_p2p_transaction, P2PBlockGenerator},
+        v2::config::PartitionerV2Config,
+        PartitionerConfig,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn test_non_conflicting_quality() {
+        let txns = (0..8)
+            .map(|_| create_non_conflicting_p2p_transaction())
+            .collect();
+        let partitioner = PartitionerV2Config::default().build();
+        let quality = evaluate(partitioner.as_ref(), txns, 4);
+        assert_eq!(quality.num_txns, 8);
+        assert_eq!(quality.num_cross_shard_deps, 0);
+        assert_eq!(quality.num_txns_with_cross_shard_deps, 0);
+        assert_eq!(quality.num_global_txns, 0);
+        assert_eq!(quality.txns_by_shard, vec![2, 2, 2, 2]);
+        assert_eq!(quality.shard_imbalance(), 1.0);
+    }
+
+    #[test]
+    fn test_save_and_load_blocks() {
+        let block_gen = P2PBlockGenerator::new(10);
+        let mut rng = thread_rng();
+        let blocks: Vec<Vec<Transaction>> = (0..3)
+            .map(|_| {
+                block_gen
+                    .rand_block(&mut rng, 5)
+                    .into_iter()
+                    .map(|txn| txn.into_txn().into_inner())
+                    .collect()
+            })
+            .collect();
+
+        let path = std::env::temp_dir().join(format!("blocks-{}.bcs", rand::random::<u64>()));
+        save_blocks(&path, &blocks).unwrap();
+        assert_eq!(load_blocks(&path).unwrap(), blocks);
+        fs::remove_file(path).unwrap();
+    }
+}
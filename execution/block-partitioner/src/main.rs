@@ -2,17 +2,55 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_block_partitioner::{
-    test_utils::P2PBlockGenerator, v2::config::PartitionerV2Config, PartitionerConfig,
+    evaluation::{self, PartitionQuality},
+    pre_partition::{
+        connected_component::config::ConnectedComponentPartitionerConfig,
+        locality_aware::config::LocalityAwarePartitionerConfig,
+        uniform_partitioner::config::UniformPartitionerConfig, PrePartitionerConfig,
+    },
+    test_utils::P2PBlockGenerator,
+    v2::config::PartitionerV2Config,
+    PartitionerConfig,
 };
 use aptos_logger::info;
-use clap::Parser;
+use aptos_types::transaction::analyzed_transaction::AnalyzedTransaction;
+use clap::{Parser, ValueEnum};
 use rand::thread_rng;
-use std::time::Instant;
+use std::path::PathBuf;
 
 #[cfg(unix)]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PartitionerType {
+    V2,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PrePartitionerType {
+    Uniform,
+    ConnectedComponent,
+    LocalityAware,
+}
+
+impl PrePartitionerType {
+    fn config(&self, load_imbalance_tolerance: f32) -> Box<dyn PrePartitionerConfig> {
+        match self {
+            PrePartitionerType::Uniform => Box::new(UniformPartitionerConfig {}),
+            PrePartitionerType::ConnectedComponent => {
+                Box::new(ConnectedComponentPartitionerConfig {
+                    load_imbalance_tolerance,
+                })
+            },
+            PrePartitionerType::LocalityAware => Box::new(LocalityAwarePartitionerConfig {
+                load_imbalance_tolerance,
+                ..LocalityAwarePartitionerConfig::default()
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Parser)]
 struct Args {
     #[clap(long, default_value_t = 1000000)]
@@ -26,28 +64,88 @@ struct Args {
 
     #[clap(long, default_value_t = 48)]
     pub num_shards: usize,
+
+    /// Evaluate on the blocks in this file (e.g. exported from a DB with the executor benchmark),
+    /// instead of on random p2p blocks.
+    #[clap(long)]
+    pub blocks_file: Option<PathBuf>,
+
+    /// The partitioners to evaluate (all on the same blocks).
+    #[clap(long, value_enum, num_args = 1.., default_value = "v2")]
+    pub partitioners: Vec<PartitionerType>,
+
+    /// The pre-partitioners to evaluate the V2 partitioner with.
+    #[clap(long, value_enum, num_args = 1.., default_value = "connected-component")]
+    pub pre_partitioners: Vec<PrePartitionerType>,
+
+    #[clap(long, default_value_t = 4)]
+    pub max_partitioning_rounds: usize,
+
+    #[clap(long, default_value_t = 0.9)]
+    pub cross_shard_dep_avoid_threshold: f32,
+
+    #[clap(long, default_value_t = 2.0)]
+    pub load_imbalance_tolerance: f32,
+}
+
+impl Args {
+    /// The configs of the partitioners to evaluate, with the names to report them under.
+    fn partitioner_configs(&self) -> Vec<(String, Box<dyn PartitionerConfig>)> {
+        let mut configs: Vec<(String, Box<dyn PartitionerConfig>)> = vec![];
+        for partitioner in &self.partitioners {
+            match partitioner {
+                PartitionerType::V2 => {
+                    for pre_partitioner in &self.pre_partitioners {
+                        let config = PartitionerV2Config::default()
+                            .max_partitioning_rounds(self.max_partitioning_rounds)
+                            .num_threads(8)
+                            .cross_shard_dep_avoid_threshold(self.cross_shard_dep_avoid_threshold)
+                            .dashmap_num_shards(64)
+                            .partition_last_round(false)
+                            .pre_partitioner_config(
+                                pre_partitioner.config(self.load_imbalance_tolerance),
+                            );
+                        configs.push((format!("V2/{:?}", pre_partitioner), Box::new(config)));
+                    }
+                },
+            }
+        }
+        configs
+    }
 }
 
 fn main() {
     aptos_logger::Logger::new().init();
     info!("Starting the block partitioning benchmark");
     let args = Args::parse();
-    let block_gen = P2PBlockGenerator::new(args.num_accounts);
-    let partitioner = PartitionerV2Config::default()
-        .max_partitioning_rounds(4)
-        .num_threads(8)
-        .cross_shard_dep_avoid_threshold(0.9)
-        .dashmap_num_shards(64)
-        .partition_last_round(false)
-        .build();
-    let mut rng = thread_rng();
-    for _ in 0..args.num_blocks {
-        let transactions = block_gen.rand_block(&mut rng, args.block_size);
-        info!("Starting to partition");
-        let now = Instant::now();
-        let _partitioned = partitioner.partition(transactions.clone(), args.num_shards);
-        let elapsed = now.elapsed();
-        info!("Time taken to partition: {:?}", elapsed);
+
+    let blocks: Vec<Vec<AnalyzedTransaction>> = match &args.blocks_file {
+        Some(path) => evaluation::load_blocks(path)
+            .expect("Failed to load the blocks.")
+            .into_iter()
+            .map(|block| block.into_iter().map(|txn| txn.into()).collect())
+            .collect(),
+        None => {
+            let block_gen = P2PBlockGenerator::new(args.num_accounts);
+            let mut rng = thread_rng();
+            (0..args.num_blocks)
+                .map(|_| block_gen.rand_block(&mut rng, args.block_size))
+                .collect()
+        },
+    };
+
+    for (name, config) in args.partitioner_configs() {
+        let partitioner = config.build();
+        let qualities: Vec<PartitionQuality> = blocks
+            .iter()
+            .map(|block| {
+                let quality =
+                    evaluation::evaluate(partitioner.as_ref(), block.clone(), args.num_shards);
+                info!("Time taken to partition: {:?}", quality.partition_time);
+                quality
+            })
+            .collect();
+        evaluation::report(&name, &qualities);
     }
 }
 
@@ -0,0 +1,32 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::pre_partition::{
+    locality_aware::LocalityAwarePartitioner, PrePartitioner, PrePartitionerConfig,
+};
+
+#[derive(Clone, Debug)]
+pub struct LocalityAwarePartitionerConfig {
+    /// A shard is not assigned more than `load_imbalance_tolerance * block_size / num_shards` txns.
+    pub load_imbalance_tolerance: f32,
+    /// Senders and keys not accessed in this many blocks are forgotten.
+    pub history_window_blocks: u64,
+}
+
+impl Default for LocalityAwarePartitionerConfig {
+    fn default() -> Self {
+        LocalityAwarePartitionerConfig {
+            load_imbalance_tolerance: 2.0,
+            history_window_blocks: 100,
+        }
+    }
+}
+
+impl PrePartitionerConfig for LocalityAwarePartitionerConfig {
+    fn build(&self) -> Box<dyn PrePartitioner> {
+        Box::new(LocalityAwarePartitioner::new(
+            self.load_imbalance_tolerance,
+            self.history_window_blocks,
+        ))
+    }
+}
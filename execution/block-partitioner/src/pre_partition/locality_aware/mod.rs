@@ -0,0 +1,214 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    pre_partition::PrePartitioner,
+    v2::{
+        state::PartitionState,
+        types::{OriginalTxnIdx, PrePartitionedTxnIdx},
+    },
+};
+use aptos_types::{block_executor::partitioner::ShardId, state_store::state_key::StateKey};
+use move_core_types::account_address::AccountAddress;
+use std::{cmp::Reverse, collections::HashMap, sync::Mutex};
+
+/// How much more a sender weighs than a single storage key when scoring the shards for a txn.
+/// Txns from the same sender always conflict, so we try hard to keep them together.
+const SENDER_WEIGHT: usize = 4;
+
+/// A `PrePartitioner` used in `PartitionerV2` that assigns txns to the shards that recently
+/// accessed the same senders/storage keys, based on the access history of the previous blocks
+/// (and of the txns already assigned in the current block).
+///
+/// Real workloads have a lot of temporal locality (the same hot contracts and accounts are
+/// accessed block after block), so keeping them on the same shard reduces cross-shard
+/// dependencies, and it keeps the per-shard caches warm.
+///
+/// Txns are assigned greedily in their original order: each txn goes to the shard with the
+/// highest affinity score among the shards that are not full, where a shard is full once it has
+/// `load_imbalance_tolerance * block_size / num_shards` txns. Ties (e.g. txns without any history)
+/// go to the least loaded shard.
+pub struct LocalityAwarePartitioner {
+    load_imbalance_tolerance: f32,
+    history_window_blocks: u64,
+    history: Mutex<KeyAccessHistory>,
+}
+
+impl LocalityAwarePartitioner {
+    pub fn new(load_imbalance_tolerance: f32, history_window_blocks: u64) -> Self {
+        Self {
+            load_imbalance_tolerance,
+            history_window_blocks,
+            history: Mutex::new(KeyAccessHistory::default()),
+        }
+    }
+
+    /// The storage keys accessed by a txn, as declared in its read/write hints.
+    fn keys_of_txn(state: &PartitionState, ori_txn_idx: OriginalTxnIdx) -> Vec<StateKey> {
+        let write_set = state.write_sets[ori_txn_idx].read().unwrap();
+        let read_set = state.read_sets[ori_txn_idx].read().unwrap();
+        write_set
+            .iter()
+            .chain(read_set.iter())
+            .map(|&key_idx| state.storage_location(key_idx).into_state_key())
+            .collect()
+    }
+}
+
+impl PrePartitioner for LocalityAwarePartitioner {
+    fn pre_partition(
+        &self,
+        state: &PartitionState,
+    ) -> (
+        Vec<OriginalTxnIdx>,
+        Vec<PrePartitionedTxnIdx>,
+        Vec<Vec<PrePartitionedTxnIdx>>,
+    ) {
+        let num_shards = state.num_executor_shards;
+        let num_txns = state.num_txns();
+        let shard_capacity =
+            ((num_txns as f32) * self.load_imbalance_tolerance / (num_shards as f32))
+                .ceil()
+                .max(((num_txns + num_shards - 1) / num_shards) as f32) as usize;
+
+        let mut history = self.history.lock().unwrap();
+        let block_seq = history.start_block();
+        let mut loads = vec![0; num_shards];
+        let mut ori_txns_idxs_by_shard: Vec<Vec<OriginalTxnIdx>> = vec![vec![]; num_shards];
+        for ori_txn_idx in 0..num_txns {
+            let sender = state.txns[ori_txn_idx]
+                .read()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .sender();
+            let keys = Self::keys_of_txn(state, ori_txn_idx);
+
+            let scores = history.scores(num_shards, sender.as_ref(), &keys);
+            let shard_id = choose_shard(&scores, &loads, shard_capacity);
+            loads[shard_id] += 1;
+            ori_txns_idxs_by_shard[shard_id].push(ori_txn_idx);
+            history.record(sender, keys, shard_id);
+        }
+        history.forget_older_than(block_seq.saturating_sub(self.history_window_blocks));
+        drop(history);
+
+        // Prepare `ori_txn_idxs`, `start_txn_idxs_by_shard` and `pre_partitioned`.
+        let mut start_txn_idxs_by_shard = vec![0; num_shards];
+        let mut ori_txn_idxs = Vec::with_capacity(num_txns);
+        let mut pre_partitioned = Vec::with_capacity(num_shards);
+        for (shard_id, txn_idxs) in ori_txns_idxs_by_shard.into_iter().enumerate() {
+            let start = ori_txn_idxs.len();
+            start_txn_idxs_by_shard[shard_id] = start;
+            pre_partitioned.push((start..start + txn_idxs.len()).collect());
+            ori_txn_idxs.extend(txn_idxs);
+        }
+
+        (ori_txn_idxs, start_txn_idxs_by_shard, pre_partitioned)
+    }
+}
+
+/// Picks the shard with the highest score that is not full. Ties go to the least loaded shard,
+/// then to the lowest shard id, so the result is deterministic.
+fn choose_shard(scores: &[usize], loads: &[usize], shard_capacity: usize) -> ShardId {
+    (0..scores.len())
+        .filter(|&shard_id| loads[shard_id] < shard_capacity)
+        .max_by_key(|&shard_id| {
+            (
+                scores[shard_id],
+                Reverse(loads[shard_id]),
+                Reverse(shard_id),
+            )
+        })
+        .expect("The shard capacity should fit all txns.")
+}
+
+/// The shard each sender/storage key was last assigned to, and in which block.
+#[derive(Default)]
+struct KeyAccessHistory {
+    block_seq: u64,
+    senders: HashMap<AccountAddress, (ShardId, u64)>,
+    keys: HashMap<StateKey, (ShardId, u64)>,
+}
+
+impl KeyAccessHistory {
+    /// Starts a new block, returning its sequence number.
+    fn start_block(&mut self) -> u64 {
+        self.block_seq += 1;
+        self.block_seq
+    }
+
+    /// The affinity of a txn to each shard.
+    fn scores(
+        &self,
+        num_shards: usize,
+        sender: Option<&AccountAddress>,
+        keys: &[StateKey],
+    ) -> Vec<usize> {
+        let mut scores = vec![0; num_shards];
+        if let Some(&(shard_id, _)) = sender.and_then(|sender| self.senders.get(sender)) {
+            // The number of shards may have changed since.
+            if shard_id < num_shards {
+                scores[shard_id] += SENDER_WEIGHT;
+            }
+        }
+        for key in keys {
+            if let Some(&(shard_id, _)) = self.keys.get(key) {
+                if shard_id < num_shards {
+                    scores[shard_id] += 1;
+                }
+            }
+        }
+        scores
+    }
+
+    fn record(&mut self, sender: Option<AccountAddress>, keys: Vec<StateKey>, shard_id: ShardId) {
+        if let Some(sender) = sender {
+            self.senders.insert(sender, (shard_id, self.block_seq));
+        }
+        for key in keys {
+            self.keys.insert(key, (shard_id, self.block_seq));
+        }
+    }
+
+    fn forget_older_than(&mut self, block_seq: u64) {
+        self.senders.retain(|_, (_, seq)| *seq >= block_seq);
+        self.keys.retain(|_, (_, seq)| *seq >= block_seq);
+    }
+}
+
+#[test]
+fn test_choose_shard() {
+    // The highest score wins.
+    assert_eq!(1, choose_shard(&[1, 5, 2], &[0, 0, 0], 2));
+    // Unless that shard is full.
+    assert_eq!(2, choose_shard(&[1, 5, 2], &[0, 2, 0], 2));
+    // Ties go to the least loaded shard, then to the lowest shard id.
+    assert_eq!(2, choose_shard(&[0, 0, 0], &[1, 1, 0], 2));
+    assert_eq!(0, choose_shard(&[0, 0, 0], &[1, 1, 1], 2));
+}
+
+#[test]
+fn test_key_access_history() {
+    let sender = AccountAddress::random();
+    let key = StateKey::raw(b"key");
+    let mut history = KeyAccessHistory::default();
+
+    let first_block = history.start_block();
+    assert_eq!(vec![0, 0], history.scores(2, Some(&sender), &[key.clone()]));
+    history.record(Some(sender), vec![key.clone()], 1);
+    assert_eq!(
+        vec![0, SENDER_WEIGHT + 1],
+        history.scores(2, Some(&sender), &[key.clone()])
+    );
+    // Assignments to shards that no longer exist are ignored.
+    assert_eq!(vec![0], history.scores(1, Some(&sender), &[key.clone()]));
+
+    history.start_block();
+    history.forget_older_than(first_block);
+    assert_eq!(1, history.scores(2, None, &[key.clone()])[1]);
+    history.forget_older_than(first_block + 1);
+    assert_eq!(vec![0, 0], history.scores(2, Some(&sender), &[key]));
+}
+
+pub mod config;
@@ -39,6 +39,7 @@ pub trait PrePartitioner: Send {
 }
 
 pub mod connected_component;
+pub mod locality_aware;
 pub mod uniform_partitioner;
 
 pub trait PrePartitionerConfig: Debug {
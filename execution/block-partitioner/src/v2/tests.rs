@@ -5,7 +5,8 @@
 
 use crate::{
     pre_partition::{
-        connected_component::ConnectedComponentPartitioner, uniform_partitioner::UniformPartitioner,
+        connected_component::ConnectedComponentPartitioner,
+        locality_aware::LocalityAwarePartitioner, uniform_partitioner::UniformPartitioner,
     },
     test_utils::{assert_deterministic_result, P2PBlockGenerator},
     v2::PartitionerV2,
@@ -95,3 +96,28 @@ fn test_partitioner_v2_connected_component_determinism() {
         assert_deterministic_result(partitioner);
     }
 }
+
+#[test]
+fn test_partitioner_v2_locality_aware_correctness() {
+    for merge_discarded in [false, true] {
+        let block_generator = P2PBlockGenerator::new(100);
+        // The same partitioner is used for all the blocks, so that it builds up a history.
+        let partitioner = PartitionerV2::new(
+            8,
+            4,
+            0.9,
+            64,
+            merge_discarded,
+            Box::new(LocalityAwarePartitioner::new(2.0, 10)),
+        );
+        let mut rng = thread_rng();
+        for _run_id in 0..20 {
+            let block_size = 10_u64.pow(rng.gen_range(0, 4)) as usize;
+            let num_shards = rng.gen_range(1, 10);
+            let block = block_generator.rand_block(&mut rng, block_size);
+            let block_clone = block.clone();
+            let partitioned = partitioner.partition(block, num_shards);
+            crate::test_utils::verify_partitioner_output(&block_clone, &partitioned);
+        }
+    }
+}
@@ -47,6 +47,9 @@ impl BlockPreparationStage {
     }
 
     pub fn process(&mut self, txns: Vec<Transaction>) -> ExecuteBlockMessage {
+        let _timer = TIMER
+            .with_label_values(&["block_preparation"])
+            .start_timer();
         let current_block_start_time = Instant::now();
         info!(
             "In iteration {}, received {:?} transactions.",
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::pipeline::{CommitBlockMessage, LedgerUpdateMessage};
+use crate::{
+    metrics::TIMER,
+    pipeline::{CommitBlockMessage, LedgerUpdateMessage},
+};
 use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_types::transaction::Version;
@@ -49,10 +52,12 @@ where
             first_block_start_time,
         } = ledger_update_message;
 
+        let timer = TIMER.with_label_values(&["ledger_update"]).start_timer();
         let output = self
             .executor
             .ledger_update(block_id, parent_block_id, state_checkpoint_output)
             .unwrap();
+        timer.stop_and_record();
 
         self.version += output.transactions_to_commit_len() as Version;
 
@@ -11,6 +11,7 @@ mod ledger_update_stage;
 mod metrics;
 pub mod native_executor;
 pub mod pipeline;
+pub mod replay;
 pub mod transaction_committer;
 pub mod transaction_executor;
 pub mod transaction_generator;
@@ -5,8 +5,8 @@
 use aptos_block_partitioner::{
     pre_partition::{
         connected_component::config::ConnectedComponentPartitionerConfig,
-        default_pre_partitioner_config, uniform_partitioner::config::UniformPartitionerConfig,
-        PrePartitionerConfig,
+        default_pre_partitioner_config, locality_aware::config::LocalityAwarePartitionerConfig,
+        uniform_partitioner::config::UniformPartitionerConfig, PrePartitionerConfig,
     },
     v2::config::PartitionerV2Config,
 };
@@ -172,6 +172,10 @@ impl ShardingOpt {
             Some("connected-component") => Box::new(ConnectedComponentPartitionerConfig {
                 load_imbalance_tolerance: self.load_imbalance_tolerance,
             }),
+            Some("locality-aware") => Box::new(LocalityAwarePartitionerConfig {
+                load_imbalance_tolerance: self.load_imbalance_tolerance,
+                ..LocalityAwarePartitionerConfig::default()
+            }),
             _ => panic!("Unknown PrePartitioner: {:?}", self.pre_partitioner),
         }
     }
@@ -361,6 +365,40 @@ enum Command {
             Sample usage: --enable-feature=V1 --disable-feature=V2 V3 where V1, V2, V3 are FeatureFlag enum variants.")]
         disable_feature: Vec<FeatureFlag>,
     },
+    /// Replay transactions from real chain history, on top of a restored state snapshot
+    Replay {
+        /// DB with the transactions to replay, e.g. a full node DB or a restored backup range
+        #[clap(long, value_parser)]
+        transactions_dir: PathBuf,
+
+        /// DB with the state snapshot to replay on top of. The replay starts right after its
+        /// latest version.
+        #[clap(long, value_parser)]
+        state_dir: PathBuf,
+
+        #[clap(long, value_parser)]
+        checkpoint_dir: PathBuf,
+
+        /// number of versions to replay
+        #[clap(long, default_value_t = 100000)]
+        num_versions: u64,
+    },
+    /// Export blocks from real chain history to a file, to evaluate block partitioners on them
+    ExportBlocks {
+        /// DB with the transactions to export
+        #[clap(long, value_parser)]
+        transactions_dir: PathBuf,
+
+        #[clap(long)]
+        start_version: u64,
+
+        /// number of versions to export
+        #[clap(long, default_value_t = 100000)]
+        num_versions: u64,
+
+        #[clap(long, value_parser)]
+        output_file: PathBuf,
+    },
     AddAccounts {
         #[clap(long, value_parser)]
         data_dir: PathBuf,
@@ -480,6 +518,37 @@ where
                 get_init_features(enable_feature, disable_feature),
             );
         },
+        Command::Replay {
+            transactions_dir,
+            state_dir,
+            checkpoint_dir,
+            num_versions,
+        } => {
+            aptos_executor_benchmark::replay::replay_transactions::<E>(
+                transactions_dir,
+                state_dir,
+                checkpoint_dir,
+                num_versions,
+                opt.pruner_opt.pruner_config(),
+                opt.enable_storage_sharding,
+                opt.pipeline_opt.pipeline_config(),
+            );
+        },
+        Command::ExportBlocks {
+            transactions_dir,
+            start_version,
+            num_versions,
+            output_file,
+        } => {
+            let blocks = aptos_executor_benchmark::replay::load_blocks(
+                &transactions_dir,
+                start_version,
+                num_versions,
+                opt.enable_storage_sharding,
+            );
+            aptos_block_partitioner::evaluation::save_blocks(&output_file, &blocks)
+                .expect("Failed to save the blocks.");
+        },
         Command::AddAccounts {
             data_dir,
             checkpoint_dir,
@@ -0,0 +1,306 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays a range of committed transactions from real chain history through the benchmark
+//! pipeline (block preparation -> execution -> ledger update -> commit), so that performance is
+//! measured on the actual contract mix instead of a synthetic workload.
+//!
+//! Two DBs are needed:
+//! - the state DB, holding a state snapshot at version `V` (e.g. restored from a backup with
+//!   `aptos-db-tool restore bootstrap-db --target-version V`). It is checkpointed before the run,
+//!   so it can be reused.
+//! - the transaction DB, holding the transactions starting at version `V + 1` (e.g. a full node
+//!   DB, or a backup range restored with `aptos-db-tool`). It is only read from.
+//!
+//! The transactions are replayed block by block, with the same block boundaries as on chain.
+
+use crate::{
+    create_checkpoint, init_db_and_executor,
+    metrics::TIMER,
+    pipeline::{Pipeline, PipelineConfig},
+    OverallMeasuring,
+};
+use aptos_block_executor::counters as block_executor_counters;
+use aptos_config::config::{NodeConfig, PrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_db::AptosDB;
+use aptos_executor::block_executor::TransactionBlockExecutor;
+use aptos_logger::info;
+use aptos_storage_interface::DbReader;
+use aptos_types::transaction::{Transaction, Version};
+use std::{path::Path, time::Instant};
+
+/// The stages reported by the replay, as labels of the benchmark `TIMER`.
+const REPLAY_STAGES: &[&str] = &["block_preparation", "execution", "ledger_update", "commit"];
+
+/// Replays `num_versions` transactions from the transaction DB on top of the state in the
+/// state DB, and reports the TPS, the per-stage latencies and the conflict rate.
+pub fn replay_transactions<V>(
+    transactions_dir: impl AsRef<Path>,
+    state_dir: impl AsRef<Path>,
+    checkpoint_dir: impl AsRef<Path>,
+    num_versions: u64,
+    pruner_config: PrunerConfig,
+    enable_storage_sharding: bool,
+    pipeline_config: PipelineConfig,
+) where
+    V: TransactionBlockExecutor + 'static,
+{
+    create_checkpoint(
+        state_dir.as_ref(),
+        checkpoint_dir.as_ref(),
+        enable_storage_sharding,
+    );
+    let mut config = NodeConfig::default();
+    config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
+    config.storage.storage_pruner_config = pruner_config;
+    config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    let (db, executor) = init_db_and_executor::<V>(&config);
+    let version = db.reader.expect_synced_version();
+
+    let blocks = load_blocks(
+        transactions_dir.as_ref(),
+        version + 1,
+        num_versions,
+        enable_storage_sharding,
+    );
+    let num_txns: usize = blocks.iter().map(|block| block.len()).sum();
+    info!(
+        "Replaying {} transactions in {} blocks, starting at version {}",
+        num_txns,
+        blocks.len(),
+        version + 1
+    );
+
+    // Real transactions can abort, that is part of the workload.
+    let pipeline_config = PipelineConfig {
+        allow_aborts: true,
+        ..pipeline_config
+    };
+    let (pipeline, block_sender) =
+        Pipeline::new(executor, version, &pipeline_config, Some(blocks.len()));
+
+    let start_stages = StageMeasurement::now();
+    let start_speculative_aborts = block_executor_counters::SPECULATIVE_ABORT_COUNT.get();
+    let mut overall_measuring = OverallMeasuring::start();
+    let num_blocks = blocks.len();
+    for block in blocks {
+        block_sender.send(block).unwrap();
+    }
+    if pipeline_config.delay_execution_start {
+        overall_measuring.start_time = Instant::now();
+    }
+    pipeline.start_execution();
+    drop(block_sender);
+    pipeline.join();
+
+    if !pipeline_config.skip_commit {
+        let num_committed = db.reader.expect_synced_version() - version;
+        // The executor appends a state checkpoint to every block.
+        assert_eq!(
+            num_committed,
+            (num_txns + num_blocks) as u64,
+            "Not all replayed transactions were committed."
+        );
+    }
+    overall_measuring.print_end("Replay", num_txns as u64);
+
+    for (stage, (latency_sum, num_blocks)) in REPLAY_STAGES
+        .iter()
+        .zip(start_stages.elapsed_delta().stages)
+    {
+        if num_blocks > 0 {
+            info!(
+                "Replay {} latency: {:.3} ms/block (over {} blocks)",
+                stage,
+                latency_sum * 1000.0 / num_blocks as f64,
+                num_blocks
+            );
+        }
+    }
+    let speculative_aborts =
+        block_executor_counters::SPECULATIVE_ABORT_COUNT.get() - start_speculative_aborts;
+    info!(
+        "Replay conflict rate: {:.4} speculative aborts/txn ({} aborts over {} txns)",
+        speculative_aborts as f64 / (num_txns as f64).max(1.0),
+        speculative_aborts,
+        num_txns
+    );
+}
+
+/// Reads the transactions to replay from the transaction DB, split into the on-chain blocks.
+pub fn load_blocks(
+    transactions_dir: &Path,
+    start_version: Version,
+    num_versions: u64,
+    enable_storage_sharding: bool,
+) -> Vec<Vec<Transaction>> {
+    let mut config = NodeConfig::default();
+    config.storage.dir = transactions_dir.to_path_buf();
+    config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    let db = AptosDB::open(
+        config.storage.get_dir_paths(),
+        true, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        config.storage.rocksdb_configs,
+        false,
+        config.storage.buffered_state_target_items,
+        config.storage.max_num_nodes_per_lru_cache_shard,
+        None,
+    )
+    .expect("Transaction DB should open.");
+
+    let first_version = db
+        .get_first_txn_version()
+        .unwrap()
+        .expect("Transaction DB is empty.");
+    assert!(
+        first_version <= start_version,
+        "Transaction DB starts at version {}, but the replay starts at version {}. Transactions may have been pruned.",
+        first_version,
+        start_version
+    );
+
+    let txns = db
+        .get_transaction_iterator(start_version, num_versions)
+        .unwrap()
+        .map(|txn| txn.expect("Failed to read transaction."));
+    split_into_blocks(txns)
+}
+
+/// Splits committed transactions into the blocks they were executed in. The state checkpoints
+/// (and block epilogues) are dropped, as the executor adds them back at the end of every block.
+/// Transactions of a trailing block that isn't complete within the range are dropped as well.
+fn split_into_blocks(txns: impl Iterator<Item = Transaction>) -> Vec<Vec<Transaction>> {
+    let mut txns = txns.peekable();
+    if let Some(first_txn) = txns.peek() {
+        assert!(
+            matches!(
+                first_txn,
+                Transaction::BlockMetadata(_) | Transaction::BlockMetadataExt(_)
+            ),
+            "The replay has to start at the beginning of a block, i.e. the state snapshot has to be at the end of a block."
+        );
+    }
+
+    let mut blocks = vec![];
+    let mut current_block = vec![];
+    for txn in txns {
+        match txn {
+            Transaction::BlockMetadata(_) | Transaction::BlockMetadataExt(_) => {
+                if !current_block.is_empty() {
+                    blocks.push(std::mem::take(&mut current_block));
+                }
+                current_block.push(txn);
+            },
+            Transaction::StateCheckpoint(_) | Transaction::BlockEpilogue(_) => {
+                if !current_block.is_empty() {
+                    blocks.push(std::mem::take(&mut current_block));
+                }
+            },
+            _ => current_block.push(txn),
+        }
+    }
+    if !current_block.is_empty() {
+        info!(
+            "Dropping {} transactions of the last block, as it isn't complete within the range",
+            current_block.len()
+        );
+    }
+    blocks
+}
+
+/// Snapshot of the per-stage timers, as (total seconds, number of blocks) per stage.
+struct StageMeasurement {
+    stages: Vec<(f64, u64)>,
+}
+
+impl StageMeasurement {
+    fn now() -> Self {
+        Self {
+            stages: REPLAY_STAGES
+                .iter()
+                .map(|stage| {
+                    let timer = TIMER.with_label_values(&[stage]);
+                    (timer.get_sample_sum(), timer.get_sample_count())
+                })
+                .collect(),
+        }
+    }
+
+    fn elapsed_delta(self) -> Self {
+        let end = Self::now();
+        Self {
+            stages: end
+                .stages
+                .into_iter()
+                .zip(self.stages)
+                .map(|((end_sum, end_count), (start_sum, start_count))| {
+                    (end_sum - start_sum, end_count - start_count)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        account_address::AccountAddress,
+        block_metadata::BlockMetadata,
+        transaction::{ChangeSet, WriteSetPayload},
+    };
+
+    fn block_metadata(round: u64) -> Transaction {
+        Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::random(),
+            1,
+            round,
+            AccountAddress::ZERO,
+            vec![],
+            vec![],
+            round,
+        ))
+    }
+
+    fn other_txn() -> Transaction {
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::empty()))
+    }
+
+    fn state_checkpoint() -> Transaction {
+        Transaction::StateCheckpoint(HashValue::random())
+    }
+
+    #[test]
+    fn test_split_into_blocks() {
+        let txns = vec![
+            block_metadata(1),
+            other_txn(),
+            other_txn(),
+            state_checkpoint(),
+            block_metadata(2),
+            state_checkpoint(),
+            block_metadata(3),
+            other_txn(),
+            state_checkpoint(),
+            // Incomplete block at the end of the range
+            block_metadata(4),
+            other_txn(),
+        ];
+        let blocks = split_into_blocks(txns.into_iter());
+        assert_eq!(
+            blocks.iter().map(|block| block.len()).collect::<Vec<_>>(),
+            vec![3, 1, 2]
+        );
+        assert!(blocks
+            .iter()
+            .all(|block| matches!(block[0], Transaction::BlockMetadata(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "has to start at the beginning of a block")]
+    fn test_split_into_blocks_mid_block() {
+        split_into_blocks(vec![other_txn(), state_checkpoint(), block_metadata(1)].into_iter());
+    }
+}
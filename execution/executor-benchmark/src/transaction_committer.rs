@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{metrics::NUM_TXNS, pipeline::CommitBlockMessage};
+use crate::{
+    metrics::{NUM_TXNS, TIMER},
+    pipeline::CommitBlockMessage,
+};
 use aptos_crypto::hash::HashValue;
 use aptos_db::metrics::API_LATENCY_SECONDS;
 use aptos_executor::{
@@ -85,6 +88,7 @@ where
                 .inc_by(num_txns as u64);
 
             self.version += num_txns as u64;
+            let timer = TIMER.with_label_values(&["commit"]).start_timer();
             let commit_start = std::time::Instant::now();
             let ledger_info_with_sigs = gen_li_with_sigs(block_id, root_hash, self.version);
             let parent_block_id = self.executor.committed_block_id();
@@ -92,6 +96,7 @@ where
                 .pre_commit_block(block_id, parent_block_id)
                 .unwrap();
             self.executor.commit_ledger(ledger_info_with_sigs).unwrap();
+            timer.stop_and_record();
 
             report_block(
                 start_version,
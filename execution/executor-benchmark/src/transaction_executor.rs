@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::pipeline::LedgerUpdateMessage;
+use crate::{metrics::TIMER, pipeline::LedgerUpdateMessage};
 use aptos_crypto::hash::HashValue;
 use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_executor_types::BlockExecutorTrait;
@@ -59,6 +59,7 @@ where
         partition_time: Duration,
         executable_block: ExecutableBlock,
     ) {
+        let _timer = TIMER.with_label_values(&["execution"]).start_timer();
         let execution_start_time = Instant::now();
         if self.maybe_first_block_start_time.is_none() {
             self.maybe_first_block_start_time = Some(current_block_start_time);
@@ -15,12 +15,14 @@ mod error;
 pub mod local_executor_helper;
 mod metrics;
 pub mod process_executor_service;
+pub mod process_executor_shards;
 mod remote_cordinator_client;
 mod remote_cross_shard_client;
 pub mod remote_executor_client;
 pub mod remote_executor_service;
 mod remote_state_view;
 mod remote_state_view_service;
+mod shard_health;
 #[cfg(test)]
 mod test_utils;
 #[cfg(test)]
@@ -30,12 +32,15 @@ mod thread_executor_service;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteExecutionResult {
+    // The id of the request this is the result of. This lets the coordinator
+    // discard late results of requests that already timed out.
+    pub request_id: u64,
     pub inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>,
 }
 
 impl RemoteExecutionResult {
-    pub fn new(inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
-        Self { inner }
+    pub fn new(request_id: u64, inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
+        Self { request_id, inner }
     }
 }
 
@@ -46,6 +51,7 @@ pub enum RemoteExecutionRequest {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteBlockCommand {
+    pub(crate) request_id: u64,
     pub(crate) sub_blocks: SubBlocksForShard<AnalyzedTransaction>,
     pub(crate) concurrency_level: usize,
     pub(crate) onchain_config: BlockExecutorConfigFromOnchain,
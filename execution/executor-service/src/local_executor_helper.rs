@@ -4,12 +4,30 @@
 use aptos_infallible::Mutex;
 use aptos_logger::info;
 use aptos_storage_interface::cached_state_view::CachedStateView;
+use aptos_types::{
+    block_executor::{
+        config::{BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig},
+        partitioner::{
+            ShardId, SubBlocksForShard, GLOBAL_ROUND_ID, MAX_ALLOWED_PARTITIONING_ROUNDS,
+        },
+    },
+    state_store::StateView,
+    transaction::{analyzed_transaction::AnalyzedTransaction, TransactionOutput},
+    vm_status::VMStatus,
+};
 use aptos_vm::{
-    sharded_block_executor::{local_executor_shard::LocalExecutorClient, ShardedBlockExecutor},
+    sharded_block_executor::{
+        cross_shard_client::CrossShardCommitSender,
+        local_executor_shard::{LocalCrossShardClient, LocalExecutorClient},
+        messages::{CrossShardMsg, RemoteTxnWrite},
+        sharded_executor_service::ShardedExecutorService,
+        ShardedBlockExecutor,
+    },
     AptosVM,
 };
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use once_cell::sync::Lazy;
-use std::sync::Arc;
+use std::{sync::Arc, thread};
 
 pub static SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<Mutex<ShardedBlockExecutor<CachedStateView, LocalExecutorClient<CachedStateView>>>>,
@@ -19,3 +37,156 @@ pub static SHARDED_BLOCK_EXECUTOR: Lazy<
         LocalExecutorClient::create_local_sharded_block_executor(AptosVM::get_num_shards(), None),
     ))
 });
+
+/// Creates a local sharded executor client with the given number of shards. The remote executor
+/// client falls back to this when the remote shards are unhealthy, so that the partitioned block
+/// can be executed as is.
+pub fn create_local_executor_client<S: StateView + Sync + Send + 'static>(
+    num_shards: usize,
+    num_threads: Option<usize>,
+) -> LocalExecutorClient<S> {
+    info!(
+        "Creating local executor client with {} shards as a fallback for remote execution",
+        num_shards
+    );
+    LocalExecutorClient::setup_local_executor_shards(num_shards, num_threads)
+}
+
+/// Re-executes the sub-blocks of the shards without outputs locally, for when their remote shards
+/// failed but the other shards succeeded. The cross-shard messages these sub-blocks depend on from
+/// the other shards are replayed from the outputs of those shards. Returns the outputs of all the
+/// shards.
+pub fn execute_failed_sub_blocks_locally<S: StateView + Sync + Send + 'static>(
+    state_view: Arc<S>,
+    sub_blocks: Vec<SubBlocksForShard<AnalyzedTransaction>>,
+    mut shard_outputs: Vec<Option<Vec<Vec<TransactionOutput>>>>,
+    concurrency_level_per_shard: usize,
+    onchain_config: BlockExecutorConfigFromOnchain,
+) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+    let num_shards = sub_blocks.len();
+    let (message_txs, mut message_rxs): (
+        Vec<Vec<Sender<CrossShardMsg>>>,
+        Vec<Vec<Receiver<CrossShardMsg>>>,
+    ) = (0..num_shards)
+        .map(|_| {
+            (0..MAX_ALLOWED_PARTITIONING_ROUNDS)
+                .map(|_| unbounded())
+                .unzip()
+        })
+        .unzip();
+    // Global transactions are not supported by remote execution, so nothing is sent here
+    let (global_message_tx, _global_message_rx) = unbounded();
+
+    // Send what the successful shards sent the failed ones, before they start executing
+    for (sub_blocks, outputs) in sub_blocks.iter().zip(&shard_outputs) {
+        let Some(outputs) = outputs else {
+            continue;
+        };
+        for (sub_block, outputs) in sub_blocks.sub_block_iter().zip(outputs) {
+            for (txn, output) in sub_block.iter().zip(outputs) {
+                for (dependent_txn, storage_locations) in
+                    txn.cross_shard_dependencies().dependent_edges().iter()
+                {
+                    if dependent_txn.round_id == GLOBAL_ROUND_ID
+                        || shard_outputs[dependent_txn.shard_id].is_some()
+                    {
+                        continue;
+                    }
+                    for storage_location in storage_locations {
+                        let state_key = storage_location.clone().into_state_key();
+                        if let Some(write_op) = output.write_set().get(&state_key) {
+                            message_txs[dependent_txn.shard_id][dependent_txn.round_id]
+                                .send(CrossShardMsg::RemoteTxnWriteMsg(RemoteTxnWrite::new(
+                                    state_key,
+                                    Some(write_op.clone()),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let config = BlockExecutorConfig {
+        local: BlockExecutorLocalConfig {
+            concurrency_level: concurrency_level_per_shard,
+            allow_fallback: true,
+            discard_failed_blocks: false,
+        },
+        onchain: onchain_config,
+    };
+    let failed_shards: Vec<(ShardId, SubBlocksForShard<AnalyzedTransaction>)> = sub_blocks
+        .into_iter()
+        .enumerate()
+        .filter(|(shard_id, _)| shard_outputs[*shard_id].is_none())
+        .collect();
+    info!(
+        "Executing the sub-blocks of shards {:?} locally",
+        failed_shards
+            .iter()
+            .map(|(shard_id, _)| *shard_id)
+            .collect::<Vec<_>>()
+    );
+    // The failed shards can depend on each other, so they are executed concurrently. The
+    // receivers of the successful shards are kept alive, but never read.
+    let results: Vec<(ShardId, Result<Vec<Vec<TransactionOutput>>, VMStatus>)> =
+        thread::scope(|scope| {
+            let handles: Vec<_> = failed_shards
+                .into_iter()
+                .map(|(shard_id, sub_blocks)| {
+                    let cross_shard_client = Arc::new(LocalCrossShardClient::new(
+                        global_message_tx.clone(),
+                        message_txs.clone(),
+                        std::mem::take(&mut message_rxs[shard_id]),
+                    ));
+                    let state_view = state_view.clone();
+                    let config = config.clone();
+                    scope.spawn(move || {
+                        let executor_thread_pool = Arc::new(
+                            rayon::ThreadPoolBuilder::new()
+                                // Two extra threads, as in ShardedExecutorService
+                                .num_threads(concurrency_level_per_shard + 2)
+                                .build()
+                                .unwrap(),
+                        );
+                        let outputs = sub_blocks
+                            .into_sub_blocks()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(round, sub_block)| {
+                                let cross_shard_commit_sender = CrossShardCommitSender::new(
+                                    shard_id,
+                                    cross_shard_client.clone(),
+                                    &sub_block,
+                                );
+                                ShardedExecutorService::execute_transactions_with_dependencies(
+                                    Some(shard_id),
+                                    executor_thread_pool.clone(),
+                                    sub_block.into_transactions_with_deps(),
+                                    cross_shard_client.clone(),
+                                    Some(cross_shard_commit_sender),
+                                    round,
+                                    state_view.as_ref(),
+                                    config.clone(),
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>();
+                        (shard_id, outputs)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+    for (shard_id, outputs) in results {
+        shard_outputs[shard_id] = Some(outputs?);
+    }
+    Ok(shard_outputs
+        .into_iter()
+        .map(|outputs| outputs.expect("All the shards have outputs"))
+        .collect())
+}
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -44,3 +44,68 @@ pub static REMOTE_EXECUTOR_REMOTE_KV_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static REMOTE_EXECUTOR_SHARD_HEALTHY: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "remote_executor_shard_healthy",
+        // metric description
+        "Whether the remote executor shard is healthy (1) or not (0), as seen by the coordinator",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_FALLBACK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_fallback_count",
+        // metric description
+        "The number of blocks executed locally instead of on the remote shards, because: \
+         1. unhealthy_shards: some shards were unhealthy when the block arrived; \
+         2. shard_failure: some shards failed or didn't respond in time, so their sub-blocks were executed locally;",
+        // metric labels (dimensions)
+        &["reason"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_STALE_RESULT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_stale_result_count",
+        // metric description
+        "The number of results received from a shard for a block that had already timed out",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_STALE_CROSS_SHARD_MSG_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_stale_cross_shard_msg_count",
+        // metric description
+        "The number of cross-shard messages received on a shard for a request it had already finished",
+        // metric labels (dimensions)
+        &["round"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_ABORTED_CROSS_SHARD_ROUND_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_aborted_cross_shard_round_count",
+        // metric description
+        "The number of rounds a shard stopped waiting for cross-shard messages in, because: \
+         1. timeout: no message arrived in time; \
+         2. newer_request: messages of a later request arrived, so the coordinator gave up on this one; \
+         3. disconnected: the channel of the round was closed;",
+        // metric labels (dimensions)
+        &["reason"],
+    )
+    .unwrap()
+});
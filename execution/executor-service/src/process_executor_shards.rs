@@ -0,0 +1,134 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::utils;
+use aptos_logger::info;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Runs the executor shards as separate processes on localhost, using the executor service
+/// binary. This is meant for tests and experiments with sharded execution, e.g. to see how the
+/// coordinator copes with a shard process that dies.
+pub struct ProcessExecutorShards {
+    binary_path: PathBuf,
+    num_threads_per_shard: usize,
+    coordinator_address: SocketAddr,
+    shard_addresses: Vec<SocketAddr>,
+    shard_processes: Vec<Option<Child>>,
+}
+
+impl ProcessExecutorShards {
+    /// Spawns `num_shards` executor service processes from the binary at `binary_path`.
+    pub fn spawn(
+        binary_path: &Path,
+        num_shards: usize,
+        num_threads_per_shard: usize,
+    ) -> io::Result<Self> {
+        let mut shards = Self {
+            binary_path: binary_path.to_path_buf(),
+            num_threads_per_shard,
+            coordinator_address: Self::local_address(),
+            shard_addresses: (0..num_shards).map(|_| Self::local_address()).collect(),
+            shard_processes: (0..num_shards).map(|_| None).collect(),
+        };
+        for shard_id in 0..num_shards {
+            shards.spawn_shard(shard_id)?;
+        }
+        Ok(shards)
+    }
+
+    fn spawn_shard(&mut self, shard_id: usize) -> io::Result<()> {
+        let mut command = Command::new(&self.binary_path);
+        command
+            .arg("--shard-id")
+            .arg(shard_id.to_string())
+            .arg("--num-shards")
+            .arg(self.shard_addresses.len().to_string())
+            .arg("--num-executor-threads")
+            .arg(self.num_threads_per_shard.to_string())
+            .arg("--coordinator-address")
+            .arg(self.coordinator_address.to_string())
+            .arg("--remote-executor-addresses")
+            .args(
+                self.shard_addresses
+                    .iter()
+                    .map(|address| address.to_string()),
+            )
+            .stdout(Stdio::null());
+        info!(
+            "Spawning executor shard {} on {}",
+            shard_id, self.shard_addresses[shard_id]
+        );
+        self.shard_processes[shard_id] = Some(command.spawn()?);
+        Ok(())
+    }
+
+    fn local_address() -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port())
+    }
+
+    pub fn coordinator_address(&self) -> SocketAddr {
+        self.coordinator_address
+    }
+
+    pub fn shard_addresses(&self) -> Vec<SocketAddr> {
+        self.shard_addresses.clone()
+    }
+
+    /// Kills the process of the shard, to simulate a shard failure.
+    pub fn kill_shard(&mut self, shard_id: usize) -> io::Result<()> {
+        if let Some(mut process) = self.shard_processes[shard_id].take() {
+            process.kill()?;
+            process.wait()?;
+        }
+        Ok(())
+    }
+
+    /// Kills the process of the shard if it is still running, and spawns it again on the same
+    /// address.
+    pub fn restart_shard(&mut self, shard_id: usize) -> io::Result<()> {
+        self.kill_shard(shard_id)?;
+        self.spawn_shard(shard_id)
+    }
+
+    /// Waits until all the running shards accept connections, or fails after `timeout`.
+    pub fn wait_until_ready(&self, timeout: Duration) -> io::Result<()> {
+        let deadline = Instant::now() + timeout;
+        for (shard_id, address) in self.shard_addresses.iter().enumerate() {
+            if self.shard_processes[shard_id].is_none() {
+                continue;
+            }
+            while let Err(error) = TcpStream::connect(address) {
+                if Instant::now() >= deadline {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "Executor shard {} on {} is not ready: {}",
+                            shard_id, address, error
+                        ),
+                    ));
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn shutdown(&mut self) {
+        for shard_id in 0..self.shard_processes.len() {
+            let _ = self.kill_shard(shard_id);
+        }
+    }
+}
+
+impl Drop for ProcessExecutorShards {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -14,13 +14,22 @@ use aptos_vm::sharded_block_executor::{
 };
 use crossbeam_channel::{Receiver, Sender};
 use rayon::prelude::*;
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 pub struct RemoteCoordinatorClient {
     state_view_client: Arc<RemoteStateViewClient>,
     command_rx: Receiver<Message>,
     result_tx: Sender<Message>,
     shard_id: ShardId,
+    // The id of the request being executed. Commands are executed one at a time.
+    // Shared with the cross-shard client, to tag the cross-shard messages.
+    current_request_id: Arc<AtomicU64>,
 }
 
 impl RemoteCoordinatorClient {
@@ -28,6 +37,7 @@ impl RemoteCoordinatorClient {
         shard_id: ShardId,
         controller: &mut NetworkController,
         coordinator_address: SocketAddr,
+        current_request_id: Arc<AtomicU64>,
     ) -> Self {
         let execute_command_type = format!("execute_command_{}", shard_id);
         let execute_result_type = format!("execute_result_{}", shard_id);
@@ -43,6 +53,7 @@ impl RemoteCoordinatorClient {
             command_rx,
             result_tx,
             shard_id,
+            current_request_id,
         }
     }
 
@@ -91,6 +102,8 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
 
                 match request {
                     RemoteExecutionRequest::ExecuteBlock(command) => {
+                        self.current_request_id
+                            .store(command.request_id, Ordering::Relaxed);
                         let init_prefetch_timer = REMOTE_EXECUTOR_TIMER
                             .with_label_values(&[&self.shard_id.to_string(), "init_prefetch"])
                             .start_timer();
@@ -113,7 +126,8 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
     }
 
     fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
-        let remote_execution_result = RemoteExecutionResult::new(result);
+        let remote_execution_result =
+            RemoteExecutionResult::new(self.current_request_id.load(Ordering::Relaxed), result);
         let output_message = bcs::to_bytes(&remote_execution_result).unwrap();
         self.result_tx.send(Message::new(output_message)).unwrap();
     }
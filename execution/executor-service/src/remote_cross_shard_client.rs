@@ -1,25 +1,62 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
+use crate::{
+    metrics::{
+        REMOTE_EXECUTOR_ABORTED_CROSS_SHARD_ROUND_COUNT,
+        REMOTE_EXECUTOR_STALE_CROSS_SHARD_MSG_COUNT,
+    },
+    remote_executor_client::get_remote_execution_timeout,
+};
+use aptos_logger::{trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::block_executor::partitioner::{RoundId, ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS};
 use aptos_vm::sharded_block_executor::{
     cross_shard_client::CrossShardClient, messages::CrossShardMsg,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::VecDeque,
     net::SocketAddr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
+/// A cross-shard message tagged with the id of the execution request it belongs to, so that the
+/// messages a shard sent for a request that timed out are not consumed by a later request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteCrossShardMsg {
+    pub request_id: u64,
+    pub msg: CrossShardMsg,
+}
+
+struct CrossShardInbox {
+    rx: Receiver<Message>,
+    // Messages of later requests, received before this shard started executing them.
+    early_msgs: VecDeque<RemoteCrossShardMsg>,
+}
+
 pub struct RemoteCrossShardClient {
     // The senders of cross-shard messages to other shards per round.
     message_txs: Arc<Vec<Vec<Mutex<Sender<Message>>>>>,
     // The receivers of cross shard messages from other shards per round.
-    message_rxs: Arc<Vec<Mutex<Receiver<Message>>>>,
+    message_rxs: Arc<Vec<Mutex<CrossShardInbox>>>,
+    // The id of the request being executed, shared with the coordinator client.
+    current_request_id: Arc<AtomicU64>,
+    // How long to wait for the next cross-shard message of a round before aborting it. The
+    // coordinator doesn't wait for the shard any longer than this either.
+    msg_timeout: Duration,
 }
 
 impl RemoteCrossShardClient {
-    pub fn new(controller: &mut NetworkController, shard_addresses: Vec<SocketAddr>) -> Self {
+    pub fn new(
+        controller: &mut NetworkController,
+        shard_addresses: Vec<SocketAddr>,
+        current_request_id: Arc<AtomicU64>,
+    ) -> Self {
         let mut message_txs = vec![];
         let mut message_rxs = vec![];
         // Create outbound channels for each shard per round.
@@ -37,14 +74,42 @@ impl RemoteCrossShardClient {
         for round in 0..MAX_ALLOWED_PARTITIONING_ROUNDS {
             let message_type = format!("cross_shard_{}", round);
             let rx = controller.create_inbound_channel(message_type);
-            message_rxs.push(Mutex::new(rx));
+            message_rxs.push(Mutex::new(CrossShardInbox {
+                rx,
+                early_msgs: VecDeque::new(),
+            }));
         }
 
         Self {
             message_txs: Arc::new(message_txs),
             message_rxs: Arc::new(message_rxs),
+            current_request_id,
+            msg_timeout: get_remote_execution_timeout(),
         }
     }
+
+    fn record_stale_msg(round: RoundId, msg: &RemoteCrossShardMsg, request_id: u64) {
+        REMOTE_EXECUTOR_STALE_CROSS_SHARD_MSG_COUNT
+            .with_label_values(&[&round.to_string()])
+            .inc();
+        trace!(
+            "RemoteCrossShardClient discarding message of request {} in round {} while executing request {}",
+            msg.request_id,
+            round,
+            request_id
+        );
+    }
+
+    fn abort_round(round: RoundId, request_id: u64, reason: &str) -> CrossShardMsg {
+        REMOTE_EXECUTOR_ABORTED_CROSS_SHARD_ROUND_COUNT
+            .with_label_values(&[reason])
+            .inc();
+        warn!(
+            "RemoteCrossShardClient aborting round {} of request {}: {}",
+            round, request_id, reason
+        );
+        CrossShardMsg::AbortMsg
+    }
 }
 
 impl CrossShardClient for RemoteCrossShardClient {
@@ -53,15 +118,57 @@ impl CrossShardClient for RemoteCrossShardClient {
     }
 
     fn send_cross_shard_msg(&self, shard_id: ShardId, round: RoundId, msg: CrossShardMsg) {
-        let input_message = bcs::to_bytes(&msg).unwrap();
+        let input_message = bcs::to_bytes(&RemoteCrossShardMsg {
+            request_id: self.current_request_id.load(Ordering::Relaxed),
+            msg,
+        })
+        .unwrap();
         let tx = self.message_txs[shard_id][round].lock().unwrap();
         tx.send(Message::new(input_message)).unwrap();
     }
 
     fn receive_cross_shard_msg(&self, current_round: RoundId) -> CrossShardMsg {
-        let rx = self.message_rxs[current_round].lock().unwrap();
-        let message = rx.recv().unwrap();
-        let msg: CrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
-        msg
+        let request_id = self.current_request_id.load(Ordering::Relaxed);
+        let mut inbox = self.message_rxs[current_round].lock().unwrap();
+        inbox.early_msgs.retain(|msg| {
+            let is_stale = msg.request_id < request_id;
+            if is_stale {
+                Self::record_stale_msg(current_round, msg, request_id);
+            }
+            !is_stale
+        });
+        if let Some(index) = inbox
+            .early_msgs
+            .iter()
+            .position(|msg| msg.request_id == request_id)
+        {
+            return inbox.early_msgs.remove(index).unwrap().msg;
+        }
+        // The coordinator only sends the next request once it stopped waiting for this one, so the
+        // remaining messages of this request may never arrive.
+        if !inbox.early_msgs.is_empty() {
+            return Self::abort_round(current_round, request_id, "newer_request");
+        }
+        loop {
+            let message = match inbox.rx.recv_timeout(self.msg_timeout) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Self::abort_round(current_round, request_id, "timeout");
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Self::abort_round(current_round, request_id, "disconnected");
+                },
+            };
+            let msg: RemoteCrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
+            if msg.request_id == request_id {
+                return msg.msg;
+            }
+            if msg.request_id > request_id {
+                inbox.early_msgs.push_back(msg);
+                return Self::abort_round(current_round, request_id, "newer_request");
+            } else {
+                Self::record_stale_msg(current_round, &msg, request_id);
+            }
+        }
     }
 }
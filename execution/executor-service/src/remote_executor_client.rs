@@ -2,15 +2,19 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
-    RemoteExecutionResult,
+    local_executor_helper::{create_local_executor_client, execute_failed_sub_blocks_locally},
+    metrics::{REMOTE_EXECUTOR_FALLBACK_COUNT, REMOTE_EXECUTOR_STALE_RESULT_COUNT},
+    remote_state_view_service::RemoteStateViewService,
+    shard_health::ShardHealthTracker,
+    ExecuteBlockCommand, RemoteExecutionRequest, RemoteExecutionResult,
 };
-use aptos_logger::{info, trace};
+use aptos_logger::{info, trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_storage_interface::cached_state_view::CachedStateView;
 use aptos_types::{
     block_executor::{
-        config::BlockExecutorConfigFromOnchain, partitioner::PartitionedTransactions,
+        config::BlockExecutorConfigFromOnchain,
+        partitioner::{PartitionedTransactions, ShardId},
     },
     state_store::StateView,
     transaction::TransactionOutput,
@@ -18,20 +22,34 @@ use aptos_types::{
 };
 use aptos_vm::sharded_block_executor::{
     executor_client::{ExecutorClient, ShardedExecutionOutput},
+    local_executor_shard::LocalExecutorClient,
     ShardedBlockExecutor,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 pub static COORDINATOR_PORT: u16 = 52200;
 
+/// How long to wait for the shards to execute a block before executing the sub-blocks of the shards
+/// that didn't respond locally
+pub const DEFAULT_REMOTE_EXECUTION_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait before sending blocks to a shard again after it failed. This doubles with
+/// every consecutive failure, up to the maximum.
+const MIN_UNHEALTHY_SHARD_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_UNHEALTHY_SHARD_BACKOFF: Duration = Duration::from_secs(300);
+
 static REMOTE_ADDRESSES: OnceCell<Vec<SocketAddr>> = OnceCell::new();
 static COORDINATOR_ADDRESS: OnceCell<SocketAddr> = OnceCell::new();
+static REMOTE_EXECUTION_TIMEOUT: OnceCell<Duration> = OnceCell::new();
 
 pub fn set_remote_addresses(addresses: Vec<SocketAddr>) {
     REMOTE_ADDRESSES.set(addresses).ok();
@@ -55,6 +73,17 @@ pub fn get_coordinator_address() -> SocketAddr {
     }
 }
 
+pub fn set_remote_execution_timeout(timeout: Duration) {
+    REMOTE_EXECUTION_TIMEOUT.set(timeout).ok();
+}
+
+pub fn get_remote_execution_timeout() -> Duration {
+    match REMOTE_EXECUTION_TIMEOUT.get() {
+        Some(value) => *value,
+        None => DEFAULT_REMOTE_EXECUTION_TIMEOUT,
+    }
+}
+
 pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<
         aptos_infallible::Mutex<
@@ -68,7 +97,8 @@ pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
             get_coordinator_address(),
             get_remote_addresses(),
             None,
-        ),
+        )
+        .with_execution_timeout(get_remote_execution_timeout()),
     ))
 });
 
@@ -85,6 +115,17 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     result_rxs: Vec<Receiver<Message>>,
     // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
     thread_pool: Arc<rayon::ThreadPool>,
+    // The health of the remote shards, used to skip them while they are unresponsive.
+    shard_health: ShardHealthTracker,
+    // How long to wait for the shards to execute a block.
+    execution_timeout: Duration,
+    // The id of the next execution request, used to match results and cross-shard messages to
+    // requests.
+    next_request_id: AtomicU64,
+    // Executes blocks locally when the remote shards are unhealthy. Created on first use.
+    fallback_executor_client: OnceCell<LocalExecutorClient<S>>,
+    // The number of blocks executed at least partly locally by this client.
+    num_fallback_blocks: AtomicU64,
 
     phantom: std::marker::PhantomData<S>,
     _join_handle: Option<thread::JoinHandle<()>>,
@@ -104,6 +145,7 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 .build()
                 .unwrap(),
         );
+        let num_shards = remote_shard_addresses.len();
         let controller_mut_ref = &mut controller;
         let (command_txs, result_rxs) = remote_shard_addresses
             .iter()
@@ -141,10 +183,50 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
+            shard_health: ShardHealthTracker::new(
+                num_shards,
+                MIN_UNHEALTHY_SHARD_BACKOFF,
+                MAX_UNHEALTHY_SHARD_BACKOFF,
+            ),
+            execution_timeout: DEFAULT_REMOTE_EXECUTION_TIMEOUT,
+            // Start from the current time, so that the ids keep increasing across restarts of the
+            // coordinator and the shards don't mistake new requests for stale ones
+            next_request_id: AtomicU64::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("System time is before the UNIX epoch")
+                    .as_micros() as u64,
+            ),
+            fallback_executor_client: OnceCell::new(),
+            num_fallback_blocks: AtomicU64::new(0),
             phantom: std::marker::PhantomData,
         }
     }
 
+    pub fn with_execution_timeout(mut self, execution_timeout: Duration) -> Self {
+        self.execution_timeout = execution_timeout;
+        self
+    }
+
+    pub fn with_unhealthy_shard_backoff(
+        mut self,
+        min_backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        self.shard_health = ShardHealthTracker::new(self.num_shards(), min_backoff, max_backoff);
+        self
+    }
+
+    pub fn unhealthy_shards(&self) -> Vec<ShardId> {
+        self.shard_health.unhealthy_shards()
+    }
+
+    /// Returns the number of blocks that were executed at least partly locally, because some
+    /// shards were unhealthy or failed to execute their sub-blocks.
+    pub fn num_fallback_blocks(&self) -> u64 {
+        self.num_fallback_blocks.load(Ordering::Relaxed)
+    }
+
     pub fn create_remote_sharded_block_executor(
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
@@ -161,15 +243,84 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
         ))
     }
 
-    fn get_output_from_shards(&self) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+    /// Waits for the results of the request from all the shards, and updates their health.
+    /// The result of a shard is None if it didn't respond in time. A shard that responded with an
+    /// error is still healthy, e.g. it fails when another shard stops sending it cross-shard
+    /// messages.
+    fn get_output_from_shards(
+        &self,
+        request_id: u64,
+    ) -> Vec<Option<Result<Vec<Vec<TransactionOutput>>, VMStatus>>> {
         trace!("RemoteExecutorClient Waiting for results");
-        let mut results = vec![];
-        for rx in self.result_rxs.iter() {
-            let received_bytes = rx.recv().unwrap().to_bytes();
+        let deadline = Instant::now() + self.execution_timeout;
+        self.result_rxs
+            .iter()
+            .enumerate()
+            .map(|(shard_id, rx)| {
+                let result = Self::receive_result(shard_id, rx, request_id, deadline);
+                if result.is_some() {
+                    self.shard_health.record_success(shard_id);
+                } else {
+                    self.shard_health.record_failure(shard_id);
+                }
+                result
+            })
+            .collect()
+    }
+
+    /// Receives the result of the request from the shard, discarding the late results of earlier
+    /// requests. Returns None if the result doesn't arrive before the deadline.
+    fn receive_result(
+        shard_id: ShardId,
+        rx: &Receiver<Message>,
+        request_id: u64,
+        deadline: Instant,
+    ) -> Option<Result<Vec<Vec<TransactionOutput>>, VMStatus>> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let received_bytes = match rx.recv_timeout(timeout) {
+                Ok(message) => message.to_bytes(),
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    warn!(
+                        "RemoteExecutorClient result channel of shard {} closed",
+                        shard_id
+                    );
+                    return None;
+                },
+            };
             let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes).unwrap();
-            results.push(result.inner?);
+            if result.request_id == request_id {
+                return Some(result.inner);
+            }
+            REMOTE_EXECUTOR_STALE_RESULT_COUNT
+                .with_label_values(&[&shard_id.to_string()])
+                .inc();
+            trace!(
+                "RemoteExecutorClient discarding stale result of request {} from shard {}",
+                result.request_id,
+                shard_id
+            );
         }
-        Ok(results)
+    }
+
+    /// Executes the whole block on the local fallback executor, for when some shards are known to
+    /// be unhealthy before the block is sent.
+    fn execute_block_locally(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        self.fallback_executor_client
+            .get_or_init(|| create_local_executor_client(self.num_shards(), None))
+            .execute_block(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            )
     }
 }
 
@@ -185,15 +336,31 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
         concurrency_level_per_shard: usize,
         onchain_config: BlockExecutorConfigFromOnchain,
     ) -> Result<ShardedExecutionOutput, VMStatus> {
+        if !self.shard_health.all_available() {
+            REMOTE_EXECUTOR_FALLBACK_COUNT
+                .with_label_values(&["unhealthy_shards"])
+                .inc();
+            self.num_fallback_blocks.fetch_add(1, Ordering::Relaxed);
+            return self.execute_block_locally(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            );
+        }
+
         trace!("RemoteExecutorClient Sending block to shards");
-        self.state_view_service.set_state_view(state_view);
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        self.state_view_service.set_state_view(state_view.clone());
         let (sub_blocks, global_txns) = transactions.into();
         if !global_txns.is_empty() {
             panic!("Global transactions are not supported yet");
         }
-        for (shard_id, sub_blocks) in sub_blocks.into_iter().enumerate() {
+        // Keep a copy of the sub-blocks, to re-execute those of the failed shards locally
+        for (shard_id, sub_blocks) in sub_blocks.clone().into_iter().enumerate() {
             let senders = self.command_txs.clone();
             let execution_request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+                request_id,
                 sub_blocks,
                 concurrency_level: concurrency_level_per_shard,
                 onchain_config: onchain_config.clone(),
@@ -206,10 +373,55 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
                 .unwrap();
         }
 
-        let execution_results = self.get_output_from_shards()?;
-
+        let execution_results = self.get_output_from_shards(request_id);
         self.state_view_service.drop_state_view();
-        Ok(ShardedExecutionOutput::new(execution_results, vec![]))
+
+        let shard_outputs: Vec<_> = execution_results
+            .into_iter()
+            .enumerate()
+            .map(|(shard_id, result)| match result {
+                Some(Ok(outputs)) => Some(outputs),
+                Some(Err(status)) => {
+                    warn!(
+                        "RemoteExecutorClient shard {} failed to execute request {}: {:?}",
+                        shard_id, request_id, status
+                    );
+                    None
+                },
+                None => None,
+            })
+            .collect();
+        let failed_shards: Vec<ShardId> = shard_outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, outputs)| outputs.is_none())
+            .map(|(shard_id, _)| shard_id)
+            .collect();
+        if failed_shards.is_empty() {
+            return Ok(ShardedExecutionOutput::new(
+                shard_outputs.into_iter().map(Option::unwrap).collect(),
+                vec![],
+            ));
+        }
+
+        warn!(
+            "RemoteExecutorClient shards {:?} failed or didn't respond within {:?}, executing their sub-blocks of request {} locally",
+            failed_shards, self.execution_timeout, request_id
+        );
+        REMOTE_EXECUTOR_FALLBACK_COUNT
+            .with_label_values(&["shard_failure"])
+            .inc();
+        self.num_fallback_blocks.fetch_add(1, Ordering::Relaxed);
+        Ok(ShardedExecutionOutput::new(
+            execute_failed_sub_blocks_locally(
+                state_view,
+                sub_blocks,
+                shard_outputs,
+                concurrency_level_per_shard,
+                onchain_config,
+            )?,
+            vec![],
+        ))
     }
 
     fn shutdown(&mut self) {
@@ -8,7 +8,11 @@ use crate::{
 use aptos_secure_net::network_controller::NetworkController;
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::sharded_block_executor::sharded_executor_service::ShardedExecutorService;
-use std::{net::SocketAddr, sync::Arc, thread};
+use std::{
+    net::SocketAddr,
+    sync::{atomic::AtomicU64, Arc},
+    thread,
+};
 
 /// A service that provides support for remote execution. Essentially, it reads a request from
 /// the remote executor client and executes the block locally and returns the result.
@@ -29,14 +33,19 @@ impl ExecutorService {
     ) -> Self {
         let service_name = format!("executor_service-{}", shard_id);
         let mut controller = NetworkController::new(service_name, self_address, 5000);
+        // The id of the request being executed, set by the coordinator client and used by the
+        // cross-shard client to tag its messages
+        let current_request_id = Arc::new(AtomicU64::new(0));
         let coordinator_client = Arc::new(RemoteCoordinatorClient::new(
             shard_id,
             &mut controller,
             coordinator_address,
+            current_request_id.clone(),
         ));
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
             &mut controller,
             remote_shard_addresses,
+            current_request_id,
         ));
 
         let executor_service = Arc::new(ShardedExecutorService::new(
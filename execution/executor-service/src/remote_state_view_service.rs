@@ -10,7 +10,7 @@ use std::{
 
 extern crate itertools;
 use crate::metrics::REMOTE_EXECUTOR_TIMER;
-use aptos_logger::trace;
+use aptos_logger::{trace, warn};
 use aptos_types::state_store::{StateView, TStateView};
 use itertools::Itertools;
 
@@ -92,16 +92,20 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
             shard_id,
             state_keys.len()
         );
+        // A shard that timed out may still ask for state after the block is done. It
+        // is executing a stale block, so there is nothing to respond with.
+        let state_view = state_view.read().unwrap().clone();
+        let Some(state_view) = state_view else {
+            warn!(
+                "remote state view service - dropping request for shard {} without a state view",
+                shard_id
+            );
+            return;
+        };
         let resp = state_keys
             .into_iter()
             .map(|state_key| {
-                let state_value = state_view
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .get_state_value(&state_key)
-                    .unwrap();
+                let state_value = state_view.get_state_value(&state_key).unwrap();
                 (state_key, state_value)
             })
             .collect_vec();
@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::REMOTE_EXECUTOR_SHARD_HEALTHY;
+use aptos_infallible::Mutex;
+use aptos_types::block_executor::partitioner::ShardId;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ShardStatus {
+    Healthy,
+    // The shard failed to respond in time. We don't send it any blocks until
+    // `retry_at`, after which the next block probes it again.
+    Unhealthy {
+        consecutive_failures: u32,
+        retry_at: Instant,
+    },
+}
+
+/// Tracks the health of the remote executor shards, so that a shard that died
+/// or stalls doesn't slow down every block. Unhealthy shards are retried with
+/// an exponential backoff.
+pub struct ShardHealthTracker {
+    shards: Vec<Mutex<ShardStatus>>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl ShardHealthTracker {
+    pub fn new(num_shards: usize, min_backoff: Duration, max_backoff: Duration) -> Self {
+        for shard_id in 0..num_shards {
+            REMOTE_EXECUTOR_SHARD_HEALTHY
+                .with_label_values(&[&shard_id.to_string()])
+                .set(1);
+        }
+        Self {
+            shards: (0..num_shards)
+                .map(|_| Mutex::new(ShardStatus::Healthy))
+                .collect(),
+            min_backoff,
+            max_backoff,
+        }
+    }
+
+    pub fn record_success(&self, shard_id: ShardId) {
+        *self.shards[shard_id].lock() = ShardStatus::Healthy;
+        REMOTE_EXECUTOR_SHARD_HEALTHY
+            .with_label_values(&[&shard_id.to_string()])
+            .set(1);
+    }
+
+    pub fn record_failure(&self, shard_id: ShardId) {
+        let mut status = self.shards[shard_id].lock();
+        let consecutive_failures = match *status {
+            ShardStatus::Healthy => 1,
+            ShardStatus::Unhealthy {
+                consecutive_failures,
+                ..
+            } => consecutive_failures.saturating_add(1),
+        };
+        let backoff = self
+            .min_backoff
+            .saturating_mul(2u32.saturating_pow(consecutive_failures - 1))
+            .min(self.max_backoff);
+        *status = ShardStatus::Unhealthy {
+            consecutive_failures,
+            retry_at: Instant::now() + backoff,
+        };
+        REMOTE_EXECUTOR_SHARD_HEALTHY
+            .with_label_values(&[&shard_id.to_string()])
+            .set(0);
+    }
+
+    /// Returns true iff the shard is healthy, or is unhealthy but due to be retried
+    pub fn is_available(&self, shard_id: ShardId) -> bool {
+        match *self.shards[shard_id].lock() {
+            ShardStatus::Healthy => true,
+            ShardStatus::Unhealthy { retry_at, .. } => Instant::now() >= retry_at,
+        }
+    }
+
+    /// Returns true iff a block can be sent to the shards. As the shards
+    /// exchange cross-shard messages, every shard has to be available.
+    pub fn all_available(&self) -> bool {
+        (0..self.shards.len()).all(|shard_id| self.is_available(shard_id))
+    }
+
+    pub fn unhealthy_shards(&self) -> Vec<ShardId> {
+        self.shards
+            .iter()
+            .enumerate()
+            .filter(|(_, status)| *status.lock() != ShardStatus::Healthy)
+            .map(|(shard_id, _)| shard_id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_health() {
+        let tracker = ShardHealthTracker::new(2, Duration::from_secs(60), Duration::from_secs(600));
+        assert!(tracker.all_available());
+        assert!(tracker.unhealthy_shards().is_empty());
+
+        // A failed shard isn't available until its backoff expires
+        tracker.record_failure(1);
+        assert!(tracker.is_available(0));
+        assert!(!tracker.is_available(1));
+        assert!(!tracker.all_available());
+        assert_eq!(tracker.unhealthy_shards(), vec![1]);
+
+        tracker.record_success(1);
+        assert!(tracker.all_available());
+        assert!(tracker.unhealthy_shards().is_empty());
+    }
+
+    #[test]
+    fn test_shard_retried_after_backoff() {
+        let tracker = ShardHealthTracker::new(1, Duration::ZERO, Duration::ZERO);
+        tracker.record_failure(0);
+        tracker.record_failure(0);
+
+        // The shard is due to be retried, but is still unhealthy until it responds
+        assert!(tracker.is_available(0));
+        assert_eq!(tracker.unhealthy_shards(), vec![0]);
+    }
+}
@@ -92,3 +92,23 @@ fn test_sharded_block_executor_with_conflict() {
         executor_service.shutdown();
     });
 }
+
+#[test]
+fn test_sharded_block_executor_falls_back_when_shard_fails() {
+    use std::{thread, time::Duration};
+
+    let num_shards = 2;
+    let (executor_client, mut executor_services) =
+        create_thread_remote_executor_shards(num_shards, Some(2));
+    let executor_client = executor_client.with_execution_timeout(Duration::from_secs(2));
+
+    // Stop one of the shards, so that it never responds. The block is executed
+    // locally instead, and the results should be the same.
+    executor_services[1].shutdown();
+    thread::sleep(std::time::Duration::from_millis(10));
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    test_utils::test_sharded_block_executor_no_conflict(sharded_block_executor);
+
+    executor_services[0].shutdown();
+}
@@ -0,0 +1,179 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs sharded execution with the executor shards in separate processes on localhost.
+
+use aptos_block_partitioner::{v2::config::PartitionerV2Config, PartitionerConfig};
+use aptos_executor_service::{
+    process_executor_shards::ProcessExecutorShards, remote_executor_client::RemoteExecutorClient,
+};
+use aptos_language_e2e_tests::{
+    common_transactions::peer_to_peer_txn, data_store::FakeDataStore, executor::FakeExecutor,
+};
+use aptos_secure_net::network_controller::NetworkController;
+use aptos_types::{
+    block_executor::{
+        config::BlockExecutorConfigFromOnchain, partitioner::PartitionedTransactions,
+    },
+    transaction::{
+        analyzed_transaction::AnalyzedTransaction,
+        signature_verified_transaction::SignatureVerifiedTransaction, Transaction,
+    },
+};
+use aptos_vm::{sharded_block_executor::ShardedBlockExecutor, AptosVM, VMExecutor};
+use std::{
+    path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+const NUM_SHARDS: usize = 2;
+const SHARD_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn create_sharded_block_executor(
+    shards: &ProcessExecutorShards,
+) -> ShardedBlockExecutor<FakeDataStore, RemoteExecutorClient<FakeDataStore>> {
+    let controller = NetworkController::new(
+        "remote-executor-coordinator".to_string(),
+        shards.coordinator_address(),
+        5000,
+    );
+    let executor_client = RemoteExecutorClient::new(shards.shard_addresses(), controller, None)
+        .with_execution_timeout(Duration::from_secs(5))
+        .with_unhealthy_shard_backoff(Duration::from_secs(1), Duration::from_secs(2));
+    ShardedBlockExecutor::new(executor_client)
+}
+
+fn generate_p2p_transactions(
+    executor: &mut FakeExecutor,
+    num_txns: usize,
+) -> Vec<AnalyzedTransaction> {
+    (0..num_txns)
+        .map(|_| {
+            let sender = executor.create_raw_account_data(3_000_000_000, 0);
+            let receiver = executor.create_raw_account_data(3_000_000_000, 0);
+            executor.add_account_data(&sender);
+            executor.add_account_data(&receiver);
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                sender.sequence_number(),
+                1_000,
+                100,
+            ))
+            .into()
+        })
+        .collect()
+}
+
+/// Executes a block on the sharded executor, and checks the results match unsharded execution.
+fn execute_and_compare(
+    sharded_block_executor: &ShardedBlockExecutor<
+        FakeDataStore,
+        RemoteExecutorClient<FakeDataStore>,
+    >,
+) {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let transactions = generate_p2p_transactions(&mut executor, 100);
+    let partitioned_txns = PartitionerV2Config::default()
+        .build()
+        .partition(transactions, NUM_SHARDS);
+
+    let sharded_txn_output = sharded_block_executor
+        .execute_block(
+            Arc::new(executor.data_store().clone()),
+            partitioned_txns.clone(),
+            2,
+            BlockExecutorConfigFromOnchain::new_no_block_limit(),
+        )
+        .unwrap();
+
+    let txns: Vec<SignatureVerifiedTransaction> =
+        PartitionedTransactions::flatten(partitioned_txns)
+            .into_iter()
+            .map(|t| t.into_txn())
+            .collect();
+    let unsharded_txn_output =
+        AptosVM::execute_block_no_limit(&txns, executor.data_store()).unwrap();
+    assert_eq!(unsharded_txn_output.len(), sharded_txn_output.len());
+    for (unsharded, sharded) in unsharded_txn_output.iter().zip(sharded_txn_output.iter()) {
+        assert_eq!(unsharded.status(), sharded.status());
+        assert_eq!(unsharded.gas_used(), sharded.gas_used());
+    }
+}
+
+#[test]
+fn test_multi_process_sharded_execution_with_shard_failure() {
+    let mut shards = ProcessExecutorShards::spawn(
+        Path::new(env!("CARGO_BIN_EXE_aptos-executor-service")),
+        NUM_SHARDS,
+        2,
+    )
+    .unwrap();
+    shards.wait_until_ready(SHARD_STARTUP_TIMEOUT).unwrap();
+    let mut sharded_block_executor = create_sharded_block_executor(&shards);
+
+    // Execute a block on the remote shards
+    execute_and_compare(&sharded_block_executor);
+    assert_eq!(
+        sharded_block_executor
+            .executor_client()
+            .num_fallback_blocks(),
+        0
+    );
+
+    // Kill a shard. The next block times out on the remote shards and is
+    // executed locally, as are the following ones while the shard is down.
+    shards.kill_shard(1).unwrap();
+    execute_and_compare(&sharded_block_executor);
+    execute_and_compare(&sharded_block_executor);
+
+    sharded_block_executor.shutdown();
+    shards.shutdown();
+}
+
+#[test]
+fn test_multi_process_sharded_execution_with_shard_restart() {
+    let mut shards = ProcessExecutorShards::spawn(
+        Path::new(env!("CARGO_BIN_EXE_aptos-executor-service")),
+        NUM_SHARDS,
+        2,
+    )
+    .unwrap();
+    shards.wait_until_ready(SHARD_STARTUP_TIMEOUT).unwrap();
+    let mut sharded_block_executor = create_sharded_block_executor(&shards);
+    let executor_client = sharded_block_executor.executor_client();
+
+    execute_and_compare(&sharded_block_executor);
+    assert_eq!(executor_client.num_fallback_blocks(), 0);
+
+    // The block after a shard dies is executed locally
+    shards.kill_shard(1).unwrap();
+    execute_and_compare(&sharded_block_executor);
+    assert_eq!(executor_client.num_fallback_blocks(), 1);
+
+    // Once the shard is back and its backoff has passed, blocks run on the remote shards again.
+    // The other shard may still be waiting for the cross-shard messages of the failed block for a
+    // while, so allow a few more blocks to fall back first.
+    shards.restart_shard(1).unwrap();
+    shards.wait_until_ready(SHARD_STARTUP_TIMEOUT).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let mut ran_remotely = false;
+    while !ran_remotely && Instant::now() < deadline {
+        thread::sleep(Duration::from_secs(1));
+        let num_fallback_blocks = executor_client.num_fallback_blocks();
+        execute_and_compare(&sharded_block_executor);
+        ran_remotely = executor_client.num_fallback_blocks() == num_fallback_blocks;
+    }
+    assert!(ran_remotely, "Blocks kept falling back to local execution");
+    assert!(executor_client.unhealthy_shards().is_empty());
+
+    // Blocks keep running remotely afterwards
+    let num_fallback_blocks = executor_client.num_fallback_blocks();
+    execute_and_compare(&sharded_block_executor);
+    assert_eq!(executor_client.num_fallback_blocks(), num_fallback_blocks);
+
+    sharded_block_executor.shutdown();
+    shards.shutdown();
+}
@@ -24,6 +24,7 @@ use aptos_executor_types::{
 };
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_infallible::RwLock;
+use aptos_lifecycle_tracing::{LifecycleSpan, Stage};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::{IntGaugeHelper, TimerHelper};
 use aptos_scratchpad::SparseMerkleTree;
@@ -220,6 +221,12 @@ where
             block_id,
             transactions,
         } = block;
+        let mut lifecycle_span =
+            aptos_lifecycle_tracing::block_span(&block_id, Stage::ExecutorExecute);
+        lifecycle_span.set_attribute(
+            "num_transactions",
+            transactions.num_transactions().to_string(),
+        );
         let mut block_vec = self
             .block_tree
             .get_blocks_opt(&[block_id, parent_block_id])?;
@@ -259,6 +266,8 @@ where
                     )?
                 };
 
+                // Spans for the sampled transactions of the block, ending once the block is executed
+                let _transaction_spans = transaction_lifecycle_spans(&transactions, block_id);
                 let chunk_output = {
                     let _timer = VM_EXECUTE_BLOCK.start_timer();
                     fail_point!("executor::vm_execute_block", |_| {
@@ -397,6 +406,12 @@ where
             LogSchema::new(LogEntry::BlockExecutor).block_id(block_id),
             "commit_ledger"
         );
+        let mut lifecycle_span =
+            aptos_lifecycle_tracing::block_span(&block_id, Stage::ExecutorCommit);
+        lifecycle_span.set_attribute(
+            "version",
+            ledger_info_with_sigs.ledger_info().version().to_string(),
+        );
 
         // Check for any potential retries
         // TODO: do we still have such retries?
@@ -424,3 +439,28 @@ where
         Ok(())
     }
 }
+
+/// Starts the execution spans of the sampled transactions of an (unsharded) block
+fn transaction_lifecycle_spans(
+    transactions: &ExecutableTransactions,
+    block_id: HashValue,
+) -> Vec<LifecycleSpan> {
+    if !aptos_lifecycle_tracing::is_enabled() {
+        return vec![];
+    }
+    match transactions {
+        ExecutableTransactions::Unsharded(txns) => txns
+            .iter()
+            .filter_map(|txn| {
+                let mut span =
+                    aptos_lifecycle_tracing::transaction_span(&txn.hash(), Stage::ExecutorExecute);
+                if !span.is_recording() {
+                    return None;
+                }
+                span.set_attribute("block_id", block_id.to_hex());
+                Some(span)
+            })
+            .collect(),
+        ExecutableTransactions::Sharded(_) => vec![],
+    }
+}
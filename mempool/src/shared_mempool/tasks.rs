@@ -24,6 +24,7 @@ use aptos_config::network_id::PeerNetworkId;
 use aptos_consensus_types::common::RejectedTransactionSummary;
 use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
+use aptos_lifecycle_tracing::Stage;
 use aptos_logger::prelude::*;
 use aptos_mempool_notifications::CommittedTransaction;
 use aptos_metrics_core::HistogramTimer;
@@ -42,7 +43,7 @@ use rayon::prelude::*;
 use std::{
     cmp,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::runtime::Handle;
 
@@ -305,6 +306,7 @@ where
 {
     let mut statuses = vec![];
 
+    let lifecycle_start_time = SystemTime::now();
     let start_storage_read = Instant::now();
     let state_view = smp
         .db
@@ -367,6 +369,24 @@ where
         &mut statuses,
         client_submitted,
     );
+    if aptos_lifecycle_tracing::is_enabled() {
+        for (txn, (mempool_status, vm_status)) in &statuses {
+            let txn_hash = txn.committed_hash();
+            if !aptos_lifecycle_tracing::is_transaction_sampled(&txn_hash) {
+                continue;
+            }
+            aptos_lifecycle_tracing::record_transaction_stage(
+                &txn_hash,
+                Stage::MempoolInsert,
+                lifecycle_start_time,
+                vec![
+                    ("mempool_status", format!("{:?}", mempool_status.code)),
+                    ("vm_status", format!("{:?}", vm_status)),
+                    ("client_submitted", client_submitted.to_string()),
+                ],
+            );
+        }
+    }
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
     statuses
 }
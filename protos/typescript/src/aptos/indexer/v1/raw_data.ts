@@ -38,7 +38,15 @@ export interface GetTransactionsRequest {
    * Optional; number of transactions in each `TransactionsResponse` for current stream.
    * If not present, default to 1000. If larger than 1000, request will be rejected.
    */
-  batchSize?: bigint | undefined;
+  batchSize?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; if present, only transactions matching this filter are returned.
+   * JSON-encoded `BooleanTransactionFilter`, see the `aptos-transaction-filter` crate.
+   * Filters that are invalid or too complex will be rejected.
+   */
+  transactionFilter?: string | undefined;
 }
 
 /** TransactionsResponse is a batch of transactions. */
@@ -167,7 +175,7 @@ export const TransactionsInStorage = {
 };
 
 function createBaseGetTransactionsRequest(): GetTransactionsRequest {
-  return { startingVersion: undefined, transactionsCount: undefined, batchSize: undefined };
+  return { startingVersion: undefined, transactionsCount: undefined, batchSize: undefined, transactionFilter: undefined };
 }
 
 export const GetTransactionsRequest = {
@@ -190,6 +198,9 @@ export const GetTransactionsRequest = {
       }
       writer.uint32(24).uint64(message.batchSize.toString());
     }
+    if (message.transactionFilter !== undefined) {
+      writer.uint32(34).string(message.transactionFilter);
+    }
     return writer;
   },
 
@@ -221,6 +232,13 @@ export const GetTransactionsRequest = {
 
           message.batchSize = longToBigint(reader.uint64() as Long);
           continue;
+        case 4:
+          if (tag !== 34) {
+            break;
+          }
+
+          message.transactionFilter = reader.string();
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -269,6 +287,7 @@ export const GetTransactionsRequest = {
       startingVersion: isSet(object.startingVersion) ? BigInt(object.startingVersion) : undefined,
       transactionsCount: isSet(object.transactionsCount) ? BigInt(object.transactionsCount) : undefined,
       batchSize: isSet(object.batchSize) ? BigInt(object.batchSize) : undefined,
+      transactionFilter: isSet(object.transactionFilter) ? globalThis.String(object.transactionFilter) : undefined,
     };
   },
 
@@ -283,6 +302,9 @@ export const GetTransactionsRequest = {
     if (message.batchSize !== undefined) {
       obj.batchSize = message.batchSize.toString();
     }
+    if (message.transactionFilter !== undefined) {
+      obj.transactionFilter = message.transactionFilter;
+    }
     return obj;
   },
 
@@ -294,6 +316,7 @@ export const GetTransactionsRequest = {
     message.startingVersion = object.startingVersion ?? undefined;
     message.transactionsCount = object.transactionsCount ?? undefined;
     message.batchSize = object.batchSize ?? undefined;
+    message.transactionFilter = object.transactionFilter ?? undefined;
     return message;
   },
 };
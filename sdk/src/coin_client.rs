@@ -8,11 +8,12 @@ use crate::{
         language_storage::{ModuleId, TypeTag},
     },
     rest_client::{Client as ApiClient, PendingTransaction},
+    signer::AccountSigner,
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
         chain_id::ChainId,
-        transaction::{EntryFunction, TransactionPayload},
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
         LocalAccount,
     },
 };
@@ -39,9 +40,42 @@ impl<'a> CoinClient<'a> {
         amount: u64,
         options: Option<TransferOptions<'_>>,
     ) -> Result<PendingTransaction> {
+        // :!:>section_1
+        let transaction_builder = self.build_transfer(to_account, amount, options).await?;
+        let signed_txn = from_account.sign_with_transaction_builder(transaction_builder);
+        self.submit(&signed_txn).await
+        // <:!:section_1
+    }
+
+    /// Transfers coins from an account whose keys are managed by the given signer, e.g., a
+    /// multi-key account or a key held by an external custody system. The caller is responsible
+    /// for tracking the sequence number of `sender`.
+    pub async fn transfer_with_signer(
+        &self,
+        signer: &dyn AccountSigner,
+        sender: AccountAddress,
+        sequence_number: u64,
+        to_account: AccountAddress,
+        amount: u64,
+        options: Option<TransferOptions<'_>>,
+    ) -> Result<PendingTransaction> {
+        let signed_txn = self
+            .build_transfer(to_account, amount, options)
+            .await?
+            .sender(sender)
+            .sequence_number(sequence_number)
+            .build_and_sign(signer)?;
+        self.submit(&signed_txn).await
+    }
+
+    async fn build_transfer(
+        &self,
+        to_account: AccountAddress,
+        amount: u64,
+        options: Option<TransferOptions<'_>>,
+    ) -> Result<TransactionBuilder> {
         let options = options.unwrap_or_default();
 
-        // :!:>section_1
         let chain_id = self
             .api_client
             .get_index()
@@ -49,7 +83,7 @@ impl<'a> CoinClient<'a> {
             .context("Failed to get chain ID")?
             .inner()
             .chain_id;
-        let transaction_builder = TransactionBuilder::new(
+        Ok(TransactionBuilder::new(
             TransactionPayload::EntryFunction(EntryFunction::new(
                 ModuleId::new(AccountAddress::ONE, Identifier::new("coin").unwrap()),
                 Identifier::new("transfer").unwrap(),
@@ -66,18 +100,17 @@ impl<'a> CoinClient<'a> {
                 + options.timeout_secs,
             ChainId::new(chain_id),
         )
-        .sender(from_account.address())
-        .sequence_number(from_account.sequence_number())
         .max_gas_amount(options.max_gas_amount)
-        .gas_unit_price(options.gas_unit_price);
-        let signed_txn = from_account.sign_with_transaction_builder(transaction_builder);
+        .gas_unit_price(options.gas_unit_price))
+    }
+
+    async fn submit(&self, signed_txn: &SignedTransaction) -> Result<PendingTransaction> {
         Ok(self
             .api_client
-            .submit(&signed_txn)
+            .submit(signed_txn)
             .await
             .context("Failed to submit transfer transaction")?
             .into_inner())
-        // <:!:section_1
     }
 
     pub async fn get_account_balance(&self, account: &AccountAddress) -> Result<u64> {
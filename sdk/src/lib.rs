@@ -11,7 +11,9 @@
 //! * `crypto` - Types used for signing and verifying
 //! * `move_types` - Includes types used when interacting with the Move VM
 //! * `rest_client` - The Aptos API Client, used for sending requests to the Aptos Blockchain.
+//! * `signer` - Signers for single key, multi-ed25519 and multi-key accounts of any supported scheme
 //! * `transaction_builder` - Includes helpers for constructing transactions
+//! * `transaction_submitter` - Submits transactions from a pool of accounts at high throughput
 //! * `types` - Includes types for Aptos on-chain data structures
 //!
 //! ## Example
@@ -36,6 +38,10 @@ pub mod rest_client {
     pub use aptos_rest_client::*;
 }
 
+pub mod signer;
+
 pub mod transaction_builder;
 
+pub mod transaction_submitter;
+
 pub mod types;
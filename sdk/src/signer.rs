@@ -0,0 +1,778 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signer abstractions for the different account authentication schemes.
+//!
+//! An [`AccountSigner`] produces an [`AccountAuthenticator`] for an account, regardless of
+//! whether the account is backed by a legacy Ed25519 key, a single key of any supported
+//! scheme, a multi-ed25519 key or a K-of-N `MultiKey`. Individual keys are abstracted by
+//! [`KeySigner`], which can be implemented for keys that never leave an external custody
+//! system or hardware device.
+//!
+//! For multi-party setups where the participants do not share a process, use
+//! [`MultiSignatureCollector`] to gather partial signatures before assembling the final
+//! authenticator.
+
+use crate::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        secp256k1_ecdsa, secp256r1_ecdsa, signing_message, HashValue, PrivateKey, SigningKey,
+    },
+    types::{
+        transaction::{
+            authenticator::{
+                AccountAuthenticator, AnyPublicKey, AnySignature, AuthenticationKey, MultiKey,
+                MultiKeyAuthenticator, SingleKeyAuthenticator, TransactionAuthenticator,
+            },
+            webauthn::{AssertionSignature, PartialAuthenticatorAssertionResponse},
+            RawTransaction, RawTransactionWithData, SignedTransaction,
+        },
+        AccountKey,
+    },
+};
+use anyhow::{bail, ensure, Result};
+use p256::ecdsa::signature::Signer as _;
+use serde::Serialize;
+use sha2::Digest;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// The message an account signs: either a plain `RawTransaction`, or a `RawTransactionWithData`
+/// for multi-agent and fee payer transactions.
+#[derive(Clone, Copy, Debug)]
+pub enum SigningMessage<'a> {
+    RawTransaction(&'a RawTransaction),
+    RawTransactionWithData(&'a RawTransactionWithData),
+}
+
+impl<'a> SigningMessage<'a> {
+    /// Returns the bytes that are signed over, i.e., the domain separation prefix followed by the
+    /// BCS serialized message. External signers (e.g., a custody system) sign these bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            SigningMessage::RawTransaction(txn) => signing_message(*txn)?,
+            SigningMessage::RawTransactionWithData(txn) => signing_message(*txn)?,
+        })
+    }
+
+    /// Signs the message with a local signing key.
+    pub fn sign<K: SigningKey>(&self, key: &K) -> Result<K::SignatureMaterial> {
+        Ok(match self {
+            SigningMessage::RawTransaction(txn) => key.sign(*txn)?,
+            SigningMessage::RawTransactionWithData(txn) => key.sign(*txn)?,
+        })
+    }
+
+    /// Verifies a signature of any supported scheme over this message.
+    pub fn verify(&self, public_key: &AnyPublicKey, signature: &AnySignature) -> Result<()> {
+        match self {
+            SigningMessage::RawTransaction(txn) => signature.verify(public_key, *txn),
+            SigningMessage::RawTransactionWithData(txn) => signature.verify(public_key, *txn),
+        }
+    }
+}
+
+impl<'a> From<&'a RawTransaction> for SigningMessage<'a> {
+    fn from(txn: &'a RawTransaction) -> Self {
+        SigningMessage::RawTransaction(txn)
+    }
+}
+
+impl<'a> From<&'a RawTransactionWithData> for SigningMessage<'a> {
+    fn from(txn: &'a RawTransactionWithData) -> Self {
+        SigningMessage::RawTransactionWithData(txn)
+    }
+}
+
+/// Produces signatures for a single public key, to be used either as a single key account or as
+/// one of the keys of a `MultiKey` account.
+///
+/// Implement this trait to sign with keys held outside of the process, e.g., a secp256k1 key in
+/// a custody system. For secp256k1 ECDSA, the custody system is expected to sign the SHA3-256
+/// digest of [`SigningMessage::to_bytes`] and return a low-S normalized signature.
+pub trait KeySigner: fmt::Debug + Send + Sync {
+    /// The public key corresponding to this signer.
+    fn public_key(&self) -> AnyPublicKey;
+
+    /// Signs the given message.
+    fn sign(&self, message: SigningMessage) -> Result<AnySignature>;
+}
+
+impl KeySigner for Ed25519PrivateKey {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::ed25519(PrivateKey::public_key(self))
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AnySignature> {
+        Ok(AnySignature::ed25519(message.sign(self)?))
+    }
+}
+
+impl KeySigner for secp256k1_ecdsa::PrivateKey {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::secp256k1_ecdsa(PrivateKey::public_key(self))
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AnySignature> {
+        Ok(AnySignature::secp256k1_ecdsa(message.sign(self)?))
+    }
+}
+
+/// Signs with a local secp256r1 (P-256) key by producing a WebAuthn assertion, which is how
+/// secp256r1 signatures are authenticated on chain.
+///
+/// The assertion mirrors what a passkey authenticator produces: the challenge is the SHA3-256
+/// digest of the signing message and the signature is over the authenticator data concatenated
+/// with the SHA-256 digest of the client data JSON.
+pub struct Secp256r1WebAuthnSigner {
+    private_key: secp256r1_ecdsa::PrivateKey,
+    rp_id: String,
+    origin: String,
+}
+
+impl Secp256r1WebAuthnSigner {
+    /// WebAuthn flags: user present and user verified.
+    const AUTHENTICATOR_FLAGS: u8 = 0x05;
+
+    pub fn new(private_key: secp256r1_ecdsa::PrivateKey, rp_id: &str, origin: &str) -> Self {
+        Self {
+            private_key,
+            rp_id: rp_id.to_string(),
+            origin: origin.to_string(),
+        }
+    }
+
+    pub fn private_key(&self) -> &secp256r1_ecdsa::PrivateKey {
+        &self.private_key
+    }
+
+    fn authenticator_data(&self) -> Vec<u8> {
+        let mut authenticator_data = sha2::Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        authenticator_data.push(Self::AUTHENTICATOR_FLAGS);
+        // Signature counter, unused by the on-chain verification.
+        authenticator_data.extend(0u32.to_be_bytes());
+        authenticator_data
+    }
+
+    fn client_data_json(&self, message: &[u8]) -> Result<Vec<u8>> {
+        #[derive(Serialize)]
+        struct ClientData<'a> {
+            #[serde(rename = "type")]
+            ty: &'a str,
+            challenge: String,
+            origin: &'a str,
+            #[serde(rename = "crossOrigin")]
+            cross_origin: bool,
+        }
+
+        let challenge = HashValue::sha3_256_of(message);
+        Ok(serde_json::to_vec(&ClientData {
+            ty: "webauthn.get",
+            challenge: base64::encode_config(challenge.to_vec(), base64::URL_SAFE_NO_PAD),
+            origin: &self.origin,
+            cross_origin: false,
+        })?)
+    }
+}
+
+impl fmt::Debug for Secp256r1WebAuthnSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secp256r1WebAuthnSigner")
+            .field("public_key", &PrivateKey::public_key(&self.private_key))
+            .field("rp_id", &self.rp_id)
+            .field("origin", &self.origin)
+            .finish()
+    }
+}
+
+impl KeySigner for Secp256r1WebAuthnSigner {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::secp256r1_ecdsa(PrivateKey::public_key(&self.private_key))
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AnySignature> {
+        let authenticator_data = self.authenticator_data();
+        let client_data_json = self.client_data_json(&message.to_bytes()?)?;
+        let verification_data = [
+            authenticator_data.as_slice(),
+            sha2::Sha256::digest(&client_data_json).as_slice(),
+        ]
+        .concat();
+
+        let signing_key = p256::ecdsa::SigningKey::from_slice(&self.private_key.to_bytes())?;
+        let signature: p256::ecdsa::Signature = signing_key.sign(&verification_data);
+        let signature =
+            secp256r1_ecdsa::Signature::make_canonical_from_bytes_unchecked(&signature.to_bytes())?;
+
+        Ok(AnySignature::webauthn(
+            PartialAuthenticatorAssertionResponse::new(
+                AssertionSignature::Secp256r1Ecdsa { signature },
+                authenticator_data,
+                client_data_json,
+            ),
+        ))
+    }
+}
+
+/// The public key of an account, whatever its authentication scheme.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccountPublicKey {
+    /// A legacy Ed25519 key, authenticated with the `Ed25519` authenticator.
+    Ed25519(Ed25519PublicKey),
+    /// A single key of any scheme, authenticated with the `SingleKey` authenticator.
+    SingleKey(AnyPublicKey),
+    Multi(MultiPublicKey),
+}
+
+impl AccountPublicKey {
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            AccountPublicKey::Ed25519(public_key) => AuthenticationKey::ed25519(public_key),
+            AccountPublicKey::SingleKey(public_key) => {
+                AuthenticationKey::any_key(public_key.clone())
+            },
+            AccountPublicKey::Multi(public_key) => public_key.authentication_key(),
+        }
+    }
+}
+
+/// Produces the account authenticator for an account, whatever its authentication scheme.
+pub trait AccountSigner: fmt::Debug + Send + Sync {
+    /// The public key of the account.
+    fn public_key(&self) -> AccountPublicKey;
+
+    /// The authentication key of the account, which determines its default address.
+    fn authentication_key(&self) -> AuthenticationKey {
+        self.public_key().authentication_key()
+    }
+
+    /// Signs the given message on behalf of the account.
+    fn sign(&self, message: SigningMessage) -> Result<AccountAuthenticator>;
+}
+
+impl AccountSigner for AccountKey {
+    fn public_key(&self) -> AccountPublicKey {
+        AccountPublicKey::Ed25519(AccountKey::public_key(self).clone())
+    }
+
+    fn authentication_key(&self) -> AuthenticationKey {
+        AccountKey::authentication_key(self)
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AccountAuthenticator> {
+        Ok(AccountAuthenticator::ed25519(
+            AccountKey::public_key(self).clone(),
+            message.sign(self.private_key())?,
+        ))
+    }
+}
+
+/// An account authenticated by a single key of any scheme, using the `SingleKey` authenticator.
+#[derive(Debug)]
+pub struct SingleKeyAccount<S> {
+    signer: S,
+}
+
+impl<S: KeySigner> SingleKeyAccount<S> {
+    pub fn new(signer: S) -> Self {
+        Self { signer }
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+}
+
+impl<S: KeySigner> AccountSigner for SingleKeyAccount<S> {
+    fn public_key(&self) -> AccountPublicKey {
+        AccountPublicKey::SingleKey(self.signer.public_key())
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AccountAuthenticator> {
+        Ok(AccountAuthenticator::single_key(
+            SingleKeyAuthenticator::new(self.signer.public_key(), self.signer.sign(message)?),
+        ))
+    }
+}
+
+/// A legacy K-of-N multi-ed25519 account for which this process holds at least K private keys.
+#[derive(Debug)]
+pub struct MultiEd25519Account {
+    public_key: MultiEd25519PublicKey,
+    private_keys: Vec<(u8, Ed25519PrivateKey)>,
+}
+
+impl MultiEd25519Account {
+    /// Creates the account from its public key and the private keys held locally, each paired
+    /// with the index of its public key in `public_key`.
+    pub fn new(
+        public_key: MultiEd25519PublicKey,
+        private_keys: Vec<(u8, Ed25519PrivateKey)>,
+    ) -> Result<Self> {
+        let multi_key = MultiKey::from(public_key.clone());
+        ensure_unique_indexes(private_keys.iter().map(|(index, _)| *index))?;
+        for (index, private_key) in &private_keys {
+            ensure_key_at_index(&multi_key, *index, &KeySigner::public_key(private_key))?;
+        }
+        ensure!(
+            private_keys.len() >= *public_key.threshold() as usize,
+            "Not enough private keys to sign, {} < {}",
+            private_keys.len(),
+            public_key.threshold(),
+        );
+        Ok(Self {
+            public_key,
+            private_keys,
+        })
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+}
+
+impl AccountSigner for MultiEd25519Account {
+    fn public_key(&self) -> AccountPublicKey {
+        AccountPublicKey::Multi(MultiPublicKey::MultiEd25519(self.public_key.clone()))
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AccountAuthenticator> {
+        let signatures = self
+            .private_keys
+            .iter()
+            .take(*self.public_key.threshold() as usize)
+            .map(|(index, private_key)| Ok((message.sign(private_key)?, *index)))
+            .collect::<Result<Vec<(Ed25519Signature, u8)>>>()?;
+        Ok(AccountAuthenticator::multi_ed25519(
+            self.public_key.clone(),
+            MultiEd25519Signature::new(signatures)?,
+        ))
+    }
+}
+
+/// A K-of-N `MultiKey` account for which this process has access to at least K key signers.
+/// Key signers may be of mixed schemes, e.g., an Ed25519 key alongside a secp256k1 key held by
+/// a custody system.
+#[derive(Debug)]
+pub struct MultiKeyAccount {
+    multi_key: MultiKey,
+    signers: Vec<(u8, Box<dyn KeySigner>)>,
+}
+
+impl MultiKeyAccount {
+    /// Creates the account from its `MultiKey` and the available signers, each paired with the
+    /// index of its public key in `multi_key`. The signers may be given in any order.
+    pub fn new(multi_key: MultiKey, mut signers: Vec<(u8, Box<dyn KeySigner>)>) -> Result<Self> {
+        ensure_unique_indexes(signers.iter().map(|(index, _)| *index))?;
+        // Signatures are matched with the bitmap of signing keys, which is in index order.
+        signers.sort_by_key(|(index, _)| *index);
+        for (index, signer) in &signers {
+            ensure_key_at_index(&multi_key, *index, &signer.public_key())?;
+        }
+        ensure!(
+            signers.len() >= multi_key.signatures_required() as usize,
+            "Not enough signers to sign, {} < {}",
+            signers.len(),
+            multi_key.signatures_required(),
+        );
+        Ok(Self { multi_key, signers })
+    }
+
+    pub fn multi_key(&self) -> &MultiKey {
+        &self.multi_key
+    }
+}
+
+impl AccountSigner for MultiKeyAccount {
+    fn public_key(&self) -> AccountPublicKey {
+        AccountPublicKey::Multi(MultiPublicKey::MultiKey(self.multi_key.clone()))
+    }
+
+    fn sign(&self, message: SigningMessage) -> Result<AccountAuthenticator> {
+        let signatures = self
+            .signers
+            .iter()
+            .take(self.multi_key.signatures_required() as usize)
+            .map(|(index, signer)| Ok((*index, signer.sign(message)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(AccountAuthenticator::multi_key(MultiKeyAuthenticator::new(
+            self.multi_key.clone(),
+            signatures,
+        )?))
+    }
+}
+
+/// The public key of an account that requires several signatures.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MultiPublicKey {
+    MultiEd25519(MultiEd25519PublicKey),
+    MultiKey(MultiKey),
+}
+
+impl MultiPublicKey {
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            MultiPublicKey::MultiEd25519(public_key) => {
+                AuthenticationKey::multi_ed25519(public_key)
+            },
+            MultiPublicKey::MultiKey(multi_key) => AuthenticationKey::multi_key(multi_key.clone()),
+        }
+    }
+
+    fn to_multi_key(&self) -> MultiKey {
+        match self {
+            MultiPublicKey::MultiEd25519(public_key) => MultiKey::from(public_key.clone()),
+            MultiPublicKey::MultiKey(multi_key) => multi_key.clone(),
+        }
+    }
+}
+
+/// Collects partial signatures from the participants of a multi-ed25519 or `MultiKey` account,
+/// e.g., when each key is held by a different party, and assembles the account authenticator
+/// once enough signatures have been gathered.
+///
+/// Every partial signature is verified against the message as it is added, so an invalid
+/// signature is attributed to the party that produced it rather than failing at submission.
+#[derive(Debug)]
+pub struct MultiSignatureCollector<'a> {
+    public_key: MultiPublicKey,
+    multi_key: MultiKey,
+    message: SigningMessage<'a>,
+    signatures: BTreeMap<u8, AnySignature>,
+}
+
+impl<'a> MultiSignatureCollector<'a> {
+    pub fn new(public_key: MultiPublicKey, message: impl Into<SigningMessage<'a>>) -> Self {
+        Self {
+            multi_key: public_key.to_multi_key(),
+            public_key,
+            message: message.into(),
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// The message each participant has to sign.
+    pub fn message(&self) -> SigningMessage<'a> {
+        self.message
+    }
+
+    /// Adds a signature produced by the key at `index`.
+    pub fn add_signature(&mut self, index: u8, signature: AnySignature) -> Result<()> {
+        let public_key = self
+            .multi_key
+            .public_keys()
+            .get(index as usize)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Signature index is out of public key range, {} >= {}",
+                    index,
+                    self.multi_key.len()
+                )
+            })?;
+        if let MultiPublicKey::MultiEd25519(_) = self.public_key {
+            ensure!(
+                matches!(signature, AnySignature::Ed25519 { .. }),
+                "Multi-ed25519 accounts only accept Ed25519 signatures, got {}",
+                signature.name(),
+            );
+        }
+        ensure!(
+            !self.signatures.contains_key(&index),
+            "Duplicate signature index, {}",
+            index
+        );
+        self.message.verify(public_key, &signature)?;
+        self.signatures.insert(index, signature);
+        Ok(())
+    }
+
+    /// Signs with the given key signer and adds the resulting signature.
+    pub fn sign_with(&mut self, index: u8, signer: &dyn KeySigner) -> Result<()> {
+        let signature = signer.sign(self.message)?;
+        self.add_signature(index, signature)
+    }
+
+    /// Indexes of the keys that have contributed a signature so far.
+    pub fn signed_indexes(&self) -> Vec<u8> {
+        self.signatures.keys().copied().collect()
+    }
+
+    /// Returns true once enough signatures have been collected.
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.multi_key.signatures_required() as usize
+    }
+
+    /// Assembles the account authenticator from the collected signatures.
+    pub fn finish(self) -> Result<AccountAuthenticator> {
+        ensure!(
+            self.is_complete(),
+            "Not enough signatures collected, {} < {}",
+            self.signatures.len(),
+            self.multi_key.signatures_required(),
+        );
+        match self.public_key {
+            MultiPublicKey::MultiEd25519(public_key) => {
+                let signatures = self
+                    .signatures
+                    .into_iter()
+                    .map(|(index, signature)| match signature {
+                        AnySignature::Ed25519 { signature } => Ok((signature, index)),
+                        _ => bail!("Multi-ed25519 accounts only accept Ed25519 signatures"),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(AccountAuthenticator::multi_ed25519(
+                    public_key,
+                    MultiEd25519Signature::new(signatures)?,
+                ))
+            },
+            MultiPublicKey::MultiKey(multi_key) => Ok(AccountAuthenticator::multi_key(
+                MultiKeyAuthenticator::new(multi_key, self.signatures.into_iter().collect())?,
+            )),
+        }
+    }
+}
+
+/// Wraps the sender's account authenticator of a single sender transaction into a
+/// `SignedTransaction`, using the dedicated transaction authenticators for Ed25519 and
+/// multi-ed25519 senders.
+pub fn signed_transaction(
+    raw_txn: RawTransaction,
+    authenticator: AccountAuthenticator,
+) -> SignedTransaction {
+    match authenticator {
+        AccountAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        } => SignedTransaction::new(raw_txn, public_key, signature),
+        AccountAuthenticator::MultiEd25519 {
+            public_key,
+            signature,
+        } => SignedTransaction::new_multisig(raw_txn, public_key, signature),
+        authenticator => SignedTransaction::new_signed_transaction(
+            raw_txn,
+            TransactionAuthenticator::single_sender(authenticator),
+        ),
+    }
+}
+
+/// Signs a single sender transaction with the given account signer.
+pub fn sign_transaction(
+    signer: &dyn AccountSigner,
+    raw_txn: RawTransaction,
+) -> Result<SignedTransaction> {
+    let authenticator = signer.sign(SigningMessage::RawTransaction(&raw_txn))?;
+    Ok(signed_transaction(raw_txn, authenticator))
+}
+
+fn ensure_unique_indexes(indexes: impl Iterator<Item = u8>) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for index in indexes {
+        ensure!(seen.insert(index), "Duplicate signer index, {}", index);
+    }
+    Ok(())
+}
+
+fn ensure_key_at_index(multi_key: &MultiKey, index: u8, public_key: &AnyPublicKey) -> Result<()> {
+    match multi_key.public_keys().get(index as usize) {
+        Some(expected) if expected == public_key => Ok(()),
+        Some(_) => bail!("Signer does not match the public key at index {}", index),
+        None => bail!(
+            "Signer index is out of public key range, {} >= {}",
+            index,
+            multi_key.len()
+        ),
+    }
+}
+
+impl From<Ed25519PrivateKey> for SingleKeyAccount<Ed25519PrivateKey> {
+    fn from(private_key: Ed25519PrivateKey) -> Self {
+        Self::new(private_key)
+    }
+}
+
+impl From<secp256k1_ecdsa::PrivateKey> for SingleKeyAccount<secp256k1_ecdsa::PrivateKey> {
+    fn from(private_key: secp256k1_ecdsa::PrivateKey) -> Self {
+        Self::new(private_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{ed25519::Ed25519PublicKey, Uniform, ValidCryptoMaterial},
+        types::{
+            account_address::AccountAddress,
+            chain_id::ChainId,
+            transaction::{Script, TransactionPayload},
+        },
+    };
+    use rand::rngs::OsRng;
+
+    fn test_raw_txn(sender: AccountAddress) -> RawTransaction {
+        RawTransaction::new(
+            sender,
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        )
+    }
+
+    fn copy_key(key: &Ed25519PrivateKey) -> Ed25519PrivateKey {
+        Ed25519PrivateKey::try_from(key.to_bytes().as_slice()).unwrap()
+    }
+
+    fn assert_signs(signer: &dyn AccountSigner) {
+        let raw_txn = test_raw_txn(signer.authentication_key().account_address());
+        let signed_txn = sign_transaction(signer, raw_txn).unwrap();
+        signed_txn.verify_signature().unwrap();
+        assert_eq!(
+            signed_txn.authenticator().sender().authentication_key(),
+            Some(signer.authentication_key())
+        );
+    }
+
+    #[test]
+    fn test_single_key_signers() {
+        assert_signs(&AccountKey::generate(&mut OsRng));
+        assert_signs(&SingleKeyAccount::new(Ed25519PrivateKey::generate(
+            &mut OsRng,
+        )));
+        assert_signs(&SingleKeyAccount::new(
+            secp256k1_ecdsa::PrivateKey::generate(&mut OsRng),
+        ));
+        assert_signs(&SingleKeyAccount::new(Secp256r1WebAuthnSigner::new(
+            secp256r1_ecdsa::PrivateKey::generate(&mut OsRng),
+            "localhost",
+            "http://localhost:4000",
+        )));
+    }
+
+    #[test]
+    fn test_multi_key_account() {
+        let ed25519_key = Ed25519PrivateKey::generate(&mut OsRng);
+        let secp256k1_key = secp256k1_ecdsa::PrivateKey::generate(&mut OsRng);
+        let unused_key = Ed25519PrivateKey::generate(&mut OsRng);
+        let multi_key = MultiKey::new(
+            vec![
+                KeySigner::public_key(&ed25519_key),
+                KeySigner::public_key(&unused_key),
+                KeySigner::public_key(&secp256k1_key),
+            ],
+            2,
+        )
+        .unwrap();
+
+        // A signer at the wrong index is rejected.
+        let misplaced_key = secp256k1_ecdsa::PrivateKey::generate(&mut OsRng);
+        assert!(MultiKeyAccount::new(multi_key.clone(), vec![
+            (0, Box::new(misplaced_key) as Box<dyn KeySigner>),
+            (
+                2,
+                Box::new(secp256k1_ecdsa::PrivateKey::generate(&mut OsRng))
+            ),
+        ])
+        .is_err());
+        // Fewer signers than required is rejected.
+        assert!(MultiKeyAccount::new(multi_key.clone(), vec![(
+            1,
+            Box::new(unused_key) as Box<dyn KeySigner>
+        )])
+        .is_err());
+        // The same key can't be used twice to reach the threshold.
+        assert!(MultiKeyAccount::new(multi_key.clone(), vec![
+            (0, Box::new(copy_key(&ed25519_key)) as Box<dyn KeySigner>),
+            (0, Box::new(copy_key(&ed25519_key))),
+        ])
+        .is_err());
+
+        let account = MultiKeyAccount::new(multi_key, vec![
+            (0, Box::new(ed25519_key) as Box<dyn KeySigner>),
+            (2, Box::new(secp256k1_key)),
+        ])
+        .unwrap();
+        assert_signs(&account);
+    }
+
+    #[test]
+    fn test_multi_key_account_signers_out_of_order() {
+        let keys: Vec<_> = (0..3)
+            .map(|_| Ed25519PrivateKey::generate(&mut OsRng))
+            .collect();
+        let multi_key = MultiKey::new(keys.iter().map(KeySigner::public_key).collect(), 2).unwrap();
+        let account = MultiKeyAccount::new(multi_key, vec![
+            (2, Box::new(copy_key(&keys[2])) as Box<dyn KeySigner>),
+            (0, Box::new(copy_key(&keys[0]))),
+        ])
+        .unwrap();
+        assert_signs(&account);
+    }
+
+    #[test]
+    fn test_multi_ed25519_account() {
+        let private_keys: Vec<_> = (0..3)
+            .map(|_| Ed25519PrivateKey::generate(&mut OsRng))
+            .collect();
+        let public_key = MultiEd25519PublicKey::new(
+            private_keys.iter().map(Ed25519PublicKey::from).collect(),
+            2,
+        )
+        .unwrap();
+        // The same key can't be used twice to reach the threshold.
+        assert!(MultiEd25519Account::new(public_key.clone(), vec![
+            (1, copy_key(&private_keys[1])),
+            (1, copy_key(&private_keys[1])),
+        ])
+        .is_err());
+
+        let account = MultiEd25519Account::new(
+            public_key,
+            private_keys
+                .into_iter()
+                .enumerate()
+                .skip(1)
+                .map(|(index, key)| (index as u8, key))
+                .collect(),
+        )
+        .unwrap();
+        assert_signs(&account);
+    }
+
+    #[test]
+    fn test_collect_partial_signatures() {
+        let keys: Vec<Box<dyn KeySigner>> = vec![
+            Box::new(Ed25519PrivateKey::generate(&mut OsRng)),
+            Box::new(secp256k1_ecdsa::PrivateKey::generate(&mut OsRng)),
+            Box::new(secp256k1_ecdsa::PrivateKey::generate(&mut OsRng)),
+        ];
+        let multi_key =
+            MultiKey::new(keys.iter().map(|key| key.public_key()).collect(), 2).unwrap();
+        let public_key = MultiPublicKey::MultiKey(multi_key);
+        let raw_txn = test_raw_txn(public_key.authentication_key().account_address());
+        let other_txn = test_raw_txn(AccountAddress::ONE);
+
+        let mut collector = MultiSignatureCollector::new(public_key, &raw_txn);
+        collector.sign_with(2, keys[2].as_ref()).unwrap();
+        assert!(!collector.is_complete());
+
+        // Duplicates, signatures from the wrong key and over the wrong message are rejected.
+        assert!(collector.sign_with(2, keys[2].as_ref()).is_err());
+        assert!(collector.sign_with(0, keys[1].as_ref()).is_err());
+        let wrong_signature = keys[0].sign((&other_txn).into()).unwrap();
+        assert!(collector.add_signature(0, wrong_signature).is_err());
+
+        let signature = keys[0].sign((&raw_txn).into()).unwrap();
+        collector.add_signature(0, signature).unwrap();
+        assert!(collector.is_complete());
+        assert_eq!(collector.signed_indexes(), vec![0, 2]);
+
+        let signed_txn = signed_transaction(raw_txn, collector.finish().unwrap());
+        signed_txn.verify_signature().unwrap();
+    }
+}
@@ -4,11 +4,15 @@
 
 use crate::{
     move_types::account_address::AccountAddress,
+    signer::{self, AccountSigner},
     types::{
         chain_id::ChainId,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, TransactionPayload},
+        transaction::{
+            authenticator::AuthenticationKey, RawTransaction, SignedTransaction, TransactionPayload,
+        },
     },
 };
+use anyhow::Result;
 pub use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::{ed25519::Ed25519PublicKey, HashValue};
 use aptos_global_constants::{GAS_UNIT_PRICE, MAX_GAS_AMOUNT};
@@ -84,6 +88,15 @@ impl TransactionBuilder {
             self.chain_id,
         )
     }
+
+    /// Builds the transaction and signs it with the given signer. If no sender has been set, the
+    /// default address of the signer's authentication key is used.
+    pub fn build_and_sign(mut self, signer: &dyn AccountSigner) -> Result<SignedTransaction> {
+        if self.sender.is_none() {
+            self.sender = Some(signer.authentication_key().account_address());
+        }
+        signer::sign_transaction(signer, self.build())
+    }
 }
 
 #[derive(Clone, Debug)]
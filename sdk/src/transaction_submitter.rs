@@ -0,0 +1,910 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A high-throughput transaction submitter.
+//!
+//! The [`TransactionSubmitter`] owns a pool of [`LocalAccount`]s and keeps their sequence numbers
+//! in sync with the chain. Payloads handed to it are assigned to the least loaded account, signed,
+//! and submitted in batches through `/transactions/batch`. Submission failures are handled the
+//! way a long running client has to handle them: stale sequence numbers trigger a resync, a full
+//! mempool pauses submission, and sequence numbers freed by rejected or expired transactions are
+//! reused so that later transactions of the same account don't get stuck behind a gap.
+//!
+//! The outcome of every request (committed, expired or failed) is reported on a stream.
+
+use crate::{
+    crypto::HashValue,
+    move_types::{account_address::AccountAddress, vm_status::StatusCode},
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode},
+        error::RestError,
+        Client,
+    },
+    transaction_builder::TransactionFactory,
+    types::{
+        transaction::{ExecutionStatus, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use anyhow::{format_err, Result};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{task::JoinHandle, time::Instant};
+
+/// Maximum number of transactions the API accepts in a single batch submission.
+const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct TransactionSubmitterConfig {
+    /// Maximum number of transactions sent in one `/transactions/batch` request.
+    pub max_batch_size: usize,
+    /// Maximum number of uncommitted transactions per account. Mempool only accepts a limited
+    /// number of transactions per account, so this should stay below that limit.
+    pub max_in_flight_per_account: usize,
+    /// Number of requests that can be queued before `submit` starts waiting.
+    pub request_queue_size: usize,
+    /// How often the sequence numbers of accounts with uncommitted transactions are checked.
+    pub commit_poll_interval: Duration,
+    /// How long submission pauses after mempool reports that it is full.
+    pub mempool_full_backoff: Duration,
+    /// How many times a request is retried after a retriable failure (full mempool, stale
+    /// sequence number, expiration) before it is reported as failed.
+    pub max_retries: usize,
+}
+
+impl Default for TransactionSubmitterConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: MAX_BATCH_SIZE,
+            max_in_flight_per_account: 50,
+            request_queue_size: 1000,
+            commit_poll_interval: Duration::from_millis(500),
+            mempool_full_backoff: Duration::from_secs(1),
+            max_retries: 5,
+        }
+    }
+}
+
+pub type RequestId = u64;
+
+/// The final outcome of a submitted request.
+#[derive(Debug)]
+pub struct TransactionOutcome {
+    pub request_id: RequestId,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    /// Hash of the last signed transaction for this request.
+    pub hash: HashValue,
+    pub status: TransactionStatus,
+}
+
+#[derive(Debug)]
+pub enum TransactionStatus {
+    /// The transaction was committed at `version`. It may still have failed during execution,
+    /// see `execution_status`.
+    Committed {
+        version: u64,
+        execution_status: ExecutionStatus,
+        gas_used: u64,
+    },
+    /// The transaction expired before being committed, after exhausting its retries.
+    Expired,
+    /// The transaction was rejected at submission.
+    Failed(String),
+}
+
+impl TransactionStatus {
+    pub fn is_success(&self) -> bool {
+        matches!(self, TransactionStatus::Committed {
+            execution_status: ExecutionStatus::Success,
+            ..
+        })
+    }
+}
+
+/// Stream of outcomes, one per submitted request.
+pub type TransactionOutcomeStream = mpsc::UnboundedReceiver<TransactionOutcome>;
+
+/// Handle used to submit payloads to a running [`TransactionSubmitter`]. Once all handles are
+/// dropped, the submitter finishes the outstanding requests and returns its accounts.
+#[derive(Clone, Debug)]
+pub struct TransactionSubmitterHandle {
+    requests: mpsc::Sender<Request>,
+    next_request_id: Arc<AtomicU64>,
+}
+
+impl TransactionSubmitterHandle {
+    /// Queues a payload for submission. Waits while the request queue is full, which gives
+    /// callers backpressure when they produce payloads faster than the chain accepts them.
+    pub async fn submit(&self, payload: TransactionPayload) -> Result<RequestId> {
+        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        self.requests
+            .clone()
+            .send(Request {
+                id,
+                payload,
+                retries: 0,
+            })
+            .await
+            .map_err(|_| format_err!("Transaction submitter has stopped"))?;
+        Ok(id)
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    id: RequestId,
+    payload: TransactionPayload,
+    retries: usize,
+}
+
+#[derive(Debug)]
+struct InFlight {
+    request: Request,
+    txn: SignedTransaction,
+    submissions: usize,
+}
+
+#[derive(Debug)]
+struct AccountState {
+    account: LocalAccount,
+    /// The lowest sequence number that has never been assigned.
+    next_sequence_number: u64,
+    /// Sequence numbers below `next_sequence_number` that were freed by rejected or expired
+    /// transactions. They are assigned first, so that later transactions are not stuck behind a
+    /// sequence number gap.
+    free_sequence_numbers: BTreeSet<u64>,
+    in_flight: BTreeMap<u64, InFlight>,
+}
+
+impl AccountState {
+    fn new(account: LocalAccount) -> Self {
+        Self {
+            next_sequence_number: account.sequence_number(),
+            account,
+            free_sequence_numbers: BTreeSet::new(),
+            in_flight: BTreeMap::new(),
+        }
+    }
+
+    fn assign_sequence_number(&mut self) -> u64 {
+        if let Some(sequence_number) = self.free_sequence_numbers.pop_first() {
+            return sequence_number;
+        }
+        let sequence_number = self.next_sequence_number;
+        self.next_sequence_number += 1;
+        sequence_number
+    }
+
+    fn release_sequence_number(&mut self, sequence_number: u64) {
+        self.free_sequence_numbers.insert(sequence_number);
+        while let Some(last) = self.free_sequence_numbers.last().copied() {
+            if last + 1 != self.next_sequence_number {
+                break;
+            }
+            self.free_sequence_numbers.remove(&last);
+            self.next_sequence_number = last;
+        }
+    }
+
+    /// Updates the local view with the on-chain sequence number: everything below it has been
+    /// consumed, by us or by someone else using the same account.
+    fn sync_sequence_number(&mut self, on_chain_sequence_number: u64) {
+        self.next_sequence_number = self.next_sequence_number.max(on_chain_sequence_number);
+        self.free_sequence_numbers = self
+            .free_sequence_numbers
+            .split_off(&on_chain_sequence_number);
+    }
+
+    /// The sequence number the account should continue from once the submitter stops.
+    fn resume_sequence_number(&self) -> u64 {
+        self.free_sequence_numbers
+            .first()
+            .copied()
+            .unwrap_or(self.next_sequence_number)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum FailureKind {
+    SequenceNumberTooOld,
+    MempoolFull,
+    Rejected,
+}
+
+fn classify_failure(error: &AptosError) -> FailureKind {
+    match error.error_code {
+        AptosErrorCode::SequenceNumberTooOld => FailureKind::SequenceNumberTooOld,
+        AptosErrorCode::MempoolIsFull => FailureKind::MempoolFull,
+        AptosErrorCode::VmError
+            if error.vm_error_code == Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD as u64) =>
+        {
+            FailureKind::SequenceNumberTooOld
+        },
+        _ => FailureKind::Rejected,
+    }
+}
+
+/// Returns the on-chain sequence number of `address` and the ledger timestamp in seconds at
+/// which it was read. Accounts that don't exist yet have sequence number 0.
+async fn get_sequence_number(client: &Client, address: AccountAddress) -> Result<(u64, u64)> {
+    match client.get_account_bcs(address).await {
+        Ok(response) => Ok((
+            response.inner().sequence_number(),
+            Duration::from_micros(response.state().timestamp_usecs).as_secs(),
+        )),
+        Err(RestError::Api(error)) if error.error.error_code == AptosErrorCode::AccountNotFound => {
+            let timestamp_usecs = error
+                .state
+                .as_ref()
+                .map_or(0, |state| state.timestamp_usecs);
+            Ok((0, Duration::from_micros(timestamp_usecs).as_secs()))
+        },
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub struct TransactionSubmitter {
+    client: Client,
+    txn_factory: TransactionFactory,
+    config: TransactionSubmitterConfig,
+    accounts: Vec<AccountState>,
+}
+
+impl TransactionSubmitter {
+    /// Creates a submitter for the given accounts, syncing their sequence numbers with the chain.
+    pub async fn new(
+        client: Client,
+        txn_factory: TransactionFactory,
+        accounts: Vec<LocalAccount>,
+        config: TransactionSubmitterConfig,
+    ) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(format_err!("At least one account is required"));
+        }
+        if config.max_batch_size == 0 || config.max_batch_size > MAX_BATCH_SIZE {
+            return Err(format_err!(
+                "max_batch_size must be between 1 and {}",
+                MAX_BATCH_SIZE
+            ));
+        }
+        for account in &accounts {
+            let (sequence_number, _) = get_sequence_number(&client, account.address()).await?;
+            account.set_sequence_number(sequence_number);
+        }
+        Ok(Self {
+            client,
+            txn_factory,
+            config,
+            accounts: accounts.into_iter().map(AccountState::new).collect(),
+        })
+    }
+
+    /// Starts submitting in the background. Returns the handle to submit payloads with, the
+    /// stream of outcomes, and a join handle resolving to the accounts once all handles have been
+    /// dropped and every outstanding request has an outcome.
+    pub fn start(
+        self,
+    ) -> (
+        TransactionSubmitterHandle,
+        TransactionOutcomeStream,
+        JoinHandle<Vec<LocalAccount>>,
+    ) {
+        let (request_tx, request_rx) = mpsc::channel(self.config.request_queue_size);
+        let (outcome_tx, outcome_rx) = mpsc::unbounded();
+        let driver = SubmitterDriver {
+            client: self.client,
+            txn_factory: self.txn_factory,
+            config: self.config,
+            accounts: self.accounts,
+            queue: VecDeque::new(),
+            resubmit: VecDeque::new(),
+            outcomes: outcome_tx,
+            paused_until: Instant::now(),
+            next_poll: Instant::now(),
+        };
+        let join_handle = tokio::spawn(driver.run(request_rx));
+        let handle = TransactionSubmitterHandle {
+            requests: request_tx,
+            next_request_id: Arc::new(AtomicU64::new(0)),
+        };
+        (handle, outcome_rx, join_handle)
+    }
+}
+
+struct SubmitterDriver {
+    client: Client,
+    txn_factory: TransactionFactory,
+    config: TransactionSubmitterConfig,
+    accounts: Vec<AccountState>,
+    /// Requests waiting to be assigned to an account.
+    queue: VecDeque<Request>,
+    /// Signed transactions whose batch submission failed in transport, identified by account
+    /// index and sequence number. Resubmitting the same signed transaction is idempotent.
+    resubmit: VecDeque<(usize, u64)>,
+    outcomes: mpsc::UnboundedSender<TransactionOutcome>,
+    paused_until: Instant,
+    next_poll: Instant,
+}
+
+impl SubmitterDriver {
+    async fn run(mut self, mut requests: mpsc::Receiver<Request>) -> Vec<LocalAccount> {
+        let mut requests_closed = false;
+        loop {
+            while !requests_closed
+                && self.queue.len() < self.config.max_batch_size
+                && self.has_capacity()
+            {
+                match requests.try_next() {
+                    Ok(Some(request)) => self.queue.push_back(request),
+                    Ok(None) => requests_closed = true,
+                    // No request is ready yet.
+                    Err(_) => break,
+                }
+            }
+
+            let has_in_flight = self
+                .accounts
+                .iter()
+                .any(|state| !state.in_flight.is_empty());
+            if requests_closed && self.queue.is_empty() && !has_in_flight {
+                break;
+            }
+
+            let now = Instant::now();
+            let can_submit = now >= self.paused_until
+                && (!self.resubmit.is_empty() || (!self.queue.is_empty() && self.has_capacity()));
+            if can_submit {
+                self.submit_batch().await;
+                continue;
+            }
+            if has_in_flight && now >= self.next_poll {
+                self.poll_commits().await;
+                continue;
+            }
+
+            let mut wake_up = self.next_poll.max(now + Duration::from_millis(1));
+            if !self.queue.is_empty() && self.has_capacity() {
+                wake_up = wake_up.min(self.paused_until);
+            }
+            if requests_closed
+                || !self.has_capacity()
+                || self.queue.len() >= self.config.max_batch_size
+            {
+                tokio::time::sleep_until(wake_up).await;
+            } else {
+                tokio::select! {
+                    request = requests.next() => match request {
+                        Some(request) => self.queue.push_back(request),
+                        None => requests_closed = true,
+                    },
+                    _ = tokio::time::sleep_until(wake_up) => {},
+                }
+            }
+        }
+
+        self.accounts
+            .into_iter()
+            .map(|state| {
+                state
+                    .account
+                    .set_sequence_number(state.resume_sequence_number());
+                state.account
+            })
+            .collect()
+    }
+
+    fn has_capacity(&self) -> bool {
+        self.least_loaded_account().is_some()
+    }
+
+    fn least_loaded_account(&self) -> Option<usize> {
+        self.accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| state.in_flight.len() < self.config.max_in_flight_per_account)
+            .min_by_key(|(_, state)| state.in_flight.len())
+            .map(|(index, _)| index)
+    }
+
+    fn emit(
+        &self,
+        state: &AccountState,
+        sequence_number: u64,
+        request_id: RequestId,
+        hash: HashValue,
+        status: TransactionStatus,
+    ) {
+        // The receiver may have been dropped by a caller that doesn't care about outcomes.
+        let _ = self.outcomes.unbounded_send(TransactionOutcome {
+            request_id,
+            sender: state.account.address(),
+            sequence_number,
+            hash,
+            status,
+        });
+    }
+
+    /// Puts a request back in the queue, or reports it with `status` once it ran out of retries.
+    fn retry_or_fail(
+        &mut self,
+        account_index: usize,
+        sequence_number: u64,
+        mut request: Request,
+        hash: HashValue,
+        status: TransactionStatus,
+    ) {
+        if request.retries < self.config.max_retries {
+            request.retries += 1;
+            self.queue.push_front(request);
+        } else {
+            self.emit(
+                &self.accounts[account_index],
+                sequence_number,
+                request.id,
+                hash,
+                status,
+            );
+        }
+    }
+
+    async fn submit_batch(&mut self) {
+        let mut batch = vec![];
+        while batch.len() < self.config.max_batch_size {
+            let Some((account_index, sequence_number)) = self.resubmit.pop_front() else {
+                break;
+            };
+            if let Some(in_flight) = self.accounts[account_index]
+                .in_flight
+                .get_mut(&sequence_number)
+            {
+                in_flight.submissions += 1;
+                batch.push((account_index, sequence_number));
+            }
+        }
+        while batch.len() < self.config.max_batch_size {
+            let Some(account_index) = self.least_loaded_account() else {
+                break;
+            };
+            let Some(request) = self.queue.pop_front() else {
+                break;
+            };
+            let state = &mut self.accounts[account_index];
+            let sequence_number = state.assign_sequence_number();
+            let raw_txn = self
+                .txn_factory
+                .payload(request.payload.clone())
+                .sender(state.account.address())
+                .sequence_number(sequence_number)
+                .build();
+            let txn = state.account.sign_transaction(raw_txn);
+            state.in_flight.insert(sequence_number, InFlight {
+                request,
+                txn,
+                submissions: 1,
+            });
+            batch.push((account_index, sequence_number));
+        }
+        if batch.is_empty() {
+            return;
+        }
+
+        let txns: Vec<_> = batch
+            .iter()
+            .map(|(account_index, sequence_number)| {
+                self.accounts[*account_index].in_flight[sequence_number]
+                    .txn
+                    .clone()
+            })
+            .collect();
+        let failures = match self.client.submit_batch_bcs(&txns).await {
+            Ok(response) => response.into_inner().transaction_failures,
+            Err(_) => {
+                // Whether any of the transactions made it to mempool is unknown. Resubmit the
+                // same signed transactions, and leave the ones that ran out of attempts to the
+                // commit poll, which will report them as committed or expired.
+                for (account_index, sequence_number) in batch {
+                    let in_flight = &self.accounts[account_index].in_flight[&sequence_number];
+                    if in_flight.submissions <= self.config.max_retries {
+                        self.resubmit.push_back((account_index, sequence_number));
+                    }
+                }
+                self.paused_until = Instant::now() + self.config.mempool_full_backoff;
+                return;
+            },
+        };
+
+        let mut to_sync = BTreeSet::new();
+        for failure in failures {
+            let Some(&(account_index, sequence_number)) = batch.get(failure.transaction_index)
+            else {
+                continue;
+            };
+            let Some(in_flight) = self.accounts[account_index]
+                .in_flight
+                .remove(&sequence_number)
+            else {
+                continue;
+            };
+            let hash = in_flight.txn.committed_hash();
+            let status = TransactionStatus::Failed(failure.error.to_string());
+            match classify_failure(&failure.error) {
+                FailureKind::SequenceNumberTooOld => {
+                    // Someone else consumed this sequence number; resync before reusing the account.
+                    to_sync.insert(account_index);
+                    self.retry_or_fail(
+                        account_index,
+                        sequence_number,
+                        in_flight.request,
+                        hash,
+                        status,
+                    );
+                },
+                FailureKind::MempoolFull => {
+                    self.accounts[account_index].release_sequence_number(sequence_number);
+                    self.paused_until = Instant::now() + self.config.mempool_full_backoff;
+                    self.retry_or_fail(
+                        account_index,
+                        sequence_number,
+                        in_flight.request,
+                        hash,
+                        status,
+                    );
+                },
+                FailureKind::Rejected => {
+                    self.accounts[account_index].release_sequence_number(sequence_number);
+                    self.emit(
+                        &self.accounts[account_index],
+                        sequence_number,
+                        in_flight.request.id,
+                        hash,
+                        status,
+                    );
+                },
+            }
+        }
+
+        for account_index in to_sync {
+            let address = self.accounts[account_index].account.address();
+            if let Ok((sequence_number, _)) = get_sequence_number(&self.client, address).await {
+                self.accounts[account_index].sync_sequence_number(sequence_number);
+            }
+        }
+    }
+
+    async fn poll_commits(&mut self) {
+        for account_index in 0..self.accounts.len() {
+            if self.accounts[account_index].in_flight.is_empty() {
+                continue;
+            }
+            let address = self.accounts[account_index].account.address();
+            let Ok((on_chain_sequence_number, ledger_timestamp_secs)) =
+                get_sequence_number(&self.client, address).await
+            else {
+                continue;
+            };
+            self.resolve_committed(account_index, on_chain_sequence_number)
+                .await;
+            self.accounts[account_index].sync_sequence_number(on_chain_sequence_number);
+            self.resolve_expired(
+                account_index,
+                on_chain_sequence_number,
+                ledger_timestamp_secs,
+            );
+        }
+        self.next_poll = Instant::now() + self.config.commit_poll_interval;
+    }
+
+    /// Reports the outcome of the in-flight transactions below the on-chain sequence number.
+    async fn resolve_committed(&mut self, account_index: usize, on_chain_sequence_number: u64) {
+        let state = &self.accounts[account_index];
+        let Some(&start) = state.in_flight.keys().next() else {
+            return;
+        };
+        if start >= on_chain_sequence_number {
+            return;
+        }
+        let limit = (on_chain_sequence_number - start).min(MAX_BATCH_SIZE as u64);
+        let Ok(response) = self
+            .client
+            .get_account_transactions_bcs(state.account.address(), Some(start), Some(limit as u16))
+            .await
+        else {
+            return;
+        };
+        let committed: HashMap<_, _> = response
+            .into_inner()
+            .into_iter()
+            .map(|txn| (txn.info.transaction_hash(), txn))
+            .collect();
+
+        let sequence_numbers: Vec<_> = self.accounts[account_index]
+            .in_flight
+            .range(start..start + limit)
+            .map(|(sequence_number, _)| *sequence_number)
+            .collect();
+        for sequence_number in sequence_numbers {
+            let in_flight = self.accounts[account_index]
+                .in_flight
+                .remove(&sequence_number)
+                .expect("sequence number must be in flight");
+            let hash = in_flight.txn.committed_hash();
+            let status = match committed.get(&hash) {
+                Some(txn) => TransactionStatus::Committed {
+                    version: txn.version,
+                    execution_status: txn.info.status().clone(),
+                    gas_used: txn.info.gas_used(),
+                },
+                None => TransactionStatus::Failed(
+                    "Sequence number was consumed by another transaction".to_string(),
+                ),
+            };
+            self.emit(
+                &self.accounts[account_index],
+                sequence_number,
+                in_flight.request.id,
+                hash,
+                status,
+            );
+        }
+    }
+
+    /// Retries the in-flight transactions that expired without being committed.
+    ///
+    /// Sequence numbers below `on_chain_sequence_number` have been consumed, possibly by our own
+    /// transaction, so they stay in flight until `resolve_committed` reports them. Retrying them
+    /// could execute a request twice.
+    fn resolve_expired(
+        &mut self,
+        account_index: usize,
+        on_chain_sequence_number: u64,
+        ledger_timestamp_secs: u64,
+    ) {
+        let expired: Vec<_> = self.accounts[account_index]
+            .in_flight
+            .range(on_chain_sequence_number..)
+            .filter(|(_, in_flight)| {
+                in_flight.txn.expiration_timestamp_secs() <= ledger_timestamp_secs
+            })
+            .map(|(sequence_number, _)| *sequence_number)
+            .collect();
+        // Retry in reverse, so that the lowest sequence number ends up first in the queue.
+        for sequence_number in expired.into_iter().rev() {
+            let state = &mut self.accounts[account_index];
+            let in_flight = state
+                .in_flight
+                .remove(&sequence_number)
+                .expect("sequence number must be in flight");
+            state.release_sequence_number(sequence_number);
+            let hash = in_flight.txn.committed_hash();
+            self.retry_or_fail(
+                account_index,
+                sequence_number,
+                in_flight.request,
+                hash,
+                TransactionStatus::Expired,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rest_client::aptos_api_types::{
+            TransactionOnChainData, TransactionsBatchSubmissionResult, X_APTOS_BLOCK_HEIGHT,
+            X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION,
+            X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_BLOCK_HEIGHT,
+        },
+        transaction_builder::aptos_stdlib,
+        types::{
+            account_config::AccountResource,
+            chain_id::ChainId,
+            event::{EventHandle, EventKey},
+            transaction::{Transaction, TransactionInfo},
+            write_set::WriteSet,
+        },
+    };
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer, Then,
+    };
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use url::Url;
+
+    /// Responds with BCS and the ledger state headers, at a ledger timestamp after the expiration
+    /// of every transaction built by the test.
+    fn respond_bcs<T: serde::Serialize>(then: Then, body: &T) {
+        let timestamp_usecs = (SystemTime::now() + Duration::from_secs(3600))
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        then.status(200)
+            .header(X_APTOS_CHAIN_ID, ChainId::test().id().to_string())
+            .header(X_APTOS_LEDGER_VERSION, "100")
+            .header(X_APTOS_LEDGER_OLDEST_VERSION, "0")
+            .header(X_APTOS_LEDGER_TIMESTAMP, timestamp_usecs.to_string())
+            .header(X_APTOS_EPOCH, "1")
+            .header(X_APTOS_BLOCK_HEIGHT, "10")
+            .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "0")
+            .body(bcs::to_bytes(body).unwrap());
+    }
+
+    fn test_driver(
+        server: &MockServer,
+        account: LocalAccount,
+    ) -> (SubmitterDriver, TransactionOutcomeStream) {
+        let (outcomes, outcome_rx) = mpsc::unbounded();
+        let driver = SubmitterDriver {
+            client: Client::new(Url::parse(&server.base_url()).unwrap()),
+            txn_factory: TransactionFactory::new(ChainId::test()),
+            config: TransactionSubmitterConfig::default(),
+            accounts: vec![AccountState::new(account)],
+            queue: VecDeque::new(),
+            resubmit: VecDeque::new(),
+            outcomes,
+            paused_until: Instant::now(),
+            next_poll: Instant::now(),
+        };
+        (driver, outcome_rx)
+    }
+
+    #[test]
+    fn test_sequence_number_bookkeeping() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        account.set_sequence_number(10);
+        let mut state = AccountState::new(account);
+
+        assert_eq!(state.assign_sequence_number(), 10);
+        assert_eq!(state.assign_sequence_number(), 11);
+        assert_eq!(state.assign_sequence_number(), 12);
+
+        // A freed sequence number in the middle is reused first.
+        state.release_sequence_number(11);
+        assert_eq!(state.resume_sequence_number(), 11);
+        assert_eq!(state.assign_sequence_number(), 11);
+        assert_eq!(state.assign_sequence_number(), 13);
+
+        // Freeing the highest sequence numbers shrinks the range instead of leaving holes.
+        state.release_sequence_number(12);
+        state.release_sequence_number(13);
+        assert!(state.free_sequence_numbers.is_empty());
+        assert_eq!(state.assign_sequence_number(), 12);
+
+        // Syncing with the chain drops holes that were consumed elsewhere.
+        state.release_sequence_number(10);
+        state.sync_sequence_number(11);
+        assert!(state.free_sequence_numbers.is_empty());
+        state.sync_sequence_number(20);
+        assert_eq!(state.assign_sequence_number(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_expired_transactions_below_on_chain_sequence_number_are_not_retried() {
+        let server = MockServer::start();
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let address = account.address();
+        let account_path = format!("/v1/accounts/{}", address.to_hex());
+        let (mut driver, mut outcome_rx) = test_driver(&server, account);
+
+        server.mock(|when, then| {
+            when.method(POST).path("/v1/transactions/batch");
+            respond_bcs(then, &TransactionsBatchSubmissionResult {
+                transaction_failures: vec![],
+            });
+        });
+        for id in 0..2 {
+            driver.queue.push_back(Request {
+                id,
+                payload: aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1),
+                retries: 0,
+            });
+        }
+        driver.submit_batch().await;
+        assert_eq!(driver.accounts[0].in_flight.len(), 2);
+
+        // Both transactions expired, and sequence number 0 was consumed on chain, but which
+        // transaction consumed it can't be looked up.
+        server.mock(|when, then| {
+            when.method(GET).path(account_path.as_str());
+            respond_bcs(
+                then,
+                &AccountResource::new(
+                    1,
+                    address.to_vec(),
+                    EventHandle::new(EventKey::new(0, address), 0),
+                    EventHandle::new(EventKey::new(1, address), 0),
+                ),
+            );
+        });
+        let mut transactions = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("{}/transactions", account_path));
+            then.status(500);
+        });
+        driver.poll_commits().await;
+
+        // Only the transaction that certainly wasn't committed is retried.
+        let state = &driver.accounts[0];
+        assert_eq!(state.in_flight.keys().copied().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(state.next_sequence_number, 1);
+        assert_eq!(driver.queue.len(), 1);
+        assert_eq!(driver.queue[0].id, 1);
+        assert!(outcome_rx.try_next().is_err());
+
+        // Once the lookup succeeds, sequence number 0 is reported as committed.
+        let txn = state.in_flight[&0].txn.clone();
+        transactions.delete();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("{}/transactions", account_path));
+            respond_bcs(then, &vec![TransactionOnChainData {
+                version: 42,
+                info: TransactionInfo::new(
+                    txn.committed_hash(),
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    None,
+                    10,
+                    ExecutionStatus::Success,
+                ),
+                transaction: Transaction::UserTransaction(txn),
+                events: vec![],
+                accumulator_root_hash: HashValue::zero(),
+                changes: WriteSet::default(),
+            }]);
+        });
+        driver.poll_commits().await;
+
+        assert!(driver.accounts[0].in_flight.is_empty());
+        let outcome = outcome_rx.try_next().unwrap().unwrap();
+        assert_eq!(outcome.request_id, 0);
+        assert!(matches!(outcome.status, TransactionStatus::Committed {
+            version: 42,
+            ..
+        }));
+        assert!(outcome_rx.try_next().is_err());
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert_eq!(
+            classify_failure(&AptosError::new_with_error_code(
+                "",
+                AptosErrorCode::SequenceNumberTooOld
+            )),
+            FailureKind::SequenceNumberTooOld
+        );
+        assert_eq!(
+            classify_failure(&AptosError::new_with_vm_status(
+                "",
+                AptosErrorCode::VmError,
+                StatusCode::SEQUENCE_NUMBER_TOO_OLD
+            )),
+            FailureKind::SequenceNumberTooOld
+        );
+        assert_eq!(
+            classify_failure(&AptosError::new_with_error_code(
+                "",
+                AptosErrorCode::MempoolIsFull
+            )),
+            FailureKind::MempoolFull
+        );
+        assert_eq!(
+            classify_failure(&AptosError::new_with_vm_status(
+                "",
+                AptosErrorCode::VmError,
+                StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE
+            )),
+            FailureKind::Rejected
+        );
+    }
+}
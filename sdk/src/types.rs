@@ -9,10 +9,14 @@ use crate::{
         traits::Uniform,
         CryptoMaterialError,
     },
+    signer::{self, AccountPublicKey, AccountSigner},
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::AuthenticationKey, RawTransaction, RawTransactionWithData,
+            SignedTransaction,
+        },
     },
 };
 use anyhow::{Context, Result};
@@ -329,6 +333,189 @@ impl LocalAccount {
     }
 }
 
+/// An account whose transactions are signed by an [`AccountSigner`], e.g., a multi-key account or
+/// a secp256k1 key held by an external custody system. Unlike [`LocalAccount`], the private keys
+/// are not necessarily available to this process, so signing can fail and is surfaced as an error.
+#[derive(Debug)]
+pub struct SignerAccount {
+    address: AccountAddress,
+    signer: Box<dyn AccountSigner>,
+    /// Latest known sequence number of the account, it can be different from validator.
+    sequence_number: AtomicU64,
+}
+
+impl SignerAccount {
+    pub fn new<S: AccountSigner + 'static>(
+        address: AccountAddress,
+        signer: S,
+        sequence_number: u64,
+    ) -> Self {
+        Self {
+            address,
+            signer: Box::new(signer),
+            sequence_number: AtomicU64::new(sequence_number),
+        }
+    }
+
+    /// Create an account at the default address of the signer's authentication key.
+    pub fn from_signer<S: AccountSigner + 'static>(signer: S, sequence_number: u64) -> Self {
+        let address = signer.authentication_key().account_address();
+        Self::new(address, signer, sequence_number)
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn signer(&self) -> &dyn AccountSigner {
+        self.signer.as_ref()
+    }
+
+    pub fn public_key(&self) -> AccountPublicKey {
+        self.signer.public_key()
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        self.signer.authentication_key()
+    }
+
+    pub fn sign_transaction(&self, txn: RawTransaction) -> Result<SignedTransaction> {
+        signer::sign_transaction(self.signer.as_ref(), txn)
+    }
+
+    /// Signs the transaction with the next sequence number, which is only consumed if signing
+    /// succeeds.
+    pub fn sign_with_transaction_builder(
+        &self,
+        builder: TransactionBuilder,
+    ) -> Result<SignedTransaction> {
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        let signed_txn = self.sign_transaction(raw_txn)?;
+        self.increment_sequence_number();
+        Ok(signed_txn)
+    }
+
+    pub fn sign_multi_agent_with_transaction_builder(
+        &self,
+        secondary_signers: Vec<&Self>,
+        builder: TransactionBuilder,
+    ) -> Result<SignedTransaction> {
+        let secondary_signer_addresses: Vec<_> = secondary_signers
+            .iter()
+            .map(|signer| signer.address())
+            .collect();
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        let message = RawTransactionWithData::new_multi_agent(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+        );
+        let sender_authenticator = self
+            .signer
+            .sign((&message).into())
+            .context("Failed to sign multi agent txn as sender")?;
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.signer.sign((&message).into()))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to sign multi agent txn as secondary signer")?;
+        self.increment_sequence_number();
+        Ok(SignedTransaction::new_multi_agent(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+        ))
+    }
+
+    pub fn sign_fee_payer_with_transaction_builder(
+        &self,
+        secondary_signers: Vec<&Self>,
+        fee_payer_signer: &Self,
+        builder: TransactionBuilder,
+    ) -> Result<SignedTransaction> {
+        let secondary_signer_addresses: Vec<_> = secondary_signers
+            .iter()
+            .map(|signer| signer.address())
+            .collect();
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        let message = RawTransactionWithData::new_fee_payer(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+            fee_payer_signer.address(),
+        );
+        let sender_authenticator = self
+            .signer
+            .sign((&message).into())
+            .context("Failed to sign fee payer txn as sender")?;
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.signer.sign((&message).into()))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to sign fee payer txn as secondary signer")?;
+        let fee_payer_authenticator = fee_payer_signer
+            .signer
+            .sign((&message).into())
+            .context("Failed to sign fee payer txn as fee payer")?;
+        self.increment_sequence_number();
+        Ok(SignedTransaction::new_fee_payer(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+            fee_payer_signer.address(),
+            fee_payer_authenticator,
+        ))
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number.load(Ordering::SeqCst)
+    }
+
+    pub fn increment_sequence_number(&self) -> u64 {
+        self.sequence_number.fetch_add(1, Ordering::SeqCst)
+    }
+
+    pub fn decrement_sequence_number(&self) -> u64 {
+        self.sequence_number.fetch_sub(1, Ordering::SeqCst)
+    }
+
+    pub fn set_sequence_number(&self, sequence_number: u64) {
+        self.sequence_number
+            .store(sequence_number, Ordering::SeqCst);
+    }
+
+    /// Replaces the signer, e.g., after the authentication key of the account has been rotated
+    /// on chain, and returns the previous one.
+    pub fn rotate_signer<S: AccountSigner + 'static>(
+        &mut self,
+        new_signer: S,
+    ) -> Box<dyn AccountSigner> {
+        std::mem::replace(&mut self.signer, Box::new(new_signer))
+    }
+}
+
+impl TransactionSigner for SignerAccount {
+    fn sign_transaction(&self, txn: RawTransaction) -> Result<SignedTransaction> {
+        SignerAccount::sign_transaction(self, txn)
+    }
+
+    fn sign_with_transaction_builder(
+        &mut self,
+        builder: TransactionBuilder,
+    ) -> Result<SignedTransaction> {
+        SignerAccount::sign_with_transaction_builder(self, builder)
+    }
+}
+
 /// Types of hardware wallet the SDK currently supports
 #[derive(Debug)]
 pub enum HardwareWalletType {
@@ -862,4 +1049,55 @@ mod tests {
         // Test invalid private key hex literal.
         assert!(LocalAccount::from_private_key("invalid_private_key", 0).is_err());
     }
+
+    /// A signer backed by an external system that is unavailable.
+    #[derive(Debug)]
+    struct UnavailableSigner(AccountKey);
+
+    impl AccountSigner for UnavailableSigner {
+        fn public_key(&self) -> AccountPublicKey {
+            AccountSigner::public_key(&self.0)
+        }
+
+        fn sign(
+            &self,
+            _message: crate::signer::SigningMessage,
+        ) -> Result<transaction::authenticator::AccountAuthenticator> {
+            anyhow::bail!("Custody system is unavailable")
+        }
+    }
+
+    #[test]
+    fn test_signer_account() {
+        let factory =
+            crate::transaction_builder::TransactionFactory::new(chain_id::ChainId::test());
+        let builder = || factory.transfer(AccountAddress::ONE, 1);
+
+        let key = AccountKey::generate(&mut rand::rngs::OsRng);
+        let public_key = key.public_key().clone();
+        let mut account = SignerAccount::from_signer(key, 5);
+        assert_eq!(account.public_key(), AccountPublicKey::Ed25519(public_key));
+        assert_eq!(
+            account.address(),
+            account.authentication_key().account_address()
+        );
+        let txn = account.sign_with_transaction_builder(builder()).unwrap();
+        txn.verify_signature().unwrap();
+        assert_eq!(txn.sequence_number(), 5);
+        assert_eq!(account.sequence_number(), 6);
+
+        // Signing failures are surfaced and don't consume a sequence number.
+        let key = AccountKey::generate(&mut rand::rngs::OsRng);
+        account.rotate_signer(UnavailableSigner(key));
+        assert!(account.sign_with_transaction_builder(builder()).is_err());
+        let secondary = SignerAccount::from_signer(AccountKey::generate(&mut rand::rngs::OsRng), 0);
+        assert!(secondary
+            .sign_multi_agent_with_transaction_builder(vec![&account], builder())
+            .is_err());
+        assert!(secondary
+            .sign_fee_payer_with_transaction_builder(vec![], &account, builder())
+            .is_err());
+        assert_eq!(account.sequence_number(), 6);
+        assert_eq!(secondary.sequence_number(), 0);
+    }
 }
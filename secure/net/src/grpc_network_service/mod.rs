@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network_controller::{metrics::NETWORK_HANDLER_TIMER, Message, MessageType};
-use aptos_logger::{error, info};
+use aptos_logger::{error, info, warn};
 use aptos_protos::remote_executor::v1::{
     network_message_service_client::NetworkMessageServiceClient,
     network_message_service_server::{NetworkMessageService, NetworkMessageServiceServer},
@@ -13,6 +13,7 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 use tokio::{runtime::Runtime, sync::oneshot};
 use tonic::{
@@ -21,6 +22,10 @@ use tonic::{
 };
 
 const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 80;
+// A message that can't be delivered after this many attempts is dropped, so that an unreachable
+// remote (e.g. an executor shard that died) doesn't bring down the sender.
+const MAX_SEND_ATTEMPTS: u32 = 3;
+const SEND_RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
 pub struct GRPCNetworkMessageServiceServerWrapper {
     inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
@@ -143,19 +148,28 @@ impl GRPCNetworkMessageServiceClientWrapper {
         message: Message,
         mt: &MessageType,
     ) {
-        let request = tonic::Request::new(NetworkMessage {
-            message: message.data,
-            message_type: mt.get_type(),
-        });
-        // TODO: Retry with exponential backoff on failures
-        match self.remote_channel.simple_msg_exchange(request).await {
-            Ok(_) => {},
-            Err(e) => {
-                panic!(
-                    "Error '{}' sending message to {} on node {:?}",
-                    e, self.remote_addr, sender_addr
-                );
-            },
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            let request = tonic::Request::new(NetworkMessage {
+                message: message.data.clone(),
+                message_type: mt.get_type(),
+            });
+            match self.remote_channel.simple_msg_exchange(request).await {
+                Ok(_) => return,
+                Err(e) if attempt < MAX_SEND_ATTEMPTS => {
+                    warn!(
+                        "Error '{}' sending message to {} on node {:?} (attempt {}), retrying",
+                        e, self.remote_addr, sender_addr, attempt
+                    );
+                    // Back off exponentially between attempts
+                    tokio::time::sleep(SEND_RETRY_BACKOFF * 2u32.pow(attempt - 1)).await;
+                },
+                Err(e) => {
+                    error!(
+                        "Error '{}' sending message to {} on node {:?}, dropping the message after {} attempts",
+                        e, self.remote_addr, sender_addr, attempt
+                    );
+                },
+            }
         }
     }
 }
@@ -15,8 +15,11 @@ use crate::{
     },
     CompiledModule,
 };
-use move_core_types::vm_status::StatusCode;
-use std::collections::BTreeSet;
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    vm_status::StatusCode,
+};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -78,17 +81,47 @@ impl Compatibility {
         old_module: &CompiledModule,
         new_module: &CompiledModule,
     ) -> PartialVMResult<()> {
+        let errors = self.incompatibilities(old_module, new_module);
+        if !errors.is_empty() {
+            Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
+                    format!(
+                        "Module update failure: new module not compatible with \
+                        existing module in `{}`: {}",
+                        old_module.self_id(),
+                        errors
+                            .iter()
+                            .map(|error| error.message.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns all incompatible changes of `new_module` relative to old module `old_module`.
+    pub fn incompatibilities(
+        &self,
+        old_module: &CompiledModule,
+        new_module: &CompiledModule,
+    ) -> Vec<Incompatibility> {
         let mut errors = vec![];
 
         // module's name and address are unchanged
         if old_module.address() != new_module.address() {
-            errors.push(format!(
+            errors.push(Incompatibility::of_module(format!(
                 "module address changed to `{}`",
                 new_module.address()
-            ));
+            )));
         }
         if old_module.name() != new_module.name() {
-            errors.push(format!("module name changed to `{}`", new_module.name()));
+            errors.push(Incompatibility::of_module(format!(
+                "module name changed to `{}`",
+                new_module.name()
+            )));
         }
 
         let old_view = ModuleView::new(old_module);
@@ -102,30 +135,39 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    errors.push(format!("removed struct `{}`", old_struct.name()));
-                    break;
+                    errors.push(Incompatibility::of_item(
+                        old_struct.name(),
+                        format!("removed struct `{}`", old_struct.name()),
+                    ));
+                    continue;
                 },
             };
 
             if !struct_abilities_compatible(old_struct.abilities(), new_struct.abilities()) {
-                errors.push(format!(
-                    "removed abilities `{}` from struct `{}`",
-                    old_struct.abilities().setminus(new_struct.abilities()),
-                    old_struct.name()
+                errors.push(Incompatibility::of_item(
+                    old_struct.name(),
+                    format!(
+                        "removed abilities `{}` from struct `{}`",
+                        old_struct.abilities().setminus(new_struct.abilities()),
+                        old_struct.name()
+                    ),
                 ));
             }
             if !struct_type_parameters_compatible(
                 old_struct.type_parameters(),
                 new_struct.type_parameters(),
             ) {
-                errors.push(format!(
-                    "changed type parameters of struct `{}`",
-                    old_struct.name()
+                errors.push(Incompatibility::of_item(
+                    old_struct.name(),
+                    format!("changed type parameters of struct `{}`", old_struct.name()),
                 ));
             }
             // Layout of old and new struct need to be compatible
             if self.check_struct_layout && !struct_layout_compatible(&old_struct, new_struct) {
-                errors.push(format!("changed layout of struct `{}`", old_struct.name()));
+                errors.push(Incompatibility::of_item(
+                    old_struct.name(),
+                    format!("changed layout of struct `{}`", old_struct.name()),
+                ));
             }
         }
 
@@ -155,7 +197,10 @@ impl Compatibility {
                     if !matches!(old_func.visibility(), Visibility::Friend)
                         || self.check_friend_linking
                     {
-                        errors.push(format!("removed function `{}`", old_func.name()));
+                        errors.push(Incompatibility::of_item(
+                            old_func.name(),
+                            format!("removed function `{}`", old_func.name()),
+                        ));
                     }
                     continue;
                 },
@@ -211,7 +256,10 @@ impl Compatibility {
                 None
             };
             if let Some(msg) = error_msg {
-                errors.push(format!("{} of function `{}`", msg, old_func.name()));
+                errors.push(Incompatibility::of_item(
+                    old_func.name(),
+                    format!("{} of function `{}`", msg, old_func.name()),
+                ));
             }
         }
 
@@ -226,32 +274,50 @@ impl Compatibility {
             let new_friend_module_ids: BTreeSet<_> =
                 new_module.immediate_friends().iter().cloned().collect();
             if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-                errors.push(format!(
+                errors.push(Incompatibility::of_module(format!(
                     "removed friend declaration {}",
                     old_friend_module_ids
                         .difference(&new_friend_module_ids)
                         .map(|id| format!("`{}`", id))
                         .collect::<Vec<_>>()
                         .join(" and ")
-                ))
+                )))
             }
         }
 
-        if !errors.is_empty() {
-            Err(
-                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
-                    format!(
-                        "Module update failure: new module not compatible with \
-                        existing module in `{}`: {}",
-                        old_view.id(),
-                        errors.join(", ")
-                    ),
-                ),
-            )
-        } else {
-            Ok(())
+        errors
+    }
+}
+
+/// An incompatible change of a module, as found by `Compatibility::incompatibilities`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Incompatibility {
+    /// The struct or function of the old module which changed, or `None` if the change concerns
+    /// the module as a whole, like its name or its friends.
+    pub item: Option<Identifier>,
+    pub message: String,
+}
+
+impl Incompatibility {
+    fn of_module(message: String) -> Self {
+        Self {
+            item: None,
+            message,
         }
     }
+
+    fn of_item(item: &IdentStr, message: String) -> Self {
+        Self {
+            item: Some(item.to_owned()),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
 // When upgrading, the new abilities must be a superset of the old abilities.
@@ -1,7 +1,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*};
+use crate::{
+    compatibility::{Compatibility, Incompatibility},
+    file_format::*,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use std::convert::TryFrom;
 
@@ -119,3 +122,55 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn all_incompatibilities_reported() {
+    let script_module = mk_module(Visibility::DEPRECATED_SCRIPT);
+    let mut renamed_module = mk_module(Visibility::Private as u8);
+    renamed_module.identifiers[0] = Identifier::new("N").unwrap();
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&script_module, &renamed_module),
+        vec![
+            Incompatibility {
+                item: None,
+                message: "module name changed to `N`".to_string(),
+            },
+            Incompatibility {
+                item: Some(Identifier::new("fn").unwrap()),
+                message: "changed visibility of function `fn`".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn all_removed_structs_reported() {
+    let mut old_module = mk_module(Visibility::Private as u8);
+    for name in ["S", "T"] {
+        old_module.identifiers.push(Identifier::new(name).unwrap());
+        old_module.struct_handles.push(StructHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex((old_module.identifiers.len() - 1) as u16),
+            abilities: AbilitySet::EMPTY,
+            type_parameters: vec![],
+        });
+        old_module.struct_defs.push(StructDefinition {
+            struct_handle: StructHandleIndex((old_module.struct_handles.len() - 1) as u16),
+            field_information: StructFieldInformation::Native,
+        });
+    }
+    let new_module = mk_module(Visibility::Private as u8);
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&old_module, &new_module),
+        vec![
+            Incompatibility {
+                item: Some(Identifier::new("S").unwrap()),
+                message: "removed struct `S`".to_string(),
+            },
+            Incompatibility {
+                item: Some(Identifier::new("T").unwrap()),
+                message: "removed struct `T`".to_string(),
+            },
+        ]
+    );
+}
@@ -50,6 +50,26 @@ pub fn get_move_compiler_block_v1_from_env() -> bool {
     read_bool_env_var(MOVE_COMPILER_BLOCK_V1_ENV_VAR) || read_bool_env_var(MVC_BLOCK_V1_ENV_VAR)
 }
 
+/// An environment variable which can be set to make package builds use the shared build cache
+/// of compiled dependencies.
+pub const MOVE_BUILD_CACHE_ENV_VAR: &str = "MOVE_BUILD_CACHE";
+
+pub fn get_move_build_cache_from_env() -> bool {
+    read_bool_env_var(MOVE_BUILD_CACHE_ENV_VAR)
+}
+
+/// An environment variable which can be set to change the number of compiled packages kept in
+/// the build cache. The least recently used packages are removed beyond it.
+const MOVE_BUILD_CACHE_MAX_ENTRIES_ENV_VAR: &str = "MOVE_BUILD_CACHE_MAX_ENTRIES";
+const DEFAULT_MOVE_BUILD_CACHE_MAX_ENTRIES: usize = 100;
+
+pub fn get_move_build_cache_max_entries_from_env() -> usize {
+    std::env::var(MOVE_BUILD_CACHE_MAX_ENTRIES_ENV_VAR)
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MOVE_BUILD_CACHE_MAX_ENTRIES)
+}
+
 pub fn read_env_var(v: &str) -> String {
     std::env::var(v).unwrap_or_else(|_| String::new())
 }
@@ -0,0 +1,65 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module checks the lint rules which a package defines in its manifest, on the model AST.
+//! See `LintRule` for the rules, and `lint_pattern` for the patterns they report.
+
+use crate::{
+    lint_common::{lint_skip_names_from_attributes, report_lint, LintRule},
+    Options,
+};
+use move_model::model::{FunctionEnv, GlobalEnv};
+use std::collections::BTreeSet;
+
+/// Check the lint rules of the package on all functions of the primary target modules.
+pub fn checker(env: &mut GlobalEnv) {
+    let Some(options) = env.get_extension::<Options>() else {
+        return;
+    };
+    let rules = &options.lint_config.rules;
+    if rules.is_empty() {
+        return;
+    }
+    for module in env.get_modules() {
+        if module.is_primary_target() {
+            let module_skips = lint_skip_names_from_attributes(env, module.get_attributes());
+            for function in module.get_functions() {
+                if function.is_native() {
+                    continue;
+                }
+                check_function(&function, rules, &module_skips);
+            }
+        }
+    }
+}
+
+/// Check the lint rules which apply to `function` on its code.
+fn check_function(function: &FunctionEnv, rules: &[LintRule], module_skips: &[String]) {
+    let env = function.module_env.env;
+    let mut skips = BTreeSet::from_iter(lint_skip_names_from_attributes(
+        env,
+        function.get_attributes(),
+    ));
+    skips.extend(module_skips.iter().cloned());
+    let applicable_rules = rules
+        .iter()
+        .filter(|rule| {
+            !skips.contains(&rule.name)
+                && !rule
+                    .allowed_in
+                    .iter()
+                    .any(|pattern| pattern.matches_function(function))
+        })
+        .collect::<Vec<_>>();
+    if applicable_rules.is_empty() {
+        return;
+    }
+    let Some(def) = function.get_def() else {
+        return;
+    };
+    for rule in applicable_rules {
+        rule.pattern.find_matches(env, def, &mut |id| {
+            report_lint(env, &rule.name, &env.get_node_loc(id), &rule.message)
+        });
+    }
+}
@@ -15,6 +15,7 @@ pub mod flow_insensitive_checkers;
 pub mod function_checker;
 pub mod inliner;
 pub mod lambda_lifter;
+pub mod lint_rules;
 pub mod model_ast_lints;
 pub mod recursive_struct_checker;
 pub mod rewrite_target;
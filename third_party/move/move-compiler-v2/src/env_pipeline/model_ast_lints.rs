@@ -13,8 +13,7 @@ mod unnecessary_boolean_identity_comparison;
 mod unnecessary_numerical_extreme_comparison;
 mod while_true;
 
-use crate::lint_common::{lint_skips_from_attributes, LintChecker};
-use move_compiler::shared::known_attributes::LintAttribute;
+use crate::lint_common::{lint_skips_from_attributes, report_lint, LintChecker};
 use move_model::{
     ast::ExpData,
     model::{FunctionEnv, GlobalEnv, Loc},
@@ -51,15 +50,9 @@ trait ExpressionLinter {
     /// Potentially emit lint warnings using `self.warning()`.
     fn visit_expr_post(&mut self, _env: &GlobalEnv, _expr: &ExpData) {}
 
-    /// Emit a lint warning with the `msg` highlighting the `loc`.
+    /// Emit a lint warning with the `msg` highlighting the `loc`, at the configured level.
     fn warning(&self, env: &GlobalEnv, loc: &Loc, msg: &str) {
-        env.lint_diag_with_notes(loc, msg, vec![
-            format!(
-                "To suppress this warning, annotate the function/module with the attribute `#[{}({})]`.",
-                LintAttribute::SKIP,
-                self.get_lint_checker()
-            ),
-        ]);
+        report_lint(env, &self.get_lint_checker().to_string(), loc, msg)
     }
 }
 
@@ -7,6 +7,7 @@ pub mod env_pipeline;
 mod experiments;
 mod file_format_generator;
 pub mod lint_common;
+pub mod lint_pattern;
 pub mod logging;
 pub mod options;
 pub mod pipeline;
@@ -15,7 +16,7 @@ pub mod plan_builder;
 use crate::{
     env_pipeline::{
         acquires_checker, ast_simplifier, cyclic_instantiation_checker, flow_insensitive_checkers,
-        function_checker, inliner, lambda_lifter, lambda_lifter::LambdaLiftingOptions,
+        function_checker, inliner, lambda_lifter, lambda_lifter::LambdaLiftingOptions, lint_rules,
         model_ast_lints, recursive_struct_checker, rewrite_target::RewritingScope,
         seqs_in_binop_checker, spec_checker, spec_rewriter, unused_params_checker,
         EnvProcessorPipeline,
@@ -337,6 +338,7 @@ pub fn check_and_rewrite_pipeline<'a, 'b>(
         // Perform all the model AST lint checks before AST transformations, to be closer
         // in form to the user code.
         env_pipeline.add("model AST lints", model_ast_lints::checker);
+        env_pipeline.add("lint rules", lint_rules::checker);
     }
 
     if options.experiment_on(Experiment::INLINING) {
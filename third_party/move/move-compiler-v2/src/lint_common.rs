@@ -4,9 +4,17 @@
 //! This module contains common code useful for lint checkers at various stages
 //! of the compilation pipeline.
 
+use crate::{
+    lint_pattern::{ExpPattern, NamePattern},
+    Options,
+};
+use codespan_reporting::diagnostic::Severity;
 use move_compiler::shared::known_attributes::LintAttribute;
-use move_model::{ast::Attribute, model::GlobalEnv};
-use std::str::FromStr;
+use move_model::{
+    ast::Attribute,
+    model::{GlobalEnv, Loc},
+};
+use std::{collections::BTreeMap, str::FromStr};
 use strum_macros::{Display, EnumString};
 
 /// Enumeration of all the lint checks that can be performed.
@@ -30,9 +38,103 @@ pub enum LintChecker {
     WhileTrue,
 }
 
+/// The level at which a lint check is reported.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum LintLevel {
+    /// The lint check is not performed.
+    Allow,
+    /// Violations are reported as warnings.
+    #[default]
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+/// The lint configuration of a package, as given in its manifest.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LintConfig {
+    /// The levels of lint checks, by name, for the checks which are not reported at the
+    /// default level `warn`. Names can refer to both `LintChecker`s and `LintRule`s.
+    pub levels: BTreeMap<String, LintLevel>,
+    /// The lint rules defined by the package.
+    pub rules: Vec<LintRule>,
+}
+
+impl LintConfig {
+    /// Returns the level of the lint check with the given name.
+    pub fn level(&self, lint: &str) -> LintLevel {
+        self.levels.get(lint).copied().unwrap_or_default()
+    }
+
+    /// Returns whether `name` is the name of a lint check, either one of the compiler or a rule.
+    pub fn is_known_lint(&self, name: &str) -> bool {
+        LintChecker::from_str(name).is_ok() || self.rules.iter().any(|rule| rule.name == name)
+    }
+}
+
+/// A lint check defined by a package rather than the compiler: the expressions matching
+/// `pattern` are reported with `message`, unless the function containing them matches any of
+/// `allowed_in`. For example, a rule with the pattern
+/// `call(aptos_framework::object::generate_signer)` and `allowed_in = ["*::*::create_*"]`
+/// restricts object signers to constructors. See `lint_pattern` for the pattern language.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintRule {
+    pub name: String,
+    pub message: String,
+    pub pattern: ExpPattern,
+    pub allowed_in: Vec<NamePattern>,
+}
+
+/// Returns the level configured for the lint check `lint` in the current compilation.
+fn lint_level(env: &GlobalEnv, lint: &str) -> LintLevel {
+    env.get_extension::<Options>()
+        .map(|options| options.lint_config.level(lint))
+        .unwrap_or_default()
+}
+
+/// Returns whether `name` is the name of a lint check in the current compilation.
+fn is_known_lint(env: &GlobalEnv, name: &str) -> bool {
+    match env.get_extension::<Options>() {
+        Some(options) => options.lint_config.is_known_lint(name),
+        None => LintChecker::from_str(name).is_ok(),
+    }
+}
+
+/// Reports a violation of the lint check `lint` with the `msg` highlighting the `loc`, at the
+/// level configured for the check.
+pub fn report_lint(env: &GlobalEnv, lint: &str, loc: &Loc, msg: &str) {
+    let (severity, kind) = match lint_level(env, lint) {
+        LintLevel::Allow => return,
+        LintLevel::Warn => (Severity::Warning, "warning"),
+        LintLevel::Deny => (Severity::Error, "error"),
+    };
+    let notes = vec![format!(
+        "To suppress this {}, annotate the function/module with the attribute `#[{}({})]`.",
+        kind,
+        LintAttribute::SKIP,
+        lint
+    )];
+    if severity == Severity::Warning {
+        env.lint_diag_with_notes(loc, msg, notes)
+    } else {
+        env.diag_with_notes(severity, loc, &format!("[lint] {}", msg), notes)
+    }
+}
+
 /// Extract all the lint checks to skip from the given attributes.
 /// Also performs error-checking on any `LintAttribute::SKIP` attributes.
 pub fn lint_skips_from_attributes(env: &GlobalEnv, attrs: &[Attribute]) -> Vec<LintChecker> {
+    lint_skip_names_from_attributes(env, attrs)
+        .iter()
+        .filter_map(|name| LintChecker::from_str(name).ok())
+        .collect()
+}
+
+/// Extract the names of all the lint checks to skip from the given attributes, including
+/// the names of lint rules.
+/// Also performs error-checking on any `LintAttribute::SKIP` attributes.
+pub fn lint_skip_names_from_attributes(env: &GlobalEnv, attrs: &[Attribute]) -> Vec<String> {
     let lint_skip = env.symbol_pool().make(LintAttribute::SKIP);
     let skip_attr = attrs.iter().find(|attr| attr.name() == lint_skip);
     if let Some(skip_attr) = skip_attr {
@@ -42,9 +144,9 @@ pub fn lint_skips_from_attributes(env: &GlobalEnv, attrs: &[Attribute]) -> Vec<L
     }
 }
 
-/// Extract all the lint checks to skip from `attr`.
+/// Extract the names of all the lint checks to skip from `attr`.
 /// Also performs error-checking on the LintAttribute::SKIP `attr`.
-fn parse_lint_skip_attribute(env: &GlobalEnv, attr: &Attribute) -> Vec<LintChecker> {
+fn parse_lint_skip_attribute(env: &GlobalEnv, attr: &Attribute) -> Vec<String> {
     match attr {
         Attribute::Assign(id, ..) => {
             env.error(
@@ -79,14 +181,15 @@ fn parse_lint_skip_attribute(env: &GlobalEnv, attr: &Attribute) -> Vec<LintCheck
                         None
                     } else {
                         let name = name.display(env.symbol_pool()).to_string();
-                        let checker = LintChecker::from_str(&name).ok();
-                        if checker.is_none() {
+                        if is_known_lint(env, &name) {
+                            Some(name)
+                        } else {
                             env.error(
                                 &env.get_node_loc(*id),
                                 &format!("unknown lint check: `{}`", name),
                             );
+                            None
                         }
-                        checker
                     }
                 },
             })
@@ -0,0 +1,578 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module contains the pattern language in which packages define their own lint rules,
+//! and the matching of patterns against the model AST.
+//!
+//! A pattern matches expressions:
+//!
+//! - `_` matches any expression.
+//! - `true`, `false` and integer literals like `0` or `0x10` match the equal values.
+//! - `call(F)` matches calls of, or closures over, the functions matching the name pattern `F`,
+//!   e.g. `call(aptos_framework::object::generate_signer)`. With argument patterns, as in
+//!   `call(0x1::features::change_feature_flags, _, _, _)`, the number of arguments must be the
+//!   same and each argument must match its pattern.
+//! - `pack(S)`, `exists(S)`, `borrow_global(S)`, `borrow_global_mut(S)`, `move_from(S)` and
+//!   `move_to(S)` match the respective operations on the structs matching the name pattern `S`.
+//! - `abort`, `loop` and `return` match expressions of the respective kind, where `loop` also
+//!   matches `while` loops.
+//! - `not(P)` matches the expressions which `P` doesn't match, `any(P, ...)` those which any of
+//!   the patterns match, and `all(P, ...)` those which all of them match.
+//! - `inside(P, Q)` matches the expressions matching `P` nested in an expression matching `Q`,
+//!   e.g. `inside(call(0x1::table::add), loop)`.
+//! - `contains(P, Q)` matches the expressions matching `P` in which an expression matches `Q`,
+//!   e.g. `contains(loop, abort)`. The `inside` patterns in `Q` only consider the expressions
+//!   nested in the one matching `P`.
+//!
+//! A name pattern `address::module::name` matches functions or structs. Each of the parts can
+//! contain `*`, which matches any sequence of characters. The address is either numerical, like
+//! `0x1`, or a named address, like `aptos_framework`.
+
+use anyhow::{anyhow, bail, Context};
+use move_core_types::account_address::AccountAddress;
+use move_model::{
+    ast::{Address, ExpData, Operation, Value},
+    model::{FunctionEnv, GlobalEnv, ModuleEnv, NodeId},
+    symbol::Symbol,
+    ty::{ReferenceKind, Type},
+};
+use num::BigInt;
+use std::{fmt, str::FromStr};
+
+/// A pattern `address::module::name` matching functions or structs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NamePattern {
+    address: String,
+    module: String,
+    name: String,
+}
+
+impl FromStr for NamePattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let parts = s.split("::").collect::<Vec<_>>();
+        let [address, module, name] = parts.as_slice() else {
+            bail!(
+                "invalid name pattern `{}`, expected `address::module::name`",
+                s
+            )
+        };
+        if parts.iter().any(|part| part.is_empty()) {
+            bail!("invalid name pattern `{}`, found an empty part", s)
+        }
+        Ok(Self {
+            address: address.to_string(),
+            module: module.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.address, self.module, self.name)
+    }
+}
+
+impl NamePattern {
+    /// Returns whether the function matches this pattern.
+    pub fn matches_function(&self, fun: &FunctionEnv) -> bool {
+        self.matches(&fun.module_env, fun.get_name())
+    }
+
+    /// Returns whether the member `name` of the module matches this pattern.
+    fn matches(&self, module: &ModuleEnv, name: Symbol) -> bool {
+        let env = module.env;
+        let module_name = module.get_name();
+        self.matches_address(env, module_name.addr())
+            && glob_matches(
+                &self.module,
+                &module_name.name().display(env.symbol_pool()).to_string(),
+            )
+            && glob_matches(&self.name, &name.display(env.symbol_pool()).to_string())
+    }
+
+    fn matches_address(&self, env: &GlobalEnv, address: &Address) -> bool {
+        if self.address.contains('*') {
+            return glob_matches(&self.address, &env.display(address).to_string());
+        }
+        let expected = AccountAddress::from_hex_literal(&self.address)
+            .ok()
+            .or_else(|| env.resolve_address_alias(env.symbol_pool().make(&self.address)));
+        match address {
+            Address::Numerical(addr) => expected == Some(*addr),
+            Address::Symbolic(name) => *env.symbol_pool().string(*name) == self.address,
+        }
+    }
+}
+
+/// Returns whether `s` matches `pattern`, in which `*` matches any sequence of characters.
+fn glob_matches(pattern: &str, s: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == s,
+        Some((prefix, rest)) => s.strip_prefix(prefix).is_some_and(|s| {
+            s.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(s.len()))
+                .any(|i| glob_matches(rest, &s[i..]))
+        }),
+    }
+}
+
+/// The operations on global storage which can be matched by the struct they operate on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StorageOperation {
+    Exists,
+    BorrowGlobal,
+    BorrowGlobalMut,
+    MoveFrom,
+    MoveTo,
+}
+
+impl StorageOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            StorageOperation::Exists => "exists",
+            StorageOperation::BorrowGlobal => "borrow_global",
+            StorageOperation::BorrowGlobalMut => "borrow_global_mut",
+            StorageOperation::MoveFrom => "move_from",
+            StorageOperation::MoveTo => "move_to",
+        }
+    }
+
+    fn matches(&self, operation: &Operation) -> bool {
+        matches!(
+            (self, operation),
+            (StorageOperation::Exists, Operation::Exists(_))
+                | (
+                    StorageOperation::BorrowGlobal,
+                    Operation::BorrowGlobal(ReferenceKind::Immutable)
+                )
+                | (
+                    StorageOperation::BorrowGlobalMut,
+                    Operation::BorrowGlobal(ReferenceKind::Mutable)
+                )
+                | (StorageOperation::MoveFrom, Operation::MoveFrom)
+                | (StorageOperation::MoveTo, Operation::MoveTo)
+        )
+    }
+}
+
+/// A pattern matching expressions of the model AST. See the module documentation for the syntax.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExpPattern {
+    Any,
+    Bool(bool),
+    Number(BigInt),
+    Call(NamePattern, Option<Vec<ExpPattern>>),
+    Pack(NamePattern),
+    Storage(StorageOperation, NamePattern),
+    Abort,
+    Loop,
+    Return,
+    Not(Box<ExpPattern>),
+    AnyOf(Vec<ExpPattern>),
+    AllOf(Vec<ExpPattern>),
+    Inside(Box<ExpPattern>, Box<ExpPattern>),
+    Contains(Box<ExpPattern>, Box<ExpPattern>),
+}
+
+impl ExpPattern {
+    /// Calls `report` on every expression in `exp` matching this pattern, in pre-order.
+    pub fn find_matches(&self, env: &GlobalEnv, exp: &ExpData, report: &mut impl FnMut(NodeId)) {
+        // The expressions currently being visited, outermost first, are the ancestors of the
+        // expression being matched. For each of them, whether it matches each `inside` context.
+        let contexts = self.inside_contexts();
+        let mut ancestors: Vec<Vec<bool>> = vec![];
+        exp.visit_pre_post(&mut |post, e| {
+            if post {
+                ancestors.pop();
+            } else {
+                if self.matches(env, e, &contexts, &ancestors) {
+                    report(e.node_id());
+                }
+                let context_matches = contexts
+                    .iter()
+                    .map(|context| context.matches(env, e, &contexts, &ancestors))
+                    .collect();
+                ancestors.push(context_matches);
+            }
+            true
+        });
+    }
+
+    /// Returns whether an expression in `exp` matches this pattern.
+    fn is_found_in(&self, env: &GlobalEnv, exp: &ExpData) -> bool {
+        let mut found = false;
+        self.find_matches(env, exp, &mut |_| found = true);
+        found
+    }
+
+    /// Returns the context patterns of the `inside` patterns, other than the ones nested in
+    /// `contains` patterns, whose expressions are matched separately.
+    fn inside_contexts(&self) -> Vec<&ExpPattern> {
+        let mut contexts = vec![];
+        self.collect_inside_contexts(&mut contexts);
+        contexts
+    }
+
+    fn collect_inside_contexts<'a>(&'a self, contexts: &mut Vec<&'a ExpPattern>) {
+        match self {
+            ExpPattern::Call(_, Some(args)) => {
+                // The arguments are matched in the context of the call, too.
+                for arg in args {
+                    arg.collect_inside_contexts(contexts)
+                }
+            },
+            ExpPattern::Not(pattern) => pattern.collect_inside_contexts(contexts),
+            ExpPattern::AnyOf(patterns) | ExpPattern::AllOf(patterns) => {
+                for pattern in patterns {
+                    pattern.collect_inside_contexts(contexts)
+                }
+            },
+            ExpPattern::Inside(pattern, context) => {
+                pattern.collect_inside_contexts(contexts);
+                context.collect_inside_contexts(contexts);
+                if !contexts.contains(&context.as_ref()) {
+                    contexts.push(context)
+                }
+            },
+            ExpPattern::Contains(pattern, _) => pattern.collect_inside_contexts(contexts),
+            ExpPattern::Any
+            | ExpPattern::Bool(_)
+            | ExpPattern::Number(_)
+            | ExpPattern::Call(_, None)
+            | ExpPattern::Pack(_)
+            | ExpPattern::Storage(..)
+            | ExpPattern::Abort
+            | ExpPattern::Loop
+            | ExpPattern::Return => {},
+        }
+    }
+
+    /// Returns whether `exp` matches this pattern, given whether its ancestors match `contexts`.
+    fn matches(
+        &self,
+        env: &GlobalEnv,
+        exp: &ExpData,
+        contexts: &[&ExpPattern],
+        ancestors: &[Vec<bool>],
+    ) -> bool {
+        match self {
+            ExpPattern::Any => true,
+            ExpPattern::Bool(expected) => {
+                matches!(exp, ExpData::Value(_, Value::Bool(b)) if b == expected)
+            },
+            ExpPattern::Number(expected) => {
+                matches!(exp, ExpData::Value(_, Value::Number(n)) if n == expected)
+            },
+            ExpPattern::Call(function, arg_patterns) => match exp {
+                ExpData::Call(_, Operation::MoveFunction(mid, fid), args)
+                | ExpData::Call(_, Operation::Closure(mid, fid), args) => {
+                    function.matches_function(&env.get_function(mid.qualified(*fid)))
+                        && arg_patterns.as_ref().map_or(true, |arg_patterns| {
+                            arg_patterns.len() == args.len()
+                                && arg_patterns.iter().zip(args).all(|(pattern, arg)| {
+                                    // The call is an ancestor of its arguments.
+                                    let mut ancestors = ancestors.to_vec();
+                                    ancestors.push(
+                                        contexts
+                                            .iter()
+                                            .map(|context| {
+                                                context.matches(env, exp, contexts, &ancestors)
+                                            })
+                                            .collect(),
+                                    );
+                                    pattern.matches(env, arg, contexts, &ancestors)
+                                })
+                        })
+                },
+                _ => false,
+            },
+            ExpPattern::Pack(pattern) => match exp {
+                ExpData::Call(_, Operation::Pack(mid, sid, _), _) => {
+                    let struct_env = env.get_struct(mid.qualified(*sid));
+                    pattern.matches(&struct_env.module_env, struct_env.get_name())
+                },
+                _ => false,
+            },
+            ExpPattern::Storage(operation, pattern) => match exp {
+                ExpData::Call(id, op, _) if operation.matches(op) => {
+                    match env.get_node_instantiation(*id).first() {
+                        Some(Type::Struct(mid, sid, _)) => {
+                            let struct_env = env.get_struct(mid.qualified(*sid));
+                            pattern.matches(&struct_env.module_env, struct_env.get_name())
+                        },
+                        _ => false,
+                    }
+                },
+                _ => false,
+            },
+            ExpPattern::Abort => matches!(exp, ExpData::Call(_, Operation::Abort, _)),
+            ExpPattern::Loop => matches!(exp, ExpData::Loop(..)),
+            ExpPattern::Return => matches!(exp, ExpData::Return(..)),
+            ExpPattern::Not(pattern) => !pattern.matches(env, exp, contexts, ancestors),
+            ExpPattern::AnyOf(patterns) => patterns
+                .iter()
+                .any(|pattern| pattern.matches(env, exp, contexts, ancestors)),
+            ExpPattern::AllOf(patterns) => patterns
+                .iter()
+                .all(|pattern| pattern.matches(env, exp, contexts, ancestors)),
+            ExpPattern::Inside(pattern, context) => {
+                let index = contexts
+                    .iter()
+                    .position(|c| *c == context.as_ref())
+                    .expect("context collected");
+                ancestors.iter().any(|matches| matches[index])
+                    && pattern.matches(env, exp, contexts, ancestors)
+            },
+            ExpPattern::Contains(pattern, inner) => {
+                pattern.matches(env, exp, contexts, ancestors) && inner.is_found_in(env, exp)
+            },
+        }
+    }
+}
+
+impl FromStr for ExpPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parser = PatternParser { input: s, pos: 0 };
+        let pattern = parser
+            .parse_pattern()
+            .and_then(|pattern| {
+                parser.skip_whitespace();
+                if parser.pos < s.len() {
+                    bail!("unexpected `{}`", &s[parser.pos..])
+                }
+                Ok(pattern)
+            })
+            .with_context(|| format!("invalid pattern `{}`", s))?;
+        Ok(pattern)
+    }
+}
+
+/// A recursive descent parser of patterns.
+struct PatternParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> PatternParser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes `c` if it is the next character, ignoring whitespace.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> anyhow::Error {
+        match self.input[self.pos..].chars().next() {
+            Some(c) => anyhow!("expected {}, found `{}`", expected, c),
+            None => anyhow!("expected {}, found the end", expected),
+        }
+    }
+
+    /// Parses a word: a keyword, a literal or a name pattern.
+    fn word(&mut self) -> anyhow::Result<&'a str> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '*' || c == ':'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.unexpected("a pattern"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Parses the arguments of a pattern, from the opening parenthesis on, with `parse`.
+    fn arguments<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut Self) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        self.expect('(')?;
+        let mut args = vec![parse(self)?];
+        while self.eat(',') {
+            args.push(parse(self)?);
+        }
+        self.expect(')')?;
+        Ok(args)
+    }
+
+    fn pattern_arguments<const N: usize>(
+        &mut self,
+        keyword: &str,
+    ) -> anyhow::Result<[ExpPattern; N]> {
+        self.arguments(Self::parse_pattern)?
+            .try_into()
+            .map_err(|_| anyhow!("`{}` expects {} patterns", keyword, N))
+    }
+
+    fn name_argument(&mut self) -> anyhow::Result<NamePattern> {
+        self.expect('(')?;
+        let name = self.word()?.parse()?;
+        self.expect(')')?;
+        Ok(name)
+    }
+
+    fn parse_pattern(&mut self) -> anyhow::Result<ExpPattern> {
+        let word = self.word()?;
+        Ok(match word {
+            "_" => ExpPattern::Any,
+            "true" => ExpPattern::Bool(true),
+            "false" => ExpPattern::Bool(false),
+            "abort" => ExpPattern::Abort,
+            "loop" => ExpPattern::Loop,
+            "return" => ExpPattern::Return,
+            "call" => {
+                self.expect('(')?;
+                let function = self.word()?.parse()?;
+                let mut args = None;
+                if self.eat(',') {
+                    let mut arg_patterns = vec![self.parse_pattern()?];
+                    while self.eat(',') {
+                        arg_patterns.push(self.parse_pattern()?);
+                    }
+                    args = Some(arg_patterns);
+                }
+                self.expect(')')?;
+                ExpPattern::Call(function, args)
+            },
+            "pack" => ExpPattern::Pack(self.name_argument()?),
+            "exists" | "borrow_global" | "borrow_global_mut" | "move_from" | "move_to" => {
+                let operation = match word {
+                    "exists" => StorageOperation::Exists,
+                    "borrow_global" => StorageOperation::BorrowGlobal,
+                    "borrow_global_mut" => StorageOperation::BorrowGlobalMut,
+                    "move_from" => StorageOperation::MoveFrom,
+                    _ => StorageOperation::MoveTo,
+                };
+                ExpPattern::Storage(operation, self.name_argument()?)
+            },
+            "not" => {
+                let [pattern] = self.pattern_arguments(word)?;
+                ExpPattern::Not(Box::new(pattern))
+            },
+            "any" => ExpPattern::AnyOf(self.arguments(Self::parse_pattern)?),
+            "all" => ExpPattern::AllOf(self.arguments(Self::parse_pattern)?),
+            "inside" => {
+                let [pattern, context] = self.pattern_arguments(word)?;
+                ExpPattern::Inside(Box::new(pattern), Box::new(context))
+            },
+            "contains" => {
+                let [pattern, inner] = self.pattern_arguments(word)?;
+                ExpPattern::Contains(Box::new(pattern), Box::new(inner))
+            },
+            _ => {
+                let number = match word.strip_prefix("0x") {
+                    Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                    None => BigInt::parse_bytes(word.as_bytes(), 10),
+                };
+                number
+                    .map(ExpPattern::Number)
+                    .ok_or_else(|| anyhow!("unknown pattern `{}`", word))?
+            },
+        })
+    }
+}
+
+impl fmt::Display for ExpPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |patterns: &[ExpPattern]| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            ExpPattern::Any => write!(f, "_"),
+            ExpPattern::Bool(b) => write!(f, "{}", b),
+            ExpPattern::Number(n) => write!(f, "{}", n),
+            ExpPattern::Call(function, None) => write!(f, "call({})", function),
+            ExpPattern::Call(function, Some(args)) => {
+                write!(f, "call({}, {})", function, list(args))
+            },
+            ExpPattern::Pack(name) => write!(f, "pack({})", name),
+            ExpPattern::Storage(operation, name) => write!(f, "{}({})", operation.name(), name),
+            ExpPattern::Abort => write!(f, "abort"),
+            ExpPattern::Loop => write!(f, "loop"),
+            ExpPattern::Return => write!(f, "return"),
+            ExpPattern::Not(pattern) => write!(f, "not({})", pattern),
+            ExpPattern::AnyOf(patterns) => write!(f, "any({})", list(patterns)),
+            ExpPattern::AllOf(patterns) => write!(f, "all({})", list(patterns)),
+            ExpPattern::Inside(pattern, context) => write!(f, "inside({}, {})", pattern, context),
+            ExpPattern::Contains(pattern, inner) => write!(f, "contains({}, {})", pattern, inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patterns() {
+        for pattern in [
+            "_",
+            "call(aptos_framework::object::generate_signer)",
+            "call(0x1::features::change_feature_flags, _, not(true), 0)",
+            "inside(any(move_to(*::*::Secret), borrow_global_mut(0x42::m::*)), loop)",
+            "contains(all(loop, not(inside(loop, loop))), abort)",
+            "pack(0x42::m::Receipt)",
+            "return",
+        ] {
+            let parsed: ExpPattern = pattern.parse().unwrap();
+            assert_eq!(parsed.to_string(), pattern);
+        }
+        assert_eq!(
+            "0x10".parse::<ExpPattern>().unwrap(),
+            ExpPattern::Number(BigInt::from(16))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_patterns() {
+        for (pattern, error) in [
+            ("call(0x1::m)", "invalid name pattern `0x1::m`"),
+            ("not(_, _)", "`not` expects 1 patterns"),
+            ("inside(_)", "`inside` expects 2 patterns"),
+            ("loop(_)", "unexpected `(_)`"),
+            ("any()", "expected a pattern, found `)`"),
+            ("call(0x1::m::f", "expected `)`, found the end"),
+            ("while", "unknown pattern `while`"),
+        ] {
+            let err = pattern.parse::<ExpPattern>().unwrap_err();
+            assert!(format!("{:#}", err).contains(error), "{:#}", err);
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("create_*", "create_object"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*_signer*", "generate_signer"));
+        assert!(!glob_matches("create_*", "recreate_object"));
+        assert!(!glob_matches("a*b", "ab_"));
+    }
+}
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::experiments::{DefaultValue, EXPERIMENTS};
+use crate::{
+    experiments::{DefaultValue, EXPERIMENTS},
+    lint_common::LintConfig,
+};
 use clap::Parser;
 use codespan_reporting::diagnostic::Severity;
 use itertools::Itertools;
@@ -121,6 +124,10 @@ pub struct Options {
     /// Whether to compile #[verify_only] code
     #[clap(skip)]
     pub compile_verify_code: bool,
+
+    /// The lint levels and lint rules of the package being compiled.
+    #[clap(skip)]
+    pub lint_config: LintConfig,
 }
 
 impl Default for Options {
@@ -9,13 +9,12 @@ mod avoid_copy_on_identity_comparison;
 mod needless_mutable_reference;
 
 use crate::{
-    lint_common::{lint_skips_from_attributes, LintChecker},
+    lint_common::{lint_skips_from_attributes, report_lint, LintChecker},
     pipeline::lint_processor::{
         avoid_copy_on_identity_comparison::AvoidCopyOnIdentityComparison,
         needless_mutable_reference::NeedlessMutableReference,
     },
 };
-use move_compiler::shared::known_attributes::LintAttribute;
 use move_model::model::{FunctionEnv, GlobalEnv, Loc};
 use move_stackless_bytecode::{
     function_target::{FunctionData, FunctionTarget},
@@ -31,15 +30,9 @@ pub trait StacklessBytecodeLinter {
     /// Examine the `target` and potentially emit lint warnings via `self.warning()`.
     fn check(&self, target: &FunctionTarget);
 
-    /// Emit a lint warning with the `msg` highlighting the `loc`.
+    /// Emit a lint warning with the `msg` highlighting the `loc`, at the configured level.
     fn warning(&self, env: &GlobalEnv, loc: &Loc, msg: &str) {
-        env.lint_diag_with_notes(loc, msg, vec![
-            format!(
-                "To suppress this warning, annotate the function/module with the attribute `#[{}({})]`.",
-                LintAttribute::SKIP,
-                self.get_lint_checker()
-            ),
-        ]);
+        report_lint(env, &self.get_lint_checker().to_string(), loc, msg)
     }
 }
 
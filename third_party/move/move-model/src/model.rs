@@ -1022,6 +1022,17 @@ impl GlobalEnv {
             .any(|(d, _)| d.message.contains(pattern))
     }
 
+    /// Returns the accumulated diagnostics of given or higher severity, in the order they were
+    /// added. This is for tools which present diagnostics in another way than `report_diag`.
+    pub fn get_diags(&self, min_severity: Severity) -> Vec<Diagnostic<FileId>> {
+        self.diags
+            .borrow()
+            .iter()
+            .filter(|(d, _)| d.severity >= min_severity)
+            .map(|(d, _)| d.clone())
+            .collect()
+    }
+
     /// Clear all accumulated diagnosis.
     pub fn clear_diag(&self) {
         self.diags.borrow_mut().clear();
@@ -229,6 +229,45 @@ fn test_vm_value_vector_u64_casting() {
     );
 }
 
+#[test]
+fn compare() -> PartialVMResult<()> {
+    use std::cmp::Ordering::*;
+
+    // Integers are compared by value, not by their (little-endian) serialization.
+    assert_eq!(Value::u64(1).compare(&Value::u64(256))?, Less);
+    assert_eq!(Value::u128(7).compare(&Value::u128(7))?, Equal);
+    assert_eq!(
+        Value::u256(U256::max_value()).compare(&Value::u256(U256::one()))?,
+        Greater
+    );
+    assert_eq!(Value::bool(false).compare(&Value::bool(true))?, Less);
+    assert_eq!(
+        Value::address(AccountAddress::ONE).compare(&Value::address(AccountAddress::TWO))?,
+        Less
+    );
+
+    // Vectors are compared lexicographically.
+    assert_eq!(
+        Value::vector_u8([1, 2]).compare(&Value::vector_u8([1, 2, 0]))?,
+        Less
+    );
+    assert_eq!(
+        Value::vector_u64([2]).compare(&Value::vector_u64([1, 5]))?,
+        Greater
+    );
+
+    // Structs are compared field by field.
+    let s = |a, b| Value::struct_(Struct::pack(vec![Value::u64(a), Value::vector_u8(b)]));
+    assert_eq!(s(1, vec![9]).compare(&s(2, vec![0]))?, Less);
+    assert_eq!(s(1, vec![9]).compare(&s(1, vec![0]))?, Greater);
+    assert_eq!(s(1, vec![9]).compare(&s(1, vec![9]))?, Equal);
+
+    // Values of different types cannot be compared.
+    assert!(Value::u64(1).compare(&Value::u128(1)).is_err());
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod native_values {
     use super::*;
@@ -23,6 +23,7 @@ use move_core_types::{
 };
 use std::{
     cell::RefCell,
+    cmp::Ordering,
     fmt::{self, Debug, Display, Formatter},
     iter,
     rc::Rc,
@@ -712,6 +713,121 @@ impl Value {
     }
 }
 
+/***************************************************************************************
+ *
+ * Comparison
+ *
+ *   Total order on Move values of the same type: integers, booleans and addresses
+ *   are compared by value, vectors lexicographically, and structs lexicographically
+ *   by their fields in declaration order (for enums, the variant tag comes first).
+ *   Errors are raised when types mismatch.
+ *
+ *   For the same reasons as for equality, the standard library traits Ord and
+ *   PartialOrd are NOT implemented. References are not supported, they need to be
+ *   read first.
+ *
+ **************************************************************************************/
+
+impl ValueImpl {
+    fn compare(&self, other: &Self) -> PartialVMResult<Ordering> {
+        use ValueImpl::*;
+
+        let res = match (self, other) {
+            (U8(l), U8(r)) => l.cmp(r),
+            (U16(l), U16(r)) => l.cmp(r),
+            (U32(l), U32(r)) => l.cmp(r),
+            (U64(l), U64(r)) => l.cmp(r),
+            (U128(l), U128(r)) => l.cmp(r),
+            (U256(l), U256(r)) => l.cmp(r),
+            (Bool(l), Bool(r)) => l.cmp(r),
+            (Address(l), Address(r)) => l.cmp(r),
+
+            (Container(l), Container(r)) => l.compare(r)?,
+
+            // Disallow comparison of delayed values, for the same reason as equality.
+            (DelayedFieldID { .. }, DelayedFieldID { .. }) => {
+                return Err(PartialVMError::new(StatusCode::VM_EXTENSION_ERROR)
+                    .with_message("cannot compare delayed values".to_string()))
+            },
+
+            (Invalid, _)
+            | (U8(_), _)
+            | (U16(_), _)
+            | (U32(_), _)
+            | (U64(_), _)
+            | (U128(_), _)
+            | (U256(_), _)
+            | (Bool(_), _)
+            | (Address(_), _)
+            | (Container(_), _)
+            | (ContainerRef(_), _)
+            | (IndexedRef(_), _)
+            | (DelayedFieldID { .. }, _) => {
+                return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                    .with_message(format!("cannot compare values: {:?}, {:?}", self, other)))
+            },
+        };
+
+        Ok(res)
+    }
+}
+
+impl Container {
+    fn compare(&self, other: &Self) -> PartialVMResult<Ordering> {
+        use Container::*;
+
+        let res = match (self, other) {
+            (Vec(l), Vec(r)) | (Struct(l), Struct(r)) => {
+                let l = &l.borrow();
+                let r = &r.borrow();
+
+                for (v1, v2) in l.iter().zip(r.iter()) {
+                    let ordering = v1.compare(v2)?;
+                    if ordering != Ordering::Equal {
+                        return Ok(ordering);
+                    }
+                }
+                l.len().cmp(&r.len())
+            },
+            (VecU8(l), VecU8(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecU16(l), VecU16(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecU32(l), VecU32(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecU64(l), VecU64(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecU128(l), VecU128(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecU256(l), VecU256(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecBool(l), VecBool(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+            (VecAddress(l), VecAddress(r)) => l.borrow().as_slice().cmp(r.borrow().as_slice()),
+
+            (Locals(_), _)
+            | (Vec(_), _)
+            | (Struct(_), _)
+            | (VecU8(_), _)
+            | (VecU16(_), _)
+            | (VecU32(_), _)
+            | (VecU64(_), _)
+            | (VecU128(_), _)
+            | (VecU256(_), _)
+            | (VecBool(_), _)
+            | (VecAddress(_), _) => {
+                return Err(
+                    PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR).with_message(format!(
+                        "cannot compare container values: {:?}, {:?}",
+                        self, other
+                    )),
+                )
+            },
+        };
+
+        Ok(res)
+    }
+}
+
+impl Value {
+    pub fn compare(&self, other: &Self) -> PartialVMResult<Ordering> {
+        self.0.compare(&other.0)
+    }
+}
+
 /***************************************************************************************
  *
  * Read Ref
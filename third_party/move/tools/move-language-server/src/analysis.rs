@@ -0,0 +1,317 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Analysis of a Move package with the v2 compiler, and the queries the server answers from it.
+
+use crate::{
+    position::{offset_of, position_of, range_of},
+    symbols::{Item, SymbolIndex},
+};
+use anyhow::Result;
+use codespan::FileId;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Position, Range,
+    Url,
+};
+use move_compiler_v2::{
+    bytecode_pipeline, check_and_rewrite_pipeline, env_pipeline::rewrite_target::RewritingScope,
+    run_bytecode_gen, run_checker, Experiment,
+};
+use move_model::{
+    metadata::{CompilerVersion, LanguageVersion},
+    model::{GlobalEnv, Loc, ModuleEnv},
+};
+use move_package::{compilation::model_builder::ModelBuilder, BuildConfig, ModelConfig};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// The result of analyzing a package: the Move model of the package together with its
+/// dependencies, including all diagnostics the compiler reported, and an index of the
+/// symbols in the model.
+pub struct PackageAnalysis {
+    pub env: GlobalEnv,
+    pub symbols: SymbolIndex,
+    /// The source files of the modules in the model, by canonical path.
+    files: BTreeMap<PathBuf, FileId>,
+    /// The source files of the package itself, as opposed to its dependencies.
+    target_files: BTreeSet<FileId>,
+}
+
+impl PackageAnalysis {
+    /// Analyzes the package at `package_path`, including its tests. Besides type checking, this
+    /// runs the AST and bytecode pipelines of the compiler, including the lint checks, as long
+    /// as no errors are found.
+    pub fn new(package_path: &Path, language_version: LanguageVersion) -> Result<Self> {
+        let build_config = BuildConfig {
+            dev_mode: true,
+            test_mode: true,
+            skip_fetch_latest_git_deps: true,
+            compiler_config: move_package::CompilerConfig {
+                compiler_version: Some(CompilerVersion::V2_0),
+                language_version: Some(language_version),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let resolution_graph =
+            build_config.resolution_graph_for_package(package_path, &mut std::io::sink())?;
+        let mut options = ModelBuilder::create(resolution_graph, ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+            compiler_version: CompilerVersion::V2_0,
+            language_version,
+        })
+        .v2_compiler_options()?
+        .set_experiment(Experiment::LINT_CHECKS, true);
+        options.compile_test_code = true;
+
+        let mut env = run_checker(options.clone())?;
+        // Index the symbols before rewriting, which e.g. inlines functions.
+        let symbols = SymbolIndex::new(&env);
+        if !env.has_errors() {
+            check_and_rewrite_pipeline(&options, false, RewritingScope::CompilationTarget)
+                .run(&mut env);
+        }
+        if !env.has_errors() {
+            let mut targets = run_bytecode_gen(&env);
+            bytecode_pipeline(&env).run_with_hook(
+                &env,
+                &mut targets,
+                |_| {},
+                |_, _, _| !env.has_errors(),
+            );
+        }
+
+        let mut files = BTreeMap::new();
+        let mut target_files = BTreeSet::new();
+        for module in env.get_modules() {
+            let file_id = module.get_loc().file_id();
+            files.insert(canonical_path(&env, file_id), file_id);
+            if module.is_primary_target() {
+                target_files.insert(file_id);
+            }
+        }
+        Ok(Self {
+            env,
+            symbols,
+            files,
+            target_files,
+        })
+    }
+
+    /// Returns the diagnostics of the package, by canonical file path. Every source file of the
+    /// package has an entry, so that diagnostics which have been fixed can be cleared. Warnings
+    /// are only reported for the package itself, not for its dependencies.
+    pub fn diagnostics(&self) -> BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> {
+        let mut result: BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> = self
+            .target_files
+            .iter()
+            .map(|file_id| (canonical_path(&self.env, *file_id), vec![]))
+            .collect();
+        for diag in self.env.get_diags(Severity::Warning) {
+            let Some(label) = diag
+                .labels
+                .iter()
+                .find(|label| label.style == LabelStyle::Primary)
+                .or_else(|| diag.labels.first())
+            else {
+                continue;
+            };
+            if diag.severity < Severity::Error && !self.target_files.contains(&label.file_id) {
+                continue;
+            }
+            let text = self.env.get_file_source(label.file_id);
+            let range = Range::new(
+                position_of(text, label.range.start),
+                position_of(text, label.range.end),
+            );
+            result
+                .entry(canonical_path(&self.env, label.file_id))
+                .or_default()
+                .push(self.to_lsp_diagnostic(&diag, range));
+        }
+        result
+    }
+
+    /// Returns the location of the definition of the item or local variable at `position`.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let (file_id, offset) = self.offset(path, position)?;
+        let occurrence = self.symbols.find_at(file_id, offset, |occ| {
+            occ.item.is_some() || occ.declaration.is_some()
+        })?;
+        match occurrence.item {
+            Some(item) => self.location(self.symbols.definition(item)?),
+            None => self.location(occurrence.declaration.as_ref()?),
+        }
+    }
+
+    /// Returns the locations of all references to the item at `position`.
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Some(item) = self.item_at(path, position) else {
+            return vec![];
+        };
+        let declaration = self
+            .symbols
+            .definition(item)
+            .filter(|_| include_declaration);
+        declaration
+            .into_iter()
+            .chain(self.symbols.references(item))
+            .filter_map(|loc| self.location(loc))
+            .collect()
+    }
+
+    /// Returns markdown describing the item or expression at `position`: the signature and
+    /// documentation of an item, or the type of an expression.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<String> {
+        let (file_id, offset) = self.offset(path, position)?;
+        let occurrence = self.symbols.find_at(file_id, offset, |_| true)?;
+        match occurrence.item {
+            Some(item) => Some(self.describe(item)),
+            None => {
+                let ty = self.env.get_node_type_opt(occurrence.node_id?)?;
+                Some(move_block(
+                    &ty.display(&self.env.get_type_display_ctx()).to_string(),
+                ))
+            },
+        }
+    }
+
+    /// Returns the modules defined in the given file.
+    pub fn modules_in_file(&self, path: &Path) -> Vec<ModuleEnv<'_>> {
+        let Some(file_id) = self.files.get(path) else {
+            return vec![];
+        };
+        self.env
+            .get_modules()
+            .filter(|module| module.get_loc().file_id() == *file_id)
+            .collect()
+    }
+
+    /// Returns markdown with the signature and documentation of the item.
+    pub fn describe(&self, item: Item) -> String {
+        let env = &self.env;
+        let (signature, doc) = match item {
+            Item::Module(module_id) => {
+                let module = env.get_module(module_id);
+                (
+                    format!("module {}", module.get_full_name_str()),
+                    module.get_doc().to_string(),
+                )
+            },
+            Item::Struct(struct_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let abilities = struct_env.get_abilities();
+                let mut signature = format!("struct {}", struct_env.get_full_name_str());
+                if !abilities.is_empty() {
+                    signature.push_str(&format!(" has {}", abilities));
+                }
+                (signature, struct_env.get_doc().to_string())
+            },
+            Item::Field(struct_id, field_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let field = struct_env.get_field(field_id);
+                (
+                    format!(
+                        "{}: {}",
+                        field.get_name().display(env.symbol_pool()),
+                        field.get_type().display(&struct_env.get_type_display_ctx())
+                    ),
+                    field.get_doc().to_string(),
+                )
+            },
+            Item::Function(fun_id) => {
+                let fun = env.get_function(fun_id);
+                (fun.get_header_string(), fun.get_doc().to_string())
+            },
+        };
+        let mut markdown = move_block(&signature);
+        if !doc.trim().is_empty() {
+            markdown.push_str("\n\n");
+            markdown.push_str(doc.trim());
+        }
+        markdown
+    }
+
+    fn item_at(&self, path: &Path, position: Position) -> Option<Item> {
+        let (file_id, offset) = self.offset(path, position)?;
+        self.symbols
+            .find_at(file_id, offset, |occ| occ.item.is_some())?
+            .item
+    }
+
+    /// Returns the file id and byte offset of `position` in the file, as it was analyzed.
+    fn offset(&self, path: &Path, position: Position) -> Option<(FileId, usize)> {
+        let file_id = *self.files.get(path)?;
+        Some((
+            file_id,
+            offset_of(self.env.get_file_source(file_id), position),
+        ))
+    }
+
+    fn location(&self, loc: &Loc) -> Option<Location> {
+        let uri = Url::from_file_path(canonical_path(&self.env, loc.file_id())).ok()?;
+        let text = self.env.get_file_source(loc.file_id());
+        Some(Location::new(uri, range_of(text, loc.span())))
+    }
+
+    fn to_lsp_diagnostic(&self, diag: &Diagnostic<FileId>, range: Range) -> lsp_types::Diagnostic {
+        let severity = match diag.severity {
+            Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Note => DiagnosticSeverity::INFORMATION,
+            Severity::Help => DiagnosticSeverity::HINT,
+        };
+        let message = std::iter::once(diag.message.clone())
+            .chain(diag.notes.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let related_information = diag
+            .labels
+            .iter()
+            .filter(|label| label.style == LabelStyle::Secondary && !label.message.is_empty())
+            .filter_map(|label| {
+                let text = self.env.get_file_source(label.file_id);
+                let uri = Url::from_file_path(canonical_path(&self.env, label.file_id)).ok()?;
+                Some(DiagnosticRelatedInformation {
+                    location: Location::new(
+                        uri,
+                        Range::new(
+                            position_of(text, label.range.start),
+                            position_of(text, label.range.end),
+                        ),
+                    ),
+                    message: label.message.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        lsp_types::Diagnostic {
+            range,
+            severity: Some(severity),
+            code: diag.code.clone().map(NumberOrString::String),
+            source: Some("move".to_string()),
+            message,
+            related_information: (!related_information.is_empty()).then_some(related_information),
+            ..Default::default()
+        }
+    }
+}
+
+/// Returns the canonical path of a file of the model, so that it can be compared with the paths
+/// of documents sent by the client.
+fn canonical_path(env: &GlobalEnv, file_id: FileId) -> PathBuf {
+    let path = PathBuf::from(env.get_file(file_id));
+    path.canonicalize().unwrap_or(path)
+}
+
+fn move_block(code: &str) -> String {
+    format!("```move\n{}\n```", code)
+}
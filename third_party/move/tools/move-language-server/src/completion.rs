@@ -0,0 +1,128 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completion of module, function, struct and field names.
+//!
+//! Completion works on the text of the document as currently edited, which usually does not
+//! compile, so it only looks at the text before the cursor to determine what kind of name is
+//! expected, and offers the matching names from the last successful analysis of the package.
+
+use crate::{analysis::PackageAnalysis, position::offset_of};
+use lsp_types::{CompletionItem, CompletionItemKind, Position};
+use move_model::model::ModuleEnv;
+use std::{collections::BTreeMap, path::Path};
+
+/// Returns the completions at `position` in `text`, the current content of the file at `path`.
+pub fn completions(
+    analysis: &PackageAnalysis,
+    path: &Path,
+    text: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let env = &analysis.env;
+    let before = &text[..offset_of(text, position)];
+    let prefix_start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map_or(before.len(), |(i, _)| i);
+    let prefix = &before[prefix_start..];
+    let context = &before[..prefix_start];
+
+    // Collect by label, so that names offered by several modules appear only once.
+    let mut items = BTreeMap::new();
+    let mut add = |label: String, kind: CompletionItemKind, detail: String| {
+        if label.starts_with(prefix) {
+            items.entry(label.clone()).or_insert(CompletionItem {
+                label,
+                kind: Some(kind),
+                detail: Some(detail),
+                ..Default::default()
+            });
+        }
+    };
+
+    if let Some(qualifier) = context.strip_suffix("::") {
+        // Members of the module named before `::`, as in `coin::` or `0x1::coin::`.
+        let module_name = qualifier
+            .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+        if let Some(module) = env.find_module_by_name(env.symbol_pool().make(module_name)) {
+            add_module_members(&module, &mut add);
+        }
+    } else if context.ends_with('.') {
+        // Fields of the structs of the current module, and receiver functions of all modules.
+        let self_sym = env.symbol_pool().make("self");
+        for module in analysis.modules_in_file(path) {
+            for struct_env in module.get_structs() {
+                for field in struct_env.get_fields() {
+                    add(
+                        field.get_name().display(env.symbol_pool()).to_string(),
+                        CompletionItemKind::FIELD,
+                        field
+                            .get_type()
+                            .display(&struct_env.get_type_display_ctx())
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        for module in env.get_modules() {
+            for fun in module.get_functions() {
+                if fun
+                    .get_parameters_ref()
+                    .first()
+                    .is_some_and(|param| param.0 == self_sym)
+                {
+                    add(
+                        fun.get_simple_name_string().to_string(),
+                        CompletionItemKind::METHOD,
+                        fun.get_header_string(),
+                    );
+                }
+            }
+        }
+    } else {
+        // Module names, and the members of the current module.
+        for module in env.get_modules() {
+            add(
+                module
+                    .get_name()
+                    .name()
+                    .display(env.symbol_pool())
+                    .to_string(),
+                CompletionItemKind::MODULE,
+                module.get_full_name_str(),
+            );
+        }
+        for module in analysis.modules_in_file(path) {
+            add_module_members(&module, &mut add);
+        }
+    }
+    items.into_values().collect()
+}
+
+fn add_module_members(
+    module: &ModuleEnv,
+    add: &mut impl FnMut(String, CompletionItemKind, String),
+) {
+    for fun in module.get_functions() {
+        add(
+            fun.get_simple_name_string().to_string(),
+            CompletionItemKind::FUNCTION,
+            fun.get_header_string(),
+        );
+    }
+    for struct_env in module.get_structs() {
+        add(
+            struct_env
+                .get_name()
+                .display(module.symbol_pool())
+                .to_string(),
+            CompletionItemKind::STRUCT,
+            struct_env.get_full_name_str(),
+        );
+    }
+}
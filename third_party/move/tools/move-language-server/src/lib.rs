@@ -0,0 +1,17 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A language server for Move, based on the Move model and the v2 compiler.
+//!
+//! The server analyzes whole packages with the package system, and provides diagnostics
+//! (including lints), go-to-definition, find references, hover and completion. Navigation is
+//! supported for modules, structs, fields and functions; local variables only show their type
+//! on hover.
+
+pub mod analysis;
+pub mod completion;
+mod position;
+pub mod server;
+pub mod symbols;
+
+pub use server::{run_stdio, ServerConfig};
@@ -0,0 +1,22 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Parser;
+use move_language_server::{run_stdio, ServerConfig};
+use move_model::metadata::LanguageVersion;
+
+/// Language server for Move, communicating over stdin and stdout.
+#[derive(Parser)]
+#[clap(author, version, about)]
+struct Args {
+    /// The Move language version to analyze packages with
+    #[clap(long, default_value_t = LanguageVersion::default(), value_parser = clap::value_parser!(LanguageVersion))]
+    language_version: LanguageVersion,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_stdio(ServerConfig {
+        language_version: args.language_version,
+    })
+}
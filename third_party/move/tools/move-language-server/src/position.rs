@@ -0,0 +1,48 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion between byte offsets into a source text, as used by the Move model, and LSP
+//! positions, which count lines and UTF-16 code units within a line.
+
+use codespan::Span;
+use lsp_types::{Position, Range};
+
+/// Returns the position of the byte `offset` in `text`.
+pub fn position_of(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = text[..line_start].matches('\n').count();
+    let character = text[line_start..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// Returns the byte offset of `position` in `text`. Positions past the end of a line are mapped
+/// to the end of that line, and positions past the last line to the end of the text.
+pub fn offset_of(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |i| line_start + i);
+    let mut units = 0;
+    for (i, c) in text[line_start..line_end].char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_end
+}
+
+/// Returns the range of `span` in `text`.
+pub fn range_of(text: &str, span: Span) -> Range {
+    Range::new(
+        position_of(text, span.start().to_usize()),
+        position_of(text, span.end().to_usize()),
+    )
+}
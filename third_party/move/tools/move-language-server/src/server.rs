@@ -0,0 +1,299 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The LSP message loop.
+//!
+//! Packages are analyzed when one of their files is opened or saved, so navigation and hover
+//! refer to the files as last saved. Completion uses the text of the document as edited.
+
+use crate::{analysis::PackageAnalysis, completion::completions};
+use anyhow::{anyhow, Result};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics, ShowMessage,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, References, Request as _},
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, Hover, HoverContents,
+    HoverProviderCapability, MarkupContent, MarkupKind, MessageType, OneOf,
+    PublishDiagnosticsParams, SaveOptions, ServerCapabilities, ShowMessageParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Url,
+};
+use move_model::metadata::LanguageVersion;
+use move_package::source_package::layout::SourcePackageLayout;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Configuration of the language server.
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// The Move language version packages are analyzed with.
+    pub language_version: LanguageVersion,
+}
+
+/// Runs the language server on stdin and stdout until the client shuts it down.
+pub fn run_stdio(config: ServerConfig) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    connection.initialize(capabilities)?;
+    Server::new(connection, config).run()?;
+    io_threads.join()?;
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(false),
+                })),
+                ..Default::default()
+            },
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+struct Server {
+    connection: Connection,
+    config: ServerConfig,
+    /// The text of the open documents, by canonical path.
+    documents: BTreeMap<PathBuf, String>,
+    /// The last successful analysis of each package with open documents, by package root.
+    packages: BTreeMap<PathBuf, PackageAnalysis>,
+    /// The files diagnostics have been published for, by package root.
+    published: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl Server {
+    fn new(connection: Connection, config: ServerConfig) -> Self {
+        Self {
+            connection,
+            config,
+            documents: BTreeMap::new(),
+            packages: BTreeMap::new(),
+            published: BTreeMap::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.on_request(request)?
+                },
+                Message::Notification(notification) => self.on_notification(notification)?,
+                Message::Response(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn on_request(&self, request: Request) -> Result<()> {
+        match request.method.as_str() {
+            HoverRequest::METHOD => {
+                let (id, params) = cast_request::<HoverRequest>(request)?;
+                let doc = params.text_document_position_params;
+                let markdown = self.with_analysis(&doc.text_document.uri, |analysis, path| {
+                    analysis.hover(path, doc.position)
+                });
+                let hover = markdown.flatten().map(|value| Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: None,
+                });
+                self.respond(id, hover)
+            },
+            GotoDefinition::METHOD => {
+                let (id, params) = cast_request::<GotoDefinition>(request)?;
+                let doc = params.text_document_position_params;
+                let location = self.with_analysis(&doc.text_document.uri, |analysis, path| {
+                    analysis.definition(path, doc.position)
+                });
+                self.respond(id, location.flatten().map(GotoDefinitionResponse::Scalar))
+            },
+            References::METHOD => {
+                let (id, params) = cast_request::<References>(request)?;
+                let doc = params.text_document_position;
+                let include_declaration = params.context.include_declaration;
+                let locations = self.with_analysis(&doc.text_document.uri, |analysis, path| {
+                    analysis.references(path, doc.position, include_declaration)
+                });
+                self.respond(id, locations)
+            },
+            Completion::METHOD => {
+                let (id, params) = cast_request::<Completion>(request)?;
+                let doc = params.text_document_position;
+                let items = self.with_analysis(&doc.text_document.uri, |analysis, path| {
+                    let text = self.documents.get(path).map_or("", String::as_str);
+                    completions(analysis, path, text, doc.position)
+                });
+                self.respond(id, items.map(CompletionResponse::Array))
+            },
+            _ => {
+                let response = Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request `{}`", request.method),
+                );
+                self.connection.sender.send(response.into())?;
+                Ok(())
+            },
+        }
+    }
+
+    fn on_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = cast_notification::<DidOpenTextDocument>(notification)?;
+                let path = document_path(&params.text_document.uri)?;
+                self.documents
+                    .insert(path.clone(), params.text_document.text);
+                if package_root(&path).map_or(true, |root| !self.packages.contains_key(&root)) {
+                    self.analyze(&path)?
+                }
+            },
+            DidChangeTextDocument::METHOD => {
+                let params = cast_notification::<DidChangeTextDocument>(notification)?;
+                let path = document_path(&params.text_document.uri)?;
+                // With full synchronization, the last change holds the whole text.
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(path, change.text);
+                }
+            },
+            DidSaveTextDocument::METHOD => {
+                let params = cast_notification::<DidSaveTextDocument>(notification)?;
+                self.analyze(&document_path(&params.text_document.uri)?)?
+            },
+            DidCloseTextDocument::METHOD => {
+                let params = cast_notification::<DidCloseTextDocument>(notification)?;
+                self.documents
+                    .remove(&document_path(&params.text_document.uri)?);
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// Analyzes the package containing the file at `path` and publishes its diagnostics. If the
+    /// package cannot be analyzed at all, e.g. because its manifest is invalid, the error is
+    /// shown to the user and the previous analysis is kept.
+    fn analyze(&mut self, path: &Path) -> Result<()> {
+        let Ok(root) = package_root(path) else {
+            return Ok(());
+        };
+        let analysis = match PackageAnalysis::new(&root, self.config.language_version) {
+            Ok(analysis) => analysis,
+            Err(err) => {
+                let params = ShowMessageParams {
+                    typ: MessageType::ERROR,
+                    message: format!(
+                        "Failed to analyze Move package {}: {:#}",
+                        root.display(),
+                        err
+                    ),
+                };
+                return self.notify::<ShowMessage>(params);
+            },
+        };
+        let diagnostics = analysis.diagnostics();
+        let previous = self
+            .published
+            .insert(root.clone(), diagnostics.keys().cloned().collect())
+            .unwrap_or_default();
+        // Clear the diagnostics of files which no longer have any entry.
+        for stale in previous
+            .iter()
+            .filter(|file| !diagnostics.contains_key(*file))
+        {
+            self.publish_diagnostics(stale, vec![])?
+        }
+        for (file, file_diagnostics) in diagnostics {
+            self.publish_diagnostics(&file, file_diagnostics)?
+        }
+        self.packages.insert(root, analysis);
+        Ok(())
+    }
+
+    /// Runs `f` with the analysis of the package containing the document, and the canonical path
+    /// of the document. Returns `None` if the package has not been analyzed.
+    fn with_analysis<T>(
+        &self,
+        uri: &Url,
+        f: impl FnOnce(&PackageAnalysis, &Path) -> T,
+    ) -> Option<T> {
+        let path = document_path(uri).ok()?;
+        let analysis = self.packages.get(&package_root(&path).ok()?)?;
+        Some(f(analysis, &path))
+    }
+
+    fn publish_diagnostics(
+        &self,
+        path: &Path,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+    ) -> Result<()> {
+        let uri = Url::from_file_path(path)
+            .map_err(|_| anyhow!("invalid file path {}", path.display()))?;
+        self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) -> Result<()> {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn respond(&self, id: RequestId, result: impl serde::Serialize) -> Result<()> {
+        let response = Response::new_ok(id, result);
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+}
+
+fn cast_request<R>(request: Request) -> Result<(RequestId, R::Params)>
+where
+    R: lsp_types::request::Request,
+{
+    request
+        .extract(R::METHOD)
+        .map_err(|err| anyhow!("malformed request: {:?}", err))
+}
+
+fn cast_notification<N>(notification: Notification) -> Result<N::Params>
+where
+    N: lsp_types::notification::Notification,
+{
+    notification
+        .extract(N::METHOD)
+        .map_err(|err| anyhow!("malformed notification: {:?}", err))
+}
+
+fn document_path(uri: &Url) -> Result<PathBuf> {
+    let path = uri
+        .to_file_path()
+        .map_err(|_| anyhow!("unsupported document uri {}", uri))?;
+    Ok(path.canonicalize().unwrap_or(path))
+}
+
+fn package_root(path: &Path) -> Result<PathBuf> {
+    SourcePackageLayout::try_find_root(path.parent().unwrap_or(path))
+}
@@ -0,0 +1,511 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An index from source locations to the items of the Move model they refer to, which is the
+//! basis for go-to-definition, find references and hover.
+
+use codespan::{FileId, Span};
+use move_model::{
+    ast::{Exp, ExpData, Operation, Pattern, ResourceSpecifier},
+    model::{
+        FieldId, FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, ModuleId, NodeId, Parameter,
+        QualifiedId, StructId,
+    },
+    symbol::Symbol,
+    ty::Type,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An item of the Move model which can be referred to from the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Item {
+    Module(ModuleId),
+    Struct(QualifiedId<StructId>),
+    Field(QualifiedId<StructId>, FieldId),
+    Function(QualifiedId<FunId>),
+}
+
+/// A range of the source which refers to an item, or is an expression with a type, or both.
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub loc: Loc,
+    pub item: Option<Item>,
+    /// The node of the expression or pattern at this range, which carries its type.
+    pub node_id: Option<NodeId>,
+    /// For a local variable or parameter, the location where it is declared.
+    pub declaration: Option<Loc>,
+}
+
+impl Occurrence {
+    fn contains(&self, offset: usize) -> bool {
+        let span = self.loc.span();
+        span.start().to_usize() <= offset && offset < span.end().to_usize()
+    }
+
+    fn len(&self) -> usize {
+        let span = self.loc.span();
+        span.end().to_usize() - span.start().to_usize()
+    }
+}
+
+/// The occurrences and definitions of all items of a `GlobalEnv`.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    occurrences: BTreeMap<FileId, Vec<Occurrence>>,
+    definitions: BTreeMap<Item, Loc>,
+}
+
+impl SymbolIndex {
+    /// Builds the index for the given environment. This should be run before any AST rewriting,
+    /// so that expressions still correspond to the source, e.g. before inlining.
+    pub fn new(env: &GlobalEnv) -> Self {
+        let mut index = Self::default();
+        for module in env.get_modules() {
+            let module_item = Item::Module(module.get_id());
+            let module_name = module.symbol_pool().string(module.get_name().name());
+            let module_loc = header_name_loc(env, &module.get_loc(), &module_name)
+                .unwrap_or_else(|| module.get_loc());
+            index.add_definition(module_item, module_loc.clone());
+            index.add_occurrence(module_loc, Some(module_item), None);
+            index.add_use_decls(env, &module);
+            for struct_env in module.get_structs() {
+                let struct_id = struct_env.get_qualified_id();
+                let item = Item::Struct(struct_id);
+                let struct_name = struct_env.symbol_pool().string(struct_env.get_name());
+                let struct_loc = header_name_loc(env, &struct_env.get_loc(), &struct_name)
+                    .unwrap_or_else(|| struct_env.get_loc());
+                index.add_definition(item, struct_loc.clone());
+                index.add_occurrence(struct_loc, Some(item), None);
+                for field in struct_env.get_fields() {
+                    let item = Item::Field(struct_id, field.get_id());
+                    index.add_definition(item, field.get_loc().clone());
+                    index.add_occurrence(field.get_loc().clone(), Some(item), None);
+                    if let Some(loc) = type_annotation_loc(env, field.get_loc(), &['}']) {
+                        index.add_type(env, &loc, &field.get_type());
+                    }
+                }
+            }
+            for fun in module.get_functions() {
+                let item = Item::Function(fun.get_qualified_id());
+                index.add_definition(item, fun.get_id_loc());
+                index.add_occurrence(fun.get_id_loc(), Some(item), None);
+                index.add_signature(env, &fun);
+                if let Some(def) = fun.get_def() {
+                    index.add_exp(env, &fun.get_parameters(), def);
+                }
+            }
+        }
+        index
+    }
+
+    /// Returns the location where the item is defined.
+    pub fn definition(&self, item: Item) -> Option<&Loc> {
+        self.definitions.get(&item)
+    }
+
+    /// Returns the locations of all occurrences of the item, excluding its definition.
+    pub fn references(&self, item: Item) -> impl Iterator<Item = &Loc> {
+        self.occurrences
+            .values()
+            .flatten()
+            .filter(move |occ| {
+                occ.item == Some(item) && self.definitions.get(&item) != Some(&occ.loc)
+            })
+            .map(|occ| &occ.loc)
+    }
+
+    /// Returns the innermost occurrence at the byte `offset` of the file which satisfies `filter`.
+    pub fn find_at(
+        &self,
+        file_id: FileId,
+        offset: usize,
+        filter: impl Fn(&Occurrence) -> bool,
+    ) -> Option<&Occurrence> {
+        self.occurrences
+            .get(&file_id)?
+            .iter()
+            .filter(|occ| occ.contains(offset) && filter(occ))
+            .min_by_key(|occ| occ.len())
+    }
+
+    fn add_definition(&mut self, item: Item, loc: Loc) {
+        self.definitions.insert(item, loc);
+    }
+
+    fn add_occurrence(&mut self, loc: Loc, item: Option<Item>, node_id: Option<NodeId>) {
+        self.push_occurrence(Occurrence {
+            loc,
+            item,
+            node_id,
+            declaration: None,
+        })
+    }
+
+    fn push_occurrence(&mut self, occurrence: Occurrence) {
+        self.occurrences
+            .entry(occurrence.loc.file_id())
+            .or_default()
+            .push(occurrence)
+    }
+
+    /// Adds an occurrence of the item at the name in `loc` which refers to it, and of the module
+    /// which qualifies the name, if any.
+    fn add_name(&mut self, env: &GlobalEnv, loc: &Loc, name: &str, item: Item, module: ModuleId) {
+        if let Some(name_loc) = name_loc(env, loc, name) {
+            if let Some(qualifier_loc) = qualifier_loc(env, &name_loc) {
+                self.add_occurrence(qualifier_loc, Some(Item::Module(module)), None);
+            }
+            self.add_occurrence(name_loc, Some(item), None);
+        }
+    }
+
+    fn add_use_decls(&mut self, env: &GlobalEnv, module: &ModuleEnv) {
+        for use_decl in module.get_use_decls() {
+            let Some(module_id) = use_decl.module_id else {
+                continue;
+            };
+            let used_module = env.get_module(module_id);
+            let pool = env.symbol_pool();
+            let module_name = pool.string(use_decl.module_name.name());
+            if let Some(loc) = first_name_loc(env, &use_decl.loc, &module_name) {
+                self.add_occurrence(loc, Some(Item::Module(module_id)), None);
+            }
+            for (loc, name, _) in &use_decl.members {
+                let item = if pool.string(*name).as_str() == "Self" {
+                    Item::Module(module_id)
+                } else if let Some(struct_env) = used_module.find_struct(*name) {
+                    Item::Struct(struct_env.get_qualified_id())
+                } else if let Some(fun) = used_module.find_function(*name) {
+                    Item::Function(fun.get_qualified_id())
+                } else {
+                    continue;
+                };
+                if let Some(loc) = first_name_loc(env, loc, &pool.string(*name)) {
+                    self.add_occurrence(loc, Some(item), None);
+                }
+            }
+        }
+    }
+
+    /// Adds the structs in the parameter and result types and the acquires clause of a function.
+    fn add_signature(&mut self, env: &GlobalEnv, fun: &FunctionEnv) {
+        for Parameter(_, ty, loc) in fun.get_parameters_ref() {
+            if let Some(loc) = type_annotation_loc(env, loc, &[')']) {
+                self.add_type(env, &loc, ty);
+            }
+        }
+        self.add_type(env, &fun.get_result_type_loc(), &fun.get_result_type());
+        for spec in fun.get_access_specifiers().unwrap_or_default() {
+            if let (loc, ResourceSpecifier::Resource(resource)) = &spec.resource {
+                self.add_struct_names(env, loc, resource.to_qualified_id());
+            }
+        }
+    }
+
+    /// Adds the structs of type `ty`, which is written at `loc`.
+    fn add_type(&mut self, env: &GlobalEnv, loc: &Loc, ty: &Type) {
+        let mut struct_ids = BTreeSet::new();
+        ty.visit(&mut |ty| {
+            if let Type::Struct(mid, sid, _) = ty {
+                struct_ids.insert(mid.qualified(*sid));
+            }
+        });
+        for struct_id in struct_ids {
+            self.add_struct_names(env, loc, struct_id)
+        }
+    }
+
+    fn add_struct_names(&mut self, env: &GlobalEnv, loc: &Loc, struct_id: QualifiedId<StructId>) {
+        let struct_env = env.get_struct(struct_id);
+        let name = struct_env.symbol_pool().string(struct_env.get_name());
+        for name_loc in name_locs(env, loc, &name) {
+            if let Some(qualifier_loc) = qualifier_loc(env, &name_loc) {
+                self.add_occurrence(qualifier_loc, Some(Item::Module(struct_id.module_id)), None);
+            }
+            self.add_occurrence(name_loc, Some(Item::Struct(struct_id)), None);
+        }
+    }
+
+    fn add_exp(&mut self, env: &GlobalEnv, params: &[Parameter], exp: &Exp) {
+        use move_model::ast::VisitorPosition::*;
+        // The variables declared by the enclosing blocks, lambdas and match arms.
+        let mut scopes: Vec<Vec<(Symbol, Loc)>> = vec![];
+        exp.visit_positions(&mut |position, e| {
+            match (e, position) {
+                (_, Pre) => {
+                    self.add_exp_node(env, params, &scopes, e);
+                    if let ExpData::Lambda(_, pat, _) = e {
+                        scopes.push(declared_vars(env, pat))
+                    }
+                },
+                (ExpData::Block(_, pat, _, _), BeforeBody) => scopes.push(declared_vars(env, pat)),
+                (ExpData::Match(_, _, arms), BeforeMatchBody(i)) => {
+                    scopes.push(declared_vars(env, &arms[i].pattern))
+                },
+                (ExpData::Lambda(..) | ExpData::Block(..), Post)
+                | (ExpData::Match(..), AfterMatchBody(_)) => {
+                    scopes.pop();
+                },
+                _ => {},
+            }
+            true
+        })
+    }
+
+    fn add_exp_node(
+        &mut self,
+        env: &GlobalEnv,
+        params: &[Parameter],
+        scopes: &[Vec<(Symbol, Loc)>],
+        e: &ExpData,
+    ) {
+        let id = e.node_id();
+        let loc = env.get_node_loc(id);
+        match e {
+            ExpData::Call(_, Operation::MoveFunction(mid, fid), _)
+            | ExpData::Call(_, Operation::Closure(mid, fid), _) => {
+                let fun = env.get_function(mid.qualified(*fid));
+                let name = fun.symbol_pool().string(fun.get_name());
+                self.add_name(
+                    env,
+                    &loc,
+                    &name,
+                    Item::Function(fun.get_qualified_id()),
+                    *mid,
+                );
+            },
+            ExpData::Call(_, Operation::Pack(mid, sid, _), _) => {
+                let struct_env = env.get_struct(mid.qualified(*sid));
+                let name = struct_env.symbol_pool().string(struct_env.get_name());
+                self.add_name(env, &loc, &name, Item::Struct(mid.qualified(*sid)), *mid);
+            },
+            ExpData::Call(_, Operation::Select(mid, sid, fid), _) => {
+                let struct_env = env.get_struct(mid.qualified(*sid));
+                let name = struct_env.get_field(*fid).get_name();
+                let name = struct_env.symbol_pool().string(name);
+                self.add_name(
+                    env,
+                    &loc,
+                    &name,
+                    Item::Field(mid.qualified(*sid), *fid),
+                    *mid,
+                );
+            },
+            ExpData::Call(
+                _,
+                Operation::Exists(_)
+                | Operation::BorrowGlobal(_)
+                | Operation::MoveFrom
+                | Operation::MoveTo,
+                _,
+            ) => {
+                // The struct is given by the instantiation, which is written out unless inferred.
+                if let Some(Type::Struct(mid, sid, _)) = env.get_node_instantiation(id).first() {
+                    let struct_env = env.get_struct(mid.qualified(*sid));
+                    let name = struct_env.symbol_pool().string(struct_env.get_name());
+                    self.add_name(env, &loc, &name, Item::Struct(mid.qualified(*sid)), *mid);
+                }
+            },
+            _ => {},
+        }
+        let declaration = match e {
+            ExpData::LocalVar(_, sym) => find_declaration(scopes, *sym),
+            ExpData::Temporary(_, index) => params.get(*index).map(|param| param.2.clone()),
+            _ => None,
+        };
+        self.push_occurrence(Occurrence {
+            loc,
+            item: None,
+            node_id: Some(id),
+            declaration,
+        });
+        match e {
+            ExpData::Block(_, pat, _, _) | ExpData::Lambda(_, pat, _) => {
+                self.add_pattern(env, pat, None)
+            },
+            ExpData::Assign(_, pat, _) => self.add_pattern(env, pat, Some(scopes)),
+            ExpData::Match(_, _, arms) => {
+                for arm in arms {
+                    self.add_pattern(env, &arm.pattern, None)
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Adds the occurrences in a pattern. The variables of the pattern are declared by it, unless
+    /// it is assigned to, in which case they are declared in `scopes`.
+    fn add_pattern(
+        &mut self,
+        env: &GlobalEnv,
+        pat: &Pattern,
+        scopes: Option<&[Vec<(Symbol, Loc)>]>,
+    ) {
+        let id = pat.node_id();
+        let loc = env.get_node_loc(id);
+        match pat {
+            Pattern::Struct(_, struct_id, _, args) => {
+                let struct_env = env.get_struct(struct_id.to_qualified_id());
+                let name = struct_env.symbol_pool().string(struct_env.get_name());
+                let item = Item::Struct(struct_id.to_qualified_id());
+                self.add_name(env, &loc, &name, item, struct_id.module_id);
+                self.add_occurrence(loc, None, Some(id));
+                for arg in args {
+                    self.add_pattern(env, arg, scopes)
+                }
+            },
+            Pattern::Tuple(_, args) => {
+                for arg in args {
+                    self.add_pattern(env, arg, scopes)
+                }
+            },
+            Pattern::Var(_, sym) => {
+                let declaration = match scopes {
+                    Some(scopes) => find_declaration(scopes, *sym),
+                    None => Some(loc.clone()),
+                };
+                self.push_occurrence(Occurrence {
+                    loc,
+                    item: None,
+                    node_id: Some(id),
+                    declaration,
+                })
+            },
+            Pattern::Wildcard(_) | Pattern::Error(_) => {},
+        }
+    }
+}
+
+fn declared_vars(env: &GlobalEnv, pat: &Pattern) -> Vec<(Symbol, Loc)> {
+    pat.vars()
+        .into_iter()
+        .map(|(id, sym)| (sym, env.get_node_loc(id)))
+        .collect()
+}
+
+fn find_declaration(scopes: &[Vec<(Symbol, Loc)>], sym: Symbol) -> Option<Loc> {
+    scopes.iter().rev().find_map(|scope| {
+        scope
+            .iter()
+            .rev()
+            .find(|(var, _)| *var == sym)
+            .map(|(_, loc)| loc.clone())
+    })
+}
+
+// The model does not keep the locations of all names, e.g. of the function in a call or of the
+// types in a signature, so they are found in the source text of the enclosing location.
+
+fn source_of<'a>(env: &'a GlobalEnv, loc: &Loc) -> (&'a str, usize) {
+    let span = loc.span();
+    let text = env.get_file_source(loc.file_id());
+    let start = span.start().to_usize();
+    (&text[start..span.end().to_usize()], start)
+}
+
+fn make_loc(loc: &Loc, start: usize, end: usize) -> Loc {
+    Loc::new(loc.file_id(), Span::new(start as u32, end as u32))
+}
+
+fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// Returns the offsets in `text` of `name` as a whole identifier, with the bracket nesting depth
+/// of each.
+fn find_name(text: &str, name: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut result = vec![];
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {},
+        }
+        if bytes[i..].starts_with(name.as_bytes())
+            && (i == 0 || !is_ident_char(bytes[i - 1]))
+            && bytes
+                .get(i + name.len())
+                .map_or(true, |c| !is_ident_char(*c))
+        {
+            result.push((i, depth));
+            i += name.len();
+        } else {
+            i += 1;
+        }
+    }
+    result
+}
+
+/// Returns the location of `name` in `loc` which refers to the item of the expression at `loc`,
+/// which is the last one outside of any brackets, e.g. `f` in `m::f(x)` or in `x.f(y)`.
+fn name_loc(env: &GlobalEnv, loc: &Loc, name: &str) -> Option<Loc> {
+    let (text, start) = source_of(env, loc);
+    let (offset, _) = find_name(text, name)
+        .into_iter()
+        .filter(|(_, depth)| *depth == 0)
+        .last()?;
+    Some(make_loc(loc, start + offset, start + offset + name.len()))
+}
+
+/// Returns the location of the first `name` in `loc`.
+fn first_name_loc(env: &GlobalEnv, loc: &Loc, name: &str) -> Option<Loc> {
+    name_locs(env, loc, name).into_iter().next()
+}
+
+/// Returns the locations of all `name`s in `loc`.
+fn name_locs(env: &GlobalEnv, loc: &Loc, name: &str) -> Vec<Loc> {
+    let (text, start) = source_of(env, loc);
+    find_name(text, name)
+        .into_iter()
+        .map(|(offset, _)| make_loc(loc, start + offset, start + offset + name.len()))
+        .collect()
+}
+
+/// Returns the location of the name declared by the module or struct at `loc`, which is the last
+/// `name` before its body.
+fn header_name_loc(env: &GlobalEnv, loc: &Loc, name: &str) -> Option<Loc> {
+    let (text, start) = source_of(env, loc);
+    let header = &text[..text.find('{').unwrap_or(text.len())];
+    let (offset, _) = find_name(header, name).into_iter().last()?;
+    Some(make_loc(loc, start + offset, start + offset + name.len()))
+}
+
+/// Returns the location of the module name which qualifies the name at `loc`, as `m` in `m::f`.
+fn qualifier_loc(env: &GlobalEnv, loc: &Loc) -> Option<Loc> {
+    let text = env.get_file_source(loc.file_id());
+    let before = text[..loc.span().start().to_usize()].trim_end();
+    let before = before.strip_suffix("::")?.trim_end();
+    let start = before
+        .bytes()
+        .rposition(|c| !is_ident_char(c))
+        .map_or(0, |i| i + 1);
+    (start < before.len()).then(|| make_loc(loc, start, before.len()))
+}
+
+/// Returns the location of the type annotation of the declaration whose name is at `loc`, as the
+/// type of a parameter or field, which ends at a `,` or at one of `terminators`.
+fn type_annotation_loc(env: &GlobalEnv, loc: &Loc, terminators: &[char]) -> Option<Loc> {
+    let text = env.get_file_source(loc.file_id());
+    let name_end = loc.span().end().to_usize();
+    let rest = &text[name_end..];
+    let colon = rest.find(|c: char| !c.is_whitespace())?;
+    if !rest[colon..].starts_with(':') {
+        return None;
+    }
+    let type_start = name_end + colon + 1;
+    let mut depth = 0usize;
+    for (i, c) in text[type_start..].char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' if depth > 0 => depth -= 1,
+            _ if depth == 0 && (c == ',' || terminators.contains(&c)) => {
+                return Some(make_loc(loc, type_start, type_start + i));
+            },
+            _ => {},
+        }
+    }
+    None
+}
@@ -0,0 +1,249 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use lsp_types::{CompletionItem, DiagnosticSeverity, Position};
+use move_language_server::{analysis::PackageAnalysis, completion::completions};
+use move_model::metadata::LanguageVersion;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::{tempdir, TempDir};
+
+const SOURCE: &str = "\
+module 0x42::m {
+    /// A struct with a field.
+    struct S has drop, store { f: u64 }
+
+    /// Makes an `S`.
+    public fun make(x: u64): S { S { f: x } }
+
+    public fun get(s: &S): u64 { s.f }
+
+    fun use_it(): u64 { get(&make(1)) }
+
+    fun unused(y: u64) {}
+}
+";
+
+/// A module which uses the one in `SOURCE`.
+const USER_SOURCE: &str = "\
+module 0x42::n {
+    use 0x42::m::{Self, S};
+
+    struct Holder has key { s: S }
+
+    fun store(account: &signer, s: S) {
+        move_to(account, Holder { s });
+    }
+
+    fun take(addr: address): S acquires Holder {
+        let Holder { s } = move_from<Holder>(addr);
+        s
+    }
+
+    fun check(addr: address): bool acquires Holder {
+        exists<Holder>(addr) && borrow_global<Holder>(addr).s == m::make(1)
+    }
+}
+";
+
+/// Creates a package with the modules in `SOURCE` and `USER_SOURCE`, and returns the canonical
+/// path of the one of `SOURCE`.
+fn create_package() -> (TempDir, PathBuf) {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Move.toml"),
+        "[package]\nname = \"Test\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("sources")).unwrap();
+    fs::write(dir.path().join("sources").join("n.move"), USER_SOURCE).unwrap();
+    let path = dir.path().join("sources").join("m.move");
+    fs::write(&path, SOURCE).unwrap();
+    let path = path.canonicalize().unwrap();
+    (dir, path)
+}
+
+fn analyze() -> (TempDir, PathBuf, PackageAnalysis) {
+    let (dir, path) = create_package();
+    let analysis = PackageAnalysis::new(dir.path(), LanguageVersion::default()).unwrap();
+    (dir, path, analysis)
+}
+
+/// Returns the position of the `nth` occurrence of `needle` in `text`.
+fn position_of(text: &str, needle: &str, nth: usize) -> Position {
+    let offset = text.match_indices(needle).nth(nth).unwrap().0;
+    let line = text[..offset].matches('\n').count();
+    let column = offset - text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    Position::new(line as u32, column as u32)
+}
+
+/// Returns the position right after the first occurrence of `needle` in `text`.
+fn position_after(text: &str, needle: &str) -> Position {
+    let position = position_of(text, needle, 0);
+    Position::new(position.line, position.character + needle.len() as u32)
+}
+
+/// Returns the sorted locations of the references, as paths and positions.
+fn reference_starts(
+    analysis: &PackageAnalysis,
+    path: &Path,
+    position: Position,
+) -> Vec<(PathBuf, Position)> {
+    let mut starts = analysis
+        .references(path, position, false)
+        .iter()
+        .map(|location| (location.uri.to_file_path().unwrap(), location.range.start))
+        .collect::<Vec<_>>();
+    starts.sort();
+    starts
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+#[test]
+fn test_definition() {
+    let (_dir, path, analysis) = analyze();
+    let location = analysis
+        .definition(&path, position_of(SOURCE, "make", 1))
+        .unwrap();
+    assert_eq!(location.uri.to_file_path().unwrap(), path);
+    assert_eq!(location.range.start, position_of(SOURCE, "make", 0));
+}
+
+#[test]
+fn test_references() {
+    let (_dir, path, analysis) = analyze();
+    let references = analysis.references(&path, position_of(SOURCE, "get", 0), true);
+    let mut starts = references
+        .iter()
+        .map(|location| location.range.start)
+        .collect::<Vec<_>>();
+    starts.sort();
+    assert_eq!(starts, vec![
+        position_of(SOURCE, "get", 0),
+        position_of(SOURCE, "get", 1)
+    ]);
+    let references = analysis.references(&path, position_of(SOURCE, "get", 0), false);
+    assert_eq!(references.len(), 1);
+}
+
+#[test]
+fn test_definition_of_local() {
+    let (_dir, path, analysis) = analyze();
+    // The argument of the pack, rather than the struct.
+    let location = analysis
+        .definition(&path, position_of(SOURCE, "x }", 0))
+        .unwrap();
+    assert_eq!(location.range.start, position_of(SOURCE, "x: u64", 0));
+
+    let user_path = path.with_file_name("n.move");
+    let location = analysis
+        .definition(&user_path, position_of(USER_SOURCE, "s\n", 0))
+        .unwrap();
+    assert_eq!(location.range.start, position_of(USER_SOURCE, "s } =", 0));
+}
+
+#[test]
+fn test_struct_references() {
+    let (_dir, path, analysis) = analyze();
+    let user_path = path.with_file_name("n.move");
+    let starts = reference_starts(&analysis, &user_path, position_of(USER_SOURCE, "Holder", 0));
+    let expected = [1, 2, 3, 4, 5, 6, 7]
+        .into_iter()
+        .map(|nth| (user_path.clone(), position_of(USER_SOURCE, "Holder", nth)))
+        .collect::<Vec<_>>();
+    assert_eq!(starts, expected);
+
+    let starts = reference_starts(&analysis, &path, position_of(SOURCE, "S has", 0));
+    let mut expected = vec![
+        // The result type, pack and parameter type in `SOURCE`
+        (path.clone(), position_of(SOURCE, "S {", 0)),
+        (path.clone(), position_of(SOURCE, "S {", 1)),
+        (path.clone(), position_of(SOURCE, "S)", 0)),
+        // The use, field type, parameter type and result type in `USER_SOURCE`
+        (user_path.clone(), position_of(USER_SOURCE, "S};", 0)),
+        (user_path.clone(), position_after(USER_SOURCE, "{ s: ")),
+        (
+            user_path.clone(),
+            position_after(USER_SOURCE, "signer, s: "),
+        ),
+        (user_path.clone(), position_after(USER_SOURCE, "address): ")),
+    ];
+    expected.sort();
+    assert_eq!(starts, expected);
+}
+
+#[test]
+fn test_module_references() {
+    let (_dir, path, analysis) = analyze();
+    let user_path = path.with_file_name("n.move");
+    let location = analysis
+        .definition(&user_path, position_of(USER_SOURCE, "m::make", 0))
+        .unwrap();
+    assert_eq!(location.uri.to_file_path().unwrap(), path);
+    assert_eq!(location.range.start, position_of(SOURCE, "m {", 0));
+
+    let starts = reference_starts(&analysis, &path, position_of(SOURCE, "m {", 0));
+    assert_eq!(starts, vec![
+        (user_path.clone(), position_of(USER_SOURCE, "m::", 0)),
+        (user_path.clone(), position_of(USER_SOURCE, "Self", 0)),
+        (user_path.clone(), position_of(USER_SOURCE, "m::make", 0)),
+    ]);
+
+    // The reference to the function is its name, not the whole call.
+    let starts = reference_starts(&analysis, &path, position_of(SOURCE, "make", 0));
+    assert!(starts.contains(&(user_path.clone(), position_of(USER_SOURCE, "make", 0))));
+}
+
+#[test]
+fn test_hover() {
+    let (_dir, path, analysis) = analyze();
+    let function = analysis
+        .hover(&path, position_of(SOURCE, "make", 1))
+        .unwrap();
+    assert!(function.contains("make(x: u64)"), "{}", function);
+    assert!(function.contains("Makes an `S`."), "{}", function);
+
+    let field = analysis.hover(&path, position_after(SOURCE, "s.")).unwrap();
+    assert!(field.contains("f: u64"), "{}", field);
+
+    let local = analysis
+        .hover(&path, position_of(SOURCE, "x }", 0))
+        .unwrap();
+    assert_eq!(local, "```move\nu64\n```");
+}
+
+#[test]
+fn test_diagnostics() {
+    let (_dir, path, analysis) = analyze();
+    let diagnostics = analysis.diagnostics();
+    let unused = diagnostics[&path]
+        .iter()
+        .find(|diag| diag.message.contains("`y`"))
+        .unwrap();
+    assert_eq!(unused.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        unused.range.start.line,
+        position_of(SOURCE, "y: u64", 0).line
+    );
+}
+
+#[test]
+fn test_completion() {
+    let (_dir, path, analysis) = analyze();
+
+    let text = SOURCE.replace("get(&make(1))", "m::");
+    let items = completions(&analysis, &path, &text, position_after(&text, "m::"));
+    let module_labels = labels(&items);
+    for label in ["make", "get", "S"] {
+        assert!(module_labels.contains(&label), "{:?}", module_labels);
+    }
+
+    let text = SOURCE.replace("s.f", "s.");
+    let items = completions(&analysis, &path, &text, position_after(&text, "s."));
+    assert_eq!(labels(&items), vec!["f"]);
+}
@@ -0,0 +1,363 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A cache of the compiled units of dependencies, shared by all packages built on the same
+//! machine.
+//!
+//! Entries are content-addressed: the key of a dependency is a hash of its source digest, the
+//! addresses it is compiled with, the keys of its own dependencies, and the build of the compiler
+//! and configuration it is compiled with. A change to any of these leads to a different key, so
+//! entries never need to be invalidated, and the least recently used ones are evicted once the
+//! cache holds too many.
+//!
+//! No code is generated for dependencies found in the cache. If they declare nothing beyond what
+//! their bytecode describes, the package is type checked against interfaces generated from their
+//! bytecode, which are stored along with it. Otherwise they are still given to the compiler as
+//! sources, so that, for example, their inline functions are available.
+
+use crate::{
+    compilation::{
+        compiled_package::{decode_compiled_unit, CompiledUnitWithSource},
+        package_layout::CompiledPackageLayout,
+    },
+    resolution::resolution_graph::ResolvedGraph,
+    source_package::parsed_manifest::PackageName,
+    CompilerConfig,
+};
+use anyhow::Result;
+use move_bytecode_source_map::utils::source_map_from_file;
+use move_command_line_common::{
+    env::MOVE_HOME,
+    files::{MOVE_COMPILED_EXTENSION, MOVE_EXTENSION, SOURCE_MAP_EXTENSION},
+};
+use move_compiler::{compiled_unit::CompiledUnit, interface_generator};
+use move_core_types::language_storage::ModuleId;
+use move_model::{ast::SpecBlockTarget, model::GlobalEnv};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// The directory of the build cache under `MOVE_HOME`.
+const BUILD_CACHE_DIR: &str = "build-cache";
+
+/// The file of an entry which describes the compiled units stored in it. Its modification time
+/// is the last time the entry was used.
+const ENTRY_INFO_FILE: &str = "Units.yaml";
+
+/// The directory of an entry with the interfaces of its modules.
+const INTERFACES_DIR: &str = "interfaces";
+
+/// Changes whenever the layout of entries or the computation of keys changes.
+const BUILD_CACHE_FORMAT_VERSION: u32 = 2;
+
+/// A compiled unit stored in a cache entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedUnit {
+    /// The name of the unit, which is the name of the bytecode and source map files.
+    name: String,
+    /// The source file of the unit, relative to the root of its package.
+    source_path: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryInfo {
+    package_name: PackageName,
+    modules: Vec<CachedUnit>,
+    scripts: Vec<CachedUnit>,
+    /// Whether the entry stores interfaces of the modules, which can replace the sources of the
+    /// package when type checking packages depending on it.
+    has_interfaces: bool,
+}
+
+/// A package found in the build cache.
+pub(crate) struct CachedPackage {
+    pub(crate) units: Vec<CompiledUnitWithSource>,
+    /// The directory with the interfaces of the modules of the package, if they can replace its
+    /// sources.
+    pub(crate) interfaces: Option<PathBuf>,
+}
+
+pub(crate) struct BuildCache {
+    root: PathBuf,
+}
+
+impl BuildCache {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Returns the build cache shared by all packages built with the current `MOVE_HOME`.
+    pub(crate) fn in_move_home() -> Self {
+        Self::new(PathBuf::from(MOVE_HOME.clone()).join(BUILD_CACHE_DIR))
+    }
+
+    /// Computes the cache keys of all packages of the resolution graph, besides the root package,
+    /// when compiled by the given build of the compiler.
+    pub(crate) fn package_keys(
+        resolution_graph: &ResolvedGraph,
+        config: &CompilerConfig,
+        bytecode_version: u32,
+        compiler_build: &str,
+    ) -> BTreeMap<PackageName, String> {
+        let build_options = &resolution_graph.build_options;
+        // Everything the generated code depends on, besides the packages themselves.
+        let compilation_context = format!(
+            "{}:{}:{:?}:{:?}:{}:{}:{}:{:?}:{:?}",
+            BUILD_CACHE_FORMAT_VERSION,
+            compiler_build,
+            config.compiler_version.unwrap_or_default(),
+            config.language_version.unwrap_or_default(),
+            bytecode_version,
+            build_options.dev_mode,
+            build_options.test_mode,
+            build_options.architecture,
+            config.experiments.iter().collect::<BTreeSet<_>>(),
+        );
+        let mut keys = BTreeMap::new();
+        for package_name in resolution_graph.package_table.keys() {
+            if *package_name != resolution_graph.root_package.package.name {
+                Self::package_key(
+                    resolution_graph,
+                    &compilation_context,
+                    *package_name,
+                    &mut keys,
+                );
+            }
+        }
+        keys
+    }
+
+    fn package_key(
+        resolution_graph: &ResolvedGraph,
+        compilation_context: &str,
+        package_name: PackageName,
+        keys: &mut BTreeMap<PackageName, String>,
+    ) -> String {
+        if let Some(key) = keys.get(&package_name) {
+            return key.clone();
+        }
+        let package = resolution_graph.get_package(&package_name);
+        let mut hasher = Sha256::new();
+        hasher.update(compilation_context.as_bytes());
+        hasher.update(format!(
+            ":{}:{}:{:?}",
+            package_name, package.source_digest, package.resolution_table
+        ));
+        for dep_name in package.immediate_dependencies(resolution_graph) {
+            let dep_key = Self::package_key(resolution_graph, compilation_context, dep_name, keys);
+            hasher.update(format!(":{}={}", dep_name, dep_key));
+        }
+        let key = format!("{:X}", hasher.finalize());
+        keys.insert(package_name, key.clone());
+        key
+    }
+
+    /// Returns the package stored under `key`, with the source paths of its units resolved
+    /// against `package_path`, or `None` if there is no entry for the key. Marks the entry as
+    /// used.
+    pub(crate) fn load(&self, key: &str, package_path: &Path) -> Result<Option<CachedPackage>> {
+        let entry_path = self.root.join(key);
+        let info_path = entry_path.join(ENTRY_INFO_FILE);
+        if !info_path.is_file() {
+            return Ok(None);
+        }
+        let info: EntryInfo = serde_yaml::from_str(&std::fs::read_to_string(&info_path)?)?;
+        File::options()
+            .write(true)
+            .open(&info_path)?
+            .set_modified(SystemTime::now())?;
+        let mut units = vec![];
+        for (category, cached_units) in [
+            (CompiledPackageLayout::CompiledModules, &info.modules),
+            (CompiledPackageLayout::CompiledScripts, &info.scripts),
+        ] {
+            for cached in cached_units {
+                let bytecode_bytes = std::fs::read(
+                    entry_path
+                        .join(category.path())
+                        .join(&cached.name)
+                        .with_extension(MOVE_COMPILED_EXTENSION),
+                )?;
+                let source_map = source_map_from_file(
+                    &entry_path
+                        .join(CompiledPackageLayout::SourceMaps.path())
+                        .join(category.path())
+                        .join(&cached.name)
+                        .with_extension(SOURCE_MAP_EXTENSION),
+                )?;
+                units.push(CompiledUnitWithSource {
+                    unit: decode_compiled_unit(
+                        info.package_name,
+                        cached.name.as_str().into(),
+                        &bytecode_bytes,
+                        source_map,
+                    )?,
+                    source_path: package_path.join(&cached.source_path),
+                })
+            }
+        }
+        Ok(Some(CachedPackage {
+            units,
+            interfaces: info.has_interfaces.then(|| entry_path.join(INTERFACES_DIR)),
+        }))
+    }
+
+    /// Stores the compiled units of a package under `key`, along with interfaces of its modules
+    /// if `with_interfaces` is set. Units are only stored if all their sources are in
+    /// `package_path`. The entry is written to a temporary directory first and then moved into
+    /// place, so that concurrent builds never see partial entries.
+    pub(crate) fn store<'a>(
+        &self,
+        key: &str,
+        package_name: PackageName,
+        package_path: &Path,
+        units: impl IntoIterator<Item = &'a CompiledUnitWithSource>,
+        bytecode_version: u32,
+        with_interfaces: bool,
+    ) -> Result<()> {
+        let entry_path = self.root.join(key);
+        if entry_path.exists() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.root)?;
+        let temp_dir = tempfile::Builder::new()
+            .prefix(".tmp")
+            .tempdir_in(&self.root)?;
+        let mut info = EntryInfo {
+            package_name,
+            modules: vec![],
+            scripts: vec![],
+            has_interfaces: with_interfaces,
+        };
+        for unit in units {
+            let Ok(source_path) = unit.source_path.strip_prefix(package_path) else {
+                return Ok(());
+            };
+            let (category, name, cached_units) = match &unit.unit {
+                CompiledUnit::Module(named) => (
+                    CompiledPackageLayout::CompiledModules,
+                    named.name,
+                    &mut info.modules,
+                ),
+                CompiledUnit::Script(named) => (
+                    CompiledPackageLayout::CompiledScripts,
+                    named.name,
+                    &mut info.scripts,
+                ),
+            };
+            let bytecode_path = temp_dir
+                .path()
+                .join(category.path())
+                .join(name.as_str())
+                .with_extension(MOVE_COMPILED_EXTENSION);
+            let source_map_path = temp_dir
+                .path()
+                .join(CompiledPackageLayout::SourceMaps.path())
+                .join(category.path())
+                .join(name.as_str())
+                .with_extension(SOURCE_MAP_EXTENSION);
+            for path in [&bytecode_path, &source_map_path] {
+                std::fs::create_dir_all(path.parent().unwrap())?;
+            }
+            std::fs::write(bytecode_path, unit.unit.serialize(Some(bytecode_version)))?;
+            std::fs::write(source_map_path, unit.unit.serialize_source_map())?;
+            if let (true, CompiledUnit::Module(named)) = (with_interfaces, &unit.unit) {
+                // Addresses are written as numbers, which is how they are resolved anyway.
+                let (id, interface) = interface_generator::write_module_to_string(
+                    &BTreeMap::<ModuleId, String>::new(),
+                    &named.module,
+                )?;
+                let interface_path = temp_dir
+                    .path()
+                    .join(INTERFACES_DIR)
+                    .join(id.address().to_hex())
+                    .join(id.name().as_str())
+                    .with_extension(MOVE_EXTENSION);
+                std::fs::create_dir_all(interface_path.parent().unwrap())?;
+                std::fs::write(interface_path, interface)?;
+            }
+            cached_units.push(CachedUnit {
+                name: name.to_string(),
+                source_path: source_path.to_path_buf(),
+            });
+        }
+        std::fs::write(
+            temp_dir.path().join(ENTRY_INFO_FILE),
+            serde_yaml::to_string(&info)?,
+        )?;
+        // If another build stored the same entry in the meantime, keep that one.
+        if std::fs::rename(temp_dir.path(), &entry_path).is_err() && !entry_path.exists() {
+            anyhow::bail!(
+                "Unable to store package '{}' in the build cache at {}",
+                package_name,
+                entry_path.display()
+            )
+        }
+        Ok(())
+    }
+
+    /// Removes the least recently used entries until at most `max_entries` remain.
+    pub(crate) fn evict(&self, max_entries: usize) -> Result<()> {
+        let mut entries = vec![];
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry_path = entry?.path();
+            // Skip the temporary directories of entries being stored.
+            if entry_path
+                .file_name()
+                .map_or(true, |name| name.to_string_lossy().starts_with('.'))
+            {
+                continue;
+            }
+            let last_used = entry_path
+                .join(ENTRY_INFO_FILE)
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((last_used, entry_path));
+        }
+        if entries.len() > max_entries {
+            entries.sort();
+            for (_, entry_path) in &entries[..entries.len() - max_entries] {
+                // Another build may be evicting the same entry concurrently.
+                let _ = std::fs::remove_dir_all(entry_path);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the modules among `units` can be replaced by interfaces generated from their
+/// bytecode when type checking the packages depending on them. This is the case if they declare
+/// nothing which is only available in their sources: no inline functions, enums, attributes,
+/// specification functions, variables or schemas.
+pub(crate) fn interfaces_suffice<'a>(
+    env: &GlobalEnv,
+    units: impl IntoIterator<Item = &'a CompiledUnitWithSource>,
+) -> bool {
+    units.into_iter().all(|unit| {
+        let CompiledUnit::Module(named) = &unit.unit else {
+            return true;
+        };
+        let Some(module) = env.find_module_by_language_storage_id(&named.module.self_id()) else {
+            return false;
+        };
+        module.get_attributes().is_empty()
+            && module
+                .get_functions()
+                .all(|fun| !fun.is_inline() && fun.get_attributes().is_empty())
+            && module
+                .get_structs()
+                .all(|st| !st.has_variants() && st.get_attributes().is_empty())
+            && module.get_spec_funs().all(|(_, fun)| fun.is_move_fun)
+            && module.get_spec_vars().next().is_none()
+            && !module
+                .get_spec_block_infos()
+                .iter()
+                .any(|info| matches!(info.target, SpecBlockTarget::Schema(..)))
+    })
+}
@@ -3,7 +3,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::{build_plan::CompilerDriverResult, package_layout::CompiledPackageLayout},
+    compilation::{
+        build_cache::{interfaces_suffice, BuildCache},
+        build_plan::CompilerDriverResult,
+        package_layout::CompiledPackageLayout,
+    },
     resolution::resolution_graph::{Renaming, ResolvedGraph, ResolvedPackage, ResolvedTable},
     source_package::{
         layout::{SourcePackageLayout, REFERENCE_TEMPLATE_FILENAME},
@@ -16,10 +20,14 @@ use colored::Colorize;
 use itertools::{Either, Itertools};
 use move_abigen::{Abigen, AbigenOptions};
 use move_binary_format::file_format::{CompiledModule, CompiledScript};
-use move_bytecode_source_map::utils::source_map_from_file;
+use move_bytecode_source_map::{source_map::SourceMap, utils::source_map_from_file};
 use move_bytecode_utils::Modules;
-use move_command_line_common::files::{
-    extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION, SOURCE_MAP_EXTENSION,
+use move_command_line_common::{
+    env::{get_move_build_cache_from_env, get_move_build_cache_max_entries_from_env},
+    files::{
+        extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
+        SOURCE_MAP_EXTENSION,
+    },
 };
 use move_compiler::{
     attr_derivation,
@@ -208,7 +216,6 @@ impl OnDiskCompiledPackage {
         package_name: Symbol,
         bytecode_path_str: &str,
     ) -> Result<CompiledUnitWithSource> {
-        let package_name_opt = Some(package_name);
         let bytecode_path = Path::new(bytecode_path_str);
         let path_to_file = CompiledPackageLayout::path_to_file_after_category(bytecode_path);
         let bytecode_bytes = std::fs::read(bytecode_path)?;
@@ -232,44 +239,15 @@ impl OnDiskCompiledPackage {
             bytecode_path_str,
             package_name
         );
-        match CompiledScript::deserialize(&bytecode_bytes) {
-            Ok(script) => {
-                let name = FileName::from(
-                    bytecode_path
-                        .file_stem()
-                        .unwrap()
-                        .to_string_lossy()
-                        .to_string(),
-                );
-                let unit = CompiledUnit::Script(NamedCompiledScript {
-                    package_name: package_name_opt,
-                    name,
-                    script,
-                    source_map,
-                });
-                Ok(CompiledUnitWithSource { unit, source_path })
-            },
-            Err(_) => {
-                let module = CompiledModule::deserialize(&bytecode_bytes)?;
-                let (address_bytes, module_name) = {
-                    let id = module.self_id();
-                    let parsed_addr = NumericalAddress::new(
-                        id.address().into_bytes(),
-                        move_compiler::shared::NumberFormat::Hex,
-                    );
-                    let module_name = FileName::from(id.name().as_str());
-                    (parsed_addr, module_name)
-                };
-                let unit = CompiledUnit::Module(NamedCompiledModule {
-                    package_name: package_name_opt,
-                    address: address_bytes,
-                    name: module_name,
-                    module,
-                    source_map,
-                });
-                Ok(CompiledUnitWithSource { unit, source_path })
-            },
-        }
+        let script_name = FileName::from(
+            bytecode_path
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+        );
+        let unit = decode_compiled_unit(package_name, script_name, &bytecode_bytes, source_map)?;
+        Ok(CompiledUnitWithSource { unit, source_path })
     }
 
     /// Save `bytes` under `path_under` relative to the package on disk
@@ -615,6 +593,70 @@ impl CompiledPackage {
             .clone()
             .into_iter()
             .partition_map(|(p, b)| if b { Either::Left(p) } else { Either::Right(p) });
+        let bytecode_version = config
+            .language_version
+            .unwrap_or_default()
+            .infer_bytecode_version(config.bytecode_version);
+        // Dependencies found in the build cache are only type checked, their compiled units are
+        // taken from the cache. Code is only reused if it was generated by the same build of the
+        // compiler.
+        let build_cache =
+            if resolution_graph.build_options.build_cache || get_move_build_cache_from_env() {
+                match &resolution_graph.build_options.compiler_build {
+                    Some(compiler_build) => Some((BuildCache::in_move_home(), compiler_build)),
+                    None => {
+                        writeln!(
+                            w,
+                            "{} the build of the compiler is unknown",
+                            "NOT USING THE BUILD CACHE".bold().yellow()
+                        )?;
+                        None
+                    },
+                }
+            } else {
+                None
+            };
+        let cache_keys = match &build_cache {
+            Some((_, compiler_build)) => {
+                BuildCache::package_keys(resolution_graph, config, bytecode_version, compiler_build)
+            },
+            None => BTreeMap::new(),
+        };
+        // Interfaces lack the attributes and code of the dependencies, which the model and
+        // documentation are built from, and do not support renamed addresses.
+        let use_interfaces = !resolution_graph.build_options.generate_docs
+            && !resolution_graph.build_options.generate_move_model
+            && resolution_graph.contains_renaming().is_none();
+        let mut cached_deps = vec![];
+        let mut cached_compiled_units = vec![];
+        let src_deps = match &build_cache {
+            Some((cache, _)) => src_deps
+                .into_iter()
+                .filter(|dep| {
+                    let dep_name = dep.name.expect("dependency has a name");
+                    let dep_path = &resolution_graph.get_package(&dep_name).package_path;
+                    match cache.load(&cache_keys[&dep_name], dep_path) {
+                        Ok(Some(cached)) => {
+                            cached_deps.push(match cached.interfaces {
+                                Some(interfaces) if use_interfaces => PackagePaths {
+                                    paths: vec![Symbol::from(
+                                        interfaces.to_string_lossy().as_ref(),
+                                    )],
+                                    ..dep.clone()
+                                },
+                                _ => dep.clone(),
+                            });
+                            cached_compiled_units
+                                .extend(cached.units.into_iter().map(|unit| (dep_name, unit)));
+                            false
+                        },
+                        // An entry which cannot be read is treated like a missing one.
+                        Ok(None) | Err(_) => true,
+                    }
+                })
+                .collect(),
+            None => src_deps,
+        };
         // If bytecode dependency is not empty, do not allow renaming
         if !bytecode_deps.is_empty() {
             if let Some(pkg_name) = resolution_graph.contains_renaming() {
@@ -636,10 +678,10 @@ impl CompiledPackage {
             .unwrap_or_default()
         {
             CompilerVersion::V1 => {
-                let mut paths = src_deps;
+                let mut paths = src_deps.clone();
                 paths.push(sources_package_paths.clone());
-                let compiler =
-                    Compiler::from_package_paths(paths, bytecode_deps, flags, &known_attributes);
+                let deps = bytecode_deps.into_iter().chain(cached_deps).collect();
+                let compiler = Compiler::from_package_paths(paths, deps, flags, &known_attributes);
                 compiler_driver_v1(compiler)?
             },
             version @ CompilerVersion::V2_0 | version @ CompilerVersion::V2_1 => {
@@ -652,6 +694,7 @@ impl CompiledPackage {
                 for pack in std::iter::once(&sources_package_paths)
                     .chain(src_deps.iter())
                     .chain(bytecode_deps.iter())
+                    .chain(cached_deps.iter())
                 {
                     for (name, val) in &pack.named_address_map {
                         if let Some(old) = global_address_map.insert(name.as_str().to_owned(), *val)
@@ -679,6 +722,7 @@ impl CompiledPackage {
                     sources_deps: src_deps.iter().flat_map(|x| to_str_vec(&x.paths)).collect(),
                     dependencies: bytecode_deps
                         .iter()
+                        .chain(cached_deps.iter())
                         .flat_map(|x| to_str_vec(&x.paths))
                         .collect(),
                     named_address_mapping: global_address_map
@@ -691,6 +735,11 @@ impl CompiledPackage {
                     compiler_version: Some(version),
                     compile_test_code: flags.keep_testing_functions(),
                     experiments: config.experiments.clone(),
+                    lint_config: resolution_graph
+                        .root_package
+                        .lints
+                        .clone()
+                        .unwrap_or_default(),
                     ..Default::default()
                 };
                 options = options.set_experiment(Experiment::ATTACH_COMPILED_MODULE, true);
@@ -734,10 +783,50 @@ impl CompiledPackage {
                 deps_compiled_units.push((package_name, unit))
             }
         }
-        let bytecode_version = config
-            .language_version
-            .unwrap_or_default()
-            .infer_bytecode_version(config.bytecode_version);
+        if let Some((cache, _)) = &build_cache {
+            for dep in &src_deps {
+                let dep_name = dep.name.expect("dependency has a name");
+                let dep_path = &resolution_graph.get_package(&dep_name).package_path;
+                let units = || {
+                    deps_compiled_units
+                        .iter()
+                        .filter(|(package_name, _)| *package_name == dep_name)
+                        .map(|(_, unit)| unit)
+                };
+                // Whether interfaces suffice can only be told from the model built by V2.
+                let with_interfaces = optional_global_env
+                    .as_ref()
+                    .is_some_and(|env| interfaces_suffice(env, units()));
+                // Failing to store a dependency only means that it is compiled again next time.
+                if let Err(err) = cache.store(
+                    &cache_keys[&dep_name],
+                    dep_name,
+                    dep_path,
+                    units(),
+                    bytecode_version,
+                    with_interfaces,
+                ) {
+                    writeln!(
+                        w,
+                        "{} {}: {:#}",
+                        "FAILED TO CACHE DEPENDENCY".bold().yellow(),
+                        dep_name,
+                        err
+                    )?;
+                }
+            }
+            if !src_deps.is_empty() {
+                if let Err(err) = cache.evict(get_move_build_cache_max_entries_from_env()) {
+                    writeln!(
+                        w,
+                        "{}: {:#}",
+                        "FAILED TO EVICT FROM THE BUILD CACHE".bold().yellow(),
+                        err
+                    )?;
+                }
+            }
+        }
+        deps_compiled_units.extend(cached_compiled_units);
         let mut compiled_docs = None;
         let mut compiled_abis = None;
         let mut move_model = None;
@@ -1058,6 +1147,44 @@ pub(crate) fn apply_named_address_renaming(
         .collect()
 }
 
+/// Decodes a compiled unit of the given package from its bytecode, which is a script named
+/// `script_name` if it can be deserialized as one, and a module otherwise.
+pub(crate) fn decode_compiled_unit(
+    package_name: Symbol,
+    script_name: FileName,
+    bytecode_bytes: &[u8],
+    source_map: SourceMap,
+) -> Result<CompiledUnit> {
+    let package_name_opt = Some(package_name);
+    match CompiledScript::deserialize(bytecode_bytes) {
+        Ok(script) => Ok(CompiledUnit::Script(NamedCompiledScript {
+            package_name: package_name_opt,
+            name: script_name,
+            script,
+            source_map,
+        })),
+        Err(_) => {
+            let module = CompiledModule::deserialize(bytecode_bytes)?;
+            let (address_bytes, module_name) = {
+                let id = module.self_id();
+                let parsed_addr = NumericalAddress::new(
+                    id.address().into_bytes(),
+                    move_compiler::shared::NumberFormat::Hex,
+                );
+                let module_name = FileName::from(id.name().as_str());
+                (parsed_addr, module_name)
+            };
+            Ok(CompiledUnit::Module(NamedCompiledModule {
+                package_name: package_name_opt,
+                address: address_bytes,
+                name: module_name,
+                module,
+                source_map,
+            }))
+        },
+    }
+}
+
 /// Collects source and dependency files with their address mappings.
 pub fn make_source_and_deps_for_compiler(
     resolution_graph: &ResolvedGraph,
@@ -33,6 +33,65 @@ impl ModelBuilder {
     // TODO: In the future we will need a better way to do this to support renaming in packages
     // where we want to support building a Move model.
     pub fn build_model(&self) -> Result<GlobalEnv> {
+        let (all_targets, all_deps) = self.targets_and_deps()?;
+        let skip_attribute_checks = self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .skip_attribute_checks;
+        let known_attributes = &self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .known_attributes;
+        match self.model_config.compiler_version {
+            CompilerVersion::V1 => run_model_builder_with_options(
+                all_targets,
+                vec![],
+                all_deps,
+                ModelBuilderOptions::default(),
+                skip_attribute_checks,
+                known_attributes,
+            ),
+            CompilerVersion::V2_0 | CompilerVersion::V2_1 => {
+                let options = self.make_v2_compiler_options(all_targets, all_deps);
+                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
+            },
+        }
+    }
+
+    /// Returns the options for running the v2 compiler on the package, e.g. for tools which run
+    /// parts of the compiler themselves.
+    pub fn v2_compiler_options(&self) -> Result<Options> {
+        let (all_targets, all_deps) = self.targets_and_deps()?;
+        Ok(self.make_v2_compiler_options(all_targets, all_deps))
+    }
+
+    fn make_v2_compiler_options(
+        &self,
+        all_targets: Vec<PackagePaths>,
+        all_deps: Vec<PackagePaths>,
+    ) -> Options {
+        let compiler_config = &self.resolution_graph.build_options.compiler_config;
+        let mut options = make_options_for_v2_compiler(all_targets, all_deps);
+        options.language_version = compiler_config.language_version;
+        options.compiler_version = Some(self.model_config.compiler_version);
+        options
+            .known_attributes
+            .clone_from(&compiler_config.known_attributes);
+        options.skip_attribute_checks = compiler_config.skip_attribute_checks;
+        options.compile_verify_code = true;
+        options.lint_config = self
+            .resolution_graph
+            .root_package
+            .lints
+            .clone()
+            .unwrap_or_default();
+        options
+    }
+
+    fn targets_and_deps(&self) -> Result<(Vec<PackagePaths>, Vec<PackagePaths>)> {
         // Make sure no renamings have been performed
         if let Some(pkg_name) = self.resolution_graph.contains_renaming() {
             anyhow::bail!(
@@ -82,7 +141,7 @@ impl ModelBuilder {
         } else {
             (vec![target], deps)
         };
-        let (all_targets, all_deps) = match &self.model_config.target_filter {
+        Ok(match &self.model_config.target_filter {
             Some(filter) => {
                 let mut new_targets = vec![];
                 let mut new_deps = all_deps.into_iter().map(|(p, _)| p).collect_vec();
@@ -115,42 +174,7 @@ impl ModelBuilder {
                 all_targets,
                 all_deps.into_iter().map(|(p, _)| p).collect_vec(),
             ),
-        };
-
-        let skip_attribute_checks = self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .skip_attribute_checks;
-        let known_attributes = &self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .known_attributes;
-        match self.model_config.compiler_version {
-            CompilerVersion::V1 => run_model_builder_with_options(
-                all_targets,
-                vec![],
-                all_deps,
-                ModelBuilderOptions::default(),
-                skip_attribute_checks,
-                known_attributes,
-            ),
-            CompilerVersion::V2_0 | CompilerVersion::V2_1 => {
-                let mut options = make_options_for_v2_compiler(all_targets, all_deps);
-                options.language_version = self
-                    .resolution_graph
-                    .build_options
-                    .compiler_config
-                    .language_version;
-                options.compiler_version = Some(self.model_config.compiler_version);
-                options.known_attributes.clone_from(known_attributes);
-                options.skip_attribute_checks = skip_attribute_checks;
-                options.compile_verify_code = true;
-                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
-                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
-            },
-        }
+        })
     }
 }
 
@@ -14,7 +14,10 @@ use crate::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
     },
     package_lock::PackageLock,
-    resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
+    resolution::{
+        lock_file::MoveLock,
+        resolution_graph::{ResolutionGraph, ResolvedGraph},
+    },
     source_package::manifest_parser,
 };
 use anyhow::{bail, Result};
@@ -152,6 +155,25 @@ pub struct BuildConfig {
     #[clap(long = "skip-fetch-latest-git-deps", global = true)]
     pub skip_fetch_latest_git_deps: bool,
 
+    /// Ignore the commits that git dependencies are locked to in `Move.lock`, resolve them to the
+    /// latest state of their revisions instead, and rewrite the lock file accordingly
+    #[clap(skip)]
+    #[serde(default)]
+    pub update_lock_file: bool,
+
+    /// Reuse the compiled dependencies of the shared build cache under MOVE_HOME, and add the
+    /// dependencies compiled by this build to it. Can also be enabled by setting the
+    /// `MOVE_BUILD_CACHE` environment variable. Only has an effect if `compiler_build` is set.
+    #[clap(skip)]
+    #[serde(default)]
+    pub build_cache: bool,
+
+    /// Identifies the build of the compiler, such as the commit of the tool embedding it. Cached
+    /// dependencies are only reused by builds with the same compiler build.
+    #[clap(skip)]
+    #[serde(default)]
+    pub compiler_build: Option<String>,
+
     #[clap(flatten)]
     pub compiler_config: CompilerConfig,
 }
@@ -261,11 +283,37 @@ impl BuildConfig {
         // This should be locked as it inspects the environment for `MOVE_HOME` which could
         // possibly be set by a different process in parallel.
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
-        ResolutionGraph::download_dependency_repos(&manifest, self, &path, writer)?;
+        let lock_file = if self.update_lock_file {
+            MoveLock::default()
+        } else {
+            MoveLock::read(&path)?
+        };
+        ResolutionGraph::download_dependency_repos(&manifest, self, &lock_file, &path, writer)?;
         mutx.unlock();
         Ok(())
     }
 
+    /// Resolves the git dependencies of the package at `path` to the latest state of their
+    /// revisions, and writes the commits they are resolved to into the package's `Move.lock`.
+    pub fn update_lock_file_for_package<W: Write>(
+        mut self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<MoveLock> {
+        self.update_lock_file = true;
+        let path = SourcePackageLayout::try_find_root(path)?;
+        let toml_manifest =
+            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let mutx = PackageLock::lock();
+        // This should be locked as it inspects the environment for `MOVE_HOME` which could
+        // possibly be set by a different process in parallel.
+        let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
+        let ret = ResolutionGraph::new(manifest, path, self, writer)
+            .map(|resolution_graph| resolution_graph.lock_file);
+        mutx.unlock();
+        ret
+    }
+
     pub fn resolution_graph_for_package<W: Write>(
         mut self,
         path: &Path,
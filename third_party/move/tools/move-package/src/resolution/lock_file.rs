@@ -0,0 +1,156 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    resolution::digest::compute_digest,
+    source_package::{
+        layout::SourcePackageLayout,
+        parsed_manifest::{GitInfo, PackageDigest, PackageName},
+    },
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The version of the lock file format written by this package system.
+const LOCK_FILE_VERSION: u64 = 1;
+
+const LOCK_FILE_HEADER: &str = "\
+# This file is generated by the Move package system when resolving git dependencies.
+# It pins each of them to a commit, and should be checked in. Do not edit it manually,
+# instead run `aptos move update-deps` to update the pinned commits.
+";
+
+/// The `Move.lock` file of a package, which pins every git dependency in the package graph to the
+/// commit it was resolved to, together with the digest of its sources.
+///
+/// As long as the `git`, `rev` and `subdir` of a dependency are unchanged in the manifests,
+/// resolution checks out the locked commit instead of the latest state of `rev`, and fails if the
+/// sources at that commit do not have the locked digest. This makes builds reproducible even if
+/// `rev` is a branch.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MoveLock {
+    pub dependencies: BTreeMap<PackageName, LockedDependency>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LockedDependency {
+    /// The git clone url, as in the manifest
+    pub git: String,
+    /// The git revision, as in the manifest
+    pub rev: String,
+    /// The path of the package in the repo, as in the manifest
+    pub subdir: PathBuf,
+    /// The commit that `rev` was resolved to
+    pub commit: String,
+    /// The digest of the package sources and manifest at `commit`
+    pub digest: PackageDigest,
+}
+
+/// The on-disk representation of `MoveLock`.
+#[derive(Serialize, Deserialize)]
+struct LockFile {
+    version: u64,
+    #[serde(default, rename = "dependency")]
+    dependencies: Vec<LockFileEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockFileEntry {
+    name: PackageName,
+    #[serde(flatten)]
+    dependency: LockedDependency,
+}
+
+impl MoveLock {
+    pub fn path(package_path: &Path) -> PathBuf {
+        package_path.join(SourcePackageLayout::LockFile.path())
+    }
+
+    /// Reads the lock file of the package at `package_path`, or returns an empty lock if there is
+    /// none.
+    pub fn read(package_path: &Path) -> Result<Self> {
+        let path = Self::path(package_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read lock file {}", path.display()))?;
+        let lock_file: LockFile = toml::from_str(&contents)
+            .with_context(|| format!("Malformed lock file {}", path.display()))?;
+        if lock_file.version != LOCK_FILE_VERSION {
+            bail!(
+                "Unsupported version {} of lock file {}, expected version {}",
+                lock_file.version,
+                path.display(),
+                LOCK_FILE_VERSION
+            )
+        }
+        Ok(Self {
+            dependencies: lock_file
+                .dependencies
+                .into_iter()
+                .map(|entry| (entry.name, entry.dependency))
+                .collect(),
+        })
+    }
+
+    /// Writes the lock file of the package at `package_path`.
+    pub fn write(&self, package_path: &Path) -> Result<()> {
+        let lock_file = LockFile {
+            version: LOCK_FILE_VERSION,
+            dependencies: self
+                .dependencies
+                .iter()
+                .map(|(name, dependency)| LockFileEntry {
+                    name: *name,
+                    dependency: dependency.clone(),
+                })
+                .collect(),
+        };
+        let path = Self::path(package_path);
+        let contents = format!("{}\n{}", LOCK_FILE_HEADER, toml::to_string(&lock_file)?);
+        fs::write(&path, contents)
+            .with_context(|| format!("Unable to write lock file {}", path.display()))
+    }
+
+    /// Returns the lock of the git dependency `dep_name`, if it is locked and its source in the
+    /// manifest has not changed since.
+    pub fn locked_dependency(
+        &self,
+        dep_name: &PackageName,
+        git_info: &GitInfo,
+    ) -> Option<&LockedDependency> {
+        self.dependencies
+            .get(dep_name)
+            .filter(|locked| locked.is_locked_version_of(git_info))
+    }
+}
+
+impl LockedDependency {
+    pub fn is_locked_version_of(&self, git_info: &GitInfo) -> bool {
+        self.git == git_info.git_url.as_str()
+            && self.rev == git_info.git_rev.as_str()
+            && self.subdir == git_info.subdir
+    }
+}
+
+/// Computes the digest of a package as recorded in the lock file. Unlike the digest of a resolved
+/// package, this does not depend on the build configuration: it covers the manifest, the sources
+/// and the scripts, i.e., exactly what is compiled when the package is a dependency.
+pub fn compute_lock_digest(package_path: &Path) -> Result<PackageDigest> {
+    let paths = [
+        SourcePackageLayout::Manifest,
+        SourcePackageLayout::Sources,
+        SourcePackageLayout::Scripts,
+    ]
+    .iter()
+    .map(|layout_path| package_path.join(layout_path.path()))
+    .filter(|path| path.exists())
+    .collect::<Vec<_>>();
+    compute_digest(&paths)
+}
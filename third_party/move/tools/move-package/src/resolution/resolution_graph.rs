@@ -4,12 +4,15 @@
 
 use crate::{
     package_hooks,
-    resolution::digest::compute_digest,
+    resolution::{
+        digest::compute_digest,
+        lock_file::{compute_lock_digest, LockedDependency, MoveLock},
+    },
     source_package::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
         parsed_manifest::{
-            Dependencies, Dependency, FileName, NamedAddress, PackageDigest, PackageName,
+            Dependencies, Dependency, FileName, GitInfo, NamedAddress, PackageDigest, PackageName,
             SourceManifest, SubstOrRename,
         },
         std_lib::{StdLib, StdVersion},
@@ -76,6 +79,8 @@ pub struct ResolutionGraph<T> {
     pub graph: DiGraphMap<PackageName, ()>,
     /// A mapping of package name to its resolution
     pub package_table: BTreeMap<PackageName, ResolutionPackage<T>>,
+    /// The commits that the git dependencies in the graph are locked to
+    pub lock_file: MoveLock,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -106,12 +111,21 @@ impl ResolvingGraph {
                 build_options.architecture = info.architecture;
             }
         }
+        let previous_lock_file = MoveLock::read(&root_package_path)?;
+        // When updating the lock file, all git dependencies are resolved again from scratch, which
+        // also drops the ones that are no longer in the package graph.
+        let lock_file = if build_options.update_lock_file {
+            MoveLock::default()
+        } else {
+            previous_lock_file.clone()
+        };
         let mut resolution_graph = Self {
             root_package_path: root_package_path.clone(),
             build_options: build_options.clone(),
             root_package: root_package.clone(),
             graph: DiGraphMap::new(),
             package_table: BTreeMap::new(),
+            lock_file,
         };
 
         let override_std = &build_options.override_std;
@@ -130,6 +144,7 @@ impl ResolvingGraph {
                     root_package.package.name
                 )
             })?;
+        resolution_graph.write_lock_file(&previous_lock_file)?;
         Ok(resolution_graph)
     }
 
@@ -140,6 +155,7 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table,
+            lock_file,
         } = self;
 
         let mut unresolved_addresses = Vec::new();
@@ -200,9 +216,24 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table: resolved_package_table,
+            lock_file,
         })
     }
 
+    /// Writes the lock file of the root package if resolution changed it. No lock file is created
+    /// for packages without git dependencies.
+    fn write_lock_file(&self, previous_lock_file: &MoveLock) -> Result<()> {
+        if self.lock_file == *previous_lock_file {
+            return Ok(());
+        }
+        if self.lock_file.dependencies.is_empty()
+            && !MoveLock::path(&self.root_package_path).exists()
+        {
+            return Ok(());
+        }
+        self.lock_file.write(&self.root_package_path)
+    }
+
     fn build_resolution_graph<W: Write>(
         &mut self,
         package: SourceManifest,
@@ -411,15 +442,25 @@ impl ResolvingGraph {
         override_std: &Option<StdVersion>,
         writer: &mut W,
     ) -> Result<(Renaming, ResolvingTable)> {
+        let locked_dep = dep
+            .git_info
+            .as_ref()
+            .and_then(|git_info| self.lock_file.locked_dependency(&dep_name_in_pkg, git_info))
+            .cloned();
+        let dep = Self::at_locked_commit(dep, locked_dep.as_ref());
         Self::download_and_update_if_remote(
             dep_name_in_pkg,
             &dep,
+            locked_dep.as_ref(),
             self.build_options.skip_fetch_latest_git_deps,
             writer,
         )?;
         let (dep_package, dep_package_dir) =
             Self::parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
+        if let Some(git_info) = &dep.git_info {
+            self.lock_git_dependency(dep_name_in_pkg, git_info, &dep_package_dir, locked_dep)?;
+        }
         self.build_resolution_graph(
             dep_package.clone(),
             dep_package_dir,
@@ -504,6 +545,48 @@ impl ResolvingGraph {
         Ok((renaming, resolution_table))
     }
 
+    // Records the commit that a git dependency was resolved to in the lock file or, if it was
+    // already locked, checks that its sources have not changed since.
+    fn lock_git_dependency(
+        &mut self,
+        dep_name: PackageName,
+        git_info: &GitInfo,
+        dep_package_dir: &Path,
+        locked_dep: Option<LockedDependency>,
+    ) -> Result<()> {
+        let digest = compute_lock_digest(dep_package_dir)?;
+        match locked_dep {
+            Some(locked_dep) => {
+                if locked_dep.digest != digest {
+                    bail!(
+                        "Source digest mismatch in git dependency '{}' locked to commit '{}'. \
+                        Expected '{}' but got '{}'. To lock the dependency to its latest state, \
+                        run `aptos move update-deps`",
+                        dep_name,
+                        locked_dep.commit,
+                        locked_dep.digest,
+                        digest
+                    )
+                }
+            },
+            None => {
+                let commit = git_head_commit(&git_info.download_to).with_context(|| {
+                    format!("Unable to find the commit of git dependency '{}'", dep_name)
+                })?;
+                self.lock_file
+                    .dependencies
+                    .insert(dep_name, LockedDependency {
+                        git: git_info.git_url.to_string(),
+                        rev: git_info.git_rev.to_string(),
+                        subdir: git_info.subdir.clone(),
+                        commit,
+                        digest,
+                    });
+            },
+        }
+        Ok(())
+    }
+
     fn get_or_add_node(&mut self, package_name: PackageName) -> Result<GraphIndex> {
         if self.graph.contains_node(package_name) {
             // If we encounter a node that we've already added we should check for cycles
@@ -546,6 +629,7 @@ impl ResolvingGraph {
     pub fn download_dependency_repos<W: Write>(
         manifest: &SourceManifest,
         build_options: &BuildConfig,
+        lock_file: &MoveLock,
         root_path: &Path,
         writer: &mut W,
     ) -> Result<()> {
@@ -559,9 +643,15 @@ impl ResolvingGraph {
         };
 
         for (dep_name, dep) in manifest.dependencies.iter().chain(additional_deps.iter()) {
+            let locked_dep = dep
+                .git_info
+                .as_ref()
+                .and_then(|git_info| lock_file.locked_dependency(dep_name, git_info));
+            let dep = &Self::at_locked_commit(dep.clone(), locked_dep);
             Self::download_and_update_if_remote(
                 *dep_name,
                 dep,
+                locked_dep,
                 build_options.skip_fetch_latest_git_deps,
                 writer,
             )?;
@@ -570,14 +660,33 @@ impl ResolvingGraph {
                 Self::parse_package_manifest(dep, dep_name, root_path.to_path_buf())
                     .with_context(|| format!("While processing dependency '{}'", *dep_name))?;
             // download dependencies of dependencies
-            Self::download_dependency_repos(&dep_manifest, build_options, root_path, writer)?;
+            Self::download_dependency_repos(
+                &dep_manifest,
+                build_options,
+                lock_file,
+                root_path,
+                writer,
+            )?;
         }
         Ok(())
     }
 
+    // A locked git dependency is checked out in a directory of its own, keyed by the locked commit,
+    // so that it does not move the shared clone of its revision, which other packages may be using.
+    fn at_locked_commit(mut dep: Dependency, locked_dep: Option<&LockedDependency>) -> Dependency {
+        if let (Some(git_info), Some(locked_dep)) = (&mut dep.git_info, locked_dep) {
+            let mut download_to = git_info.download_to.clone().into_os_string();
+            download_to.push(format!("_{}", locked_dep.commit));
+            git_info.download_to = PathBuf::from(download_to);
+            dep.local = git_info.download_to.join(&git_info.subdir);
+        }
+        dep
+    }
+
     fn download_and_update_if_remote<W: Write>(
         dep_name: PackageName,
         dep: &Dependency,
+        locked_dep: Option<&LockedDependency>,
         skip_fetch_latest_git_deps: bool,
         writer: &mut W,
     ) -> Result<()> {
@@ -586,8 +695,17 @@ impl ResolvingGraph {
             let git_rev = git_info.git_rev.as_str();
             let git_path = &git_info.download_to.display().to_string();
 
-            // If there is no cached dependency, download it
-            if !git_info.download_to.exists() {
+            if let Some(locked_dep) = locked_dep {
+                // The dependency is locked, so use the locked commit rather than the latest state
+                Self::checkout_locked_commit(
+                    dep_name,
+                    git_info,
+                    &locked_dep.commit,
+                    skip_fetch_latest_git_deps,
+                    writer,
+                )?;
+            } else if !git_info.download_to.exists() {
+                // If there is no cached dependency, download it
                 writeln!(
                     writer,
                     "{} {}",
@@ -714,6 +832,120 @@ impl ResolvingGraph {
         }
         Ok(())
     }
+
+    fn checkout_locked_commit<W: Write>(
+        dep_name: PackageName,
+        git_info: &GitInfo,
+        commit: &str,
+        skip_fetch_latest_git_deps: bool,
+        writer: &mut W,
+    ) -> Result<()> {
+        let git_url = git_info.git_url.as_str();
+        let git_path = &git_info.download_to.display().to_string();
+
+        if git_info.download_to.exists()
+            && git_head_commit(&git_info.download_to).is_ok_and(|head| head == commit)
+        {
+            return Ok(());
+        }
+        confirm_git_available()?;
+
+        if !git_info.download_to.exists() {
+            writeln!(
+                writer,
+                "{} {}",
+                "FETCHING GIT DEPENDENCY".bold().green(),
+                git_url,
+            )?;
+            let status = Command::new("git")
+                .args(["clone", git_url, git_path])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map_err(|_| {
+                    anyhow::anyhow!("Failed to clone Git repository for package '{}'", dep_name)
+                })?;
+            if !status.success() {
+                bail!(
+                    "Failed to clone Git repository for package '{}' | Exit status: {}",
+                    dep_name,
+                    status
+                )
+            }
+        }
+
+        // The commit may not be in the cached repository yet, e.g., if the lock file was written
+        // on another machine.
+        let has_commit = Command::new("git")
+            .args([
+                "-C",
+                git_path,
+                "cat-file",
+                "-e",
+                &format!("{}^{{commit}}", commit),
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if !has_commit {
+            if skip_fetch_latest_git_deps {
+                bail!(
+                    "Locked commit '{}' of package '{}' is not available locally, to fetch it \
+                    unset --skip-fetch-latest-git-deps",
+                    commit,
+                    dep_name
+                )
+            }
+            writeln!(
+                writer,
+                "{} {}",
+                "UPDATING GIT DEPENDENCY".bold().green(),
+                git_url,
+            )?;
+            let status = Command::new("git")
+                .args(["-C", git_path, "fetch", "origin"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "Failed to fetch latest Git state for package '{}'",
+                        dep_name
+                    )
+                })?;
+            if !status.success() {
+                bail!(
+                    "Failed to fetch latest Git state for package '{}' | Exit status: {}",
+                    dep_name,
+                    status
+                )
+            }
+        }
+
+        let status = Command::new("git")
+            .args(["-C", git_path, "checkout", "--force", "--detach", commit])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Failed to checkout locked commit '{}' for package '{}'",
+                    commit,
+                    dep_name
+                )
+            })?;
+        if !status.success() {
+            bail!(
+                "Failed to checkout locked commit '{}' for package '{}' | Exit status: {}",
+                commit,
+                dep_name,
+                status
+            )
+        }
+        Ok(())
+    }
 }
 
 impl ResolvingPackage {
@@ -966,6 +1198,22 @@ impl ResolvedPackage {
     }
 }
 
+fn git_head_commit(git_path: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(git_path)
+        .args(["rev-parse", "HEAD"])
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "Failed to get the current commit of Git repository at {} | Exit status: {}",
+            git_path.display(),
+            output.status
+        )
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
 fn confirm_git_available() -> Result<()> {
     match Command::new("git").arg("--version").output() {
         Ok(_) => Ok(()),
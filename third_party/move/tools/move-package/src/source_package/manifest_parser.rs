@@ -6,11 +6,16 @@ use super::layout::SourcePackageLayout;
 use crate::{package_hooks, source_package::parsed_manifest as PM, Architecture};
 use anyhow::{bail, format_err, Context, Result};
 use move_command_line_common::env::MOVE_HOME;
+use move_compiler_v2::{
+    lint_common::{LintChecker, LintConfig, LintLevel, LintRule},
+    lint_pattern::NamePattern,
+};
 use move_core_types::account_address::{AccountAddress, AccountAddressParseError};
 use move_symbol_pool::symbol::Symbol;
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use toml::Value as TV;
 
@@ -22,6 +27,8 @@ const ADDRESSES_NAME: &str = "addresses";
 const DEV_ADDRESSES_NAME: &str = "dev-addresses";
 const DEPENDENCY_NAME: &str = "dependencies";
 const DEV_DEPENDENCY_NAME: &str = "dev-dependencies";
+const LINTS_NAME: &str = "lints";
+const LINT_RULES_NAME: &str = "lint-rules";
 
 const KNOWN_NAMES: &[&str] = &[
     PACKAGE_NAME,
@@ -30,6 +37,8 @@ const KNOWN_NAMES: &[&str] = &[
     DEV_ADDRESSES_NAME,
     DEPENDENCY_NAME,
     DEV_DEPENDENCY_NAME,
+    LINTS_NAME,
+    LINT_RULES_NAME,
 ];
 
 const REQUIRED_FIELDS: &[&str] = &[PACKAGE_NAME];
@@ -86,6 +95,24 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 .transpose()
                 .context("Error parsing '[dev-dependencies]' section of manifest")?
                 .unwrap_or_default();
+            let lint_rules = table
+                .remove(LINT_RULES_NAME)
+                .map(parse_lint_rules)
+                .transpose()
+                .context("Error parsing '[lint-rules]' section of manifest")?;
+            let lint_levels = table
+                .remove(LINTS_NAME)
+                .map(|tval| parse_lint_levels(tval, lint_rules.as_deref().unwrap_or_default()))
+                .transpose()
+                .context("Error parsing '[lints]' section of manifest")?;
+            let lints = if lint_levels.is_some() || lint_rules.is_some() {
+                Some(LintConfig {
+                    levels: lint_levels.unwrap_or_default(),
+                    rules: lint_rules.unwrap_or_default(),
+                })
+            } else {
+                None
+            };
             Ok(PM::SourceManifest {
                 package,
                 addresses,
@@ -93,6 +120,7 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 build,
                 dependencies,
                 dev_dependencies,
+                lints,
             })
         },
         x => {
@@ -214,6 +242,106 @@ pub fn parse_build_info(tval: TV) -> Result<PM::BuildInfo> {
     }
 }
 
+pub fn parse_lint_rules(tval: TV) -> Result<Vec<LintRule>> {
+    match tval {
+        TV::Table(table) => table
+            .into_iter()
+            .map(|(name, rule)| {
+                parse_lint_rule(name.clone(), rule)
+                    .with_context(|| format!("Error parsing lint rule '{}'", name))
+            })
+            .collect(),
+        x => bail!(
+            "Malformed section in manifest {}. Expected a table, but encountered a {}",
+            x,
+            x.type_str()
+        ),
+    }
+}
+
+fn parse_lint_rule(name: String, tval: TV) -> Result<LintRule> {
+    let mut table = match tval {
+        TV::Table(table) => table,
+        x => bail!("Expected a table, but encountered a {}", x.type_str()),
+    };
+    if LintChecker::from_str(&name).is_ok() {
+        bail!("The name of a lint check of the compiler cannot be used for a lint rule")
+    }
+    check_for_required_field_names(&table, &["message", "pattern"])?;
+    warn_if_unknown_field_names(&table, &["message", "pattern", "allowed_in"]);
+    let message = table
+        .remove("message")
+        .and_then(|message| message.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| format_err!("'message' must be a string"))?;
+    let pattern = table
+        .remove("pattern")
+        .and_then(|pattern| pattern.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| format_err!("'pattern' must be a string"))?
+        .parse()?;
+    let allowed_in = table
+        .remove("allowed_in")
+        .map(parse_function_patterns)
+        .transpose()?
+        .unwrap_or_default();
+    Ok(LintRule {
+        name,
+        message,
+        pattern,
+        allowed_in,
+    })
+}
+
+fn parse_function_patterns(tval: TV) -> Result<Vec<NamePattern>> {
+    tval.as_array()
+        .ok_or_else(|| format_err!("Expected a list of function patterns"))?
+        .iter()
+        .map(|pattern| {
+            pattern
+                .as_str()
+                .ok_or_else(|| {
+                    format_err!(
+                        "Invalid function pattern '{}' of type {} found. Expected a string.",
+                        pattern,
+                        pattern.type_str()
+                    )
+                })?
+                .parse()
+        })
+        .collect()
+}
+
+pub fn parse_lint_levels(tval: TV, rules: &[LintRule]) -> Result<BTreeMap<String, LintLevel>> {
+    match tval {
+        TV::Table(table) => {
+            let mut levels = BTreeMap::new();
+            for (name, level) in table.into_iter() {
+                if LintChecker::from_str(&name).is_err()
+                    && !rules.iter().any(|rule| rule.name == name)
+                {
+                    bail!("Unknown lint check '{}'", name)
+                }
+                let level = level
+                    .as_str()
+                    .and_then(|level| LintLevel::from_str(level).ok())
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Invalid level {} for lint check '{}'. Expected one of \"allow\", \"warn\" or \"deny\".",
+                            level,
+                            name
+                        )
+                    })?;
+                levels.insert(name, level);
+            }
+            Ok(levels)
+        },
+        x => bail!(
+            "Malformed section in manifest {}. Expected a table, but encountered a {}",
+            x,
+            x.type_str()
+        ),
+    }
+}
+
 pub fn parse_addresses(tval: TV) -> Result<PM::AddressDeclarations> {
     match tval {
         TV::Table(table) => {
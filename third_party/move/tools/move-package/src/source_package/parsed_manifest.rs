@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::Architecture;
+use move_compiler_v2::lint_common::LintConfig;
 use move_core_types::account_address::AccountAddress;
 use move_symbol_pool::symbol::Symbol;
 use std::{collections::BTreeMap, fmt, fmt::Debug, path::PathBuf};
@@ -26,6 +27,7 @@ pub struct SourceManifest {
     pub build: Option<BuildInfo>,
     pub dependencies: Dependencies,
     pub dev_dependencies: Dependencies,
+    pub lints: Option<LintConfig>,
 }
 
 impl fmt::Display for SourceManifest {
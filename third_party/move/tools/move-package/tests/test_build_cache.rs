@@ -0,0 +1,154 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_model::metadata::CompilerVersion;
+use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const LIB_SOURCE: &str = "
+module 0x42::lib {
+    public inline fun twice(x: u64): u64 { x + x }
+
+    public fun four(): u64 { twice(2) }
+}
+";
+
+const APP_SOURCE: &str = "
+module 0x43::app {
+    public fun eight(): u64 { 0x42::lib::twice(0x42::lib::four()) }
+}
+";
+
+const PLAIN_LIB_SOURCE: &str = "
+module 0x44::plain {
+    struct Pair has drop { x: u64, y: u64 }
+
+    public fun pair(x: u64, y: u64): Pair { Pair { x, y } }
+
+    public fun sum(p: &Pair): u64 { p.x + p.y }
+}
+";
+
+const PLAIN_APP_SOURCE: &str = "
+module 0x45::plain_app {
+    public fun three(): u64 { 0x44::plain::sum(&0x44::plain::pair(1, 2)) }
+}
+";
+
+fn write_package(path: &Path, manifest: &str, module: &str, source: &str) {
+    fs::create_dir_all(path.join("sources")).unwrap();
+    fs::write(path.join("Move.toml"), manifest).unwrap();
+    fs::write(path.join("sources").join(module), source).unwrap();
+}
+
+fn build(path: &Path, install_dir: &Path, compiler_build: &str) -> CompiledPackage {
+    BuildConfig {
+        install_dir: Some(install_dir.to_path_buf()),
+        build_cache: true,
+        compiler_build: Some(compiler_build.to_string()),
+        compiler_config: CompilerConfig {
+            compiler_version: Some(CompilerVersion::V2_0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .compile_package(path, &mut Vec::new())
+    .unwrap()
+}
+
+fn dep_bytecode(package: &CompiledPackage) -> Vec<Vec<u8>> {
+    package
+        .deps_compiled_units
+        .iter()
+        .map(|(_, unit)| unit.unit.serialize(None))
+        .collect()
+}
+
+#[test]
+fn test_dependencies_reused_from_build_cache() {
+    let move_home = tempdir().unwrap();
+    // The cache is located in MOVE_HOME, which is read once on first use.
+    std::env::set_var("MOVE_HOME", move_home.path());
+    let cache_dir = move_home.path().join("build-cache");
+    let dir = tempdir().unwrap();
+    let lib = dir.path().join("lib");
+    let app = dir.path().join("app");
+    write_package(
+        &lib,
+        "[package]\nname = \"Lib\"\nversion = \"0.0.0\"\n",
+        "lib.move",
+        LIB_SOURCE,
+    );
+    write_package(
+        &app,
+        "[package]\nname = \"App\"\nversion = \"0.0.0\"\n\n[dependencies]\nLib = { local = \"../lib\" }\n",
+        "app.move",
+        APP_SOURCE,
+    );
+    let entries = || fs::read_dir(&cache_dir).unwrap().count();
+    let entries_with_interfaces = || {
+        fs::read_dir(&cache_dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().join("interfaces").is_dir())
+            .count()
+    };
+
+    // The first build stores the dependency in the cache.
+    let first = build(&app, dir.path(), "a");
+    assert_eq!(entries(), 1);
+
+    // The second build takes the dependency from the cache, and can still inline its functions,
+    // which interfaces would lack.
+    let second = build(&app, dir.path(), "a");
+    assert_eq!(entries(), 1);
+    assert_eq!(entries_with_interfaces(), 0);
+    assert_eq!(dep_bytecode(&first), dep_bytecode(&second));
+    assert_eq!(second.root_compiled_units.len(), 1);
+
+    // Changing the dependency leads to a new entry.
+    fs::write(
+        lib.join("sources").join("lib.move"),
+        LIB_SOURCE.replace("twice(2)", "twice(2) + 0"),
+    )
+    .unwrap();
+    build(&app, dir.path(), "a");
+    assert_eq!(entries(), 2);
+
+    // So does a different build of the compiler.
+    build(&app, dir.path(), "b");
+    assert_eq!(entries(), 3);
+
+    // A dependency declaring nothing beyond its bytecode is stored with interfaces, against which
+    // the package is type checked when the dependency is found in the cache.
+    let plain = dir.path().join("plain");
+    let plain_app = dir.path().join("plain_app");
+    write_package(
+        &plain,
+        "[package]\nname = \"Plain\"\nversion = \"0.0.0\"\n",
+        "plain.move",
+        PLAIN_LIB_SOURCE,
+    );
+    write_package(
+        &plain_app,
+        "[package]\nname = \"PlainApp\"\nversion = \"0.0.0\"\n\n[dependencies]\nPlain = { local = \"../plain\" }\n",
+        "plain_app.move",
+        PLAIN_APP_SOURCE,
+    );
+    let first = build(&plain_app, dir.path(), "a");
+    assert_eq!(entries(), 4);
+    assert_eq!(entries_with_interfaces(), 1);
+    let second = build(&plain_app, dir.path(), "a");
+    assert_eq!(entries(), 4);
+    assert_eq!(
+        first.root_compiled_units[0].unit.serialize(None),
+        second.root_compiled_units[0].unit.serialize(None)
+    );
+
+    // Storing an entry evicts the least recently used ones beyond the maximum, which keeps the
+    // entry just used.
+    std::env::set_var("MOVE_BUILD_CACHE_MAX_ENTRIES", "2");
+    build(&app, dir.path(), "c");
+    assert_eq!(entries(), 2);
+    assert_eq!(entries_with_interfaces(), 1);
+}
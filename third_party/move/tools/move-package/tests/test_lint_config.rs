@@ -0,0 +1,138 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use codespan_reporting::diagnostic::Severity;
+use move_compiler_v2::{run_checker_and_rewriters, Experiment};
+use move_model::metadata::{CompilerVersion, LanguageVersion};
+use move_package::{
+    compilation::model_builder::ModelBuilder, BuildConfig, CompilerConfig, ModelConfig,
+};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"
+[package]
+name = "Lints"
+version = "0.0.0"
+
+[lints]
+while_true = "allow"
+no_secret = "deny"
+
+[lint-rules.no_secret]
+message = "`secret` must only be called from constructors"
+pattern = "call(0x42::m::secret)"
+allowed_in = ["*::m::create_*"]
+
+[lint-rules.no_abort_in_loops]
+message = "loops must not abort"
+pattern = "inside(abort, loop)"
+"#;
+
+const SOURCE: &str = "
+module 0x42::m {
+    fun secret(): u64 { 42 }
+
+    public fun create_thing(): u64 { secret() }
+
+    public fun misuse(): u64 { secret() }
+
+    #[lint::skip(no_secret)]
+    public fun tolerated(): u64 { secret() }
+
+    public fun spin() { while (true) {} }
+
+    public fun count(n: u64): u64 {
+        let i = 0;
+        while (i < n) {
+            if (i == 10) abort 1;
+            i = i + 1;
+        };
+        if (i > 20) abort 2;
+        i
+    }
+}
+";
+
+/// Compiles the package with the lint checks and returns its diagnostics as
+/// `(severity, message)`.
+fn lint(path: &Path) -> Vec<(Severity, String)> {
+    let build_config = BuildConfig {
+        compiler_config: CompilerConfig {
+            compiler_version: Some(CompilerVersion::V2_0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let resolution_graph = build_config
+        .resolution_graph_for_package(path, &mut Vec::new())
+        .unwrap();
+    let options = ModelBuilder::create(resolution_graph, ModelConfig {
+        all_files_as_targets: false,
+        target_filter: None,
+        compiler_version: CompilerVersion::V2_0,
+        language_version: LanguageVersion::default(),
+    })
+    .v2_compiler_options()
+    .unwrap()
+    .set_experiment(Experiment::LINT_CHECKS, true);
+    let env = run_checker_and_rewriters(options).unwrap();
+    env.get_diags(Severity::Warning)
+        .into_iter()
+        .map(|diag| (diag.severity, diag.message))
+        .collect()
+}
+
+#[test]
+fn test_lint_rules_and_levels() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Move.toml"), MANIFEST).unwrap();
+    fs::create_dir(dir.path().join("sources")).unwrap();
+    fs::write(dir.path().join("sources").join("m.move"), SOURCE).unwrap();
+
+    // Only the call in `misuse` violates the first rule, only the first abort in `count`
+    // violates the second one, and `while_true` is not reported.
+    assert_eq!(lint(dir.path()), vec![
+        (Severity::Warning, "[lint] loops must not abort".to_string()),
+        (
+            Severity::Error,
+            "[lint] `secret` must only be called from constructors".to_string()
+        ),
+    ]);
+}
+
+#[test]
+fn test_invalid_lint_rule_pattern() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Move.toml"),
+        "[package]\nname = \"Lints\"\nversion = \"0.0.0\"\n\n[lint-rules.r]\nmessage = \"m\"\npattern = \"inside(abort)\"\n",
+    )
+    .unwrap();
+    let err = BuildConfig::default()
+        .resolution_graph_for_package(dir.path(), &mut Vec::new())
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("`inside` expects 2 patterns"),
+        "{:#}",
+        err
+    );
+}
+
+#[test]
+fn test_unknown_lint_in_manifest() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Move.toml"),
+        "[package]\nname = \"Lints\"\nversion = \"0.0.0\"\n\n[lints]\nno_such_lint = \"deny\"\n",
+    )
+    .unwrap();
+    let err = BuildConfig::default()
+        .resolution_graph_for_package(dir.path(), &mut Vec::new())
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Unknown lint check 'no_such_lint'"),
+        "{:#}",
+        err
+    );
+}
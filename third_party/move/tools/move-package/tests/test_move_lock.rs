@@ -0,0 +1,123 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{
+    resolution::lock_file::MoveLock, source_package::manifest_parser::git_repo_cache_path,
+    BuildConfig,
+};
+use move_symbol_pool::Symbol;
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn dep_module(value: u64) -> String {
+    format!(
+        "module 0x42::dep {{ public fun value(): u64 {{ {} }} }}",
+        value
+    )
+}
+
+/// Commits a new version of the dependency and returns its commit.
+fn commit_dep(repo: &Path, value: u64) -> String {
+    fs::write(repo.join("sources").join("dep.move"), dep_module(value)).unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-m", &format!("value {}", value)]);
+    git(repo, &["rev-parse", "HEAD"])
+}
+
+fn resolve(root: &Path) -> anyhow::Result<MoveLock> {
+    BuildConfig::default()
+        .resolution_graph_for_package(root, &mut Vec::new())
+        .map(|resolved_graph| resolved_graph.lock_file)
+}
+
+#[test]
+fn test_git_dependency_is_locked() {
+    let dir = tempdir().unwrap();
+    // Keep the downloaded dependencies out of the user's cache.
+    std::env::set_var("MOVE_HOME", dir.path().join("move_home"));
+
+    let dep_repo = dir.path().join("dep");
+    fs::create_dir_all(dep_repo.join("sources")).unwrap();
+    fs::write(
+        dep_repo.join("Move.toml"),
+        "[package]\nname = \"Dep\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+    git(&dep_repo, &["init"]);
+    git(&dep_repo, &["checkout", "-b", "main"]);
+    let first_commit = commit_dep(&dep_repo, 1);
+
+    let git_url = dep_repo.to_str().unwrap();
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n[dependencies]\n\
+            Dep = {{ git = \"{}\", rev = \"main\" }}\n",
+            git_url
+        ),
+    )
+    .unwrap();
+    let dep_name = Symbol::from("Dep");
+    let cached_repo = git_repo_cache_path(git_url, "main");
+    let cached_module = cached_repo.join("sources").join("dep.move");
+    // Locked dependencies are checked out in a directory of their own per commit.
+    let locked_module = |commit: &str| {
+        let mut locked_repo = cached_repo.clone().into_os_string();
+        locked_repo.push(format!("_{}", commit));
+        Path::new(&locked_repo).join("sources").join("dep.move")
+    };
+
+    // The first resolution locks the dependency to the current commit of the branch.
+    let lock_file = resolve(&root).unwrap();
+    assert_eq!(lock_file.dependencies[&dep_name].commit, first_commit);
+    assert_eq!(MoveLock::read(&root).unwrap(), lock_file);
+
+    // Later resolutions keep using the locked commit, even though the branch has moved on.
+    let second_commit = commit_dep(&dep_repo, 2);
+    let lock_file = resolve(&root).unwrap();
+    assert_eq!(lock_file.dependencies[&dep_name].commit, first_commit);
+    assert_eq!(
+        fs::read_to_string(locked_module(&first_commit)).unwrap(),
+        dep_module(1)
+    );
+    // The shared clone of the branch stays on the branch.
+    git(&cached_repo, &["symbolic-ref", "HEAD"]);
+
+    // Updating the lock file moves the dependency to the latest commit of the branch.
+    let lock_file = BuildConfig::default()
+        .update_lock_file_for_package(&root, &mut Vec::new())
+        .unwrap();
+    assert_eq!(lock_file.dependencies[&dep_name].commit, second_commit);
+    assert_eq!(MoveLock::read(&root).unwrap(), lock_file);
+    assert_eq!(fs::read_to_string(&cached_module).unwrap(), dep_module(2));
+
+    let lock_file = resolve(&root).unwrap();
+    assert_eq!(lock_file.dependencies[&dep_name].commit, second_commit);
+    assert_eq!(
+        fs::read_to_string(locked_module(&second_commit)).unwrap(),
+        dep_module(2)
+    );
+
+    // Sources that do not match the locked digest are rejected.
+    fs::write(locked_module(&second_commit), dep_module(3)).unwrap();
+    let err = resolve(&root).unwrap_err();
+    assert!(format!("{:#}", err).contains("Source digest mismatch"));
+}
@@ -95,6 +95,9 @@ pub enum FeatureFlag {
     FEDERATED_KEYLESS = 77,
     TRANSACTION_SIMULATION_ENHANCEMENT = 78,
     COLLECTION_OWNER = 79,
+    SECP256R1_NATIVES = 80,
+    POSEIDON_BN254_NATIVES = 81,
+    BIG_ORDERED_MAP_NATIVES = 82,
 }
 
 impl FeatureFlag {